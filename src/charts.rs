@@ -5,12 +5,22 @@ use iced::{
 use iced::{
     mouse, widget::canvas, widget::canvas::{event::{self, Event}, stroke::Stroke, Geometry, Path}, Renderer, Size
 };
+use serde::{Deserialize, Serialize};
 
 pub mod heatmap;
 pub mod footprint;
 pub mod candlestick;
 pub mod timeandsales;
 
+/// Share of a chart's height given to the volume bars at the bottom
+pub const DEFAULT_VOLUME_RATIO: f32 = 0.125;
+pub const MAX_VOLUME_RATIO: f32 = 0.3;
+
+/// Font size of the axis and crosshair labels, axes grow along with it
+pub const DEFAULT_LABEL_SIZE: f32 = 12.0;
+pub const MIN_LABEL_SIZE: f32 = 10.0;
+pub const MAX_LABEL_SIZE: f32 = 20.0;
+
 #[derive(Debug, Clone, Copy)]
 pub enum Message {
     Translated(Vector),
@@ -20,6 +30,16 @@ pub enum Message {
     CrosshairToggle,
     CrosshairMoved(Point),
     YScaling(f32, bool),
+    /// Heatmap bucket to review, `None` follows the live book
+    ReviewMoved(Option<usize>),
+    /// Heatmap time window, in milliseconds
+    VisibleSpanChanged(u32),
+    FibToolToggle,
+    VolumeProfileToggle,
+    FibDragged(FibDraw),
+    FibCleared,
+    /// Span held with the right mouse button, from where it started to the cursor
+    MeasureUpdate(Option<(Point, Point)>),
 }
 struct CommonChartData {
     main_cache: Cache,
@@ -45,6 +65,50 @@ struct CommonChartData {
     autoscale: bool,
 
     bounds: Rectangle,
+
+    crosshair_style: CrosshairStyle,
+
+    volume_ratio: f32,
+
+    /// The instrument's min tick size and the decimals it's shown with, once known
+    min_tick_size: Option<f32>,
+    price_decimals: Option<usize>,
+
+    last_price: Option<LastPrice>,
+    price_ruler: bool,
+
+    palette: ChartPalette,
+
+    label_size: f32,
+
+    /// Explicit (lowest, highest) prices overriding the computed y-axis range
+    pinned_range: Option<(f32, f32)>,
+    /// Widen the pinned range when price leaves it instead of clipping
+    expand_on_breakout: bool,
+
+    /// Ticks above and below the last price to draw guide lines at
+    offset_guides: Option<f32>,
+
+    /// Grid the y-axis labels snap to instead of `PRICE_STEPS`
+    price_step: Option<f32>,
+
+    /// Corner readout of the loaded datapoints, for telling an empty feed from a frozen one
+    debug_overlay: bool,
+
+    /// Window is minimized, new data gets stored without clearing the caches for a redraw
+    idle: bool,
+
+    /// Time hovered on a linked pane, drawn as a guide while the cursor isn't over this one
+    sync_crosshair_time: Option<i64>,
+
+    /// Volume the bars are scaled to, shared by a link group, `None` scales to the visible bars
+    shared_volume_max: Option<f32>,
+
+    /// Price the grouping grid runs through, 0 keeps levels on plain multiples of the tick size
+    grid_anchor: f32,
+
+    measure_cache: Cache,
+    measure: Option<(Point, Point)>,
 }
 impl Default for CommonChartData {
     fn default() -> Self {
@@ -72,6 +136,420 @@ impl Default for CommonChartData {
             autoscale: true,
 
             bounds: Rectangle::default(),
+
+            crosshair_style: CrosshairStyle::default(),
+
+            volume_ratio: DEFAULT_VOLUME_RATIO,
+
+            min_tick_size: None,
+            price_decimals: None,
+
+            last_price: None,
+            price_ruler: true,
+
+            palette: ChartPalette::default(),
+
+            label_size: DEFAULT_LABEL_SIZE,
+
+            pinned_range: None,
+            expand_on_breakout: false,
+
+            offset_guides: None,
+
+            price_step: None,
+
+            debug_overlay: false,
+
+            idle: false,
+
+            sync_crosshair_time: None,
+
+            shared_volume_max: None,
+
+            grid_anchor: 0.0,
+
+            measure_cache: Cache::default(),
+            measure: None,
+        }
+    }
+}
+
+impl CommonChartData {
+    fn update_last_price(&mut self, price: f32) {
+        let rising = match self.last_price {
+            Some(last) if last.price == price => last.rising,
+            Some(last) => price > last.price,
+            None => true,
+        };
+
+        self.last_price = Some(LastPrice { price, rising });
+    }
+
+    fn set_label_size(&mut self, label_size: f32) {
+        self.label_size = label_size.clamp(MIN_LABEL_SIZE, MAX_LABEL_SIZE);
+
+        self.x_labels_cache.clear();
+        self.y_labels_cache.clear();
+        self.crosshair_cache.clear();
+        self.x_crosshair_cache.clear();
+        self.y_crosshair_cache.clear();
+    }
+
+    /// A pinned range keeps the frame still, so autoscale is switched off with it
+    fn set_pinned_range(&mut self, pinned_range: Option<(f32, f32)>) {
+        self.pinned_range = pinned_range;
+
+        if pinned_range.is_some() {
+            self.autoscale = false;
+        }
+    }
+
+    /// Stretches the pinned range just past whichever of `low`/`high` left it, 
+    /// returns whether it changed
+    fn expand_pinned_range(&mut self, low: f32, high: f32) -> bool {
+        let Some((pinned_low, pinned_high)) = self.pinned_range.filter(|_| self.expand_on_breakout) else {
+            return false;
+        };
+
+        if low >= pinned_low && high <= pinned_high {
+            return false;
+        }
+
+        let margin = (pinned_high - pinned_low) * BREAKOUT_MARGIN;
+
+        self.pinned_range = Some((
+            if low < pinned_low { low - margin } else { pinned_low },
+            if high > pinned_high { high + margin } else { pinned_high },
+        ));
+
+        self.mesh_cache.clear();
+        self.y_labels_cache.clear();
+
+        true
+    }
+
+    fn set_price_step(&mut self, price_step: Option<f32>) {
+        self.price_step = price_step;
+
+        self.mesh_cache.clear();
+        self.y_labels_cache.clear();
+    }
+
+    /// Time under the cursor, `None` once it left the chart
+    fn crosshair_time(&self) -> Option<i64> {
+        if self.crosshair_position == Point::ORIGIN || self.bounds.width <= 0.0 {
+            return None;
+        }
+
+        let ratio = f64::from(self.crosshair_position.x / self.bounds.width);
+
+        Some(self.x_min_time + (ratio * (self.x_max_time - self.x_min_time) as f64) as i64)
+    }
+
+    /// Restores the crosshair and autoscale buttons, `None` keeps the defaults.
+    /// Autoscale stays off while a range is pinned
+    fn set_view_toggles(&mut self, crosshair: Option<bool>, autoscale: Option<bool>) {
+        if let Some(crosshair) = crosshair {
+            self.crosshair = crosshair;
+        }
+        if let Some(autoscale) = autoscale.filter(|_| self.pinned_range.is_none()) {
+            self.autoscale = autoscale;
+        }
+
+        self.crosshair_cache.clear();
+    }
+
+    fn set_sync_crosshair_time(&mut self, time: Option<i64>) {
+        if self.sync_crosshair_time == time {
+            return;
+        }
+        self.sync_crosshair_time = time;
+
+        self.crosshair_cache.clear();
+    }
+
+    fn set_shared_volume_max(&mut self, shared_volume_max: Option<f32>) {
+        if self.shared_volume_max == shared_volume_max {
+            return;
+        }
+        self.shared_volume_max = shared_volume_max;
+
+        self.main_cache.clear();
+    }
+
+    /// Where a grid of `step` sized levels starts, so one of them lands on the anchor
+    fn grid_offset(&self, step: f32) -> f32 {
+        if step <= 0.0 {
+            return 0.0;
+        }
+        self.grid_anchor.rem_euclid(step)
+    }
+
+    /// Highest volume the bars get scaled to, never below the chart's own visible one
+    fn volume_scale(&self, visible_max_volume: f32) -> f32 {
+        self.shared_volume_max.map_or(visible_max_volume, |shared| shared.max(visible_max_volume))
+    }
+
+    /// Vertical line at the time hovered on a linked pane, if it's in view
+    fn draw_sync_guide(&self, frame: &mut canvas::Frame, bounds: Rectangle) {
+        let Some(time) = self.sync_crosshair_time else {
+            return;
+        };
+
+        let (earliest, latest) = (self.x_min_time, self.x_max_time);
+        if latest <= earliest || time < earliest || time > latest {
+            return;
+        }
+
+        let x = ((time - earliest) as f64 / (latest - earliest) as f64) as f32 * bounds.width;
+
+        frame.stroke(
+            &Path::line(Point::new(x, 0.0), Point::new(x, bounds.height)),
+            self.crosshair_style.stroke()
+        );
+    }
+
+    fn set_measure(&mut self, span: Option<(Point, Point)>) {
+        self.measure = span;
+        self.measure_cache.clear();
+    }
+
+    /// Box over the measured span, with its price change, bar count and duration next to the cursor
+    fn draw_measure(&self, frame: &mut canvas::Frame, bounds: Rectangle, timeframe: u16, price_at: impl Fn(f32) -> f32) {
+        let Some((start, end)) = self.measure else {
+            return;
+        };
+
+        let (start_price, end_price) = (price_at(start.y), price_at(end.y));
+        let change = end_price - start_price;
+        let rising = change >= 0.0;
+
+        let top_left = Point::new(start.x.min(end.x), start.y.min(end.y));
+        let size = Size::new((end.x - start.x).abs(), (end.y - start.y).abs());
+
+        frame.fill_rectangle(top_left, size, self.palette.directional(rising, 0.15));
+        frame.stroke(
+            &Path::rectangle(top_left, size),
+            Stroke::default().with_color(self.palette.directional(rising, 0.6)).with_width(1.0)
+        );
+
+        let time_at = |x: f32| self.x_min_time as f64 + f64::from(x / bounds.width) * (self.x_max_time - self.x_min_time) as f64;
+        let elapsed = (time_at(end.x) - time_at(start.x)) as i64;
+        let bars = elapsed as f64 / (f64::from(timeframe) * 60_000.0);
+
+        let percent = if start_price != 0.0 { change / start_price * 100.0 } else { 0.0 };
+
+        let lines = [
+            format!(
+                "{}{} ({:+.2}%)",
+                if rising { "+" } else { "-" },
+                format_price(change.abs(), self.price_decimals),
+                percent
+            ),
+            format!("{:.0} bars, {}", bars, format_duration(elapsed)),
+        ];
+
+        let longest = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+        let box_size = Size::new(
+            longest as f32 * self.label_size * 0.6 + 12.0,
+            lines.len() as f32 * self.label_size * 1.3 + 8.0,
+        );
+
+        // keep the readout next to the cursor, flipped to the other side near the edges
+        let mut position = Point::new(end.x + 12.0, end.y + 12.0);
+        if position.x + box_size.width > bounds.width {
+            position.x = end.x - box_size.width - 12.0;
+        }
+        if position.y + box_size.height > bounds.height {
+            position.y = end.y - box_size.height - 12.0;
+        }
+
+        frame.fill_rectangle(position, box_size, Color::from_rgba8(0, 0, 0, 0.75));
+        frame.fill_text(canvas::Text {
+            content: lines.join("\n"),
+            position: Point::new(position.x + 6.0, position.y + 4.0),
+            size: iced::Pixels(self.label_size),
+            color: self.palette.directional(rising, 1.0),
+            ..canvas::Text::default()
+        });
+    }
+
+    /// Multiplier for the axis dimensions, relative to the default label size
+    fn label_scale(&self) -> f32 {
+        self.label_size / DEFAULT_LABEL_SIZE
+    }
+}
+
+/// Latest traded price, and whether the last change was an uptick
+#[derive(Debug, Clone, Copy)]
+struct LastPrice {
+    price: f32,
+    rising: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum CrosshairColor {
+    #[default]
+    Gray,
+    White,
+    Yellow,
+    Cyan,
+}
+
+impl CrosshairColor {
+    pub const ALL: [CrosshairColor; 4] = [CrosshairColor::Gray, CrosshairColor::White, CrosshairColor::Yellow, CrosshairColor::Cyan];
+
+    fn color(&self) -> Color {
+        match self {
+            CrosshairColor::Gray => Color::from_rgba8(200, 200, 200, 0.6),
+            CrosshairColor::White => Color::from_rgba8(255, 255, 255, 0.8),
+            CrosshairColor::Yellow => Color::from_rgba8(230, 200, 80, 0.8),
+            CrosshairColor::Cyan => Color::from_rgba8(80, 200, 230, 0.8),
+        }
+    }
+}
+
+impl std::fmt::Display for CrosshairColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CrosshairColor::Gray => write!(f, "Gray"),
+            CrosshairColor::White => write!(f, "White"),
+            CrosshairColor::Yellow => write!(f, "Yellow"),
+            CrosshairColor::Cyan => write!(f, "Cyan"),
+        }
+    }
+}
+
+/// Look of the crosshair lines, shared by every chart
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CrosshairStyle {
+    pub dashed: bool,
+    pub color: CrosshairColor,
+    pub thickness: f32,
+}
+
+impl Default for CrosshairStyle {
+    fn default() -> Self {
+        Self {
+            dashed: false,
+            color: CrosshairColor::Gray,
+            thickness: 1.0,
+        }
+    }
+}
+
+impl CrosshairStyle {
+    fn stroke(&self) -> Stroke<'static> {
+        let stroke = Stroke::default()
+            .with_color(self.color.color())
+            .with_width(self.thickness);
+
+        if self.dashed {
+            Stroke {
+                line_dash: canvas::LineDash {
+                    segments: &[4.0, 4.0],
+                    offset: 0,
+                },
+                ..stroke
+            }
+        } else {
+            stroke
+        }
+    }
+}
+
+/// Colors a chart draws trades, candles and depth with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum ChartPalette {
+    #[default]
+    Classic,
+    ColorBlind,
+    Mono,
+}
+
+impl ChartPalette {
+    pub const ALL: [ChartPalette; 3] = [ChartPalette::Classic, ChartPalette::ColorBlind, ChartPalette::Mono];
+
+    fn buy(&self) -> Color {
+        match self {
+            ChartPalette::Classic => Color::from_rgb8(81, 205, 160),
+            ChartPalette::ColorBlind => Color::from_rgb8(70, 140, 230),
+            ChartPalette::Mono => Color::from_rgb8(210, 210, 210),
+        }
+    }
+
+    fn sell(&self) -> Color {
+        match self {
+            ChartPalette::Classic => Color::from_rgb8(192, 80, 77),
+            ChartPalette::ColorBlind => Color::from_rgb8(230, 150, 40),
+            ChartPalette::Mono => Color::from_rgb8(110, 110, 110),
+        }
+    }
+
+    fn bid(&self) -> Color {
+        match self {
+            ChartPalette::Classic => Color::from_rgb8(0, 144, 144),
+            ChartPalette::ColorBlind => Color::from_rgb8(40, 110, 200),
+            ChartPalette::Mono => Color::from_rgb8(180, 180, 180),
+        }
+    }
+
+    fn ask(&self) -> Color {
+        match self {
+            ChartPalette::Classic => Color::from_rgb8(192, 0, 192),
+            ChartPalette::ColorBlind => Color::from_rgb8(220, 120, 20),
+            ChartPalette::Mono => Color::from_rgb8(90, 90, 90),
+        }
+    }
+
+    /// Buy color when `up`, sell color otherwise
+    fn directional(&self, up: bool, alpha: f32) -> Color {
+        let color = if up { self.buy() } else { self.sell() };
+
+        Color { a: alpha, ..color }
+    }
+}
+
+impl std::fmt::Display for ChartPalette {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChartPalette::Classic => write!(f, "Classic"),
+            ChartPalette::ColorBlind => write!(f, "Color blind"),
+            ChartPalette::Mono => write!(f, "Mono"),
+        }
+    }
+}
+
+/// Tint for candles standing out against the previous bar, e.g. inside and outside bars
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum BarTint {
+    Amber,
+    Blue,
+    Purple,
+    White,
+}
+
+impl BarTint {
+    pub const ALL: [BarTint; 4] = [BarTint::Amber, BarTint::Blue, BarTint::Purple, BarTint::White];
+
+    fn color(&self) -> Color {
+        match self {
+            BarTint::Amber => Color::from_rgb8(230, 170, 50),
+            BarTint::Blue => Color::from_rgb8(80, 150, 240),
+            BarTint::Purple => Color::from_rgb8(170, 100, 220),
+            BarTint::White => Color::from_rgb8(235, 235, 235),
+        }
+    }
+}
+
+impl std::fmt::Display for BarTint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BarTint::Amber => write!(f, "Amber"),
+            BarTint::Blue => write!(f, "Blue"),
+            BarTint::Purple => write!(f, "Purple"),
+            BarTint::White => write!(f, "White"),
         }
     }
 }
@@ -88,6 +566,12 @@ pub enum Interaction {
     None,
     Zoomin { last_position: Point },
     Panning { translation: Vector, start: Point },
+    /// Dragging out a new retracement from the anchor price
+    FibDrawing { anchor: f32 },
+    /// Moving one end of the retracement, the other stays at the anchor price
+    FibEditing { anchor: f32 },
+    /// Right mouse button held, measuring from where it was pressed
+    Measuring { start: Point },
 }
 impl Default for Interaction {
     fn default() -> Self {
@@ -117,7 +601,7 @@ fn chart_button(theme: &Theme, _status: button::Status, is_active: bool) -> butt
 }
 
 // price steps, to be used for y-axis labels across all charts
-const PRICE_STEPS: [f32; 15] = [
+const PRICE_STEPS: [f32; 21] = [
     1000.0,
     500.0,
     200.0,
@@ -132,16 +616,39 @@ const PRICE_STEPS: [f32; 15] = [
     0.2,
     0.1,
     0.05,
+    0.02,
     0.01,
+    0.005,
+    0.002,
+    0.001,
+    0.0005,
+    0.0001,
 ];
-fn calculate_price_step(highest: f32, lowest: f32, labels_can_fit: i32) -> (f32, f32) {
+
+/// Grid bases a pane's y-axis labels can be forced onto
+pub const PRICE_STEP_BASES: [f32; 9] = [0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 5.0, 10.0, 25.0];
+
+/// Label step and the first level under `lowest`, never finer than `tick_size`
+/// as no price can land between two ticks
+fn calculate_price_step(highest: f32, lowest: f32, labels_can_fit: i32, grid_base: Option<f32>, tick_size: Option<f32>) -> (f32, f32) {
     let range = highest - lowest;
     let mut step = 1000.0; 
 
-    for &s in PRICE_STEPS.iter().rev() {
-        if range / s <= labels_can_fit as f32 {
-            step = s;
-            break;
+    // steps finer than f32 can resolve around these prices would repeat labels
+    let min_step = (highest.abs().max(lowest.abs()) * f32::EPSILON * 16.0)
+        .max(tick_size.unwrap_or(0.0));
+
+    if let Some(base) = grid_base.filter(|base| *base > 0.0) {
+        step = grid_price_step(base, range, labels_can_fit, min_step);
+    } else {
+        for &s in PRICE_STEPS.iter().rev() {
+            if s < min_step {
+                continue;
+            }
+            if range / s <= labels_can_fit as f32 {
+                step = s;
+                break;
+            }
         }
     }
     let rounded_lowest = (lowest / step).floor() * step;
@@ -149,6 +656,257 @@ fn calculate_price_step(highest: f32, lowest: f32, labels_can_fit: i32) -> (f32,
     (step, rounded_lowest)
 }
 
+/// Smallest step on a 1-2-4 progression of `base` multiples that fits the labels,
+/// so a 0.25 grid labels at 0.25, 0.5, 1, 2.5, 5, 10...
+fn grid_price_step(base: f32, range: f32, labels_can_fit: i32, min_step: f32) -> f32 {
+    let mut magnitude = base;
+
+    while magnitude < 1e9 {
+        for multiplier in [1.0, 2.0, 4.0] {
+            let step = magnitude * multiplier;
+
+            if step >= min_step && range / step <= labels_can_fit as f32 {
+                return step;
+            }
+        }
+        magnitude *= 10.0;
+    }
+
+    magnitude
+}
+
+/// Decimals needed so that labels one `step` apart never read the same
+fn step_decimals(step: f32) -> usize {
+    (-step.log10() - 1e-4).ceil().max(0.0) as usize
+}
+
+/// Decimals a min tick size is written with, so a 0.25 tick shows 2 where `step_decimals` gives 1
+fn tick_decimals(tick_size: f32) -> usize {
+    (0..8)
+        .find(|&decimals| {
+            let scaled = f64::from(tick_size) * 10f64.powi(decimals);
+            scaled.round() >= 1.0 && (scaled - scaled.round()).abs() < scaled * 1e-4
+        })
+        .map_or(8, |decimals| decimals as usize)
+}
+
+/// Guide lines a fixed number of ticks above and below the last price, following it
+fn draw_offset_guides(
+    frame: &mut canvas::Frame,
+    chart: &CommonChartData,
+    tick_size: f32,
+    width: f32,
+    y_of: impl Fn(f32) -> f32,
+) {
+    let (Some(ticks), Some(last_price)) = (chart.offset_guides, chart.last_price) else {
+        return;
+    };
+
+    let stroke = Stroke {
+        line_dash: canvas::LineDash {
+            segments: &[6.0, 4.0],
+            offset: 0,
+        },
+        ..Stroke::default().with_color(OFFSET_GUIDE_COLOR).with_width(1.0)
+    };
+
+    for (sign, price) in [("+", last_price.price + ticks * tick_size), ("-", last_price.price - ticks * tick_size)] {
+        let y_position = y_of(price);
+
+        frame.stroke(&Path::line(Point::new(0.0, y_position), Point::new(width, y_position)), stroke);
+
+        frame.fill_text(canvas::Text {
+            content: format!("{sign}{ticks}t"),
+            position: Point::new(width - 4.0, y_position - chart.label_size - 2.0),
+            size: iced::Pixels(chart.label_size),
+            color: OFFSET_GUIDE_COLOR,
+            horizontal_alignment: iced::alignment::Horizontal::Right,
+            ..canvas::Text::default()
+        });
+    }
+}
+
+/// Datapoint count and time of the latest one in the top left corner, when the debug overlay is on,
+/// followed by whatever else the chart wants to report
+fn draw_debug_overlay(frame: &mut canvas::Frame, chart: &CommonChartData, count: usize, last_time: Option<i64>, detail: Option<String>) {
+    if !chart.debug_overlay {
+        return;
+    }
+
+    let last_update = last_time
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .map_or("none".to_string(), |time| time.format("%H:%M:%S%.3f UTC").to_string());
+
+    frame.fill_text(canvas::Text {
+        content: match detail {
+            Some(detail) => format!("{count} datapoints, latest {last_update}, {detail}"),
+            None => format!("{count} datapoints, latest {last_update}"),
+        },
+        position: Point::new(4.0, 4.0),
+        size: iced::Pixels(chart.label_size),
+        color: DEBUG_OVERLAY_COLOR,
+        ..canvas::Text::default()
+    });
+}
+
+/// Share of the pinned range kept past a price that broke out of it
+const BREAKOUT_MARGIN: f32 = 0.05;
+
+const DEBUG_OVERLAY_COLOR: Color = Color { r: 0.85, g: 0.85, b: 0.5, a: 0.9 };
+
+const OFFSET_GUIDE_COLOR: Color = Color { r: 0.55, g: 0.75, b: 0.95, a: 0.8 };
+
+/// Span of time like `2d 4h`, `1h 30m` or `45m`, negative when measured backwards
+fn format_duration(millis: i64) -> String {
+    let sign = if millis < 0 { "-" } else { "" };
+    let minutes = millis.abs() / 60_000;
+
+    let (days, hours, minutes) = (minutes / 1440, minutes % 1440 / 60, minutes % 60);
+
+    if days > 0 {
+        format!("{sign}{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{sign}{hours}h {minutes}m")
+    } else {
+        format!("{sign}{minutes}m")
+    }
+}
+
+/// Formats a price with the instrument's precision if known
+fn format_price(price: f32, decimals: Option<usize>) -> String {
+    match decimals {
+        Some(decimals) => format!("{price:.decimals$}"),
+        None => price.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum PriceScale {
+    #[default]
+    Regular,
+    /// Change relative to a base price, usually the first visible bar
+    Percent,
+    Log,
+}
+
+impl PriceScale {
+    pub const ALL: [PriceScale; 3] = [PriceScale::Regular, PriceScale::Percent, PriceScale::Log];
+
+    /// Maps a price into the linear space the y-axis is drawn in
+    pub fn to_scale(self, price: f32, base: f32) -> f32 {
+        match self {
+            PriceScale::Regular => price,
+            PriceScale::Percent => (price / base - 1.0) * 100.0,
+            PriceScale::Log => price.ln(),
+        }
+    }
+
+    /// Inverse of `to_scale`
+    pub fn unscale(self, value: f32, base: f32) -> f32 {
+        match self {
+            PriceScale::Regular => value,
+            PriceScale::Percent => base * (1.0 + value / 100.0),
+            PriceScale::Log => value.exp(),
+        }
+    }
+
+    /// Y position of a price within an area of `height`, shared by the charts and their y-axis labels
+    pub fn y_position(self, price: f32, lowest: f32, highest: f32, base: f32, height: f32) -> f32 {
+        let (low, high) = (self.to_scale(lowest, base), self.to_scale(highest, base));
+
+        height - ((self.to_scale(price, base) - low) / (high - low) * height)
+    }
+
+    /// Price at a y position, inverse of `y_position`
+    pub fn price_at(self, y: f32, lowest: f32, highest: f32, base: f32, height: f32) -> f32 {
+        let (low, high) = (self.to_scale(lowest, base), self.to_scale(highest, base));
+
+        self.unscale(low + (high - low) * (height - y) / height, base)
+    }
+
+    /// Label levels as (price, shown value) pairs along with the label step,
+    /// `grid_base` and `tick_size` only apply to prices so percent labels keep the regular steps
+    fn label_levels(
+        self,
+        highest: f32,
+        lowest: f32,
+        base: f32,
+        labels_can_fit: i32,
+        grid_base: Option<f32>,
+        tick_size: Option<f32>,
+    ) -> (f32, Vec<(f32, f32)>) {
+        let mut levels = vec![];
+
+        match self {
+            PriceScale::Percent => {
+                let (low, high) = (self.to_scale(lowest, base), self.to_scale(highest, base));
+                let (step, rounded_lowest) = calculate_price_step(high, low, labels_can_fit, None, None);
+
+                let mut value = rounded_lowest;
+                let mut i = 0;
+                while value <= high {
+                    levels.push((self.unscale(value, base), value));
+                    i += 1;
+                    value = rounded_lowest + i as f32 * step;
+                }
+
+                (step, levels)
+            },
+            PriceScale::Regular | PriceScale::Log => {
+                let (step, rounded_lowest) = calculate_price_step(highest, lowest, labels_can_fit, grid_base, tick_size);
+
+                let mut price = rounded_lowest;
+                let mut i = 0;
+                while price <= highest {
+                    levels.push((price, price));
+                    i += 1;
+                    price = rounded_lowest + i as f32 * step;
+                }
+
+                (step, levels)
+            },
+        }
+    }
+
+    /// Text of a y-axis label `step` away from its neighbours, with at least the instrument's decimals
+    fn label(self, value: f32, step: f32, price_decimals: Option<usize>) -> String {
+        match self {
+            PriceScale::Percent => format!("{value:.*}%", step_decimals(step)),
+            _ => format!("{value:.*}", price_decimals.unwrap_or(0).max(step_decimals(step))),
+        }
+    }
+}
+
+impl std::fmt::Display for PriceScale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PriceScale::Regular => write!(f, "Regular"),
+            PriceScale::Percent => write!(f, "Percent"),
+            PriceScale::Log => write!(f, "Logarithmic"),
+        }
+    }
+}
+
+/// Fibonacci retracement between two prices, 0% sits at `high` and 100% at `low`
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct FibDraw {
+    pub high: f32,
+    pub low: f32,
+}
+
+impl FibDraw {
+    const LEVELS: [f32; 6] = [0.0, 0.236, 0.382, 0.5, 0.618, 1.0];
+
+    pub fn new(from: f32, to: f32) -> Self {
+        Self { high: from.max(to), low: from.min(to) }
+    }
+
+    /// Each level's ratio with its price
+    fn levels(&self) -> impl Iterator<Item = (f32, f32)> + '_ {
+        Self::LEVELS.iter().map(|ratio| (*ratio, self.high - (self.high - self.low) * ratio))
+    }
+}
+
 // time steps in ms, to be used for x-axis labels on candlesticks and footprint charts
 const M1_TIME_STEPS: [i64; 9] = [
     1000 * 60 * 720, // 12 hour
@@ -183,6 +941,27 @@ const M5_TIME_STEPS: [i64; 9] = [
     1000 * 60 * 15, // 15 min
     1000 * 60 * 5, // 5 min
 ];
+const H1_TIME_STEPS: [i64; 9] = [
+    1000 * 60 * 10080, // 7 day
+    1000 * 60 * 4320, // 3 day
+    1000 * 60 * 2880, // 2 day
+    1000 * 60 * 1440, // 24 hour
+    1000 * 60 * 720, // 12 hour
+    1000 * 60 * 360, // 6 hour
+    1000 * 60 * 240, // 4 hour
+    1000 * 60 * 120, // 2 hour
+    1000 * 60 * 60, // 1 hour
+];
+const D1_TIME_STEPS: [i64; 6] = [
+    1000 * 60 * 1440 * 90, // 90 day
+    1000 * 60 * 1440 * 30, // 30 day
+    1000 * 60 * 1440 * 14, // 14 day
+    1000 * 60 * 1440 * 7, // 7 day
+    1000 * 60 * 1440 * 2, // 2 day
+    1000 * 60 * 1440, // 1 day
+];
+/// Steps of a day or longer are labeled with the date instead of the time
+const DAY_MS: i64 = 1000 * 60 * 1440;
 
 // time steps in ms, to be used for x-axis labels on heatmap chart
 const TIME_STEPS: [i64; 8] = [
@@ -206,6 +985,9 @@ fn calculate_time_step(earliest: i64, latest: i64, labels_can_fit: i32, timefram
             5 => &M5_TIME_STEPS,
             15 => &M5_TIME_STEPS[..7],
             30 => &M5_TIME_STEPS[..6],
+            60 => &H1_TIME_STEPS,
+            240 => &H1_TIME_STEPS[..7],
+            1440 => &D1_TIME_STEPS,
             _ => &M1_TIME_STEPS,
         };
 
@@ -247,6 +1029,7 @@ pub struct AxisLabelXCanvas<'a> {
     min: i64,
     max: i64,
     timeframe: Option<u16>,
+    label_size: f32,
 }
 impl canvas::Program<Message> for AxisLabelXCanvas<'_> {
     type State = Interaction;
@@ -275,7 +1058,9 @@ impl canvas::Program<Message> for AxisLabelXCanvas<'_> {
         let latest_in_millis = self.max; 
         let earliest_in_millis = self.min; 
 
-        let x_labels_can_fit = (bounds.width / 192.0) as i32;
+        let label_scale = self.label_size / DEFAULT_LABEL_SIZE;
+
+        let x_labels_can_fit = (bounds.width / (192.0 * label_scale)) as i32;
 
         let (time_step, rounded_earliest) = calculate_time_step(earliest_in_millis, latest_in_millis, x_labels_can_fit, self.timeframe);
         
@@ -291,11 +1076,13 @@ impl canvas::Program<Message> for AxisLabelXCanvas<'_> {
                     }
 
                     if x_position >= 0.0 && x_position <= bounds.width as f64 {
-                        let text_size = 12.0;
+                        let text_size = self.label_size;
                         let time_as_datetime = NaiveDateTime::from_timestamp(time / 1000, 0);
                         
                         let time_format: &str;
-                        if self.timeframe.is_some() {
+                        if self.timeframe.is_some() && time_step >= DAY_MS {
+                            time_format = "%d %b";
+                        } else if self.timeframe.is_some() {
                             time_format = "%H:%M";
                         } else {
                             time_format = "%M:%S";
@@ -303,7 +1090,7 @@ impl canvas::Program<Message> for AxisLabelXCanvas<'_> {
 
                         let label = canvas::Text {
                             content: time_as_datetime.format(time_format).to_string(),
-                            position: Point::new(x_position as f32 - (text_size*4.0/3.0), bounds.height - 20.0),
+                            position: Point::new(x_position as f32 - (text_size*4.0/3.0), bounds.height - (text_size + 8.0)),
                             size: iced::Pixels(text_size),
                             color: Color::from_rgba8(200, 200, 200, 1.0),
                             ..canvas::Text::default()
@@ -337,7 +1124,9 @@ impl canvas::Program<Message> for AxisLabelXCanvas<'_> {
                     let rounded_time = NaiveDateTime::from_timestamp(rounded_timestamp, 0);
         
                     let snap_ratio = (rounded_timestamp as f64 * 1000.0 - earliest_in_millis as f64) / (latest_in_millis as f64 - earliest_in_millis as f64);
-                    (snap_ratio, rounded_time.format("%H:%M").to_string())
+                    let time_format = if i64::from(timeframe) * 60_000 >= DAY_MS { "%d %b" } else { "%H:%M" };
+
+                    (snap_ratio, rounded_time.format(time_format).to_string())
                 } else {
                     let crosshair_millis = (crosshair_millis / 100.0).round() * 100.0;
                     let crosshair_time = NaiveDateTime::from_timestamp((crosshair_millis / 1000.0).floor() as i64, ((crosshair_millis % 1000.0) * 1_000_000.0).round() as u32);
@@ -353,15 +1142,13 @@ impl canvas::Program<Message> for AxisLabelXCanvas<'_> {
                     return;
                 }
         
-                let text_size = 12.0;
+                let text_size = self.label_size;
                 let growth_amount = 6.0;
-                let (rectangle_position, text_position) = if self.timeframe.is_some() {
-                    (Point::new(snap_x as f32 - 14.0 - growth_amount, bounds.height - 20.0),
-                     Point::new(snap_x as f32 - 14.0, bounds.height - 20.0))
-                } else {
-                    (Point::new(snap_x as f32 - 26.0 - growth_amount, bounds.height - 20.0),
-                     Point::new(snap_x as f32 - 26.0, bounds.height - 20.0))
-                };
+                let text_y = bounds.height - (text_size + 8.0);
+                let half_width = if self.timeframe.is_some() { 14.0 } else { 26.0 } * label_scale;
+
+                let rectangle_position = Point::new(snap_x as f32 - half_width - growth_amount, text_y);
+                let text_position = Point::new(snap_x as f32 - half_width, text_y);
         
                 let text_background = canvas::Path::rectangle(rectangle_position, Size::new(text_content.len() as f32 * text_size/2.0 + 2.0 * growth_amount + 1.0, text_size + text_size/2.0));
                 frame.fill(&text_background, Color::from_rgba8(200, 200, 200, 1.0));
@@ -395,7 +1182,9 @@ impl canvas::Program<Message> for AxisLabelXCanvas<'_> {
             Interaction::None if cursor.is_over(bounds) => {
                 mouse::Interaction::ResizingHorizontally
             }
-            Interaction::None => mouse::Interaction::default(),
+            Interaction::None | Interaction::FibDrawing { .. } | Interaction::FibEditing { .. } | Interaction::Measuring { .. } => {
+                mouse::Interaction::default()
+            }
         }
     }
 }
@@ -407,6 +1196,18 @@ pub struct AxisLabelYCanvas<'a> {
     max: f32,
     crosshair_position: Point,
     crosshair: bool,
+    price_scale: PriceScale,
+    base_price: f32,
+    volume_ratio: f32,
+    min_tick_size: Option<f32>,
+    price_decimals: Option<usize>,
+    /// Pinned at its y position when the latest price ruler is on
+    last_price: Option<LastPrice>,
+    /// Time left on the forming bar, drawn under the latest price
+    bar_countdown: Option<String>,
+    palette: ChartPalette,
+    label_size: f32,
+    price_step: Option<f32>,
 }
 impl canvas::Program<Message> for AxisLabelYCanvas<'_> {
     type State = Interaction;
@@ -484,23 +1285,21 @@ impl canvas::Program<Message> for AxisLabelYCanvas<'_> {
             return vec![];
         }
 
-        let y_labels_can_fit = (bounds.height / 32.0) as i32;
-        let (step, rounded_lowest) = calculate_price_step(self.max, self.min, y_labels_can_fit);
+        let y_labels_can_fit = (bounds.height / (32.0 * self.label_size / DEFAULT_LABEL_SIZE)) as i32;
+        let (step, levels) = self.price_scale.label_levels(
+            self.max, self.min, self.base_price, y_labels_can_fit, self.price_step, self.min_tick_size
+        );
 
-        let volume_area_height = bounds.height / 8.0; 
+        let volume_area_height = bounds.height * self.volume_ratio; 
         let candlesticks_area_height = bounds.height - volume_area_height;
 
         let labels = self.labels_cache.draw(renderer, bounds.size(), |frame| {
             frame.with_save(|frame| {
-                let y_range = self.max - self.min;
-                let mut y = rounded_lowest;
-
-                while y <= self.max {
-                    let y_position = candlesticks_area_height - ((y - self.min) / y_range * candlesticks_area_height);
+                for &(price, y) in &levels {
+                    let y_position = self.price_scale.y_position(price, self.min, self.max, self.base_price, candlesticks_area_height);
 
-                    let text_size = 12.0;
-                    let decimal_places = if step < 0.5 { 2 } else { usize::from(step < 1.0) };
-                    let label_content = format!("{y:.decimal_places$}");
+                    let text_size = self.label_size;
+                    let label_content = self.price_scale.label(y, step, self.price_decimals);
                     let label = canvas::Text {
                         content: label_content,
                         position: Point::new(10.0, y_position - text_size / 2.0),
@@ -512,17 +1311,21 @@ impl canvas::Program<Message> for AxisLabelYCanvas<'_> {
                     label.draw_with(|path, color| {
                         frame.fill(&path, color);
                     });
-
-                    y += step;
                 }
             });
         });
         let crosshair = self.y_croshair_cache.draw(renderer, bounds.size(), |frame| {
             if self.crosshair && self.crosshair_position.y > 0.0 {
-                let text_size = 12.0;
-                let y_range = self.max - self.min;
-                let decimal_places = if step < 1.0 { 2 } else { 1 };
-                let label_content = format!("{:.*}", decimal_places, self.min + (y_range * (candlesticks_area_height - self.crosshair_position.y) / candlesticks_area_height));
+                let text_size = self.label_size;
+                let decimal_places = self.price_decimals
+                    .unwrap_or(if step < 1.0 { step_decimals(step).max(2) } else { 1 });
+                let crosshair_price = self.price_scale.price_at(
+                    self.crosshair_position.y, self.min, self.max, self.base_price, candlesticks_area_height
+                );
+                let label_content = match self.price_scale {
+                    PriceScale::Percent => format!("{:.2}%", self.price_scale.to_scale(crosshair_price, self.base_price)),
+                    _ => format!("{:.*}", decimal_places, crosshair_price),
+                };
                 
                 let growth_amount = 3.0; 
                 let rectangle_position = Point::new(8.0 - growth_amount, self.crosshair_position.y - text_size / 2.0 - 3.0);
@@ -545,7 +1348,52 @@ impl canvas::Program<Message> for AxisLabelYCanvas<'_> {
             }
         });
 
-        vec![labels, crosshair]
+        // redrawn every frame as the price moves far more often than the labels
+        let mut price_ruler = canvas::Frame::new(renderer, bounds.size());
+
+        if let Some(last_price) = self.last_price {
+            let y_position = self.price_scale.y_position(
+                last_price.price, self.min, self.max, self.base_price, candlesticks_area_height
+            );
+
+            if (0.0..=candlesticks_area_height).contains(&y_position) {
+                let text_size = self.label_size + 1.0;
+                let label_content = match self.price_scale {
+                    PriceScale::Percent => format!("{:.2}%", self.price_scale.to_scale(last_price.price, self.base_price)),
+                    _ => format_price(last_price.price, self.price_decimals),
+                };
+
+                let color = self.palette.directional(last_price.rising, 1.0);
+
+                let countdown_height = if self.bar_countdown.is_some() { self.label_size + 2.0 } else { 0.0 };
+
+                price_ruler.fill_rectangle(
+                    Point::new(0.0, y_position - text_size / 2.0 - 3.0), 
+                    Size::new(bounds.width, text_size + 6.0 + countdown_height), 
+                    color
+                );
+
+                price_ruler.fill_text(canvas::Text {
+                    content: label_content,
+                    position: Point::new(6.0, y_position - text_size / 2.0 - 1.0),
+                    size: iced::Pixels(text_size),
+                    color: Color::from_rgba8(0, 0, 0, 1.0),
+                    ..canvas::Text::default()
+                });
+
+                if let Some(countdown) = &self.bar_countdown {
+                    price_ruler.fill_text(canvas::Text {
+                        content: countdown.clone(),
+                        position: Point::new(6.0, y_position + text_size / 2.0 + 1.0),
+                        size: iced::Pixels(self.label_size),
+                        color: Color::from_rgba8(0, 0, 0, 0.8),
+                        ..canvas::Text::default()
+                    });
+                }
+            }
+        }
+
+        vec![labels, price_ruler.into_geometry(), crosshair]
     }
 
     fn mouse_interaction(
@@ -560,7 +1408,110 @@ impl canvas::Program<Message> for AxisLabelYCanvas<'_> {
             Interaction::None if cursor.is_over(bounds) => {
                 mouse::Interaction::ResizingVertically
             }
-            Interaction::None => mouse::Interaction::default(),
+            Interaction::None | Interaction::FibDrawing { .. } | Interaction::FibEditing { .. } | Interaction::Measuring { .. } => {
+                mouse::Interaction::default()
+            }
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() <= b.abs() * 1e-4, "{a} != {b}");
+    }
+
+    #[test]
+    fn log_scale_round_trips() {
+        for price in [0.0001, 0.5, 1.0, 42.0, 27_350.5, 68_000.0] {
+            assert_close(PriceScale::Log.unscale(PriceScale::Log.to_scale(price, 1.0), 1.0), price);
+        }
+    }
+
+    #[test]
+    fn log_scale_positions_round_trip() {
+        let (lowest, highest, height) = (20_000.0, 80_000.0, 600.0);
+
+        for price in [20_000.0, 25_000.0, 40_000.0, 79_999.0] {
+            let y = PriceScale::Log.y_position(price, lowest, highest, 1.0, height);
+            assert_close(PriceScale::Log.price_at(y, lowest, highest, 1.0, height), price);
+        }
+
+        // equal ratios take up equal heights
+        let y_of = |price| PriceScale::Log.y_position(price, lowest, highest, 1.0, height);
+        assert_close(y_of(20_000.0) - y_of(40_000.0), y_of(40_000.0) - y_of(80_000.0));
+    }
+
+    #[test]
+    fn percent_scale_round_trips() {
+        let base = 64_000.0;
+
+        assert_close(PriceScale::Percent.to_scale(base, base) + 1.0, 1.0);
+        assert_close(PriceScale::Percent.to_scale(70_400.0, base), 10.0);
+
+        for price in [32_000.0, 64_000.0, 70_400.0] {
+            assert_close(PriceScale::Percent.unscale(PriceScale::Percent.to_scale(price, base), base), price);
+        }
+    }
+
+    fn labels(highest: f32, lowest: f32, labels_can_fit: i32, grid_base: Option<f32>, tick_size: Option<f32>) -> Vec<String> {
+        let scale = PriceScale::Regular;
+        let (step, levels) = scale.label_levels(highest, lowest, 1.0, labels_can_fit, grid_base, tick_size);
+
+        levels.into_iter()
+            .map(|(_, value)| scale.label(value, step, tick_size.map(tick_decimals)))
+            .collect()
+    }
+
+    fn assert_distinct(labels: &[String]) {
+        for pair in labels.windows(2) {
+            assert_ne!(pair[0], pair[1], "{labels:?}");
+        }
+    }
+
+    #[test]
+    fn tick_decimals_follow_the_tick_size() {
+        for (tick_size, decimals) in [(5.0, 0), (1.0, 0), (0.5, 1), (0.25, 2), (0.1, 1), (0.01, 2), (0.005, 3), (0.0001, 4)] {
+            assert_eq!(tick_decimals(tick_size), decimals, "{tick_size}");
+        }
+    }
+
+    #[test]
+    fn labels_keep_the_instrument_decimals() {
+        assert!(labels(64_010.0, 64_000.0, 10, None, Some(0.1)).iter().all(|label| label.ends_with(".0")));
+        assert!(labels(1.01, 1.0, 10, None, Some(0.0001)).iter().all(|label| label.len() == "1.0000".len()));
+    }
+
+    #[test]
+    fn labels_never_step_finer_than_a_tick() {
+        for tick_size in [0.01, 0.1, 0.25, 0.5, 1.0] {
+            // a range of a couple ticks with room for plenty of labels
+            let labels = labels(100.0 + tick_size * 2.0, 100.0, 40, None, Some(tick_size));
+
+            assert!(labels.len() <= 3, "{tick_size}: {labels:?}");
+            assert_distinct(&labels);
+        }
+    }
+
+    #[test]
+    fn extreme_zoom_labels_stay_distinct() {
+        for (lowest, range) in [(68_000.0, 0.05), (68_000.0, 0.5), (42_000.0, 2.0), (1.2345, 0.0002), (0.00012, 0.000_005)] {
+            assert_distinct(&labels(lowest + range, lowest, 12, None, None));
+        }
+    }
+
+    #[test]
+    fn quarter_grid_labels_land_on_quarters() {
+        let (step, levels) = PriceScale::Regular.label_levels(5_003.0, 5_000.1, 1.0, 12, Some(0.25), Some(0.25));
+
+        assert_eq!(step, 0.25);
+        for (price, _) in levels {
+            assert_close((price / 0.25).round() * 0.25, price);
+        }
+
+        // wider ranges keep to multiples of the base
+        let (step, _) = PriceScale::Regular.label_levels(5_100.0, 5_000.0, 1.0, 12, Some(0.25), None);
+        assert_close((step / 0.25).round() * 0.25, step);
+    }
+}
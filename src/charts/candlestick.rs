@@ -1,17 +1,210 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, time::Duration};
 use iced::{
-    alignment, mouse, widget::{button, canvas::{self, event::{self, Event}, stroke::Stroke, Canvas, Geometry, Path}}, Color, Element, Length, Point, Rectangle, Renderer, Size, Theme
+    alignment, mouse, widget::{button, canvas::{self, event::{self, Event}, stroke::Stroke, Cache, Canvas, Geometry, Path}}, Color, Element, Length, Point, Rectangle, Renderer, Size, Theme
 };
 use iced::widget::{Column, Row, Container, Text};
+use serde::{Deserialize, Serialize};
 use crate::data_providers::Kline;
 
-use super::{Chart, CommonChartData, Message, Interaction, AxisLabelXCanvas, AxisLabelYCanvas};
-use super::{chart_button, calculate_price_step, calculate_time_step};
+use super::{Chart, CommonChartData, Message, Interaction, AxisLabelXCanvas, AxisLabelYCanvas, CrosshairStyle, ChartPalette, BarTint, FibDraw, PriceScale, MAX_VOLUME_RATIO, tick_decimals, format_price, draw_offset_guides, LastPrice, draw_debug_overlay};
+use super::{chart_button, calculate_time_step};
+
+/// How far back the ghost overlay's klines are taken from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum GhostOffset {
+    Day,
+    TwoDays,
+    Week,
+}
+
+impl GhostOffset {
+    pub const ALL: [GhostOffset; 3] = [GhostOffset::Day, GhostOffset::TwoDays, GhostOffset::Week];
+
+    pub fn duration(&self) -> Duration {
+        match self {
+            GhostOffset::Day => Duration::from_secs(24 * 3600),
+            GhostOffset::TwoDays => Duration::from_secs(2 * 24 * 3600),
+            GhostOffset::Week => Duration::from_secs(7 * 24 * 3600),
+        }
+    }
+}
+
+impl std::fmt::Display for GhostOffset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GhostOffset::Day => write!(f, "1 day ago"),
+            GhostOffset::TwoDays => write!(f, "2 days ago"),
+            GhostOffset::Week => write!(f, "1 week ago"),
+        }
+    }
+}
+
+/// UTC hours a trading session spans, the end is exclusive and wraps past midnight when it's not after the start
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SessionHours {
+    pub start: u8,
+    pub end: u8,
+}
+
+impl SessionHours {
+    /// Start and end of the session opening on the day starting at `day_start`
+    fn span(&self, day_start: i64) -> (i64, i64) {
+        let start = day_start + i64::from(self.start % 24) * 3_600_000;
+        let mut end = day_start + i64::from(self.end % 24) * 3_600_000;
+
+        if end <= start {
+            end += 86_400_000;
+        }
+        (start, end)
+    }
+}
+
+/// Hours of the sessions shaded behind candlesticks, shared by every chart
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Sessions {
+    pub asia: SessionHours,
+    pub london: SessionHours,
+    pub new_york: SessionHours,
+}
+
+impl Default for Sessions {
+    fn default() -> Self {
+        Self {
+            asia: SessionHours { start: 0, end: 8 },
+            london: SessionHours { start: 7, end: 16 },
+            new_york: SessionHours { start: 13, end: 21 },
+        }
+    }
+}
+
+impl Sessions {
+    pub const HOURS: [u8; 24] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23];
+
+    const ASIA_COLOR: Color = Color { r: 0.35, g: 0.55, b: 0.95, a: 0.06 };
+    const LONDON_COLOR: Color = Color { r: 0.95, g: 0.8, b: 0.35, a: 0.05 };
+    const NEW_YORK_COLOR: Color = Color { r: 0.45, g: 0.9, b: 0.55, a: 0.05 };
+
+    fn shaded(&self) -> [(SessionHours, Color); 3] {
+        [
+            (self.asia, Self::ASIA_COLOR),
+            (self.london, Self::LONDON_COLOR),
+            (self.new_york, Self::NEW_YORK_COLOR),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum MaKind {
+    Sma,
+    Ema,
+}
+
+impl MaKind {
+    pub const ALL: [MaKind; 2] = [MaKind::Sma, MaKind::Ema];
+}
+
+impl std::fmt::Display for MaKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MaKind::Sma => write!(f, "SMA"),
+            MaKind::Ema => write!(f, "EMA"),
+        }
+    }
+}
+
+/// Moving average of closes drawn over the candles
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct MaConfig {
+    pub period: usize,
+    pub kind: MaKind,
+    pub color: BarTint,
+}
+
+impl MaConfig {
+    /// Moving averages a pane can have at once
+    pub const SLOTS: usize = 3;
+    pub const PERIOD_RANGE: std::ops::RangeInclusive<u32> = 2..=200;
+
+    /// Next one added to a pane, colored apart from the ones in the earlier slots
+    pub fn for_slot(slot: usize) -> Self {
+        Self {
+            period: [20, 50, 100][slot % 3],
+            kind: MaKind::Ema,
+            color: BarTint::ALL[slot % BarTint::ALL.len()],
+        }
+    }
+
+    /// Values at each close from the first full period on, none if there aren't enough bars
+    fn values(&self, closes: &[(i64, f32)]) -> Vec<(i64, f32)> {
+        let period = self.period;
+
+        if period == 0 || closes.len() < period {
+            return vec![];
+        }
+
+        let seed = closes[..period].iter().map(|(_, close)| f64::from(*close)).sum::<f64>() / period as f64;
+        let mut values = Vec::with_capacity(closes.len() - period + 1);
+        values.push((closes[period - 1].0, seed as f32));
+
+        match self.kind {
+            MaKind::Sma => {
+                let mut sum = seed * period as f64;
+
+                for (index, (time, close)) in closes.iter().enumerate().skip(period) {
+                    sum += f64::from(*close) - f64::from(closes[index - period].1);
+                    values.push((*time, (sum / period as f64) as f32));
+                }
+            },
+            MaKind::Ema => {
+                let smoothing = 2.0 / (period as f64 + 1.0);
+                let mut ema = seed;
+
+                for (time, close) in &closes[period..] {
+                    ema += (f64::from(*close) - ema) * smoothing;
+                    values.push((*time, ema as f32));
+                }
+            },
+        }
+
+        values
+    }
+}
 
 pub struct CandlestickChart {
     chart: CommonChartData,
     data_points: BTreeMap<i64, Kline>,
     timeframe: u16,
+    price_scale: PriceScale,
+    base_price: f32,
+    mark_doji: bool,
+    /// Tints of (inside, outside) bars, `None` draws them like any other
+    bar_patterns: Option<(BarTint, BarTint)>,
+    fib: Option<FibDraw>,
+    min_tick_size: Option<f32>,
+    /// Next left drag draws a retracement instead of panning
+    fib_tool: bool,
+    ghost_offset: Option<Duration>,
+    /// Earlier klines keyed by their time shifted forward by `ghost_offset`
+    ghost_klines: BTreeMap<i64, Kline>,
+    /// Shades the background by how much volume traded at each price of the visible bars
+    volume_heat: bool,
+    volume_heat_cache: Cache,
+    /// Visible volume by price along the right edge
+    show_volume_profile: bool,
+    volume_profile_cache: Cache,
+    moving_averages: Vec<MaConfig>,
+    /// Each moving average's values over the visible bars and one past either edge
+    ma_lines: Vec<(BarTint, Vec<(i64, f32)>)>,
+    ma_cache: Cache,
+    /// Dashed line across the chart at the forming bar's close
+    price_line: bool,
+    /// Time until the forming bar closes, under the latest price label
+    bar_countdown: bool,
+    /// Session bands shaded behind the candles, `None` is off
+    sessions: Option<Sessions>,
+    /// Empty bars kept ahead of the latest one when panned all the way right
+    right_margin: u32,
 }
 
 impl Chart for CandlestickChart {
@@ -28,6 +221,24 @@ impl Chart for CandlestickChart {
 impl CandlestickChart {
     const MIN_SCALING: f32 = 0.1;
     const MAX_SCALING: f32 = 2.0;
+    /// Pixels kept free between neighbouring candle bodies
+    const MIN_BAR_GAP: f32 = 1.0;
+    /// Bodies thinner than this are drawn as a line so flat bars stay visible
+    const MIN_BODY_HEIGHT: f32 = 1.0;
+    /// Body to range ratio at or below which a bar counts as a doji
+    const DOJI_BODY_RATIO: f32 = 0.1;
+    /// How close to a retracement's end line a press has to land to grab it
+    const FIB_GRAB_DISTANCE: f32 = 5.0;
+    const FIB_COLOR: Color = Color { r: 0.85, g: 0.65, b: 0.25, a: 0.8 };
+    const GHOST_ALPHA: f32 = 0.2;
+    const HEAT_COLOR: Color = Color { r: 0.95, g: 0.55, b: 0.2, a: 0.3 };
+    /// Share of the chart width the largest profile level spans
+    const PROFILE_WIDTH_RATIO: f32 = 0.2;
+    /// Levels are widened by whole ticks until the visible range fits in this many
+    const MAX_PROFILE_LEVELS: f32 = 200.0;
+    const PROFILE_ALPHA: f32 = 0.35;
+    pub const DEFAULT_RIGHT_MARGIN: u32 = 3;
+    pub const RIGHT_MARGIN_RANGE: std::ops::RangeInclusive<u32> = 0..=50;
 
     pub fn new(klines: Vec<Kline>, timeframe: u16) -> CandlestickChart {
         let mut klines_raw = BTreeMap::new();
@@ -36,19 +247,361 @@ impl CandlestickChart {
             klines_raw.insert(kline.time as i64, kline);
         }
 
+        let mut chart = CommonChartData::default();
+
+        if let Some(kline) = klines_raw.values().last() {
+            chart.last_price = Some(LastPrice { price: kline.close, rising: kline.close >= kline.open });
+        }
+
         CandlestickChart {
-            chart: CommonChartData::default(),
+            chart,
             data_points: klines_raw,
             timeframe,
+            price_scale: PriceScale::default(),
+            base_price: 0.0,
+            mark_doji: false,
+            bar_patterns: None,
+            fib: None,
+            min_tick_size: None,
+            fib_tool: false,
+            ghost_offset: None,
+            ghost_klines: BTreeMap::new(),
+            volume_heat: false,
+            volume_heat_cache: Cache::default(),
+            show_volume_profile: false,
+            volume_profile_cache: Cache::default(),
+            moving_averages: vec![],
+            ma_lines: vec![],
+            ma_cache: Cache::default(),
+            price_line: false,
+            bar_countdown: false,
+            sessions: None,
+            right_margin: Self::DEFAULT_RIGHT_MARGIN,
         }
     }
 
-    pub fn update_latest_kline(&mut self, kline: &Kline) {
-        self.data_points.insert(kline.time as i64, *kline);
+    pub fn set_crosshair_style(&mut self, crosshair_style: CrosshairStyle) {
+        self.chart.crosshair_style = crosshair_style;
+
+        self.chart.crosshair_cache.clear();
+    }
+
+    pub fn set_min_tick_size(&mut self, min_tick_size: f32) {
+        self.chart.min_tick_size = Some(min_tick_size);
+        self.chart.price_decimals = Some(tick_decimals(min_tick_size));
+        self.min_tick_size = Some(min_tick_size);
+
+        self.chart.main_cache.clear();
+        self.chart.crosshair_cache.clear();
+        self.chart.y_labels_cache.clear();
+        self.chart.y_crosshair_cache.clear();
+    }
+
+    pub fn set_mark_doji(&mut self, mark_doji: bool) {
+        self.mark_doji = mark_doji;
 
+        self.chart.main_cache.clear();
+    }
+
+    pub fn set_volume_heat(&mut self, enabled: bool) {
+        self.volume_heat = enabled;
+
+        self.volume_heat_cache.clear();
+        self.volume_profile_cache.clear();
+    }
+
+    pub fn set_sessions(&mut self, sessions: Option<Sessions>) {
+        self.sessions = sessions;
+
+        self.chart.mesh_cache.clear();
+    }
+
+    pub fn set_moving_averages(&mut self, moving_averages: &[Option<MaConfig>]) {
+        self.moving_averages = moving_averages.iter().flatten().copied().collect();
+
+        if self.moving_averages.is_empty() {
+            self.ma_lines.clear();
+        }
         self.render_start();
     }
 
+    fn calculate_moving_averages(&mut self, earliest: i64, latest: i64) {
+        self.ma_cache.clear();
+
+        if self.moving_averages.is_empty() {
+            return;
+        }
+
+        let closes: Vec<(i64, f32)> = self.data_points.iter()
+            .map(|(time, kline)| (*time, kline.close))
+            .collect();
+
+        self.ma_lines = self.moving_averages.iter()
+            .map(|ma| {
+                let values = ma.values(&closes);
+
+                let start = values.partition_point(|(time, _)| *time < earliest).saturating_sub(1);
+                let end = (values.partition_point(|(time, _)| *time <= latest) + 1).min(values.len());
+
+                (ma.color, values.get(start..end).map_or(vec![], <[_]>::to_vec))
+            })
+            .collect();
+    }
+
+    pub fn set_right_margin(&mut self, bars: u32) {
+        self.right_margin = bars;
+
+        self.render_start();
+    }
+
+    pub fn set_bar_countdown(&mut self, enabled: bool) {
+        self.bar_countdown = enabled;
+    }
+
+    pub fn has_bar_countdown(&self) -> bool {
+        self.bar_countdown
+    }
+
+    /// Time left until the forming bar closes by the wall clock, `None` when off
+    fn bar_countdown(&self) -> Option<String> {
+        if !self.bar_countdown {
+            return None;
+        }
+
+        let (last_open, _) = self.data_points.last_key_value()?;
+        let close_time = last_open + i64::from(self.timeframe) * 60_000;
+
+        let remaining_secs = ((close_time - chrono::Utc::now().timestamp_millis()) / 1000).max(0);
+        let (hours, minutes, seconds) = (remaining_secs / 3600, (remaining_secs % 3600) / 60, remaining_secs % 60);
+
+        if hours > 0 {
+            Some(format!("{hours}:{minutes:02}:{seconds:02}"))
+        } else {
+            Some(format!("{minutes:02}:{seconds:02}"))
+        }
+    }
+
+    pub fn set_price_line(&mut self, enabled: bool) {
+        self.price_line = enabled;
+
+        self.chart.main_cache.clear();
+    }
+
+    pub fn set_bar_patterns(&mut self, bar_patterns: Option<(BarTint, BarTint)>) {
+        self.bar_patterns = bar_patterns;
+
+        self.chart.main_cache.clear();
+    }
+
+    pub fn set_offset_guides(&mut self, offset_guides: Option<f32>) {
+        self.chart.offset_guides = offset_guides;
+
+        self.chart.main_cache.clear();
+    }
+
+    pub fn set_ghost_offset(&mut self, ghost_offset: Option<Duration>) {
+        if ghost_offset != self.ghost_offset {
+            self.ghost_klines.clear();
+        }
+        self.ghost_offset = ghost_offset;
+
+        self.chart.main_cache.clear();
+    }
+
+    /// Klines from `ghost_offset` ago, shifted onto the current x-axis
+    pub fn set_ghost_klines(&mut self, klines: Vec<Kline>) {
+        let Some(offset) = self.ghost_offset else {
+            return;
+        };
+        let offset_ms = offset.as_millis() as i64;
+
+        self.ghost_klines = klines.into_iter()
+            .map(|kline| (kline.time as i64 + offset_ms, kline))
+            .collect();
+
+        self.chart.main_cache.clear();
+    }
+
+    pub fn set_fib(&mut self, fib: Option<FibDraw>) {
+        self.fib = fib;
+
+        self.chart.main_cache.clear();
+    }
+
+    pub fn get_fib(&self) -> Option<FibDraw> {
+        self.fib
+    }
+
+    /// Price under a y coordinate of the candlestick area
+    fn price_at(&self, y: f32, bounds: Rectangle) -> f32 {
+        let chart = self.get_common_data();
+        let candlesticks_area_height = bounds.height * (1.0 - chart.volume_ratio);
+
+        self.price_scale.price_at(y, chart.y_min_price, chart.y_max_price, self.base_price, candlesticks_area_height)
+    }
+
+    /// Price of the retracement end opposite to the one at `y`, if one is close enough to grab
+    fn fib_anchor_at(&self, y: f32, bounds: Rectangle) -> Option<f32> {
+        let chart = self.get_common_data();
+        let fib = self.fib?;
+
+        let candlesticks_area_height = bounds.height * (1.0 - chart.volume_ratio);
+        let y_of = |price: f32| self.price_scale.y_position(price, chart.y_min_price, chart.y_max_price, self.base_price, candlesticks_area_height);
+
+        if (y_of(fib.high) - y).abs() <= Self::FIB_GRAB_DISTANCE {
+            Some(fib.low)
+        } else if (y_of(fib.low) - y).abs() <= Self::FIB_GRAB_DISTANCE {
+            Some(fib.high)
+        } else {
+            None
+        }
+    }
+
+    pub fn set_price_ruler(&mut self, enabled: bool) {
+        self.chart.price_ruler = enabled;
+    }
+
+    pub fn set_debug_overlay(&mut self, enabled: bool) {
+        self.chart.debug_overlay = enabled;
+
+        self.chart.main_cache.clear();
+    }
+
+    pub fn set_label_size(&mut self, label_size: f32) {
+        self.chart.set_label_size(label_size);
+    }
+
+    pub fn set_price_step(&mut self, price_step: Option<f32>) {
+        self.chart.set_price_step(price_step);
+    }
+
+    pub fn set_palette(&mut self, palette: ChartPalette) {
+        self.chart.palette = palette;
+
+        self.chart.main_cache.clear();
+    }
+
+    pub fn set_volume_ratio(&mut self, volume_ratio: f32) {
+        self.chart.volume_ratio = volume_ratio.clamp(0.0, MAX_VOLUME_RATIO);
+
+        self.chart.main_cache.clear();
+        self.volume_heat_cache.clear();
+        self.volume_profile_cache.clear();
+        self.ma_cache.clear();
+        self.chart.y_labels_cache.clear();
+        self.chart.y_crosshair_cache.clear();
+    }
+
+    pub fn set_price_scale(&mut self, price_scale: PriceScale) {
+        self.price_scale = price_scale;
+
+        self.chart.mesh_cache.clear();
+        self.volume_heat_cache.clear();
+        self.volume_profile_cache.clear();
+        self.render_start();
+    }
+
+    pub fn set_pinned_range(&mut self, pinned_range: Option<(f32, f32)>) {
+        self.chart.set_pinned_range(pinned_range);
+
+        self.chart.mesh_cache.clear();
+        self.render_start();
+    }
+
+    pub fn get_pinned_range(&self) -> Option<(f32, f32)> {
+        self.chart.pinned_range
+    }
+
+    pub fn set_expand_on_breakout(&mut self, enabled: bool) {
+        self.chart.expand_on_breakout = enabled;
+    }
+
+    pub fn get_price_scale(&self) -> PriceScale {
+        self.price_scale
+    }
+
+    pub fn get_timeframe(&self) -> u16 {
+        self.timeframe
+    }
+
+    pub fn get_klines(&self) -> Vec<Kline> {
+        self.data_points.values().copied().collect()
+    }
+
+    pub fn update_latest_kline(&mut self, kline: &Kline) {
+        if self.data_points.insert(kline.time as i64, *kline).is_none() {
+            self.volume_heat_cache.clear();
+        }
+        self.volume_profile_cache.clear();
+
+        self.chart.update_last_price(kline.close);
+        self.chart.expand_pinned_range(kline.low, kline.high);
+
+        if !self.chart.idle {
+            self.render_start();
+        }
+    }
+
+    /// Fetched klines replace the bars with the same open time, the older ones stay
+    pub fn merge_klines(&mut self, klines: &[Kline]) {
+        for kline in klines {
+            self.data_points.insert(kline.time as i64, *kline);
+        }
+        self.volume_heat_cache.clear();
+        self.volume_profile_cache.clear();
+
+        if self.chart.last_price.is_none() {
+            if let Some(kline) = self.data_points.values().last() {
+                self.chart.last_price = Some(LastPrice { price: kline.close, rising: kline.close >= kline.open });
+            }
+        }
+
+        if !self.chart.idle {
+            self.render_start();
+        }
+    }
+
+    /// Coming back from idle renders whatever got collected in the meantime
+    pub fn set_idle(&mut self, idle: bool) {
+        self.chart.idle = idle;
+
+        if !idle {
+            self.render_start();
+        }
+    }
+
+    pub fn crosshair_time(&self) -> Option<i64> {
+        self.chart.crosshair_time()
+    }
+
+    pub fn set_view_toggles(&mut self, crosshair: Option<bool>, autoscale: Option<bool>) {
+        self.chart.set_view_toggles(crosshair, autoscale);
+    }
+
+    /// (crosshair, autoscale)
+    pub fn get_view_toggles(&self) -> (bool, bool) {
+        (self.chart.crosshair, self.chart.autoscale)
+    }
+
+    pub fn set_sync_crosshair_time(&mut self, time: Option<i64>) {
+        self.chart.set_sync_crosshair_time(time);
+    }
+
+    /// Highest buy or sell volume of the bars in view
+    pub fn visible_max_volume(&self) -> f32 {
+        if self.chart.x_min_time > self.chart.x_max_time {
+            return 0.0;
+        }
+
+        self.data_points.range(self.chart.x_min_time..=self.chart.x_max_time)
+            .map(|(_, kline)| kline.volume.0.max(kline.volume.1))
+            .fold(0.0, f32::max)
+    }
+
+    pub fn set_shared_volume_max(&mut self, shared_volume_max: Option<f32>) {
+        self.chart.set_shared_volume_max(shared_volume_max);
+    }
+
     pub fn render_start(&mut self) {
         let (latest, earliest, highest, lowest) = self.calculate_range();
 
@@ -56,11 +609,22 @@ impl CandlestickChart {
             return;
         }
 
-        let chart_state = self.get_common_data_mut();
+        // percent scale is relative to the first visible bar
+        let base_price = self.data_points.range(earliest..=latest)
+            .next()
+            .map_or(self.base_price, |(_, kline)| kline.close);
+        let base_changed = base_price != self.base_price;
+        self.base_price = base_price;
+
+        self.calculate_moving_averages(earliest, latest);
+
+        let chart_state = &mut self.chart;
 
-        if earliest != chart_state.x_min_time || latest != chart_state.x_max_time || lowest != chart_state.y_min_price || highest != chart_state.y_max_price {
+        if earliest != chart_state.x_min_time || latest != chart_state.x_max_time || lowest != chart_state.y_min_price || highest != chart_state.y_max_price || base_changed {
             chart_state.x_labels_cache.clear();
             chart_state.mesh_cache.clear();
+            self.volume_heat_cache.clear();
+            self.volume_profile_cache.clear();
         }
 
         chart_state.x_min_time = earliest;
@@ -74,10 +638,27 @@ impl CandlestickChart {
         chart_state.main_cache.clear();
     }
 
+    /// Buy and sell volume of the bars between `earliest` and `latest` by price level, keyed by
+    /// the level's index in steps of the returned size, whole ticks wide when the tick is known
+    fn volume_profile(&self, earliest: i64, latest: i64, lowest: f32, highest: f32) -> (f32, BTreeMap<i64, (f32, f32)>) {
+        let range = highest - lowest;
+        if range <= 0.0 {
+            return (0.0, BTreeMap::new());
+        }
+
+        let step = match self.min_tick_size.filter(|tick| *tick > 0.0) {
+            Some(tick) => tick * (range / (tick * Self::MAX_PROFILE_LEVELS)).ceil().max(1.0),
+            None => range / Self::MAX_PROFILE_LEVELS,
+        };
+
+        (step, volume_by_level(self.data_points.range(earliest..=latest).map(|(_, kline)| kline), step))
+    }
+
     fn calculate_range(&self) -> (i64, i64, f32, f32) {
         let chart = self.get_common_data();
 
-        let timestamp_latest = self.data_points.keys().last().map_or(0, |time| *time);
+        let timestamp_latest = self.data_points.keys().last().map_or(0, |time| *time)
+            + i64::from(self.right_margin) * i64::from(self.timeframe) * 60_000;
     
         let latest: i64 = timestamp_latest - ((chart.translation.x*8000.0)*(self.timeframe as f32)) as i64;
         let earliest: i64 = latest - ((6400000.0*self.timeframe as f32) / (chart.scaling / (chart.bounds.width/800.0))) as i64;
@@ -99,7 +680,10 @@ impl CandlestickChart {
         }
     
         let avg_body_height = if count > 1 { avg_body_height / (count - 1) as f32 } else { 0.0 };
-        let (highest, lowest) = (highest + avg_body_height, lowest - avg_body_height);
+        let (highest, lowest) = match chart.pinned_range {
+            Some((pinned_low, pinned_high)) => (pinned_high, pinned_low),
+            None => (highest + avg_body_height, lowest - avg_body_height),
+        };
     
         (latest, earliest, highest, lowest)
     }
@@ -137,12 +721,29 @@ impl CandlestickChart {
             Message::ChartBounds(bounds) => {
                 self.chart.bounds = *bounds;
             },
-            Message::AutoscaleToggle => {
+            Message::AutoscaleToggle if self.chart.pinned_range.is_none() => {
                 self.chart.autoscale = !self.chart.autoscale;
             },
             Message::CrosshairToggle => {
                 self.chart.crosshair = !self.chart.crosshair;
             },
+            Message::FibToolToggle => {
+                self.fib_tool = !self.fib_tool;
+            },
+            Message::VolumeProfileToggle => {
+                self.show_volume_profile = !self.show_volume_profile;
+
+                self.volume_profile_cache.clear();
+            },
+            Message::FibDragged(fib) => {
+                self.set_fib(Some(*fib));
+            },
+            Message::FibCleared => {
+                self.set_fib(None);
+            },
+            Message::MeasureUpdate(span) => {
+                self.chart.set_measure(*span);
+            },
             Message::CrosshairMoved(position) => {
                 let chart = self.get_common_data_mut();
 
@@ -172,10 +773,13 @@ impl CandlestickChart {
                 crosshair_cache: &chart_state.x_crosshair_cache, 
                 crosshair_position: chart_state.crosshair_position, 
                 crosshair: chart_state.crosshair,
-                timeframe: Some(self.timeframe)
+                timeframe: Some(self.timeframe),
+                label_size: chart_state.label_size,
             })
             .width(Length::FillPortion(10))
-            .height(Length::Fixed(26.0));
+            .height(Length::Fixed(26.0 * chart_state.label_scale()));
+
+        let bar_countdown = self.bar_countdown();
     
         let axis_labels_y = Canvas::new(
             AxisLabelYCanvas { 
@@ -184,9 +788,19 @@ impl CandlestickChart {
                 min: chart_state.y_min_price,
                 max: chart_state.y_max_price,
                 crosshair_position: chart_state.crosshair_position, 
-                crosshair: chart_state.crosshair
+                crosshair: chart_state.crosshair,
+                price_scale: self.price_scale,
+                base_price: self.base_price,
+                volume_ratio: chart_state.volume_ratio,
+                min_tick_size: chart_state.min_tick_size,
+                price_decimals: chart_state.price_decimals,
+                last_price: chart_state.last_price.filter(|_| chart_state.price_ruler || bar_countdown.is_some()),
+                bar_countdown,
+                palette: chart_state.palette,
+                label_size: chart_state.label_size,
+                price_step: chart_state.price_step,
             })
-            .width(Length::Fixed(60.0))
+            .width(Length::Fixed(60.0 * chart_state.label_scale()))
             .height(Length::FillPortion(10));
 
         let autoscale_button = button(
@@ -207,14 +821,35 @@ impl CandlestickChart {
             .height(Length::Fill)
             .on_press(Message::CrosshairToggle)
             .style(|_theme: &Theme, _status: iced::widget::button::Status| chart_button(_theme, _status, chart_state.crosshair));
+        let fib_button = button(
+            Text::new("F")
+                .size(12)
+                .align_x(alignment::Horizontal::Center)
+            ) 
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .on_press(Message::FibToolToggle)
+            .style(|_theme: &Theme, _status: iced::widget::button::Status| chart_button(_theme, _status, self.fib_tool));
+    
+        let profile_button = button(
+            Text::new("V")
+                .size(12)
+                .align_x(alignment::Horizontal::Center)
+            ) 
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .on_press(Message::VolumeProfileToggle)
+            .style(|_theme: &Theme, _status: iced::widget::button::Status| chart_button(_theme, _status, self.show_volume_profile));
     
         let chart_controls = Container::new(
             Row::new()
                 .push(autoscale_button)
-                .push(crosshair_button).spacing(2)
+                .push(crosshair_button)
+                .push(fib_button)
+                .push(profile_button).spacing(2)
             ).padding([0, 2])
-            .width(Length::Fixed(60.0))
-            .height(Length::Fixed(26.0));
+            .width(Length::Fixed(60.0 * chart_state.label_scale()))
+            .height(Length::Fixed(26.0 * chart_state.label_scale()));
 
         let chart_and_y_labels = Row::new()
             .push(chart)
@@ -251,7 +886,19 @@ impl canvas::Program<Message> for CandlestickChart {
         } 
         
         if let Event::Mouse(mouse::Event::ButtonReleased(_)) = event {
+            let released = *interaction;
             *interaction = Interaction::None;
+
+            match released {
+                // the tool is one-shot, switch it off once a retracement is placed
+                Interaction::FibDrawing { .. } => {
+                    return (event::Status::Captured, Some(Message::FibToolToggle));
+                }
+                Interaction::Measuring { .. } => {
+                    return (event::Status::Captured, Some(Message::MeasureUpdate(None)));
+                }
+                _ => {}
+            }
         }
 
         let Some(cursor_position) = cursor.position_in(bounds) else {
@@ -269,12 +916,25 @@ impl canvas::Program<Message> for CandlestickChart {
                 mouse::Event::ButtonPressed(button) => {
                     let message = match button {
                         mouse::Button::Left => {
-                            *interaction = Interaction::Panning {
-                                translation: chart_state.translation,
-                                start: cursor_position,
-                            };
+                            if self.fib_tool {
+                                *interaction = Interaction::FibDrawing {
+                                    anchor: self.price_at(cursor_position.y, bounds),
+                                };
+                            } else if let Some(anchor) = self.fib_anchor_at(cursor_position.y, bounds) {
+                                *interaction = Interaction::FibEditing { anchor };
+                            } else {
+                                *interaction = Interaction::Panning {
+                                    translation: chart_state.translation,
+                                    start: cursor_position,
+                                };
+                            }
                             None
                         }
+                        mouse::Button::Right => {
+                            *interaction = Interaction::Measuring { start: cursor_position };
+
+                            Some(Message::MeasureUpdate(Some((cursor_position, cursor_position))))
+                        }
                         _ => None,
                     };
 
@@ -289,6 +949,14 @@ impl canvas::Program<Message> for CandlestickChart {
                                         * (1.0 / chart_state.scaling),
                             ))
                         }
+                        Interaction::FibDrawing { anchor } | Interaction::FibEditing { anchor } => {
+                            Some(Message::FibDragged(
+                                FibDraw::new(anchor, self.price_at(cursor_position.y, bounds))
+                            ))
+                        }
+                        Interaction::Measuring { start } => {
+                            Some(Message::MeasureUpdate(Some((start, cursor_position))))
+                        }
                         Interaction::None => 
                             if chart_state.crosshair && cursor.is_over(bounds) {
                                 Some(Message::CrosshairMoved(cursor_position))
@@ -368,18 +1036,44 @@ impl canvas::Program<Message> for CandlestickChart {
         let (latest, earliest) = (chart.x_max_time, chart.x_min_time);    
         let (lowest, highest) = (chart.y_min_price, chart.y_max_price);
 
-        let y_range = highest - lowest;
-
-        let volume_area_height = bounds.height / 8.0; 
+        let volume_area_height = bounds.height * chart.volume_ratio; 
         let candlesticks_area_height = bounds.height - volume_area_height;
 
+        let price_scale = self.price_scale;
+        let base_price = self.base_price;
+        let y_of = |price: f32| price_scale.y_position(price, lowest, highest, base_price, candlesticks_area_height);
+
         let y_labels_can_fit = (bounds.height / 32.0) as i32;
-        let (step, rounded_lowest) = calculate_price_step(highest, lowest, y_labels_can_fit);
+        let (_, levels) = price_scale.label_levels(highest, lowest, base_price, y_labels_can_fit, chart.price_step, chart.min_tick_size);
 
         let x_labels_can_fit = (bounds.width / 90.0) as i32;
         let (time_step, rounded_earliest) = calculate_time_step(earliest, latest, x_labels_can_fit, Some(self.timeframe));
 
         let background = chart.mesh_cache.draw(renderer, bounds.size(), |frame| {
+            // sessions span hours, a bar of a day or more would cover them all
+            if let Some(sessions) = self.sessions.filter(|_| self.timeframe < 1440 && latest > earliest) {
+                let x_of = |time: i64| ((time - earliest) as f64 / (latest - earliest) as f64) as f32 * bounds.width;
+
+                // from the day before, as a session opening then can run into the visible window
+                let mut day_start = (earliest / 86_400_000 - 1) * 86_400_000;
+
+                while day_start <= latest {
+                    for (hours, color) in sessions.shaded() {
+                        let (start, end) = hours.span(day_start);
+                        let (start, end) = (start.max(earliest), end.min(latest));
+
+                        if start < end {
+                            frame.fill_rectangle(
+                                Point::new(x_of(start), 0.0),
+                                Size::new(x_of(end) - x_of(start), bounds.height),
+                                color
+                            );
+                        }
+                    }
+                    day_start += 86_400_000;
+                }
+            }
+
             frame.with_save(|frame| {
                 let mut time = rounded_earliest;
 
@@ -399,46 +1093,151 @@ impl canvas::Program<Message> for CandlestickChart {
             });
             
             frame.with_save(|frame| {
-                let mut y = rounded_lowest;
-
-                while y <= highest {
-                    let y_position = candlesticks_area_height - ((y - lowest) / y_range * candlesticks_area_height);
+                for &(price, _) in &levels {
+                    let y_position = y_of(price);
                     let line = Path::line(
                         Point::new(0.0, y_position), 
                         Point::new(bounds.width, y_position)
                     );
                     frame.stroke(&line, Stroke::default().with_color(Color::from_rgba8(27, 27, 27, 1.0)).with_width(1.0));
-                    y += step;
                 }
             });
         });
 
+        let volume_heat = self.volume_heat.then(|| self.volume_heat_cache.draw(renderer, bounds.size(), |frame| {
+            // same levels as the profile, so its bars and the glow line up
+            let (step, levels) = self.volume_profile(earliest, latest, lowest, highest);
+
+            let max_volume = levels.values().map(|(buy, sell)| buy + sell).fold(0.0f32, f32::max);
+            if max_volume <= 0.0 {
+                return;
+            }
+
+            for (level, (buy, sell)) in &levels {
+                let (y_low, y_high) = (y_of(*level as f32 * step), y_of((*level + 1) as f32 * step));
+
+                frame.fill_rectangle(
+                    Point::new(0.0, y_high.min(y_low)),
+                    Size::new(bounds.width, (y_low - y_high).abs().max(1.0)),
+                    Color { a: Self::HEAT_COLOR.a * ((buy + sell) / max_volume), ..Self::HEAT_COLOR }
+                );
+            }
+        }));
+
+        let volume_profile = self.show_volume_profile.then(|| self.volume_profile_cache.draw(renderer, bounds.size(), |frame| {
+            let (step, profile) = self.volume_profile(earliest, latest, lowest, highest);
+
+            let max_volume = profile.values().map(|(buy, sell)| buy + sell).fold(0.0f32, f32::max);
+            if max_volume <= 0.0 {
+                return;
+            }
+            let max_width = bounds.width * Self::PROFILE_WIDTH_RATIO;
+
+            for (level, (buy, sell)) in &profile {
+                let (y_low, y_high) = (y_of(*level as f32 * step), y_of((*level + 1) as f32 * step));
+                let height = (y_low - y_high).abs().max(1.0);
+
+                let sell_width = sell / max_volume * max_width;
+                let buy_width = buy / max_volume * max_width;
+
+                frame.fill_rectangle(
+                    Point::new(bounds.width - sell_width, y_high.min(y_low)),
+                    Size::new(sell_width, height),
+                    chart.palette.directional(false, Self::PROFILE_ALPHA)
+                );
+                frame.fill_rectangle(
+                    Point::new(bounds.width - sell_width - buy_width, y_high.min(y_low)),
+                    Size::new(buy_width, height),
+                    chart.palette.directional(true, Self::PROFILE_ALPHA)
+                );
+            }
+        }));
+
+        let moving_averages = (!self.ma_lines.is_empty()).then(|| self.ma_cache.draw(renderer, bounds.size(), |frame| {
+            let x_of = |time: i64| ((time - earliest) as f64 / (latest - earliest) as f64) as f32 * bounds.width;
+
+            for (tint, values) in &self.ma_lines {
+                if values.len() < 2 {
+                    continue;
+                }
+
+                let line = Path::new(|builder| {
+                    builder.move_to(Point::new(x_of(values[0].0), y_of(values[0].1)));
+
+                    for (time, value) in &values[1..] {
+                        builder.line_to(Point::new(x_of(*time), y_of(*value)));
+                    }
+                });
+                frame.stroke(&line, Stroke::default().with_color(tint.color()).with_width(1.5));
+            }
+        }));
+
+        let bar_spacing = (self.timeframe as f64 * 60_000.0 / (latest - earliest) as f64) as f32 * bounds.width;
+        let body_width = body_width(chart.scaling, bar_spacing);
+        let half_body = body_width / 2.0;
+
         let candlesticks = chart.main_cache.draw(renderer, bounds.size(), |frame| {
+            draw_debug_overlay(frame, chart, self.data_points.len(), self.data_points.keys().next_back().copied(), None);
+
             let mut max_volume: f32 = 0.0;
 
             for (_, kline) in self.data_points.range(earliest..=latest) {
                 max_volume = max_volume.max(kline.volume.0.max(kline.volume.1));
             }
+            let max_volume = chart.volume_scale(max_volume);
+
+            // ghosts past the latest bar are cut off, the earlier session may not line up in length
+            let ghost_end = self.data_points.keys().next_back().map_or(latest, |last| latest.min(*last));
+
+            if ghost_end >= earliest {
+                for (time, kline) in self.ghost_klines.range(earliest..=ghost_end) {
+                    let x_position = ((time - earliest) as f64 / (latest - earliest) as f64) * bounds.width as f64;
+
+                    let body = body_rect(x_position as f32, y_of(kline.open), y_of(kline.close), body_width);
+                    let color = chart.palette.directional(kline.close >= kline.open, Self::GHOST_ALPHA);
+
+                    frame.fill_rectangle(body.position(), body.size(), color);
+                    frame.stroke(
+                        &Path::line(Point::new(x_position as f32, y_of(kline.high)), Point::new(x_position as f32, y_of(kline.low))),
+                        Stroke::default().with_color(color).with_width(1.0)
+                    );
+                }
+            }
+
+            let mut prev_kline = self.data_points.range(..earliest).next_back().map(|(_, kline)| kline);
 
             for (time, kline) in self.data_points.range(earliest..=latest) {
+                let pattern_tint = self.bar_patterns
+                    .zip(prev_kline)
+                    .and_then(|((inside, outside), prev)| bar_pattern_tint(kline, prev, inside, outside));
+                prev_kline = Some(kline);
+
                 let x_position: f64 = ((time - earliest) as f64 / (latest - earliest) as f64) * bounds.width as f64;
 
                 if x_position.is_nan() {
                     continue;
                 }
                 
-                let y_open = candlesticks_area_height - ((kline.open - lowest) / y_range * candlesticks_area_height);
-                let y_high = candlesticks_area_height - ((kline.high - lowest) / y_range * candlesticks_area_height);
-                let y_low = candlesticks_area_height - ((kline.low - lowest) / y_range * candlesticks_area_height);
-                let y_close = candlesticks_area_height - ((kline.close - lowest) / y_range * candlesticks_area_height);
+                let y_open = y_of(kline.open);
+                let y_high = y_of(kline.high);
+                let y_low = y_of(kline.low);
+                let y_close = y_of(kline.close);
                 
-                let color = if kline.close >= kline.open { Color::from_rgb8(81, 205, 160) } else { Color::from_rgb8(192, 80, 77) };
+                let color = pattern_tint
+                    .unwrap_or_else(|| chart.palette.directional(kline.close >= kline.open, 1.0));
 
-                let body = Path::rectangle(
-                    Point::new(x_position as f32 - (2.0 * chart.scaling), y_open.min(y_close)), 
-                    Size::new(4.0 * chart.scaling, (y_open - y_close).abs())
-                );                    
-                frame.fill(&body, color);
+                let body = body_rect(x_position as f32, y_open, y_close, body_width);
+
+                if self.mark_doji && is_doji(kline) {
+                    // neutral tick a bit wider than the body
+                    frame.fill_rectangle(
+                        Point::new(body.x - 2.0, (y_open + y_close) / 2.0 - (Self::MIN_BODY_HEIGHT / 2.0)), 
+                        Size::new(body.width + 4.0, Self::MIN_BODY_HEIGHT), 
+                        Color::from_rgb8(200, 200, 200)
+                    );
+                } else {
+                    frame.fill_rectangle(body.position(), body.size(), color);
+                }
                 
                 let wick = Path::line(
                     Point::new(x_position as f32, y_high), 
@@ -452,27 +1251,73 @@ impl canvas::Program<Message> for CandlestickChart {
                     
                     let buy_bar = Path::rectangle(
                         Point::new(x_position as f32, bounds.height - buy_bar_height), 
-                        Size::new(2.0 * chart.scaling, buy_bar_height)
+                        Size::new(half_body, buy_bar_height)
                     );
-                    frame.fill(&buy_bar, Color::from_rgb8(81, 205, 160)); 
+                    frame.fill(&buy_bar, chart.palette.buy()); 
                     
                     let sell_bar = Path::rectangle(
-                        Point::new(x_position as f32 - (2.0 * chart.scaling), bounds.height - sell_bar_height), 
-                        Size::new(2.0 * chart.scaling, sell_bar_height)
+                        Point::new(x_position as f32 - half_body, bounds.height - sell_bar_height), 
+                        Size::new(half_body, sell_bar_height)
                     );
-                    frame.fill(&sell_bar, Color::from_rgb8(192, 80, 77)); 
+                    frame.fill(&sell_bar, chart.palette.sell()); 
                 } else {
                     let bar_height = ((kline.volume.1) / max_volume) * volume_area_height;
                     
                     let bar = Path::rectangle(
-                        Point::new(x_position as f32 - (2.0 * chart.scaling), bounds.height - bar_height), 
-                        Size::new(4.0 * chart.scaling, bar_height)
+                        Point::new(x_position as f32 - half_body, bounds.height - bar_height), 
+                        Size::new(body_width, bar_height)
                     );
-                    let color = if kline.close >= kline.open { Color::from_rgba8(81, 205, 160, 0.8) } else { Color::from_rgba8(192, 80, 77, 0.8) };
+                    let color = chart.palette.directional(kline.close >= kline.open, 0.8);
 
                     frame.fill(&bar, color);
                 }
             }
+
+            if let Some(min_tick_size) = self.min_tick_size {
+                draw_offset_guides(frame, chart, min_tick_size, bounds.width, y_of);
+            }
+
+            if let Some(kline) = self.data_points.values().last().filter(|_| self.price_line) {
+                let rising = chart.last_price.map_or(kline.close >= kline.open, |last_price| last_price.rising);
+                let y_position = y_of(kline.close);
+
+                frame.stroke(
+                    &Path::line(Point::new(0.0, y_position), Point::new(bounds.width, y_position)),
+                    Stroke {
+                        line_dash: canvas::LineDash {
+                            segments: &[2.0, 4.0],
+                            offset: 0,
+                        },
+                        ..Stroke::default().with_color(chart.palette.directional(rising, 0.5)).with_width(1.0)
+                    }
+                );
+            }
+
+            if let Some(fib) = self.fib {
+                for (ratio, price) in fib.levels() {
+                    let y_position = y_of(price);
+
+                    let line = Path::line(
+                        Point::new(0.0, y_position), 
+                        Point::new(bounds.width, y_position)
+                    );
+                    frame.stroke(&line, Stroke::default().with_color(Self::FIB_COLOR).with_width(1.0));
+
+                    frame.fill_text(canvas::Text {
+                        content: format!("{:.1}% ({})", ratio * 100.0, format_price(price, chart.price_decimals)),
+                        position: Point::new(4.0, y_position - chart.label_size - 2.0),
+                        size: iced::Pixels(chart.label_size),
+                        color: Self::FIB_COLOR,
+                        ..canvas::Text::default()
+                    });
+                }
+            }
+        });
+
+        let measure = chart.measure.map(|_| {
+            chart.measure_cache.draw(renderer, bounds.size(), |frame| {
+                chart.draw_measure(frame, bounds, self.timeframe, |y| self.price_at(y, bounds));
+            })
         });
 
         if chart.crosshair {
@@ -482,7 +1327,7 @@ impl canvas::Program<Message> for CandlestickChart {
                         Point::new(0.0, cursor_position.y), 
                         Point::new(bounds.width, cursor_position.y)
                     );
-                    frame.stroke(&line, Stroke::default().with_color(Color::from_rgba8(200, 200, 200, 0.6)).with_width(1.0));
+                    frame.stroke(&line, chart.crosshair_style.stroke());
 
                     let crosshair_ratio = cursor_position.x as f64 / bounds.width as f64;
                     let crosshair_millis = earliest as f64 + crosshair_ratio * (latest - earliest) as f64;
@@ -499,7 +1344,7 @@ impl canvas::Program<Message> for CandlestickChart {
                         Point::new(snap_x as f32, 0.0), 
                         Point::new(snap_x as f32, bounds.height)
                     );
-                    frame.stroke(&line, Stroke::default().with_color(Color::from_rgba8(200, 200, 200, 0.6)).with_width(1.0));
+                    frame.stroke(&line, chart.crosshair_style.stroke());
 
                     if let Some((_, kline)) = self.data_points.iter()
                         .find(|(time, _)| **time == rounded_timestamp) {
@@ -508,30 +1353,48 @@ impl canvas::Program<Message> for CandlestickChart {
                         let tooltip_text: String = if kline.volume.0 != -1.0 {
                             format!(
                                 "O: {} H: {} L: {} C: {}\nBuyV: {:.0} SellV: {:.0}",
-                                kline.open, kline.high, kline.low, kline.close, kline.volume.0, kline.volume.1
+                                format_price(kline.open, chart.price_decimals), format_price(kline.high, chart.price_decimals),
+                                format_price(kline.low, chart.price_decimals), format_price(kline.close, chart.price_decimals),
+                                kline.volume.0, kline.volume.1
                             )
                         } else {
                             format!(
                                 "O: {} H: {} L: {} C: {}\nVolume: {:.0}",
-                                kline.open, kline.high, kline.low, kline.close, kline.volume.1
+                                format_price(kline.open, chart.price_decimals), format_price(kline.high, chart.price_decimals),
+                                format_price(kline.low, chart.price_decimals), format_price(kline.close, chart.price_decimals),
+                                kline.volume.1
                             )
                         };
 
                         let text = canvas::Text {
                             content: tooltip_text,
                             position: Point::new(10.0, 10.0),
-                            size: iced::Pixels(12.0),
+                            size: iced::Pixels(chart.label_size),
                             color: Color::from_rgba8(120, 120, 120, 1.0),
                             ..canvas::Text::default()
                         };
                         frame.fill_text(text);
                     }
+                } else {
+                    chart.draw_sync_guide(frame, bounds);
                 }
             });
 
-            vec![background, crosshair, candlesticks]
+            let mut layers = vec![background];
+            layers.extend(volume_heat);
+            layers.extend(volume_profile);
+            layers.extend([crosshair, candlesticks]);
+            layers.extend(moving_averages);
+            layers.extend(measure);
+            layers
         }   else {
-            vec![background, candlesticks]
+            let mut layers = vec![background];
+            layers.extend(volume_heat);
+            layers.extend(volume_profile);
+            layers.push(candlesticks);
+            layers.extend(moving_averages);
+            layers.extend(measure);
+            layers
         }
     }
 
@@ -544,8 +1407,15 @@ impl canvas::Program<Message> for CandlestickChart {
         match interaction {
             Interaction::Panning { .. } => mouse::Interaction::Grabbing,
             Interaction::Zoomin { .. } => mouse::Interaction::ZoomIn,
+            Interaction::FibDrawing { .. } | Interaction::FibEditing { .. } => mouse::Interaction::ResizingVertically,
+            Interaction::Measuring { .. } => mouse::Interaction::Crosshair,
             Interaction::None if cursor.is_over(bounds) => {
-                if self.chart.crosshair {
+                let over_fib = cursor.position_in(bounds)
+                    .is_some_and(|position| self.fib_anchor_at(position.y, bounds).is_some());
+
+                if over_fib {
+                    mouse::Interaction::ResizingVertically
+                } else if self.chart.crosshair {
                     mouse::Interaction::Crosshair
                 } else {
                     mouse::Interaction::default()
@@ -554,4 +1424,148 @@ impl canvas::Program<Message> for CandlestickChart {
             Interaction::None => { mouse::Interaction::default() }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Inside bars sit within the previous bar's range, outside bars engulf it
+fn bar_pattern_tint(kline: &Kline, prev: &Kline, inside: BarTint, outside: BarTint) -> Option<Color> {
+    if kline.high < prev.high && kline.low > prev.low {
+        Some(inside.color())
+    } else if kline.high > prev.high && kline.low < prev.low {
+        Some(outside.color())
+    } else {
+        None
+    }
+}
+
+/// Width of the bodies of bars `bar_spacing` pixels apart, narrowed so they never overlap
+fn body_width(scaling: f32, bar_spacing: f32) -> f32 {
+    (4.0 * scaling).min(bar_spacing - CandlestickChart::MIN_BAR_GAP).max(1.0)
+}
+
+/// Body of a bar centered on `x`, a flat one becomes a line of the minimum height at its price
+fn body_rect(x: f32, y_open: f32, y_close: f32, body_width: f32) -> Rectangle {
+    let height = (y_open - y_close).abs();
+
+    if height < CandlestickChart::MIN_BODY_HEIGHT {
+        Rectangle::new(
+            Point::new(x - body_width / 2.0, (y_open + y_close) / 2.0 - CandlestickChart::MIN_BODY_HEIGHT / 2.0),
+            Size::new(body_width, CandlestickChart::MIN_BODY_HEIGHT),
+        )
+    } else {
+        Rectangle::new(Point::new(x - body_width / 2.0, y_open.min(y_close)), Size::new(body_width, height))
+    }
+}
+
+/// Buy and sell volume of `klines` in price levels `step` wide, keyed by the level's index.
+/// Each bar's volume is spread evenly over the levels its range covers,
+/// bars without a buy/sell split count toward their direction
+fn volume_by_level<'a>(klines: impl Iterator<Item = &'a Kline>, step: f32) -> BTreeMap<i64, (f32, f32)> {
+    let mut levels = BTreeMap::new();
+
+    for kline in klines {
+        let (buy, sell) = if kline.volume.0 == -1.0 {
+            if kline.close >= kline.open { (kline.volume.1, 0.0) } else { (0.0, kline.volume.1) }
+        } else {
+            kline.volume
+        };
+
+        let (low_level, high_level) = ((kline.low / step).floor() as i64, (kline.high / step).floor() as i64);
+        let covered = (high_level - low_level + 1) as f32;
+
+        for level in low_level..=high_level {
+            let entry = levels.entry(level).or_insert((0.0, 0.0));
+            entry.0 += buy / covered;
+            entry.1 += sell / covered;
+        }
+    }
+
+    levels
+}
+
+fn is_doji(kline: &Kline) -> bool {
+    let range = kline.high - kline.low;
+
+    range <= 0.0 || (kline.close - kline.open).abs() <= range * CandlestickChart::DOJI_BODY_RATIO
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bodies_never_overlap_when_bars_are_packed() {
+        // 500 bars squeezed into 1200 pixels at the widest zoom
+        let bar_spacing = 1200.0 / 500.0;
+        let body_width = body_width(CandlestickChart::MAX_SCALING, bar_spacing);
+
+        let bodies: Vec<Rectangle> = (0..500)
+            .map(|i| body_rect(i as f32 * bar_spacing, 100.0, 120.0, body_width))
+            .collect();
+
+        for pair in bodies.windows(2) {
+            assert!(pair[0].x + pair[0].width < pair[1].x, "{pair:?}");
+        }
+    }
+
+    #[test]
+    fn bodies_keep_their_width_when_there_is_room() {
+        assert_eq!(body_width(1.0, 40.0), 4.0);
+    }
+
+    #[test]
+    fn flat_and_doji_bodies_stay_visible() {
+        // open == close
+        let flat = body_rect(50.0, 200.0, 200.0, 4.0);
+        assert_eq!(flat.height, CandlestickChart::MIN_BODY_HEIGHT);
+        assert_eq!(flat.center_y(), 200.0);
+
+        // a fraction of a pixel apart
+        let doji = body_rect(50.0, 200.0, 200.3, 4.0);
+        assert_eq!(doji.height, CandlestickChart::MIN_BODY_HEIGHT);
+        assert!((doji.center_y() - 200.15).abs() < 1e-4);
+    }
+
+    #[test]
+    fn normal_bodies_span_open_to_close() {
+        let body = body_rect(50.0, 180.0, 120.0, 4.0);
+
+        assert_eq!(body, Rectangle::new(Point::new(48.0, 120.0), Size::new(4.0, 60.0)));
+    }
+
+    fn minute_klines(minutes: std::ops::Range<u64>, close: f32) -> Vec<Kline> {
+        minutes
+            .map(|minute| Kline { time: minute * 60_000, open: close, high: close, low: close, close, volume: (1.0, 1.0) })
+            .collect()
+    }
+
+    #[test]
+    fn merging_an_overlapping_page_leaves_no_duplicates_or_gaps() {
+        let mut chart = CandlestickChart::new(minute_klines(0..10, 1.0), 1);
+
+        // refetched after a reconnect, overlapping the last three bars
+        chart.merge_klines(&minute_klines(7..15, 2.0));
+
+        let times: Vec<i64> = chart.data_points.keys().copied().collect();
+        assert_eq!(times, (0..15).map(|minute| minute * 60_000).collect::<Vec<i64>>());
+
+        // the fetched page wins where they overlap, the older bars stay as they were
+        assert_eq!(chart.data_points[&(6 * 60_000)].close, 1.0);
+        assert_eq!(chart.data_points[&(7 * 60_000)].close, 2.0);
+    }
+
+    #[test]
+    fn volume_spreads_over_the_levels_a_bar_covers() {
+        let klines = [
+            Kline { time: 0, open: 10.0, high: 13.5, low: 10.0, close: 13.0, volume: (4.0, 8.0) },
+            // no taker split, a red bar counts as selling
+            Kline { time: 60_000, open: 12.5, high: 12.5, low: 12.0, close: 12.0, volume: (-1.0, 6.0) },
+        ];
+
+        let levels = volume_by_level(klines.iter(), 1.0);
+
+        assert_eq!(levels.keys().copied().collect::<Vec<_>>(), [10, 11, 12, 13]);
+        assert_eq!(levels[&10], (1.0, 2.0));
+        assert_eq!(levels[&12], (1.0, 8.0));
+        assert_eq!(levels[&13], (1.0, 2.0));
+    }
+}
@@ -1,11 +1,11 @@
 use std::collections::{BTreeMap, HashMap};
 use iced::{
-    alignment, mouse, widget::{button, canvas::{self, event::{self, Event}, stroke::Stroke, Canvas, Geometry, Path}}, Color, Element, Length, Point, Rectangle, Renderer, Size, Theme
+    alignment, mouse, widget::{button, canvas::{self, event::{self, Event}, Canvas, Geometry, Path}}, Color, Element, Length, Point, Rectangle, Renderer, Size, Theme
 };
 use iced::widget::{Column, Row, Container, Text};
 use crate::data_providers::{Kline, Trade};
 
-use super::{Chart, CommonChartData, Message, Interaction, AxisLabelXCanvas, AxisLabelYCanvas};
+use super::{Chart, CommonChartData, Message, Interaction, AxisLabelXCanvas, AxisLabelYCanvas, CrosshairStyle, ChartPalette, PriceScale, MAX_VOLUME_RATIO, tick_decimals, format_price, LastPrice, draw_debug_overlay};
 use super::chart_button;
 
 
@@ -25,13 +25,21 @@ pub struct FootprintChart {
     data_points: BTreeMap<i64, (HashMap<i64, (f32, f32)>, Kline)>,
     timeframe: u16,
     tick_size: f32,
+    /// Price step trades are binned at, `None` bins at the display tick size
+    agg_tick_size: Option<f32>,
     raw_trades: Vec<Trade>,
+    /// Draws the bar still taking trades faded until it closes
+    live_bar: bool,
+    /// Open time of the bar trades are currently going into
+    current_bar: Option<i64>,
 }
 
 impl FootprintChart {
     const MIN_SCALING: f32 = 0.4;
     const MAX_SCALING: f32 = 3.6;
 
+    const CURRENT_BAR_ALPHA: f32 = 0.45;
+
     pub fn new(timeframe: u16, tick_size: f32, klines_raw: Vec<Kline>, raw_trades: Vec<Trade>) -> Self {
         let mut data_points = BTreeMap::new();
         let aggregate_time = 1000 * 60 * timeframe as i64;
@@ -60,12 +68,26 @@ impl FootprintChart {
             }
         };
 
+        let mut chart = CommonChartData::default();
+
+        if let Some(last_trade) = raw_trades.last() {
+            chart.update_last_price(last_trade.price);
+        } else if let Some((_, (_, kline))) = data_points.last_key_value() {
+            chart.last_price = Some(LastPrice { price: kline.close, rising: kline.close >= kline.open });
+        }
+
+        let current_bar = raw_trades.last()
+            .map(|trade| (trade.time / aggregate_time) * aggregate_time);
+
         FootprintChart {
-            chart: CommonChartData::default(),
+            chart,
             data_points,
             timeframe,
             tick_size,
+            agg_tick_size: None,
             raw_trades,
+            live_bar: false,
+            current_bar,
         }
     }
 
@@ -74,9 +96,12 @@ impl FootprintChart {
         let rounded_depth_update = (depth_update / aggregate_time) * aggregate_time;
     
         self.data_points.entry(rounded_depth_update).or_insert((HashMap::new(), Kline::default()));
+        self.current_bar = Some(rounded_depth_update);
         
+        let agg_tick_size = self.agg_tick_size();
+
         for trade in trades_buffer {
-            let price_level: i64 = (trade.price * (1.0 / self.tick_size)).round() as i64;
+            let price_level = self.price_level(trade.price, agg_tick_size);
             if let Some((trades, _)) = self.data_points.get_mut(&rounded_depth_update) {     
                 if let Some((buy_qty, sell_qty)) = trades.get_mut(&price_level) {
                     if trade.is_sell {
@@ -93,6 +118,10 @@ impl FootprintChart {
 
             self.raw_trades.push(*trade);
         }
+
+        if let Some(last_trade) = trades_buffer.last() {
+            self.chart.update_last_price(last_trade.price);
+        }
     }
 
     pub fn update_latest_kline(&mut self, kline: &Kline) {
@@ -104,20 +133,212 @@ impl FootprintChart {
             kline_value.volume = kline.volume;
         } 
 
-        self.render_start();
+        if !self.chart.idle {
+            self.render_start();
+        }
+    }
+
+    /// Fetched klines replace the OHLCV of bars with the same open time, their trades stay
+    pub fn merge_klines(&mut self, klines: &[Kline]) {
+        for kline in klines {
+            self.data_points.entry(kline.time as i64)
+                .and_modify(|(_, kline_value)| *kline_value = *kline)
+                .or_insert((HashMap::new(), *kline));
+        }
+
+        if self.chart.last_price.is_none() {
+            if let Some((_, (_, kline))) = self.data_points.last_key_value() {
+                self.chart.last_price = Some(LastPrice { price: kline.close, rising: kline.close >= kline.open });
+            }
+        }
+
+        if !self.chart.idle {
+            self.render_start();
+        }
+    }
+
+    /// Coming back from idle renders whatever got collected in the meantime
+    pub fn set_idle(&mut self, idle: bool) {
+        self.chart.idle = idle;
+
+        if !idle {
+            self.render_start();
+        }
+    }
+
+    pub fn crosshair_time(&self) -> Option<i64> {
+        self.chart.crosshair_time()
+    }
+
+    pub fn set_view_toggles(&mut self, crosshair: Option<bool>, autoscale: Option<bool>) {
+        self.chart.set_view_toggles(crosshair, autoscale);
+    }
+
+    /// (crosshair, autoscale)
+    pub fn get_view_toggles(&self) -> (bool, bool) {
+        (self.chart.crosshair, self.chart.autoscale)
+    }
+
+    pub fn set_sync_crosshair_time(&mut self, time: Option<i64>) {
+        self.chart.set_sync_crosshair_time(time);
+    }
+
+    /// Highest buy or sell volume of the bars in view
+    pub fn visible_max_volume(&self) -> f32 {
+        if self.chart.x_min_time > self.chart.x_max_time {
+            return 0.0;
+        }
+
+        self.data_points.range(self.chart.x_min_time..=self.chart.x_max_time)
+            .map(|(_, (_, kline))| kline.volume.0.max(kline.volume.1))
+            .fold(0.0, f32::max)
+    }
+
+    pub fn set_shared_volume_max(&mut self, shared_volume_max: Option<f32>) {
+        self.chart.set_shared_volume_max(shared_volume_max);
+    }
+
+    pub fn get_klines(&self) -> Vec<Kline> {
+        self.data_points.values().map(|(_, kline)| *kline).collect()
     }
 
     pub fn get_raw_trades(&self) -> Vec<Trade> {
         self.raw_trades.clone()
     }
     
+    pub fn set_crosshair_style(&mut self, crosshair_style: CrosshairStyle) {
+        self.chart.crosshair_style = crosshair_style;
+
+        self.chart.crosshair_cache.clear();
+    }
+
+    pub fn set_min_tick_size(&mut self, min_tick_size: f32) {
+        self.chart.min_tick_size = Some(min_tick_size);
+        self.chart.price_decimals = Some(tick_decimals(min_tick_size));
+
+        self.chart.main_cache.clear();
+        self.chart.crosshair_cache.clear();
+        self.chart.y_labels_cache.clear();
+        self.chart.y_crosshair_cache.clear();
+    }
+
+    pub fn set_price_ruler(&mut self, enabled: bool) {
+        self.chart.price_ruler = enabled;
+    }
+
+    pub fn set_debug_overlay(&mut self, enabled: bool) {
+        self.chart.debug_overlay = enabled;
+
+        self.chart.main_cache.clear();
+    }
+
+    pub fn set_live_bar(&mut self, enabled: bool) {
+        self.live_bar = enabled;
+
+        self.chart.main_cache.clear();
+    }
+
+    pub fn set_label_size(&mut self, label_size: f32) {
+        self.chart.set_label_size(label_size);
+    }
+
+    pub fn set_price_step(&mut self, price_step: Option<f32>) {
+        self.chart.set_price_step(price_step);
+    }
+
+    pub fn set_palette(&mut self, palette: ChartPalette) {
+        self.chart.palette = palette;
+
+        self.chart.main_cache.clear();
+    }
+
+    pub fn set_volume_ratio(&mut self, volume_ratio: f32) {
+        self.chart.volume_ratio = volume_ratio.clamp(0.0, MAX_VOLUME_RATIO);
+
+        self.chart.main_cache.clear();
+        self.chart.y_labels_cache.clear();
+        self.chart.y_crosshair_cache.clear();
+    }
+
     pub fn get_tick_size(&self) -> f32 {
         self.tick_size
     }
     
     pub fn change_tick_size(&mut self, new_tick_size: f32) {
+        self.tick_size = new_tick_size;
+
+        self.reaggregate();
+    }
+
+    pub fn set_agg_tick_size(&mut self, agg_tick_size: Option<f32>) {
+        if agg_tick_size == self.agg_tick_size {
+            return;
+        }
+        self.agg_tick_size = agg_tick_size;
+
+        self.reaggregate();
+        self.chart.main_cache.clear();
+    }
+
+    fn agg_tick_size(&self) -> f32 {
+        self.agg_tick_size.unwrap_or(self.tick_size)
+    }
+
+    /// Level a price falls into on a grid of `step` sized levels running through the grid anchor
+    fn price_level(&self, price: f32, step: f32) -> i64 {
+        ((price - self.chart.grid_offset(step)) * (1.0 / step)).round() as i64
+    }
+
+    fn level_price(&self, level: i64, step: f32) -> f32 {
+        level as f32 * step + self.chart.grid_offset(step)
+    }
+
+    pub fn set_grid_anchor(&mut self, grid_anchor: f32) {
+        if self.chart.grid_anchor == grid_anchor {
+            return;
+        }
+        self.chart.grid_anchor = grid_anchor;
+
+        self.reaggregate();
+        self.chart.main_cache.clear();
+    }
+
+    /// Price step of a drawn row, the coarser of the aggregation and display tick sizes
+    fn row_tick_size(&self) -> f32 {
+        self.agg_tick_size().max(self.tick_size)
+    }
+
+    /// Cells of a bar as (price, (buy qty, sell qty)), merged into display rows
+    /// when trades are binned finer than the display tick size
+    fn row_cells(&self, trades: &HashMap<i64, (f32, f32)>) -> Vec<(f32, (f32, f32))> {
+        let agg_tick_size = self.agg_tick_size();
+
+        if agg_tick_size >= self.tick_size {
+            return trades.iter()
+                .map(|(level, qtys)| (self.level_price(*level, agg_tick_size), *qtys))
+                .collect();
+        }
+
+        let mut rows: HashMap<i64, (f32, f32)> = HashMap::new();
+
+        for (level, (buy_qty, sell_qty)) in trades {
+            let row = self.price_level(self.level_price(*level, agg_tick_size), self.tick_size);
+            let entry = rows.entry(row).or_insert((0.0, 0.0));
+
+            entry.0 += buy_qty;
+            entry.1 += sell_qty;
+        }
+
+        rows.into_iter()
+            .map(|(row, qtys)| (self.level_price(row, self.tick_size), qtys))
+            .collect()
+    }
+
+    /// Rebins the retained raw trades at the current aggregation tick size
+    fn reaggregate(&mut self) {
         let mut new_data_points = BTreeMap::new();
         let aggregate_time = 1000 * 60 * self.timeframe as i64;
+        let agg_tick_size = self.agg_tick_size();
 
         for (time, (_, kline_values)) in &self.data_points {
             new_data_points.entry(*time).or_insert((HashMap::new(), *kline_values));
@@ -125,7 +346,7 @@ impl FootprintChart {
 
         for trade in self.raw_trades.iter() {
             let rounded_time = (trade.time / aggregate_time) * aggregate_time;
-            let price_level: i64 = (trade.price * (1.0 / new_tick_size)).round() as i64;
+            let price_level = self.price_level(trade.price, agg_tick_size);
 
             let entry = new_data_points
                 .entry(rounded_time)
@@ -145,7 +366,6 @@ impl FootprintChart {
         }
     
         self.data_points = new_data_points;
-        self.tick_size = new_tick_size;
     }
 
     pub fn render_start(&mut self) {
@@ -241,6 +461,9 @@ impl FootprintChart {
             Message::CrosshairToggle => {
                 self.chart.crosshair = !self.chart.crosshair;
             },
+            Message::MeasureUpdate(span) => {
+                self.chart.set_measure(*span);
+            },
             Message::CrosshairMoved(position) => {
                 let chart = self.get_common_data_mut();
 
@@ -270,10 +493,11 @@ impl FootprintChart {
                 crosshair_cache: &chart_state.x_crosshair_cache, 
                 crosshair_position: chart_state.crosshair_position, 
                 crosshair: chart_state.crosshair,
-                timeframe: Some(self.timeframe)
+                timeframe: Some(self.timeframe),
+                label_size: chart_state.label_size,
             })
             .width(Length::FillPortion(10))
-            .height(Length::Fixed(26.0));
+            .height(Length::Fixed(26.0 * chart_state.label_scale()));
 
         let axis_labels_y = Canvas::new(
             AxisLabelYCanvas { 
@@ -282,9 +506,19 @@ impl FootprintChart {
                 min: chart_state.y_min_price,
                 max: chart_state.y_max_price,
                 crosshair_position: chart_state.crosshair_position, 
-                crosshair: chart_state.crosshair
+                crosshair: chart_state.crosshair,
+                price_scale: PriceScale::Regular,
+                base_price: 0.0,
+                volume_ratio: chart_state.volume_ratio,
+                min_tick_size: chart_state.min_tick_size,
+                price_decimals: chart_state.price_decimals,
+                last_price: chart_state.last_price.filter(|_| chart_state.price_ruler),
+                bar_countdown: None,
+                palette: chart_state.palette,
+                label_size: chart_state.label_size,
+                price_step: chart_state.price_step,
             })
-            .width(Length::Fixed(60.0))
+            .width(Length::Fixed(60.0 * chart_state.label_scale()))
             .height(Length::FillPortion(10));
 
         let autoscale_button = button(
@@ -311,8 +545,8 @@ impl FootprintChart {
                 .push(autoscale_button)
                 .push(crosshair_button).spacing(2)
             ).padding([0, 2])
-            .width(Length::Fixed(60.0))
-            .height(Length::Fixed(26.0));
+            .width(Length::Fixed(60.0 * chart_state.label_scale()))
+            .height(Length::Fixed(26.0 * chart_state.label_scale()));
 
         let chart_and_y_labels = Row::new()
             .push(chart)
@@ -349,7 +583,12 @@ impl canvas::Program<Message> for FootprintChart {
         } 
         
         if let Event::Mouse(mouse::Event::ButtonReleased(_)) = event {
+            let measured = matches!(interaction, Interaction::Measuring { .. });
             *interaction = Interaction::None;
+
+            if measured {
+                return (event::Status::Captured, Some(Message::MeasureUpdate(None)));
+            }
         }
 
         let Some(cursor_position) = cursor.position_in(bounds) else {
@@ -373,6 +612,11 @@ impl canvas::Program<Message> for FootprintChart {
                             };
                             None
                         }
+                        mouse::Button::Right => {
+                            *interaction = Interaction::Measuring { start: cursor_position };
+
+                            Some(Message::MeasureUpdate(Some((cursor_position, cursor_position))))
+                        }
                         _ => None,
                     };
 
@@ -387,6 +631,9 @@ impl canvas::Program<Message> for FootprintChart {
                                         * (1.0 / chart_state.scaling),
                             ))
                         }
+                        Interaction::Measuring { start } => {
+                            Some(Message::MeasureUpdate(Some((start, cursor_position))))
+                        }
                         Interaction::None => 
                             if chart_state.crosshair && cursor.is_over(bounds) {
                                 Some(Message::CrosshairMoved(cursor_position))
@@ -468,20 +715,28 @@ impl canvas::Program<Message> for FootprintChart {
 
         let y_range: f32 = highest - lowest;
 
-        let volume_area_height: f32 = bounds.height / 8.0; 
+        let volume_area_height: f32 = bounds.height * chart.volume_ratio; 
         let footprint_area_height: f32 = bounds.height - volume_area_height;
 
         let footprint = chart.main_cache.draw(renderer, bounds.size(), |frame| {
+            draw_debug_overlay(frame, chart, self.data_points.len(), self.data_points.keys().next_back().copied(), None);
+
             let mut x_positions: Vec<f32> = Vec::new();
             let mut max_trade_qty: f32 = 0.0;
             let mut max_volume: f32 = 0.0;
             let mut min_distance: f32 = f32::MAX;
             let mut previous_x_position: Option<f32> = None;
 
+            let mut visible_cells: Vec<Vec<(f32, (f32, f32))>> = Vec::new();
+
             for (time, (trades, kline)) in self.data_points.range(earliest..=latest) {
-                for trade in trades {
-                    max_trade_qty = max_trade_qty.max(trade.1.0.max(trade.1.1));
+                let cells = self.row_cells(trades);
+
+                for (_, (buy_qty, sell_qty)) in &cells {
+                    max_trade_qty = max_trade_qty.max(buy_qty.max(*sell_qty));
                 }
+                visible_cells.push(cells);
+
                 max_volume = max_volume.max(kline.volume.0.max(kline.volume.1));
 
                 let x_position: f32 = ((time - earliest) as f32 / (latest - earliest) as f32) * bounds.width;
@@ -498,46 +753,47 @@ impl canvas::Program<Message> for FootprintChart {
                 }
             }
 
+            let max_volume = chart.volume_scale(max_volume);
+
             let max_bar_width = min_distance / 2.0;
 
-            let bar_height = ((footprint_area_height / (y_range / self.tick_size) as f32).floor()).max(1.0);
+            let bar_height = ((footprint_area_height / (y_range / self.row_tick_size())).floor()).max(1.0);
 
-            for (time, (trades, kline)) in self.data_points.range(earliest..=latest) {
+            for ((time, (_, kline)), cells) in self.data_points.range(earliest..=latest).zip(&visible_cells) {
                 let x_position: f32 = ((time - earliest) as f32 / (latest - earliest) as f32) * bounds.width;
 
                 if x_position.is_nan() {
                     continue;
                 }
 
+                let in_progress = self.live_bar && self.current_bar == Some(*time);
+                let fade = |color: Color| if in_progress {
+                    Color { a: color.a * Self::CURRENT_BAR_ALPHA, ..color }
+                } else {
+                    color
+                };
+
                 let y_open = footprint_area_height - ((kline.open - lowest) / y_range * footprint_area_height);
                 let y_high = footprint_area_height - ((kline.high - lowest) / y_range * footprint_area_height);
                 let y_low = footprint_area_height - ((kline.low - lowest) / y_range * footprint_area_height);
                 let y_close = footprint_area_height - ((kline.close - lowest) / y_range * footprint_area_height);
 
-                let body_color = 
-                    if kline.close >= kline.open { 
-                        Color::from_rgba8(81, 205, 160, 0.8) 
-                    } else { Color::from_rgba8(192, 80, 77, 0.8) 
-                };
+                let body_color = chart.palette.directional(kline.close >= kline.open, 0.8);
                 frame.fill_rectangle(
                     Point::new(x_position - (2.0 * chart.scaling), y_open.min(y_close)), 
                     Size::new(4.0 * chart.scaling, (y_open - y_close).abs()), 
-                    body_color
+                    fade(body_color)
                 );
 
-                let wick_color = 
-                    if kline.close >= kline.open { 
-                        Color::from_rgba8(81, 205, 160, 0.4) 
-                    } else { Color::from_rgba8(192, 80, 77, 0.4) 
-                };
+                let wick_color = chart.palette.directional(kline.close >= kline.open, 0.4);
                 frame.fill_rectangle(
                     Point::new(x_position - chart.scaling, y_high),
                     Size::new(2.0 * chart.scaling, (y_high - y_low).abs()),
-                    wick_color
+                    fade(wick_color)
                 );
 
-                for trade in trades {
-                    let price = (*trade.0 as f32) / (1.0 / self.tick_size);
+                for trade in cells {
+                    let price = trade.0;
                     let y_position = footprint_area_height - ((price - lowest) / y_range * footprint_area_height);
 
                     if trade.1.0 > 0.0 {
@@ -546,7 +802,7 @@ impl canvas::Program<Message> for FootprintChart {
                         frame.fill_rectangle(
                             Point::new(x_position + (3.0 * chart.scaling), y_position), 
                             Size::new(bar_width, bar_height) , 
-                            Color::from_rgba8(81, 205, 160, 1.0)
+                            fade(chart.palette.buy())
                         );
                     } 
                     if trade.1.1 > 0.0 {
@@ -555,7 +811,7 @@ impl canvas::Program<Message> for FootprintChart {
                         frame.fill_rectangle(
                             Point::new(x_position - (3.0 * chart.scaling), y_position), 
                             Size::new(bar_width, bar_height), 
-                            Color::from_rgba8(192, 80, 77, 1.0)
+                            fade(chart.palette.sell())
                         );
                     }
                 }
@@ -571,46 +827,52 @@ impl canvas::Program<Message> for FootprintChart {
                         frame.fill_rectangle(
                             Point::new(sell_bar_x_position, bounds.height - sell_bar_height), 
                             Size::new(bar_width, sell_bar_height),
-                            Color::from_rgb8(192, 80, 77)
+                            fade(chart.palette.sell())
                         );
 
                         frame.fill_rectangle(
                             Point::new(x_position + (5.0*chart.scaling), bounds.height - buy_bar_height), 
                             Size::new(bar_width, buy_bar_height),
-                            Color::from_rgb8(81, 205, 160)
+                            fade(chart.palette.buy())
                         );
 
                     } else {
                         let bar_height = (kline.volume.1 / max_volume) * volume_area_height;
 
-                        let color = 
-                            if kline.close >= kline.open { 
-                                Color::from_rgba8(81, 205, 160, 0.8) 
-                            } else { Color::from_rgba8(192, 80, 77, 0.8) 
-                        };
+                        let color = chart.palette.directional(kline.close >= kline.open, 0.8);
 
                         frame.fill_rectangle(
                             Point::new(x_position - (3.0*chart.scaling), bounds.height - bar_height), 
                             Size::new(6.0 * chart.scaling, bar_height),
-                            color
+                            fade(color)
                         );
                     }
                 }
             } 
             
-            let text_size = 9.0;
-            let text_content = format!("{max_volume:.2}");
-            let text_width = (text_content.len() as f32 * text_size) / 1.5;
+            if volume_area_height > 0.0 {
+                let text_size = 9.0;
+                let text_content = format!("{max_volume:.2}");
+                let text_width = (text_content.len() as f32 * text_size) / 1.5;
 
-            let text_position = Point::new(bounds.width - text_width, bounds.height - volume_area_height);
-            
-            frame.fill_text(canvas::Text {
-                content: text_content,
-                position: text_position,
-                size: iced::Pixels(text_size),
-                color: Color::from_rgba8(81, 81, 81, 1.0),
-                ..canvas::Text::default()
-            });
+                let text_position = Point::new(bounds.width - text_width, bounds.height - volume_area_height);
+                
+                frame.fill_text(canvas::Text {
+                    content: text_content,
+                    position: text_position,
+                    size: iced::Pixels(text_size),
+                    color: Color::from_rgba8(81, 81, 81, 1.0),
+                    ..canvas::Text::default()
+                });
+            }
+        });
+
+        let measure = chart.measure.map(|_| {
+            chart.measure_cache.draw(renderer, bounds.size(), |frame| {
+                chart.draw_measure(frame, bounds, self.timeframe, |y| {
+                    highest - (y / footprint_area_height) * y_range
+                });
+            })
         });
 
         if chart.crosshair {
@@ -620,7 +882,7 @@ impl canvas::Program<Message> for FootprintChart {
                         Point::new(0.0, cursor_position.y), 
                         Point::new(bounds.width, cursor_position.y)
                     );
-                    frame.stroke(&line, Stroke::default().with_color(Color::from_rgba8(200, 200, 200, 0.6)).with_width(1.0));
+                    frame.stroke(&line, chart.crosshair_style.stroke());
 
                     let crosshair_ratio = cursor_position.x as f64 / bounds.width as f64;
                     let crosshair_millis = earliest as f64 + crosshair_ratio * (latest - earliest) as f64;
@@ -633,7 +895,7 @@ impl canvas::Program<Message> for FootprintChart {
                         Point::new(snap_x as f32, 0.0), 
                         Point::new(snap_x as f32, bounds.height)
                     );
-                    frame.stroke(&line, Stroke::default().with_color(Color::from_rgba8(200, 200, 200, 0.6)).with_width(1.0));
+                    frame.stroke(&line, chart.crosshair_style.stroke());
 
                     if let Some((_, kline)) = self.data_points.iter()
                         .find(|(time, _)| **time == rounded_timestamp) {
@@ -641,30 +903,40 @@ impl canvas::Program<Message> for FootprintChart {
                             let tooltip_text: String = if kline.1.volume.0 != -1.0 {
                                 format!(
                                     "O: {} H: {} L: {} C: {}\nBuyV: {:.0} SellV: {:.0}",
-                                    kline.1.open, kline.1.high, kline.1.low, kline.1.close, kline.1.volume.0, kline.1.volume.1
+                                    format_price(kline.1.open, chart.price_decimals), format_price(kline.1.high, chart.price_decimals),
+                                    format_price(kline.1.low, chart.price_decimals), format_price(kline.1.close, chart.price_decimals),
+                                    kline.1.volume.0, kline.1.volume.1
                                 )
                             } else {
                                 format!(
                                     "O: {} H: {} L: {} C: {}\nVolume: {:.0}",
-                                    kline.1.open, kline.1.high, kline.1.low, kline.1.close, kline.1.volume.1
+                                    format_price(kline.1.open, chart.price_decimals), format_price(kline.1.high, chart.price_decimals),
+                                    format_price(kline.1.low, chart.price_decimals), format_price(kline.1.close, chart.price_decimals),
+                                    kline.1.volume.1
                                 )
                             };
 
                             let text = canvas::Text {
                                 content: tooltip_text,
                                 position: Point::new(10.0, 10.0),
-                                size: iced::Pixels(12.0),
+                                size: iced::Pixels(chart.label_size),
                                 color: Color::from_rgba8(120, 120, 120, 1.0),
                                 ..canvas::Text::default()
                             };
                             frame.fill_text(text);
                     }
+                } else {
+                    chart.draw_sync_guide(frame, bounds);
                 }
             });
 
-            vec![crosshair, footprint]
+            let mut layers = vec![crosshair, footprint];
+            layers.extend(measure);
+            layers
         }   else {
-            vec![footprint]
+            let mut layers = vec![footprint];
+            layers.extend(measure);
+            layers
         }
     }
 
@@ -677,6 +949,8 @@ impl canvas::Program<Message> for FootprintChart {
         match interaction {
             Interaction::Panning { .. } => mouse::Interaction::Grabbing,
             Interaction::Zoomin { .. } => mouse::Interaction::ZoomIn,
+            Interaction::FibDrawing { .. } | Interaction::FibEditing { .. } => mouse::Interaction::ResizingVertically,
+            Interaction::Measuring { .. } => mouse::Interaction::Crosshair,
             Interaction::None if cursor.is_over(bounds) => {
                 if self.chart.crosshair {
                     mouse::Interaction::Crosshair
@@ -687,4 +961,62 @@ impl canvas::Program<Message> for FootprintChart {
             Interaction::None => { mouse::Interaction::default() }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(price: f32, qty: f32, is_sell: bool) -> Trade {
+        Trade { time: 0, is_sell, price, qty }
+    }
+
+    fn sorted_cells(chart: &FootprintChart) -> Vec<(f32, (f32, f32))> {
+        let (trades, _) = chart.data_points.values().next().expect("one bar");
+        let mut cells = chart.row_cells(trades);
+        cells.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        cells
+    }
+
+    #[test]
+    fn fine_aggregation_merges_into_display_rows() {
+        let trades = vec![trade(100.2, 1.0, false), trade(100.4, 2.0, true), trade(101.1, 3.0, false)];
+        let mut chart = FootprintChart::new(1, 1.0, vec![], trades);
+
+        chart.set_agg_tick_size(Some(0.1));
+
+        // trades stay binned at 0.1 while rows are drawn a whole tick apart
+        assert_eq!(chart.data_points.values().next().unwrap().0.len(), 3);
+        assert_eq!(chart.row_tick_size(), 1.0);
+        assert_eq!(sorted_cells(&chart), vec![(100.0, (1.0, 2.0)), (101.0, (3.0, 0.0))]);
+    }
+
+    #[test]
+    fn coarse_aggregation_outranks_the_display_tick() {
+        let trades = vec![trade(100.2, 1.0, false), trade(103.9, 2.0, true), trade(106.0, 3.0, false)];
+        let mut chart = FootprintChart::new(1, 1.0, vec![], trades);
+
+        chart.set_agg_tick_size(Some(5.0));
+
+        assert_eq!(chart.row_tick_size(), 5.0);
+        assert_eq!(sorted_cells(&chart), vec![(100.0, (1.0, 0.0)), (105.0, (3.0, 2.0))]);
+
+        // back to binning at the display tick, from the same retained trades
+        chart.set_agg_tick_size(None);
+        assert_eq!(sorted_cells(&chart).len(), 3);
+    }
+
+    #[test]
+    fn merging_klines_keeps_the_collected_trades() {
+        let kline = |minute: u64, close: f32| Kline { time: minute * 60_000, open: close, high: close, low: close, close, volume: (1.0, 1.0) };
+
+        let mut chart = FootprintChart::new(1, 1.0, vec![kline(0, 1.0), kline(1, 1.0)], vec![trade(100.0, 1.0, false)]);
+
+        chart.merge_klines(&[kline(0, 2.0), kline(1, 2.0), kline(2, 2.0)]);
+
+        assert_eq!(chart.data_points.keys().copied().collect::<Vec<i64>>(), vec![0, 60_000, 120_000]);
+        assert!(chart.data_points.values().all(|(_, kline)| kline.close == 2.0));
+        assert_eq!(chart.data_points[&0].0.len(), 1);
+    }
+}
@@ -1,25 +1,192 @@
 use std::{collections::{BTreeMap, HashMap, VecDeque}, rc::Rc, time::Instant};
 use chrono::NaiveDateTime;
 use iced::{
-    alignment, mouse, widget::{button, canvas::{self, event::{self, Event}, stroke::Stroke, Canvas, Geometry, Path}}, Color, Element, Length, Point, Rectangle, Renderer, Size, Theme, Vector
+    alignment, mouse, widget::{button, canvas::{self, event::{self, Event}, Cache, Canvas, Geometry, Path, Stroke}}, Color, Element, Length, Point, Rectangle, Renderer, Size, Theme
 };
-use iced::widget::{Column, Row, Container, Text};
+use iced::widget::{Column, Row, Container, Slider, Text};
+use serde::{Deserialize, Serialize};
 
-use crate::data_providers::{Depth, Order, Trade};
+use crate::data_providers::{Depth, Exchange, Order, Ticker, Trade};
 
-use super::{Chart, CommonChartData, Message, chart_button, Interaction, AxisLabelYCanvas, AxisLabelXCanvas};
+use super::{Chart, CommonChartData, Message, chart_button, Interaction, AxisLabelYCanvas, AxisLabelXCanvas, CrosshairStyle, ChartPalette, PriceScale, MAX_VOLUME_RATIO, tick_decimals, draw_offset_guides, draw_debug_overlay};
 
 #[derive(Debug, Clone, Default)]
 pub struct GroupedDepth {
     pub bids: Box<[Order]>,
     pub asks: Box<[Order]>,
 }
+#[derive(Serialize)]
 pub struct GroupedTrade {
     pub is_sell: bool,
     pub price: f32,
     pub qty: f32,
 }
 
+/// A bucket of the heatmap: its time, the book as of its end and the trades within it
+type DepthDataPoint = (i64, (GroupedDepth, Box<[GroupedTrade]>));
+
+/// Latest grouped depth and trades of a heatmap, as exported to JSON
+#[derive(Serialize)]
+pub struct DepthSnapshot<'a> {
+    pub time: i64,
+    pub tick_size: f32,
+    pub bids: Vec<SnapshotLevel>,
+    pub asks: Vec<SnapshotLevel>,
+    pub trades: &'a [GroupedTrade],
+}
+
+#[derive(Serialize)]
+pub struct SnapshotLevel {
+    pub price: f32,
+    pub qty: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum VolumeStyle {
+    /// Separate buy and sell bars per bucket
+    #[default]
+    Split,
+    /// One bar per bucket, sized by total volume and colored by net delta
+    Delta,
+}
+
+impl VolumeStyle {
+    pub const ALL: [VolumeStyle; 2] = [VolumeStyle::Split, VolumeStyle::Delta];
+}
+
+impl std::fmt::Display for VolumeStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VolumeStyle::Split => write!(f, "Split"),
+            VolumeStyle::Delta => write!(f, "Delta"),
+        }
+    }
+}
+
+/// Reference volume the volume bars are scaled against
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, Serialize)]
+pub enum VolumeNorm {
+    /// Largest bucket in view, bars rescale while panning
+    #[default]
+    VisibleMax,
+    /// A set volume, bars above it are capped
+    Fixed(f32),
+    /// Given percentile (0-100) of all collected buckets
+    Percentile(f32),
+}
+
+impl VolumeNorm {
+    pub const DEFAULT_FIXED: f32 = 100.0;
+    pub const DEFAULT_PERCENTILE: f32 = 95.0;
+
+    /// One option per mode, keeping the values of the current one
+    pub fn options(current: VolumeNorm) -> [VolumeNorm; 3] {
+        [
+            VolumeNorm::VisibleMax,
+            match current {
+                VolumeNorm::Fixed(volume) => VolumeNorm::Fixed(volume),
+                _ => VolumeNorm::Fixed(Self::DEFAULT_FIXED),
+            },
+            match current {
+                VolumeNorm::Percentile(percentile) => VolumeNorm::Percentile(percentile),
+                _ => VolumeNorm::Percentile(Self::DEFAULT_PERCENTILE),
+            },
+        ]
+    }
+}
+
+impl std::fmt::Display for VolumeNorm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VolumeNorm::VisibleMax => write!(f, "Visible max"),
+            VolumeNorm::Fixed(_) => write!(f, "Fixed"),
+            VolumeNorm::Percentile(_) => write!(f, "Percentile"),
+        }
+    }
+}
+
+/// Curve mapping a trade's size onto its circle radius
+/// Bucket width offered in the pane settings, in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AggregateInterval(pub i64);
+
+impl AggregateInterval {
+    pub const ALL: [AggregateInterval; 4] = [
+        AggregateInterval(50), AggregateInterval(100), AggregateInterval(250), AggregateInterval(500),
+    ];
+}
+
+impl std::fmt::Display for AggregateInterval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}ms", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum RadiusScale {
+    #[default]
+    Linear,
+    Sqrt,
+    Log,
+}
+
+impl RadiusScale {
+    pub const ALL: [RadiusScale; 3] = [RadiusScale::Linear, RadiusScale::Sqrt, RadiusScale::Log];
+
+    /// Reshapes a qty normalized to 0..=1, keeping both ends in place
+    fn apply(&self, normalized: f32) -> f32 {
+        match self {
+            RadiusScale::Linear => normalized,
+            RadiusScale::Sqrt => normalized.sqrt(),
+            RadiusScale::Log => (1.0 + 9.0 * normalized).log10(),
+        }
+    }
+}
+
+impl std::fmt::Display for RadiusScale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RadiusScale::Linear => write!(f, "Linear"),
+            RadiusScale::Sqrt => write!(f, "Square root"),
+            RadiusScale::Log => write!(f, "Logarithmic"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum SpreadUnit {
+    #[default]
+    Absolute,
+    /// (ask - bid) / mid * 10000
+    Bps,
+}
+
+impl SpreadUnit {
+    pub const ALL: [SpreadUnit; 2] = [SpreadUnit::Absolute, SpreadUnit::Bps];
+
+    pub fn format(&self, best_bid: f32, best_ask: f32, price_decimals: Option<usize>) -> String {
+        let spread = best_ask - best_bid;
+
+        match self {
+            SpreadUnit::Absolute => format!("{spread:.*}", price_decimals.unwrap_or(2)),
+            SpreadUnit::Bps => {
+                let mid_price = (best_ask + best_bid) / 2.0;
+
+                format!("{:.2} bps", spread / mid_price * 10000.0)
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for SpreadUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpreadUnit::Absolute => write!(f, "Absolute"),
+            SpreadUnit::Bps => write!(f, "Basis points"),
+        }
+    }
+}
+
 #[derive(Default)]
 struct QtyScale {
     max_trade_qty: f32,
@@ -30,15 +197,46 @@ struct QtyScale {
 
 pub struct HeatmapChart {
     chart: CommonChartData,
-    data_points: Vec<(i64, (GroupedDepth, Box<[GroupedTrade]>))>,
+    data_points: Vec<DepthDataPoint>,
     tick_size: f32,
+    /// Width of a bucket, depth updates and trades within it are grouped together
+    aggregate_ms: i64,
     y_scaling: i32,
+    /// Time window shown across the chart's width
+    visible_span_ms: u32,
     size_filter: f32,
+    volume_style: VolumeStyle,
+    volume_norm: VolumeNorm,
+    radius_scale: RadiusScale,
+    /// Trade circles drawn per bucket, the smaller ones past it get merged into one
+    max_trade_markers: u32,
+    /// Weight of the previous buckets when smoothing depth intensity over time, 0 is off
+    depth_smoothing: f32,
+    /// Width of the separate current book panel, 0 keeps the bars inline on the heatmap
+    depth_profile_width: f32,
+    depth_profile_cache: Cache,
+    /// Tints levels whose qty dropped sharply since the previous bucket
+    highlight_pulls: bool,
+    /// Line through the largest trade of each bucket at or above this notional, `None` is off
+    trade_trail: Option<f32>,
+    /// Distances from mid in percent to sum the book's liquidity within, `None` is off
+    depth_bands: Option<[f32; 3]>,
+    /// Folds levels outside `price_band` into one level past each edge when grouping depth
+    depth_cutoff: bool,
+    /// Visible price range from the last render, widened by its own height on both sides
+    price_band: Option<(f32, f32)>,
+    /// Bucket of `data_points` frozen in the depth bars, `None` shows the latest one
+    review_index: Option<usize>,
+    spread_unit: SpreadUnit,
+    best_bid_ask: Option<(f32, f32)>,
+    source_depths: Vec<((Exchange, Ticker), Rc<Depth>)>,
     qty_scales: QtyScale,
+    /// Buckets dropped early to stay under the memory budget, for the debug overlay
+    evicted_buckets: usize,
 }
 
 impl Chart for HeatmapChart {
-    type DataPoint = Vec<(i64, (GroupedDepth, Box<[GroupedTrade]>))>;
+    type DataPoint = Vec<DepthDataPoint>;
 
     fn get_common_data(&self) -> &CommonChartData {
         &self.chart
@@ -49,40 +247,195 @@ impl Chart for HeatmapChart {
 }
 
 impl HeatmapChart {
-    const MIN_SCALING: f32 = 1.0;
-    const MAX_SCALING: f32 = 3.0;
+    const MAX_BUCKETS: usize = 2400;
+    /// Buckets dropped at once when `MAX_BUCKETS` is reached
+    const BUCKET_TRIM: usize = 400;
+    /// Latest buckets averaged for the memory estimate
+    const MEMORY_SAMPLE: usize = 50;
+
+    pub const DEFAULT_VISIBLE_SPAN_MS: u32 = 60_000;
+    pub const VISIBLE_SPAN_RANGE: std::ops::RangeInclusive<u32> = 10_000..=600_000;
+    /// Share of the span kept empty ahead of the latest bucket
+    const RIGHT_PADDING: f32 = 0.05;
+
+    pub const DEFAULT_AGGREGATE_MS: i64 = 100;
+
+    pub const DEFAULT_MAX_TRADE_MARKERS: u32 = 1000;
+    pub const TRADE_MARKERS_RANGE: std::ops::RangeInclusive<u32> = 10..=1000;
+
+    pub const MAX_DEPTH_SMOOTHING: f32 = 0.9;
+    pub const MAX_DEPTH_PROFILE_WIDTH: f32 = 240.0;
+
+    /// Share of a level's qty that has to vanish between buckets before it gets tinted
+    const MIN_PULL_RATIO: f32 = 0.3;
+    const PULL_COLOR: Color = Color { r: 1.0, g: 0.78, b: 0.16, a: 1.0 };
+
+    const TRAIL_COLOR: Color = Color { r: 0.86, g: 0.86, b: 0.86, a: 0.7 };
+    const DEPTH_BAND_COLOR: Color = Color { r: 0.6, g: 0.6, b: 0.6, a: 0.6 };
 
     pub fn new(tick_size: f32) -> Self {
         HeatmapChart {
             chart: CommonChartData::default(),
             data_points: Vec::new(),
             tick_size,
+            aggregate_ms: Self::DEFAULT_AGGREGATE_MS,
             y_scaling: 100,
+            visible_span_ms: Self::DEFAULT_VISIBLE_SPAN_MS,
             size_filter: 0.0,
+            volume_style: VolumeStyle::default(),
+            volume_norm: VolumeNorm::default(),
+            radius_scale: RadiusScale::default(),
+            max_trade_markers: Self::DEFAULT_MAX_TRADE_MARKERS,
+            depth_smoothing: 0.0,
+            depth_profile_width: 0.0,
+            depth_profile_cache: Cache::default(),
+            highlight_pulls: false,
+            depth_bands: None,
+            depth_cutoff: false,
+            price_band: None,
+            trade_trail: None,
+            review_index: None,
+            spread_unit: SpreadUnit::default(),
+            best_bid_ask: None,
+            source_depths: Vec::new(),
             qty_scales: QtyScale::default(),
+            evicted_buckets: 0,
         }
     }
 
     fn group_by_price(&self, orders: &[Order], is_bid: bool) -> Box<[Order]> {
         let mut grouped: HashMap<i64, f32> = HashMap::new();
 
+        let offset = self.chart.grid_offset(self.tick_size);
+
+        let band = if self.depth_cutoff { self.price_band } else { None };
+
+        // best level always stays, so the mid price holds even when the band lags behind
+        let best_price = if is_bid {
+            orders.iter().map(|order| order.price).reduce(f32::max)
+        } else {
+            orders.iter().map(|order| order.price).reduce(f32::min)
+        };
+
+        let (mut below_qty, mut above_qty) = (0.0f32, 0.0f32);
+
         for &order in orders {
+            if let Some((low, high)) = band {
+                if Some(order.price) != best_price {
+                    if order.price < low {
+                        below_qty += order.qty;
+                        continue;
+                    } else if order.price > high {
+                        above_qty += order.qty;
+                        continue;
+                    }
+                }
+            }
+
             let rounded_price = if is_bid {
-                ((order.price * (1.0 / self.tick_size)).floor()) as i64
+                (((order.price - offset) * (1.0 / self.tick_size)).floor()) as i64
             } else {
-                ((order.price * (1.0 / self.tick_size)).ceil()) as i64
+                (((order.price - offset) * (1.0 / self.tick_size)).ceil()) as i64
             };
             *grouped.entry(rounded_price).or_insert(0.0) += order.qty;
         }
 
+        if let Some((low, high)) = band {
+            if below_qty > 0.0 {
+                let edge_price = ((low - offset) / self.tick_size).floor() as i64 - 1;
+                *grouped.entry(edge_price).or_insert(0.0) += below_qty;
+            }
+            if above_qty > 0.0 {
+                let edge_price = ((high - offset) / self.tick_size).ceil() as i64 + 1;
+                *grouped.entry(edge_price).or_insert(0.0) += above_qty;
+            }
+        }
+
         grouped.into_iter().map(
             |(price, qty)| Order {
-                price: price as f32 * self.tick_size, 
+                price: price as f32 * self.tick_size + offset, 
                 qty 
             }
         ).collect()
     }
 
+    fn group_depth(&self, bids: &[Order], asks: &[Order]) -> GroupedDepth {
+        //let start = Instant::now();
+
+        let mut grouped_bids = self.group_by_price(bids, true);
+        let mut grouped_asks = self.group_by_price(asks, false);
+
+        grouped_bids.sort_by(|a, b| a.price.partial_cmp(&b.price).unwrap());
+        grouped_asks.sort_by(|a, b| a.price.partial_cmp(&b.price).unwrap());
+
+        //log::info!("Heatmap depth grouping time: {:?}us, {} levels", start.elapsed().as_micros(), grouped_bids.len() + grouped_asks.len());
+
+        GroupedDepth {
+            bids: grouped_bids,
+            asks: grouped_asks,
+        }
+    }
+
+    fn group_trade_price(&self, price: f32, is_sell: bool) -> f32 {
+        let offset = self.chart.grid_offset(self.tick_size);
+
+        let grouped_price = if is_sell {
+            ((price - offset) * (1.0 / self.tick_size)).floor()
+        } else {
+            ((price - offset) * (1.0 / self.tick_size)).ceil()
+        };
+        grouped_price as f32 * self.tick_size + offset
+    }
+
+    pub fn set_crosshair_style(&mut self, crosshair_style: CrosshairStyle) {
+        self.chart.crosshair_style = crosshair_style;
+
+        self.chart.crosshair_cache.clear();
+    }
+
+    pub fn set_min_tick_size(&mut self, min_tick_size: f32) {
+        self.chart.min_tick_size = Some(min_tick_size);
+        self.chart.price_decimals = Some(tick_decimals(min_tick_size));
+
+        self.chart.main_cache.clear();
+        self.chart.crosshair_cache.clear();
+        self.chart.y_labels_cache.clear();
+        self.chart.y_crosshair_cache.clear();
+    }
+
+    pub fn set_price_ruler(&mut self, enabled: bool) {
+        self.chart.price_ruler = enabled;
+    }
+
+    pub fn set_debug_overlay(&mut self, enabled: bool) {
+        self.chart.debug_overlay = enabled;
+
+        self.chart.main_cache.clear();
+    }
+
+    pub fn set_label_size(&mut self, label_size: f32) {
+        self.chart.set_label_size(label_size);
+    }
+
+    pub fn set_price_step(&mut self, price_step: Option<f32>) {
+        self.chart.set_price_step(price_step);
+    }
+
+    pub fn set_palette(&mut self, palette: ChartPalette) {
+        self.chart.palette = palette;
+
+        self.chart.main_cache.clear();
+        self.depth_profile_cache.clear();
+    }
+
+    pub fn set_volume_ratio(&mut self, volume_ratio: f32) {
+        self.chart.volume_ratio = volume_ratio.clamp(0.0, MAX_VOLUME_RATIO);
+
+        self.chart.main_cache.clear();
+        self.chart.y_labels_cache.clear();
+        self.chart.y_crosshair_cache.clear();
+    }
+
     pub fn set_size_filter(&mut self, size_filter: f32) {
         self.size_filter = size_filter;
     }
@@ -90,68 +443,432 @@ impl HeatmapChart {
         self.size_filter
     }
 
+    pub fn set_volume_style(&mut self, volume_style: VolumeStyle) {
+        self.volume_style = volume_style;
+
+        self.chart.main_cache.clear();
+    }
+
+    pub fn set_volume_norm(&mut self, volume_norm: VolumeNorm) {
+        self.volume_norm = volume_norm;
+
+        self.render_start();
+    }
+
+    pub fn set_radius_scale(&mut self, radius_scale: RadiusScale) {
+        self.radius_scale = radius_scale;
+
+        self.chart.main_cache.clear();
+    }
+
+    /// Buckets of another width don't line up with the new ones, so the collected ones are dropped
+    pub fn set_aggregate_time(&mut self, aggregate_ms: i64) {
+        let aggregate_ms = aggregate_ms.max(1);
+
+        if aggregate_ms == self.aggregate_ms {
+            return;
+        }
+
+        self.aggregate_ms = aggregate_ms;
+
+        self.data_points.clear();
+        self.review_index = None;
+
+        // fewer, narrower buckets may no longer cover the span
+        self.set_visible_span(self.visible_span_ms);
+
+        self.chart.main_cache.clear();
+        self.depth_profile_cache.clear();
+    }
+
+    pub fn set_visible_span(&mut self, visible_span_ms: u32) {
+        let range = Self::visible_span_range(self.aggregate_ms);
+
+        self.visible_span_ms = visible_span_ms.clamp(*range.start(), *range.end());
+
+        self.render_start();
+    }
+
+    /// `VISIBLE_SPAN_RANGE` cut down to the time `MAX_BUCKETS` of `aggregate_ms` cover,
+    /// a wider span would only show the trimmed history as a gap
+    pub fn visible_span_range(aggregate_ms: i64) -> std::ops::RangeInclusive<u32> {
+        let kept_ms = u32::try_from(Self::MAX_BUCKETS as i64 * aggregate_ms).unwrap_or(u32::MAX);
+        let start = *Self::VISIBLE_SPAN_RANGE.start();
+
+        start..=(*Self::VISIBLE_SPAN_RANGE.end()).min(kept_ms).max(start)
+    }
+
+    pub fn get_visible_span(&self) -> u32 {
+        self.visible_span_ms
+    }
+
+    pub fn set_max_trade_markers(&mut self, max_trade_markers: u32) {
+        self.max_trade_markers = max_trade_markers.max(1);
+
+        self.chart.main_cache.clear();
+    }
+
+    pub fn set_depth_smoothing(&mut self, depth_smoothing: f32) {
+        self.depth_smoothing = depth_smoothing.clamp(0.0, Self::MAX_DEPTH_SMOOTHING);
+
+        self.chart.main_cache.clear();
+    }
+
+    pub fn set_depth_bands(&mut self, depth_bands: Option<[f32; 3]>) {
+        self.depth_bands = depth_bands;
+
+        self.chart.main_cache.clear();
+    }
+
+    pub fn set_depth_cutoff(&mut self, enabled: bool) {
+        self.depth_cutoff = enabled;
+    }
+
+    pub fn set_highlight_pulls(&mut self, enabled: bool) {
+        self.highlight_pulls = enabled;
+
+        self.chart.main_cache.clear();
+    }
+
+    pub fn set_offset_guides(&mut self, offset_guides: Option<f32>) {
+        self.chart.offset_guides = offset_guides;
+
+        self.chart.main_cache.clear();
+    }
+
+    pub fn set_trade_trail(&mut self, trade_trail: Option<f32>) {
+        self.trade_trail = trade_trail;
+
+        self.chart.main_cache.clear();
+    }
+
+    pub fn set_pinned_range(&mut self, pinned_range: Option<(f32, f32)>) {
+        self.chart.set_pinned_range(pinned_range);
+
+        self.render_start();
+    }
+
+    pub fn get_pinned_range(&self) -> Option<(f32, f32)> {
+        self.chart.pinned_range
+    }
+
+    pub fn set_view_toggles(&mut self, crosshair: Option<bool>, autoscale: Option<bool>) {
+        self.chart.set_view_toggles(crosshair, autoscale);
+    }
+
+    /// (crosshair, autoscale)
+    pub fn get_view_toggles(&self) -> (bool, bool) {
+        (self.chart.crosshair, self.chart.autoscale)
+    }
+
+    pub fn set_expand_on_breakout(&mut self, enabled: bool) {
+        self.chart.expand_on_breakout = enabled;
+    }
+
+    pub fn set_depth_profile_width(&mut self, width: f32) {
+        self.depth_profile_width = width.clamp(0.0, Self::MAX_DEPTH_PROFILE_WIDTH);
+
+        self.chart.main_cache.clear();
+        self.depth_profile_cache.clear();
+    }
+
+    pub fn set_spread_unit(&mut self, spread_unit: SpreadUnit) {
+        self.spread_unit = spread_unit;
+
+        self.chart.main_cache.clear();
+    }
+
     pub fn change_tick_size(&mut self, tick_size: f32) {
         self.tick_size = tick_size;
 
         self.data_points.clear();
+        self.review_index = None;
     }
 
-    pub fn insert_datapoint(&mut self, trades_buffer: &[Trade], depth_update: i64, depth: Rc<Depth>) {
-        let aggregate_time = 100; // 100 ms
-        let rounded_depth_update = (depth_update / aggregate_time) * aggregate_time;
+    /// Whether collected datapoints can be regrouped into `tick_size` without clearing,
+    /// which holds when it's a multiple of the current tick size
+    pub fn can_regroup(&self, tick_size: f32) -> bool {
+        let ratio = tick_size / self.tick_size;
 
-        let grouped_depth = {
-            let mut grouped_bids = self.group_by_price(&depth.bids, true);
-            let mut grouped_asks = self.group_by_price(&depth.asks, false);
+        ratio >= 1.0 && (ratio - ratio.round()).abs() < 0.001
+    }
 
-            grouped_bids.sort_by(|a, b| a.price.partial_cmp(&b.price).unwrap());
-            grouped_asks.sort_by(|a, b| a.price.partial_cmp(&b.price).unwrap());
+    /// Changes the tick size while keeping history, regrouping the collected datapoints.
+    /// Falls back to clearing if they can't be regrouped
+    pub fn regroup_tick_size(&mut self, tick_size: f32) {
+        if !self.can_regroup(tick_size) {
+            self.change_tick_size(tick_size);
+            return;
+        }
 
-            GroupedDepth {
-                bids: grouped_bids,
-                asks: grouped_asks,
-            }
+        self.tick_size = tick_size;
+
+        self.regroup_data_points();
+    }
+
+    /// Moves the grouping grid to run through `grid_anchor`, regrouping collected datapoints onto it
+    pub fn set_grid_anchor(&mut self, grid_anchor: f32) {
+        if self.chart.grid_anchor == grid_anchor {
+            return;
+        }
+        self.chart.grid_anchor = grid_anchor;
+
+        self.regroup_data_points();
+    }
+
+    fn regroup_data_points(&mut self) {
+        let data_points = std::mem::take(&mut self.data_points);
+
+        self.data_points = data_points.into_iter()
+            .map(|(time, (depth, trades))| {
+                let grouped_depth = self.group_depth(&depth.bids, &depth.asks);
+
+                let grouped_trades = trades.iter()
+                    .map(|trade| GroupedTrade {
+                        is_sell: trade.is_sell,
+                        price: self.group_trade_price(trade.price, trade.is_sell),
+                        qty: trade.qty,
+                    })
+                    .collect();
+
+                (time, (grouped_depth, grouped_trades))
+            })
+            .collect();
+
+        self.render_start();
+    }
+
+    pub fn latest_snapshot(&self) -> Option<DepthSnapshot<'_>> {
+        let (time, (depth, trades)) = self.data_points.last()?;
+
+        let levels = |orders: &[Order]| orders.iter()
+            .map(|order| SnapshotLevel { price: order.price, qty: order.qty })
+            .collect();
+
+        Some(DepthSnapshot {
+            time: *time,
+            tick_size: self.tick_size,
+            bids: levels(&depth.bids),
+            asks: levels(&depth.asks),
+            trades,
+        })
+    }
+
+    /// Bucket drawn as the current book, the reviewed one if any
+    fn shown_datapoint(&self) -> Option<&DepthDataPoint> {
+        match self.review_index {
+            Some(index) => self.data_points.get(index),
+            None => self.data_points.last(),
+        }
+    }
+
+    pub fn has_data(&self) -> bool {
+        !self.data_points.is_empty()
+    }
+
+    /// Drops the collected datapoints and per-source books, e.g. after the depth sources of the pane changed
+    pub fn reset_sources(&mut self) {
+        self.source_depths.clear();
+        self.data_points.clear();
+        self.review_index = None;
+    }
+
+    pub fn insert_datapoint(&mut self, trades_buffer: &[Trade], depth_update: i64, depth: Rc<Depth>) {
+        self.push_datapoint(trades_buffer, depth_update, &depth, false);
+    }
+
+    /// Inserts an update from one of several depth sources, 
+    /// the stored book is the sum of the latest book of each source
+    pub fn insert_merged_datapoint(&mut self, source: (Exchange, Ticker), trades_buffer: &[Trade], depth_update: i64, depth: Rc<Depth>) {
+        match self.source_depths.iter_mut().find(|(key, _)| *key == source) {
+            Some((_, source_depth)) => *source_depth = depth,
+            None => self.source_depths.push((source, depth)),
+        }
+
+        let merged_depth = Depth {
+            time: depth_update,
+            bids: self.source_depths.iter()
+                .flat_map(|(_, depth)| depth.bids.iter().copied())
+                .collect(),
+            asks: self.source_depths.iter()
+                .flat_map(|(_, depth)| depth.asks.iter().copied())
+                .collect(),
         };
 
-        let grouped_trades = trades_buffer
+        self.push_datapoint(trades_buffer, depth_update, &merged_depth, true);
+    }
+
+    fn push_datapoint(&mut self, trades_buffer: &[Trade], depth_update: i64, depth: &Depth, merge_bucket: bool) {
+        let rounded_depth_update = (depth_update / self.aggregate_ms) * self.aggregate_ms;
+
+        let best_bid = depth.bids.iter().map(|order| order.price).reduce(f32::max);
+        let best_ask = depth.asks.iter().map(|order| order.price).reduce(f32::min);
+
+        self.best_bid_ask = best_bid.zip(best_ask);
+
+        if let Some(last_trade) = trades_buffer.last() {
+            self.chart.update_last_price(last_trade.price);
+        }
+
+        if let Some((low, high)) = trades_buffer.iter()
+            .map(|trade| (trade.price, trade.price))
+            .reduce(|(low, high), (price, _)| (low.min(price), high.max(price))) 
+        {
+            self.chart.expand_pinned_range(low, high);
+        }
+
+        let grouped_depth = self.group_depth(&depth.bids, &depth.asks);
+
+        let grouped_trades: Box<[GroupedTrade]> = trades_buffer
             .iter()
             .map(|trade| GroupedTrade {
                 is_sell: trade.is_sell,
-                price: {
-                    let grouped_price = if trade.is_sell {
-                        (trade.price * (1.0 / self.tick_size)).floor()
-                    } else {
-                        (trade.price * (1.0 / self.tick_size)).ceil()
-                    };
-                    grouped_price as f32 * self.tick_size
-                },
+                price: self.group_trade_price(trade.price, trade.is_sell),
                 qty: trade.qty,
             })
             .collect();
         
-        self.data_points.push((rounded_depth_update, (grouped_depth, grouped_trades)));
+        match self.data_points.last_mut() {
+            // updates of other sources landing in the same bucket extend it instead of adding a new one
+            Some((time, (last_depth, last_trades))) if merge_bucket && *time == rounded_depth_update => {
+                *last_depth = grouped_depth;
+
+                let mut trades = std::mem::take(last_trades).into_vec();
+                trades.extend(grouped_trades.into_vec());
+
+                *last_trades = trades.into_boxed_slice();
+            },
+            _ => {
+                self.data_points.push((rounded_depth_update, (grouped_depth, grouped_trades)));
+            },
+        }
     
-        if self.data_points.len() > 2400 {
-            self.data_points.drain(0..400);
+        if self.data_points.len() > Self::MAX_BUCKETS {
+            self.drop_oldest(Self::BUCKET_TRIM);
         }
         
-        self.render_start();
+        if !self.chart.idle {
+            self.render_start();
+        }
+    }
+
+    fn drop_oldest(&mut self, count: usize) {
+        let count = count.min(self.data_points.len());
+
+        self.data_points.drain(0..count);
+
+        // keeps pointing at the same moment, or the oldest one left if it got dropped
+        self.review_index = self.review_index.map(|index| index.saturating_sub(count));
+    }
+
+    /// Approximate bytes held by the buckets, their count times the average size of the latest ones
+    pub fn memory_estimate(&self) -> usize {
+        let sample = &self.data_points[self.data_points.len().saturating_sub(Self::MEMORY_SAMPLE)..];
+
+        if sample.is_empty() {
+            return 0;
+        }
+
+        let sample_bytes: usize = sample.iter()
+            .map(|(_, (depth, trades))| {
+                std::mem::size_of::<DepthDataPoint>()
+                    + (depth.bids.len() + depth.asks.len()) * std::mem::size_of::<Order>()
+                    + trades.len() * std::mem::size_of::<GroupedTrade>()
+            })
+            .sum();
+
+        sample_bytes / sample.len() * self.data_points.len()
+    }
+
+    /// Drops the oldest share of the buckets, returns how many went
+    pub fn evict_share(&mut self, share: f32) -> usize {
+        let count = (self.data_points.len() as f32 * share.clamp(0.0, 1.0)) as usize;
+
+        self.drop_oldest(count);
+        self.evicted_buckets += count;
+
+        count
+    }
+
+    /// Coming back from idle renders whatever got collected in the meantime
+    pub fn set_idle(&mut self, idle: bool) {
+        self.chart.idle = idle;
+
+        if !idle {
+            self.render_start();
+        }
     }
 
     fn visible_data_iter(
         &self, 
         earliest: i64, latest: i64
-    ) -> impl Iterator<Item = &(i64, (GroupedDepth, Box<[GroupedTrade]>))> {
+    ) -> impl Iterator<Item = &DepthDataPoint> {
         self.data_points.iter().filter(move |(time, _)| *time >= earliest && *time <= latest)
     }
 
+    /// Grouped depth quantity at the price level nearest to `price`, 
+    /// from the bucket nearest to `time`
+    fn depth_qty_at(&self, time: i64, price: f32) -> Option<(bool, f32)> {
+        let idx = self.data_points.partition_point(|(t, _)| *t < time);
+
+        let (_, (depth, _)) = [idx.checked_sub(1), Some(idx)].into_iter()
+            .flatten()
+            .filter_map(|i| self.data_points.get(i))
+            .min_by_key(|(t, _)| (t - time).abs())?;
+
+        depth.bids.iter().map(|order| (true, order))
+            .chain(depth.asks.iter().map(|order| (false, order)))
+            .filter(|(_, order)| (order.price - price).abs() <= self.tick_size / 2.0)
+            .min_by(|(_, a), (_, b)| {
+                (a.price - price).abs().total_cmp(&(b.price - price).abs())
+            })
+            .map(|(is_bid, order)| (is_bid, order.qty))
+    }
+
+    fn bucket_volume(&self, trades: &[GroupedTrade]) -> f32 {
+        let (buy_volume, sell_volume) = trades.iter().fold((0.0f32, 0.0f32), |(buy, sell), trade| {
+            if trade.is_sell {
+                (buy, sell + trade.qty)
+            } else {
+                (buy + trade.qty, sell)
+            }
+        });
+
+        match self.volume_style {
+            VolumeStyle::Split => buy_volume.max(sell_volume),
+            VolumeStyle::Delta => buy_volume + sell_volume,
+        }
+    }
+
+    /// Bucket volume at `percentile` across every collected datapoint, ignoring empty buckets
+    fn volume_percentile(&self, percentile: f32) -> Option<f32> {
+        let mut volumes: Vec<f32> = self.data_points.iter()
+            .map(|(_, (_, trades))| self.bucket_volume(trades))
+            .filter(|volume| *volume > 0.0)
+            .collect();
+
+        if volumes.is_empty() {
+            return None;
+        }
+
+        let index = ((percentile.clamp(0.0, 100.0) / 100.0) * (volumes.len() - 1) as f32).round() as usize;
+        let (_, volume, _) = volumes.select_nth_unstable_by(index, f32::total_cmp);
+
+        Some(*volume)
+    }
+
     fn calculate_scales(&self) -> (i64, i64, f32, f32, QtyScale) {
         //let start = Instant::now();
 
         let timestamp_latest: &i64 = self.data_points.last().map(|(timestamp, _)| timestamp).unwrap_or(&0);
 
-        let latest: i64 = *timestamp_latest - ((self.chart.translation.x - (self.chart.bounds.width/20.0)) * 60.0) as i64;
-        let earliest: i64 = latest - (48000.0 / (self.chart.scaling / (self.chart.bounds.width/800.0))) as i64;
+        // panning moves by the same share of the span whatever its length
+        let span = self.visible_span_ms as f32;
+        let ms_per_pixel = span / self.chart.bounds.width.max(1.0);
+
+        let latest: i64 = *timestamp_latest 
+            - (self.chart.translation.x * ms_per_pixel - span * Self::RIGHT_PADDING) as i64;
+        let earliest: i64 = latest - i64::from(self.visible_span_ms);
     
         let (mut highest, mut lowest) = (0.0f32, f32::MAX);
 
@@ -176,6 +893,10 @@ impl HeatmapChart {
             }
         }
 
+        if let Some((pinned_low, pinned_high)) = self.chart.pinned_range {
+            (highest, lowest) = (pinned_high, pinned_low);
+        }
+
         for (_, (depth, trades)) in self.visible_data_iter(earliest, latest) {
             let (mut buy_volume, mut sell_volume) = (0.0, 0.0);
 
@@ -190,7 +911,10 @@ impl HeatmapChart {
                 }
             }
 
-            max_aggr_volume = max_aggr_volume.max(buy_volume).max(sell_volume);
+            max_aggr_volume = match self.volume_style {
+                VolumeStyle::Split => max_aggr_volume.max(buy_volume).max(sell_volume),
+                VolumeStyle::Delta => max_aggr_volume.max(buy_volume + sell_volume),
+            };
 
             for order in depth.asks.iter().chain(depth.bids.iter()) {
                 if order.price > highest || order.price < lowest {
@@ -200,6 +924,13 @@ impl HeatmapChart {
             }
         }
 
+        let max_aggr_volume = match self.volume_norm {
+            VolumeNorm::VisibleMax => max_aggr_volume,
+            VolumeNorm::Fixed(volume) => volume,
+            VolumeNorm::Percentile(percentile) => self.volume_percentile(percentile)
+                .unwrap_or(max_aggr_volume),
+        };
+
         //log::info!("Heatmap scales calculation time: {:?}us", start.elapsed().as_micros());
 
         (
@@ -231,6 +962,9 @@ impl HeatmapChart {
 
         self.qty_scales = visible_qty_scales;
 
+        let band_margin = highest - lowest;
+        self.price_band = Some((lowest - band_margin, highest + band_margin));
+
         let chart_state = self.get_common_data_mut();
 
         if earliest != chart_state.x_min_time || latest != chart_state.x_max_time {         
@@ -251,6 +985,8 @@ impl HeatmapChart {
         
         chart_state.crosshair_cache.clear();     
         chart_state.main_cache.clear();   
+
+        self.depth_profile_cache.clear();
     }
 
     pub fn update(&mut self, message: &Message) {
@@ -287,12 +1023,15 @@ impl HeatmapChart {
                 self.chart.bounds = *bounds;
             },
             Message::AutoscaleToggle => {
+                if self.chart.pinned_range.is_some() {
+                    return;
+                }
+
                 self.chart.autoscale = !self.chart.autoscale;
 
+                // only re-centers price, the time zoom and position are left as they were
                 if self.chart.autoscale {
-                    self.chart.translation = Vector::default();
-
-                    self.chart.scaling = 1.0;
+                    self.chart.translation.y = 0.0;
                 }
             },
             Message::CrosshairToggle => {
@@ -329,6 +1068,21 @@ impl HeatmapChart {
                     }
                 }
             },
+            Message::VisibleSpanChanged(visible_span_ms) => {
+                self.set_visible_span(*visible_span_ms);
+            },
+            Message::ReviewMoved(index) => {
+                let last_index = self.data_points.len().checked_sub(1);
+
+                self.review_index = match (index, last_index) {
+                    (Some(index), Some(last_index)) if *index < last_index => Some(*index),
+                    _ => None,
+                };
+
+                self.chart.main_cache.clear();
+                self.depth_profile_cache.clear();
+            },
+            Message::FibToolToggle | Message::FibDragged(_) | Message::FibCleared | Message::VolumeProfileToggle | Message::MeasureUpdate(_) => {},
         }
     }
 
@@ -348,9 +1102,10 @@ impl HeatmapChart {
                 crosshair_position: chart_state.crosshair_position, 
                 crosshair: chart_state.crosshair,
                 timeframe: None,
+                label_size: chart_state.label_size,
             })
             .width(Length::FillPortion(10))
-            .height(Length::Fixed(26.0));
+            .height(Length::Fixed(26.0 * chart_state.label_scale()));
 
         let axis_labels_y = Canvas::new(
             AxisLabelYCanvas { 
@@ -360,8 +1115,18 @@ impl HeatmapChart {
                 max: chart_state.y_max_price,
                 crosshair_position: chart_state.crosshair_position, 
                 crosshair: chart_state.crosshair,
+                price_scale: PriceScale::Regular,
+                base_price: 0.0,
+                volume_ratio: chart_state.volume_ratio,
+                min_tick_size: chart_state.min_tick_size,
+                price_decimals: chart_state.price_decimals,
+                last_price: chart_state.last_price.filter(|_| chart_state.price_ruler),
+                bar_countdown: None,
+                palette: chart_state.palette,
+                label_size: chart_state.label_size,
+                price_step: chart_state.price_step,
             })
-            .width(Length::Fixed(60.0))
+            .width(Length::Fixed(60.0 * chart_state.label_scale()))
             .height(Length::FillPortion(10));
 
         let autoscale_button = button(
@@ -388,22 +1153,74 @@ impl HeatmapChart {
                 .push(autoscale_button)
                 .push(crosshair_button).spacing(2)
             ).padding([0, 2])
-            .width(Length::Fixed(60.0))
-            .height(Length::Fixed(26.0));
+            .width(Length::Fixed(60.0 * chart_state.label_scale()))
+            .height(Length::Fixed(26.0 * chart_state.label_scale()));
+
+        let mut chart_and_y_labels = Row::new()
+            .push(chart);
+
+        if self.depth_profile_width > 0.0 {
+            chart_and_y_labels = chart_and_y_labels.push(
+                Canvas::new(
+                    DepthProfileCanvas {
+                        cache: &self.depth_profile_cache,
+                        depth: self.shown_datapoint().map(|(_, (depth, _))| depth),
+                        trades: self.review_index
+                            .and_then(|_| self.shown_datapoint())
+                            .map_or(&[], |(_, (_, trades))| trades),
+                        min: chart_state.y_min_price,
+                        max: chart_state.y_max_price,
+                        tick_size: self.tick_size,
+                        volume_ratio: chart_state.volume_ratio,
+                        palette: chart_state.palette,
+                    })
+                    .width(Length::Fixed(self.depth_profile_width))
+                    .height(Length::FillPortion(10))
+            );
+        }
 
-        let chart_and_y_labels = Row::new()
-            .push(chart)
+        let chart_and_y_labels = chart_and_y_labels
             .push(axis_labels_y);
     
         let bottom_row = Row::new()
             .push(axis_labels_x)
             .push(chart_controls);
     
-        let content = Column::new()
+        let mut content = Column::new()
             .push(chart_and_y_labels)
             .push(bottom_row)
             .spacing(0)
             .padding(5);
+
+        if let Some(last_index) = self.data_points.len().checked_sub(1).filter(|last| *last > 0) {
+            let review_index = self.review_index.unwrap_or(last_index);
+
+            let live_button = button(
+                Text::new("Live")
+                    .size(12)
+                    .align_x(alignment::Horizontal::Center)
+                )
+                .on_press(Message::ReviewMoved(None))
+                .style(move |_theme: &Theme, _status: iced::widget::button::Status| chart_button(_theme, _status, self.review_index.is_none()));
+
+            let review_time = self.data_points.get(review_index)
+                .and_then(|(time, _)| chrono::DateTime::from_timestamp_millis(*time))
+                .map(|time| time.format("%H:%M:%S").to_string())
+                .unwrap_or_default();
+
+            content = content.push(
+                Row::new()
+                    .spacing(8)
+                    .padding([2, 0])
+                    .align_y(alignment::Vertical::Center)
+                    .push(
+                        Slider::new(0.0..=last_index as f32, review_index as f32, |index| Message::ReviewMoved(Some(index as usize)))
+                            .step(1.0)
+                    )
+                    .push(Text::new(review_time).size(12))
+                    .push(live_button)
+            );
+        }
     
         content.into()
     }
@@ -482,13 +1299,15 @@ impl canvas::Program<Message> for HeatmapChart {
                 }
                 mouse::Event::WheelScrolled { delta } => match delta {
                     mouse::ScrollDelta::Lines { y, .. } | mouse::ScrollDelta::Pixels { y, .. } => {                        
-                        if y < 0.0 && chart_state.scaling > Self::MIN_SCALING
-                            || y > 0.0 && chart_state.scaling < Self::MAX_SCALING 
-                        {
-                            let scaling = (chart_state.scaling * (1.0 + y / 30.0))
-                                .clamp(Self::MIN_SCALING, Self::MAX_SCALING);
+                        let range = Self::visible_span_range(self.aggregate_ms);
+
+                        // scrolling up zooms in, so the span shrinks
+                        let visible_span_ms = (self.visible_span_ms as f32 * (1.0 - y / 30.0))
+                            .clamp(*range.start() as f32, *range.end() as f32)
+                            as u32;
 
-                            (event::Status::Captured, Some(Message::Scaled(scaling, None)))
+                        if visible_span_ms != self.visible_span_ms {
+                            (event::Status::Captured, Some(Message::VisibleSpanChanged(visible_span_ms)))
                         } else {
                             (event::Status::Captured, None)
                         }
@@ -515,12 +1334,24 @@ impl canvas::Program<Message> for HeatmapChart {
 
         let y_range: f32 = highest - lowest;
         
-        let volume_area_height: f32 = bounds.height / 8.0; 
+        let volume_area_height: f32 = bounds.height * chart.volume_ratio; 
         let heatmap_area_height: f32 = bounds.height - volume_area_height;
 
         let depth_area_width: f32 = bounds.width / 20.0;
 
         let heatmap = chart.main_cache.draw(renderer, bounds.size(), |frame| {
+            draw_debug_overlay(
+                frame, 
+                chart, 
+                self.data_points.len(), 
+                self.data_points.last().map(|(time, _)| *time),
+                Some(format!(
+                    "~{:.1}MB, {} evicted over budget", 
+                    self.memory_estimate() as f32 / (1024.0 * 1024.0), 
+                    self.evicted_buckets
+                )),
+            );
+
             //let start = Instant::now();
             let mut bar_height: f32 = 1.0;
 
@@ -528,8 +1359,8 @@ impl canvas::Program<Message> for HeatmapChart {
             let max_depth_qty = self.qty_scales.max_depth_qty;
             let (min_trade_qty, max_trade_qty) = (self.qty_scales.min_trade_qty, self.qty_scales.max_trade_qty);
 
-            // draw: current depth as bars on the right side
-            if let Some((latest_timestamp, (grouped_depth, _))) = self.data_points.last() {
+            // draw: current depth as bars on the right side, or the reviewed bucket's depth at its own time
+            if let Some((latest_timestamp, (grouped_depth, _))) = self.shown_datapoint() {
                 let x_position = ((latest_timestamp - earliest) as f32 / (latest - earliest) as f32) * bounds.width;
 
                 if x_position.is_nan() {
@@ -558,33 +1389,16 @@ impl canvas::Program<Message> for HeatmapChart {
                 
                 bar_height = (((lowest_bid_y_pos - highest_ask_y_pos) / (y_range / self.tick_size) as f32).floor()).max(1.0);
 
-                let mut max_qty = latest_bids.iter()
-                    .map(|(_, qty)| qty)
-                    .chain(latest_asks.iter().map(|(_, qty)| qty))
-                    .fold(f32::MIN, |price: f32, qty: &f32| f32::max(price, *qty));
-                max_qty = (max_qty / 5.0).ceil() * 5.0;
-
-                for (price, qty) in &latest_bids {     
-                    let y_position = heatmap_area_height - ((price - lowest) / y_range * heatmap_area_height);
-                
-                    let bar_width = (qty / max_qty) * depth_area_width;
+                // shown in its own panel instead when that's enabled
+                let inline_profile = self.depth_profile_width <= 0.0;
 
-                    frame.fill_rectangle(
-                        Point::new(x_position, y_position - (bar_height/2.0)), 
-                        Size::new(bar_width, bar_height), 
-                        Color::from_rgba8(0, 144, 144, 0.5)
-                    );
-                }
-                
-                for (price, qty) in &latest_asks {
-                    let y_position = heatmap_area_height - ((price - lowest) / y_range * heatmap_area_height);
-                
-                    let bar_width = (qty / max_qty) * depth_area_width;
+                let max_qty = depth_bars_max_qty(&latest_bids, &latest_asks);
 
-                    frame.fill_rectangle(
-                        Point::new(x_position, y_position - (bar_height/2.0)), 
-                        Size::new(bar_width, bar_height), 
-                        Color::from_rgba8(192, 0, 192, 0.5)
+                if inline_profile {
+                    draw_depth_bars(
+                        frame, &latest_bids, &latest_asks, max_qty, chart.palette,
+                        DepthBarArea { x_start: x_position, width: depth_area_width, bar_height },
+                        |price| heatmap_area_height - ((price - lowest) / y_range * heatmap_area_height)
                     );
                 }
                 
@@ -597,19 +1411,39 @@ impl canvas::Program<Message> for HeatmapChart {
 
                 // max bid/ask quantity text
                 let text_size = 9.0;
-                let text_content = format!("{max_qty:.0}");
-                let text_position = Point::new(x_position + depth_area_width, 0.0);
-                frame.fill_text(canvas::Text {
-                    content: text_content,
-                    position: text_position,
-                    size: iced::Pixels(text_size),
-                    color: Color::from_rgba8(121, 121, 121, 1.0),
-                    ..canvas::Text::default()
-                });
+                if inline_profile {
+                    let text_content = format!("{max_qty:.0}");
+                    let text_position = Point::new(x_position + depth_area_width, 0.0);
+                    frame.fill_text(canvas::Text {
+                        content: text_content,
+                        position: text_position,
+                        size: iced::Pixels(text_size),
+                        color: Color::from_rgba8(121, 121, 121, 1.0),
+                        ..canvas::Text::default()
+                    });
+                }
+
+                // spread text
+                if let Some((best_bid, best_ask)) = self.best_bid_ask {
+                    frame.fill_text(canvas::Text {
+                        content: format!("Spread: {}", self.spread_unit.format(best_bid, best_ask, chart.price_decimals)),
+                        position: Point::new(x_position + depth_area_width, text_size + 4.0),
+                        size: iced::Pixels(text_size),
+                        color: Color::from_rgba8(121, 121, 121, 1.0),
+                        ..canvas::Text::default()
+                    });
+                }
+
+                if let Some(depth_bands) = self.depth_bands {
+                    draw_depth_bands(
+                        frame, grouped_depth, depth_bands, x_position, chart.label_size,
+                        |price| heatmap_area_height - ((price - lowest) / y_range * heatmap_area_height)
+                    );
+                }
 
                 // max aggregated volume text
                 let text_content = format!("{max_aggr_volume:.2}");
-                if x_position > bounds.width {      
+                if volume_area_height > 0.0 && x_position > bounds.width {      
                     let text_width = (text_content.len() as f32 * text_size) / 1.5;
 
                     let text_position = Point::new(bounds.width - text_width, bounds.height - (volume_area_height - bar_height));
@@ -622,7 +1456,7 @@ impl canvas::Program<Message> for HeatmapChart {
                         ..canvas::Text::default()
                     });
 
-                } else {
+                } else if volume_area_height > 0.0 {
                     let text_position = Point::new(x_position + 5.0, bounds.height - (volume_area_height - bar_height));
 
                     frame.fill_text(canvas::Text {
@@ -643,6 +1477,45 @@ impl canvas::Program<Message> for HeatmapChart {
 
             let mut prev_x_position: Option<f32> = None;
 
+            // per price level EMA of the drawn qty, carried across buckets so levels
+            // blinking in and out between snapshots don't strobe
+            let (tick_size, depth_smoothing) = (self.tick_size, self.depth_smoothing);
+            let mut smoothed_qtys: HashMap<i64, f32> = HashMap::new();
+
+            let mut smooth_qty = |price: f32, qty: f32| -> f32 {
+                if depth_smoothing <= 0.0 {
+                    return qty;
+                }
+
+                let ema = smoothed_qtys.entry((price / tick_size).round() as i64).or_insert(qty);
+                *ema = *ema * depth_smoothing + qty * (1.0 - depth_smoothing);
+                *ema
+            };
+
+            // qty of each level in the previous bucket, to spot liquidity being pulled
+            let highlight_pulls = self.highlight_pulls;
+            let mut prev_bid_levels: HashMap<i64, f32> = HashMap::new();
+            let mut prev_ask_levels: HashMap<i64, f32> = HashMap::new();
+
+            let level_color = |levels: &mut HashMap<i64, f32>, order: &Order, alpha: f32, color: Color| -> Color {
+                if !highlight_pulls {
+                    return Color { a: alpha, ..color };
+                }
+
+                let prev_qty = levels.insert((order.price / tick_size).round() as i64, order.qty);
+
+                match prev_qty.map(|prev_qty| pull_ratio(prev_qty, order.qty)) {
+                    Some(ratio) if ratio > Self::MIN_PULL_RATIO => {
+                        let tint = (ratio - Self::MIN_PULL_RATIO) / (1.0 - Self::MIN_PULL_RATIO);
+
+                        Color { a: alpha.max(tint), ..mix_colors(color, Self::PULL_COLOR, tint) }
+                    },
+                    _ => Color { a: alpha, ..color },
+                }
+            };
+
+            let mut trail_points: Vec<Point> = Vec::new();
+
             for (time, (depth, trades)) in self.visible_data_iter(earliest, latest) {
                 let x_position = ((time - earliest) as f32 / (latest - earliest) as f32) * bounds.width;
 
@@ -652,17 +1525,21 @@ impl canvas::Program<Message> for HeatmapChart {
 
                 for order in depth.bids.iter() {
                     if order.price >= lowest {
+                        let drawn_qty = smooth_qty(order.price, order.qty);
+
                         if let (Some(prev_price), Some(prev_qty), Some(prev_x)) = (prev_bid_price, prev_bid_qty, prev_x_position) {
                             let y_position = heatmap_area_height - ((order.price - lowest) / y_range * heatmap_area_height);
-                            let color_alpha = (order.qty / max_depth_qty).min(1.0);
+                            let color_alpha = (drawn_qty / max_depth_qty).min(1.0);
 
                             if prev_price != order.price || prev_qty != order.qty {
                                 frame.fill_rectangle(
                                     Point::new(prev_x, y_position - (bar_height/2.0)),
                                     Size::new(x_position - prev_x, bar_height),
-                                    Color::from_rgba8(0, 144, 144, color_alpha)
+                                    level_color(&mut prev_bid_levels, order, color_alpha, chart.palette.bid())
                                 );
                             }
+                        } else if highlight_pulls {
+                            prev_bid_levels.insert((order.price / tick_size).round() as i64, order.qty);
                         }
                         prev_bid_price = Some(order.price);
                         prev_bid_qty = Some(order.qty);
@@ -671,17 +1548,21 @@ impl canvas::Program<Message> for HeatmapChart {
 
                 for order in depth.asks.iter() {
                     if order.price <= highest {
+                        let drawn_qty = smooth_qty(order.price, order.qty);
+
                         if let (Some(prev_price), Some(prev_qty), Some(prev_x)) = (prev_ask_price, prev_ask_qty, prev_x_position) {
                             let y_position = heatmap_area_height - ((order.price - lowest) / y_range * heatmap_area_height);
-                            let color_alpha = (order.qty / max_depth_qty).min(1.0);
+                            let color_alpha = (drawn_qty / max_depth_qty).min(1.0);
 
                             if prev_price != order.price || prev_qty != order.qty {
                                 frame.fill_rectangle(
                                     Point::new(prev_x, y_position - (bar_height/2.0)), 
                                     Size::new(x_position - prev_x, bar_height), 
-                                    Color::from_rgba8(192, 0, 192, color_alpha)
+                                    level_color(&mut prev_ask_levels, order, color_alpha, chart.palette.ask())
                                 );
                             }
+                        } else if highlight_pulls {
+                            prev_ask_levels.insert((order.price / tick_size).round() as i64, order.qty);
                         }
                         prev_ask_price = Some(order.price);
                         prev_ask_qty = Some(order.qty);
@@ -693,6 +1574,10 @@ impl canvas::Program<Message> for HeatmapChart {
                 let mut buy_volume: f32 = 0.0;
                 let mut sell_volume: f32 = 0.0;
 
+                let mut largest_trade: Option<&GroupedTrade> = None;
+
+                let mut markers: Vec<&GroupedTrade> = Vec::new();
+
                 for trade in trades.iter() {
                     if trade.is_sell {
                         sell_volume += trade.qty;
@@ -704,45 +1589,115 @@ impl canvas::Program<Message> for HeatmapChart {
                         continue;
                     }
 
-                    if trade.qty *  trade.price > self.size_filter {
-                        let x_position = (((time - 100) - earliest) as f32 / (latest - earliest) as f32) * bounds.width;
-                        let y_position = heatmap_area_height - ((trade.price - lowest) / y_range * heatmap_area_height);
+                    if self.trade_trail.is_some_and(|threshold| trade.qty * trade.price >= threshold)
+                        && !largest_trade.is_some_and(|largest| largest.qty >= trade.qty) {
+                        largest_trade = Some(trade);
+                    }
 
-                        let color = if trade.is_sell {
-                            Color::from_rgba8(192, 80, 77, 1.0)
-                        } else {
-                            Color::from_rgba8(81, 205, 160, 1.0)
-                        };
-
-                        let radius: f32 = match max_trade_qty == min_trade_qty {
-                            true => 1.0,
-                            false => 1.0 + (trade.qty - min_trade_qty) * (35.0 - 1.0) / (max_trade_qty - min_trade_qty),
-                        };
-
-                        frame.fill(
-                            &Path::circle(Point::new(x_position, y_position), radius), 
-                            color
-                        );
+                    if trade.qty *  trade.price > self.size_filter {
+                        markers.push(trade);
                     }
                 }
 
-                if max_aggr_volume > 0.0 {
-                    let buy_bar_height = (buy_volume / max_aggr_volume) * (volume_area_height - bar_height);
-                    frame.fill_rectangle(
-                        Point::new(x_position as f32 + 2.0, bounds.height - buy_bar_height), 
-                        Size::new(1.0, buy_bar_height), 
-                        Color::from_rgb8(81, 205, 160)
-                    );
+                let trade_x = trade_x(*time, self.aggregate_ms, earliest, latest, bounds.width);
+
+                // keeps busy buckets from drawing hundreds of circles, the rest are summed into one
+                let merged_rest = (markers.len() > self.max_trade_markers as usize).then(|| {
+                    markers.sort_unstable_by(|a, b| b.qty.total_cmp(&a.qty));
+
+                    merge_trades(&markers.split_off(self.max_trade_markers as usize))
+                });
 
-                    let sell_bar_height = (sell_volume / max_aggr_volume) * (volume_area_height - bar_height);
-                    frame.fill_rectangle(
-                        Point::new(x_position as f32, bounds.height - sell_bar_height), 
-                        Size::new(1.0, sell_bar_height), 
-                        Color::from_rgb8(192, 80, 77)
+                for trade in markers.into_iter().chain(merged_rest.as_ref()) {
+                    let y_position = heatmap_area_height - ((trade.price - lowest) / y_range * heatmap_area_height);
+
+                    let color = chart.palette.directional(!trade.is_sell, 1.0);
+
+                    let radius: f32 = match max_trade_qty == min_trade_qty {
+                        true => 1.0,
+                        false => {
+                            let normalized = ((trade.qty - min_trade_qty) / (max_trade_qty - min_trade_qty)).min(1.0);
+
+                            1.0 + self.radius_scale.apply(normalized) * (35.0 - 1.0)
+                        },
+                    };
+
+                    frame.fill(
+                        &Path::circle(Point::new(trade_x, y_position), radius), 
+                        color
                     );
                 }
+
+                if let Some(trade) = largest_trade {
+                    let y_position = heatmap_area_height - ((trade.price - lowest) / y_range * heatmap_area_height);
+
+                    trail_points.push(Point::new(trade_x, y_position));
+                }
+
+                if max_aggr_volume > 0.0 && volume_area_height > bar_height {
+                    match self.volume_style {
+                        VolumeStyle::Split => {
+                            let buy_bar_height = (buy_volume / max_aggr_volume).min(1.0) * (volume_area_height - bar_height);
+                            frame.fill_rectangle(
+                                Point::new(x_position + 2.0, bounds.height - buy_bar_height), 
+                                Size::new(1.0, buy_bar_height), 
+                                chart.palette.buy()
+                            );
+
+                            let sell_bar_height = (sell_volume / max_aggr_volume).min(1.0) * (volume_area_height - bar_height);
+                            frame.fill_rectangle(
+                                Point::new(x_position, bounds.height - sell_bar_height), 
+                                Size::new(1.0, sell_bar_height), 
+                                chart.palette.sell()
+                            );
+                        },
+                        VolumeStyle::Delta => {
+                            let total_volume = buy_volume + sell_volume;
+
+                            // an empty bucket has no delta to color by, and nothing to draw
+                            if total_volume > 0.0 {
+                                let total_bar_height = (total_volume / max_aggr_volume).min(1.0) * (volume_area_height - bar_height);
+
+                                let delta = buy_volume - sell_volume;
+                                let delta_alpha = 0.4 + 0.6 * (delta.abs() / total_volume);
+
+                                let color = chart.palette.directional(delta >= 0.0, delta_alpha);
+
+                                frame.fill_rectangle(
+                                    Point::new(x_position, bounds.height - total_bar_height), 
+                                    Size::new(3.0, total_bar_height), 
+                                    color
+                                );
+                            }
+                        },
+                    }
+                }
             };
 
+            // draw: trail through the largest trades, over the trade circles
+            if trail_points.len() > 1 {
+                let trail = Path::new(|builder| {
+                    builder.move_to(trail_points[0]);
+
+                    for point in &trail_points[1..] {
+                        builder.line_to(*point);
+                    }
+                });
+
+                frame.stroke(
+                    &trail, 
+                    Stroke::default().with_color(Self::TRAIL_COLOR).with_width(1.5)
+                );
+            }
+
+            draw_offset_guides(
+                frame, 
+                chart, 
+                self.tick_size, 
+                bounds.width, 
+                |price| heatmap_area_height - ((price - lowest) / y_range * heatmap_area_height)
+            );
+
             //log::info!("Heatmap draw time: {:?}us", start.elapsed().as_micros());
         });
 
@@ -753,10 +1708,11 @@ impl canvas::Program<Message> for HeatmapChart {
                         Point::new(0.0, cursor_position.y), 
                         Point::new(bounds.width, cursor_position.y)
                     );
-                    frame.stroke(&line, Stroke::default().with_color(Color::from_rgba8(200, 200, 200, 0.6)).with_width(1.0));
+                    frame.stroke(&line, chart.crosshair_style.stroke());
 
                     let crosshair_ratio = cursor_position.x as f64 / bounds.width as f64;
-                    let crosshair_millis = (earliest as f64 + crosshair_ratio * (latest as f64 - earliest as f64)).round() / 100.0 * 100.0;
+                    let aggregate_ms = self.aggregate_ms as f64;
+                    let crosshair_millis = ((earliest as f64 + crosshair_ratio * (latest as f64 - earliest as f64)) / aggregate_ms).round() * aggregate_ms;
                     let crosshair_time = NaiveDateTime::from_timestamp((crosshair_millis / 1000.0).floor() as i64, ((crosshair_millis % 1000.0) * 1_000_000.0).round() as u32);
 
                     let crosshair_timestamp = crosshair_time.timestamp_millis();
@@ -768,7 +1724,23 @@ impl canvas::Program<Message> for HeatmapChart {
                         Point::new(snap_x as f32, 0.0), 
                         Point::new(snap_x as f32, bounds.height)
                     );
-                    frame.stroke(&line, Stroke::default().with_color(Color::from_rgba8(200, 200, 200, 0.6)).with_width(1.0));
+                    frame.stroke(&line, chart.crosshair_style.stroke());
+
+                    // depth quantity at the hovered price level
+                    if cursor_position.y < heatmap_area_height {
+                        let crosshair_price = lowest + ((heatmap_area_height - cursor_position.y) / heatmap_area_height) * y_range;
+
+                        if let Some((is_bid, qty)) = self.depth_qty_at(crosshair_timestamp, crosshair_price) {
+                            let text = canvas::Text {
+                                content: format!("{}: {qty:.2}", if is_bid { "Bid" } else { "Ask" }),
+                                position: Point::new(cursor_position.x + 8.0, cursor_position.y - 16.0),
+                                size: iced::Pixels(chart.label_size),
+                                color: Color::from_rgba8(200, 200, 200, 1.0),
+                                ..canvas::Text::default()
+                            };
+                            frame.fill_text(text);
+                        }
+                    }
                 }
             });
 
@@ -787,6 +1759,8 @@ impl canvas::Program<Message> for HeatmapChart {
         match interaction {
             Interaction::Panning { .. } => mouse::Interaction::Grabbing,
             Interaction::Zoomin { .. } => mouse::Interaction::ZoomIn,
+            Interaction::FibDrawing { .. } | Interaction::FibEditing { .. } => mouse::Interaction::ResizingVertically,
+            Interaction::Measuring { .. } => mouse::Interaction::default(),
             Interaction::None if cursor.is_over(bounds) => {
                 if self.chart.crosshair {
                     mouse::Interaction::Crosshair
@@ -797,4 +1771,313 @@ impl canvas::Program<Message> for HeatmapChart {
             Interaction::None => { mouse::Interaction::default() }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Largest visible level qty, rounded up to a multiple of 5 so the scale doesn't jitter
+/// Share of `prev_qty` that's gone, 0 when the level grew
+/// A bucket's depth is drawn from the previous bucket up to its own time, its trades sit at that start
+fn trade_x(time: i64, aggregate_ms: i64, earliest: i64, latest: i64, width: f32) -> f32 {
+    (((time - aggregate_ms) - earliest) as f32 / (latest - earliest) as f32) * width
+}
+
+fn pull_ratio(prev_qty: f32, qty: f32) -> f32 {
+    if prev_qty <= 0.0 || qty >= prev_qty {
+        return 0.0;
+    }
+
+    (prev_qty - qty) / prev_qty
+}
+
+fn mix_colors(from: Color, to: Color, t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+
+    Color {
+        r: from.r + (to.r - from.r) * t,
+        g: from.g + (to.g - from.g) * t,
+        b: from.b + (to.b - from.b) * t,
+        a: from.a + (to.a - from.a) * t,
+    }
+}
+
+fn depth_bars_max_qty(bids: &[(f32, f32)], asks: &[(f32, f32)]) -> f32 {
+    let max_qty = bids.iter()
+        .chain(asks.iter())
+        .fold(f32::MIN, |max_qty, (_, qty)| max_qty.max(*qty));
+
+    (max_qty / 5.0).ceil() * 5.0
+}
+
+/// Draws (price, qty) levels as bars growing right from `x_start`, `max_qty` spanning `width`
+/// Segments left of the current book spanning each band around mid, labeled with the bid and ask qty within it
+/// One trade standing in for several, at their qty weighted price and on the side with more qty
+fn merge_trades(trades: &[&GroupedTrade]) -> GroupedTrade {
+    let (mut buy_qty, mut sell_qty, mut notional) = (0.0f32, 0.0f32, 0.0f32);
+
+    for trade in trades {
+        if trade.is_sell {
+            sell_qty += trade.qty;
+        } else {
+            buy_qty += trade.qty;
+        }
+        notional += trade.price * trade.qty;
+    }
+
+    let qty = buy_qty + sell_qty;
+
+    GroupedTrade {
+        is_sell: sell_qty > buy_qty,
+        price: if qty > 0.0 { notional / qty } else { trades.first().map_or(0.0, |trade| trade.price) },
+        qty,
+    }
+}
+
+fn draw_depth_bands(
+    frame: &mut canvas::Frame,
+    depth: &GroupedDepth,
+    bands: [f32; 3],
+    x_position: f32,
+    label_size: f32,
+    y_of: impl Fn(f32) -> f32,
+) {
+    let (Some(best_bid), Some(best_ask)) = (depth.bids.last(), depth.asks.first()) else {
+        return;
+    };
+    let mid_price = (best_bid.price + best_ask.price) / 2.0;
+
+    let mut bands = bands;
+    bands.sort_by(|a, b| a.total_cmp(b));
+
+    for (index, band) in bands.iter().enumerate() {
+        let (low, high) = (mid_price * (1.0 - band / 100.0), mid_price * (1.0 + band / 100.0));
+
+        let bid_qty: f32 = depth.bids.iter().filter(|order| order.price >= low).map(|order| order.qty).sum();
+        let ask_qty: f32 = depth.asks.iter().filter(|order| order.price <= high).map(|order| order.qty).sum();
+
+        let x = x_position - 4.0 - index as f32 * 4.0;
+        let (y_high, y_low) = (y_of(high), y_of(low));
+
+        frame.stroke(
+            &Path::line(Point::new(x, y_high), Point::new(x, y_low)),
+            Stroke::default().with_color(HeatmapChart::DEPTH_BAND_COLOR).with_width(2.0)
+        );
+
+        frame.fill_text(canvas::Text {
+            content: format!("{band:.2}%  {bid_qty:.1} / {ask_qty:.1}"),
+            position: Point::new(x - 4.0, y_high),
+            size: iced::Pixels(label_size),
+            color: HeatmapChart::DEPTH_BAND_COLOR,
+            horizontal_alignment: alignment::Horizontal::Right,
+            vertical_alignment: alignment::Vertical::Center,
+            ..canvas::Text::default()
+        });
+    }
+}
+
+/// Column the depth bars grow across from `x_start`, a level being `bar_height` tall
+struct DepthBarArea {
+    x_start: f32,
+    width: f32,
+    bar_height: f32,
+}
+
+fn draw_depth_bars(
+    frame: &mut canvas::Frame,
+    bids: &[(f32, f32)],
+    asks: &[(f32, f32)],
+    max_qty: f32,
+    palette: ChartPalette,
+    area: DepthBarArea,
+    y_of: impl Fn(f32) -> f32,
+) {
+    let DepthBarArea { x_start, width, bar_height } = area;
+
+    for (levels, color) in [
+        (bids, Color { a: 0.5, ..palette.bid() }), 
+        (asks, Color { a: 0.5, ..palette.ask() }),
+    ] {
+        for (price, qty) in levels {
+            frame.fill_rectangle(
+                Point::new(x_start, y_of(*price) - (bar_height/2.0)), 
+                Size::new((qty / max_qty) * width, bar_height), 
+                color
+            );
+        }
+    }
+}
+
+/// The latest book as a fixed histogram beside the heatmap, with its own qty scale
+struct DepthProfileCanvas<'a> {
+    cache: &'a Cache,
+    depth: Option<&'a GroupedDepth>,
+    min: f32,
+    max: f32,
+    tick_size: f32,
+    volume_ratio: f32,
+    palette: ChartPalette,
+    /// Trades of the reviewed bucket, empty while live
+    trades: &'a [GroupedTrade],
+}
+
+impl canvas::Program<Message> for DepthProfileCanvas<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let y_range = self.max - self.min;
+
+        let Some(depth) = self.depth.filter(|_| y_range > 0.0) else {
+            return vec![];
+        };
+
+        let heatmap_area_height = bounds.height - (bounds.height * self.volume_ratio);
+
+        let profile = self.cache.draw(renderer, bounds.size(), |frame| {
+            let bids: Vec<(f32, f32)> = depth.bids.iter()
+                .filter(|order| order.price >= self.min)
+                .map(|order| (order.price, order.qty))
+                .collect();
+            let asks: Vec<(f32, f32)> = depth.asks.iter()
+                .filter(|order| order.price <= self.max)
+                .map(|order| (order.price, order.qty))
+                .collect();
+
+            let bar_height = (heatmap_area_height / (y_range / self.tick_size)).floor().max(1.0);
+            let max_qty = depth_bars_max_qty(&bids, &asks);
+
+            draw_depth_bars(
+                frame, &bids, &asks, max_qty, self.palette,
+                DepthBarArea { x_start: 1.0, width: bounds.width - 1.0, bar_height },
+                |price| heatmap_area_height - ((price - self.min) / y_range * heatmap_area_height)
+            );
+
+            for trade in self.trades.iter().filter(|trade| trade.price >= self.min && trade.price <= self.max) {
+                let y_position = heatmap_area_height - ((trade.price - self.min) / y_range * heatmap_area_height);
+
+                frame.fill(
+                    &Path::circle(Point::new(bounds.width / 2.0, y_position), 3.0), 
+                    self.palette.directional(!trade.is_sell, 1.0)
+                );
+            }
+
+            frame.fill_rectangle(
+                Point::new(0.0, 0.0), 
+                Size::new(1.0, bounds.height), 
+                Color::from_rgba8(100, 100, 100, 0.2)
+            );
+
+            frame.fill_text(canvas::Text {
+                content: format!("{max_qty:.0}"),
+                position: Point::new(bounds.width - 4.0, 0.0),
+                size: iced::Pixels(9.0),
+                color: Color::from_rgba8(121, 121, 121, 1.0),
+                horizontal_alignment: alignment::Horizontal::Right,
+                ..canvas::Text::default()
+            });
+        });
+
+        vec![profile]
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iced::Vector;
+
+    fn depth() -> Rc<Depth> {
+        Rc::new(Depth {
+            time: 0,
+            bids: vec![Order { price: 99.0, qty: 1.0 }],
+            asks: vec![Order { price: 101.0, qty: 1.0 }],
+        })
+    }
+
+    fn trade(time: i64, price: f32) -> Trade {
+        Trade { time, is_sell: false, price, qty: 1.0 }
+    }
+
+    #[test]
+    fn trades_line_up_with_their_bucket_at_any_interval() {
+        for aggregate_ms in [100, 250, 1_000, 5_000] {
+            let mut chart = HeatmapChart::new(1.0);
+            chart.set_aggregate_time(aggregate_ms);
+
+            // mid-bucket update lands on the bucket's time
+            let update_time = aggregate_ms * 5 + aggregate_ms / 2;
+            chart.insert_datapoint(&[trade(update_time, 100.0)], update_time, depth());
+
+            let bucket_time = chart.data_points.first().expect("one bucket").0;
+            assert_eq!(bucket_time, aggregate_ms * 5);
+
+            // drawn at the start of the bucket's span, where the previous bucket ends
+            let (earliest, latest, width) = (0, aggregate_ms * 20, 400.0);
+            let previous_bucket_x = ((bucket_time - aggregate_ms) as f32 / latest as f32) * width;
+
+            assert_eq!(trade_x(bucket_time, aggregate_ms, earliest, latest, width), previous_bucket_x);
+            assert_eq!(previous_bucket_x, 80.0);
+        }
+    }
+
+    #[test]
+    fn autoscale_keeps_the_time_zoom() {
+        let mut chart = HeatmapChart::new(1.0);
+
+        chart.update(&Message::AutoscaleToggle);
+        chart.update(&Message::Scaled(2.5, Some(Vector::new(-120.0, 40.0))));
+        chart.update(&Message::Translated(Vector::new(-300.0, 80.0)));
+
+        // turning autoscale back on re-centers price only
+        chart.update(&Message::AutoscaleToggle);
+
+        assert!(chart.chart.autoscale);
+        assert_eq!(chart.chart.scaling, 2.5);
+        assert_eq!(chart.chart.translation, Vector::new(-300.0, 0.0));
+    }
+
+    #[test]
+    fn grouped_levels_run_through_the_grid_anchor() {
+        let bids = [Order { price: 103.0, qty: 1.0 }, Order { price: 91.0, qty: 2.0 }];
+        let asks = [Order { price: 107.0, qty: 1.0 }, Order { price: 118.0, qty: 2.0 }];
+
+        let mut chart = HeatmapChart::new(10.0);
+
+        let prices = |chart: &HeatmapChart| {
+            let grouped = chart.group_depth(&bids, &asks);
+            grouped.bids.iter().chain(grouped.asks.iter()).map(|order| order.price).collect::<Vec<f32>>()
+        };
+
+        // plain multiples of the tick size by default
+        assert_eq!(prices(&chart), vec![90.0, 100.0, 110.0, 120.0]);
+
+        chart.set_grid_anchor(25.0);
+        assert_eq!(prices(&chart), vec![85.0, 95.0, 115.0, 125.0]);
+        assert_eq!(chart.group_trade_price(103.0, false), 105.0);
+        assert_eq!(chart.group_trade_price(103.0, true), 95.0);
+    }
+
+    #[test]
+    fn span_never_outruns_the_kept_buckets() {
+        let mut chart = HeatmapChart::new(1.0);
+        chart.set_aggregate_time(1_000);
+        chart.set_visible_span(600_000);
+        assert_eq!(chart.get_visible_span(), 600_000);
+
+        // 2400 buckets of 100ms only hold 4 minutes
+        chart.set_aggregate_time(100);
+        assert_eq!(chart.get_visible_span(), 240_000);
+
+        chart.set_visible_span(600_000);
+        assert_eq!(chart.get_visible_span(), 240_000);
+
+        // the floor stays put however small the buckets get
+        assert_eq!(*HeatmapChart::visible_span_range(1).start(), *HeatmapChart::VISIBLE_SPAN_RANGE.start());
+        assert_eq!(*HeatmapChart::visible_span_range(1).end(), *HeatmapChart::VISIBLE_SPAN_RANGE.start());
+    }
+}
@@ -3,9 +3,40 @@ use iced::{
     alignment, Element, Length
 };
 use iced::widget::{Column, Row, Container, Text, container, Space};
+use serde::{Deserialize, Serialize};
 use crate::screen::dashboard::pane::Message;
 use crate::{style, data_providers::Trade};
 
+/// Notional (qty * price) thresholds that grade how strongly a print is highlighted
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NotionalTiers {
+    pub enabled: bool,
+    pub medium: f32,
+    pub large: f32,
+}
+impl Default for NotionalTiers {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            medium: 50_000.0,
+            large: 250_000.0,
+        }
+    }
+}
+impl NotionalTiers {
+    /// Background alpha for a print of the given notional
+    fn alpha(&self, notional: f32) -> f32 {
+        if notional >= self.large {
+            0.9
+        } else if notional >= self.medium {
+            0.5
+        } else {
+            0.15
+        }
+    }
+}
+
 struct ConvertedTrade {
     time: NaiveDateTime,
     price: f32,
@@ -16,6 +47,7 @@ pub struct TimeAndSales {
     recent_trades: Vec<ConvertedTrade>,
     size_filter: f32,
     filter_sync_heatmap: bool,
+    notional_tiers: NotionalTiers,
 }
 impl TimeAndSales {
     pub fn new() -> Self {
@@ -23,8 +55,13 @@ impl TimeAndSales {
             recent_trades: Vec::new(),
             size_filter: 0.0,
             filter_sync_heatmap: false,
+            notional_tiers: NotionalTiers::default(),
         }
     }
+
+    pub fn set_notional_tiers(&mut self, notional_tiers: NotionalTiers) {
+        self.notional_tiers = notional_tiers;
+    }
     
     pub fn set_size_filter(&mut self, value: f32) {
         self.size_filter = value;
@@ -95,7 +132,11 @@ impl TimeAndSales {
                             .width(Length::FillPortion(4))
                     );
 
-                let color_alpha = trade.qty / max_qty;
+                let color_alpha = if self.notional_tiers.enabled {
+                    self.notional_tiers.alpha(trade.qty * trade.price)
+                } else {
+                    trade.qty / max_qty
+                };
     
                 trades_column = trades_column.push(container(trade_row)
                     .style( move |_| if trade.is_sell { style::sell_side_red(color_alpha) } else { style::buy_side_green(color_alpha) }));
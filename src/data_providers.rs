@@ -1,7 +1,18 @@
+use std::{fs::File, io::{BufWriter, Write}, sync::{atomic::{AtomicBool, AtomicI64, AtomicU32, Ordering}, Mutex}};
+
+use iced::futures::{future::BoxFuture, stream::{BoxStream, Stream, StreamExt}};
+use bytes::Bytes;
+use fastwebsockets::FragmentCollector;
+use http_body_util::Empty;
+use hyper::{header::{CONNECTION, UPGRADE}, upgrade::Upgraded, Request};
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpStream;
+use tokio_rustls::{rustls::{ClientConfig, OwnedTrustAnchor, ServerName}, TlsConnector};
 use serde::{Deserialize, Serialize};
 
 pub mod binance;
 pub mod bybit;
+pub mod coinbase;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum StreamType {
@@ -17,6 +28,22 @@ pub enum StreamType {
     None,
 }
 
+/// The connections an exchange's streams go over, depth and trades apart from the klines
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StreamKind {
+    DepthAndTrades,
+    Kline,
+}
+
+impl std::fmt::Display for StreamKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreamKind::DepthAndTrades => write!(f, "depth/trades"),
+            StreamKind::Kline => write!(f, "kline"),
+        }
+    }
+}
+
 // data types
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Order {
@@ -118,6 +145,248 @@ pub struct FeedLatency {
     pub trade_latency: Option<i64>,
 }
 
+pub const DEFAULT_CONNECT_TIMEOUT_SECS: u32 = 10;
+pub const MIN_CONNECT_TIMEOUT_SECS: u32 = 3;
+pub const MAX_CONNECT_TIMEOUT_SECS: u32 = 60;
+
+/// How long a websocket handshake may hang before the attempt is dropped and retried
+pub static CONNECT_TIMEOUT_SECS: AtomicU32 = AtomicU32::new(DEFAULT_CONNECT_TIMEOUT_SECS);
+
+fn tls_connector() -> anyhow::Result<TlsConnector> {
+    let mut root_store = tokio_rustls::rustls::RootCertStore::empty();
+
+    root_store.add_trust_anchors(
+        webpki_roots::TLS_SERVER_ROOTS.0.iter().map(|ta| {
+            OwnedTrustAnchor::from_subject_spki_name_constraints(
+                ta.subject,
+                ta.spki,
+                ta.name_constraints,
+            )
+        }),
+    );
+
+    let config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(root_store)
+        .with_no_client_auth();
+
+    Ok(TlsConnector::from(std::sync::Arc::new(config)))
+}
+
+struct SpawnExecutor;
+
+impl<Fut> hyper::rt::Executor<Fut> for SpawnExecutor
+where
+    Fut: std::future::Future + Send + 'static,
+    Fut::Output: Send + 'static,
+{
+    fn execute(&self, fut: Fut) {
+        tokio::task::spawn(fut);
+    }
+}
+
+/// Websocket handshake for `url` over TLS to port 443 of `domain`
+///
+/// No `Sec-WebSocket-Extensions` is offered on purpose: fastwebsockets 0.7 has no
+/// permessage-deflate and errors on frames with RSV1 set, so a server accepting
+/// compression would break the stream on its first compressed frame
+pub async fn connect_websocket(domain: &str, url: &str) -> anyhow::Result<FragmentCollector<TokioIo<Upgraded>>> {
+    let addr = format!("{domain}:443");
+
+    let tcp_stream = TcpStream::connect(&addr).await?;
+    let server_name = ServerName::try_from(domain).map_err(|_| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid dnsname")
+    })?;
+
+    let tls_stream = tls_connector()?.connect(server_name, tcp_stream).await?;
+
+    let req: Request<Empty<Bytes>> = Request::builder()
+        .method("GET")
+        .uri(url)
+        .header("Host", &addr)
+        .header(UPGRADE, "websocket")
+        .header(CONNECTION, "upgrade")
+        .header("Sec-WebSocket-Key", fastwebsockets::handshake::generate_key())
+        .header("Sec-WebSocket-Version", "13")
+        .body(Empty::<Bytes>::new())?;
+
+    let (ws, _) = fastwebsockets::handshake::client(&SpawnExecutor, req, tls_stream).await?;
+
+    Ok(FragmentCollector::new(ws))
+}
+
+/// Runs a websocket connect under the configured timeout, giving back the reason to report on failure
+pub async fn connect_with_timeout<T, E: std::fmt::Display>(
+    connect: impl std::future::Future<Output = Result<T, E>>,
+) -> Result<T, String> {
+    let timeout = tokio::time::Duration::from_secs(CONNECT_TIMEOUT_SECS.load(Ordering::Relaxed).into());
+
+    match tokio::time::timeout(timeout, connect).await {
+        Ok(Ok(websocket)) => Ok(websocket),
+        Ok(Err(e)) => Err(format!("Failed to connect to websocket: {e}")),
+        Err(_) => Err("connect timeout".to_string()),
+    }
+}
+
+/// Kline streams split over connections of at most `per_connection` streams each and merged back
+/// into one, `connect_batch` gets each batch with the label its stats are reported under
+pub fn connect_kline_batches<S>(
+    streams: Vec<(Ticker, Timeframe)>,
+    per_connection: usize,
+    connect_batch: impl Fn(Vec<(Ticker, Timeframe)>, String) -> S,
+) -> impl Stream<Item = Event>
+where
+    S: Stream<Item = Event> + Send + 'static,
+{
+    let connections = streams
+        .chunks(per_connection.max(1))
+        .enumerate()
+        .map(|(index, batch)| {
+            let stats_label = if index == 0 {
+                format!("klines ({})", batch.len())
+            } else {
+                format!("klines #{} ({})", index + 1, batch.len())
+            };
+
+            connect_batch(batch.to_vec(), stats_label).boxed()
+        })
+        .collect::<Vec<_>>();
+
+    iced::futures::stream::select_all(connections)
+}
+
+/// Toggled from the stream stats panel, counting is skipped while off
+pub static STREAM_STATS_ENABLED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StreamStats {
+    pub msgs_per_sec: f32,
+    pub bytes_per_sec: f32,
+}
+
+/// Frame counters of a single websocket, flushed about once a second
+#[derive(Debug, Default)]
+pub struct StreamStatsCounter {
+    messages: u32,
+    bytes: usize,
+    window_start: i64,
+}
+
+impl StreamStatsCounter {
+    pub fn record(&mut self, bytes: usize) -> Option<StreamStats> {
+        if !STREAM_STATS_ENABLED.load(Ordering::Relaxed) {
+            self.window_start = 0;
+            return None;
+        }
+
+        let now = chrono::Utc::now().timestamp_millis();
+
+        if self.window_start == 0 {
+            *self = Self { messages: 0, bytes: 0, window_start: now };
+        }
+
+        self.messages += 1;
+        self.bytes += bytes;
+
+        let elapsed = now - self.window_start;
+
+        if elapsed < 1000 {
+            return None;
+        }
+
+        let secs = elapsed as f32 / 1000.0;
+        let stats = StreamStats {
+            msgs_per_sec: self.messages as f32 / secs,
+            bytes_per_sec: self.bytes as f32 / secs,
+        };
+
+        *self = Self { messages: 0, bytes: 0, window_start: now };
+
+        Some(stats)
+    }
+}
+
+pub const FRAME_DUMP_PATH: &str = "frames.log";
+pub const DEFAULT_FRAME_DUMP_MINS: u32 = 5;
+/// The dump stops early once the file grows past this
+const MAX_FRAME_DUMP_SIZE: u64 = 50_000_000; // 50 MB
+
+/// Time in ms until which raw text frames are written to the dump file, 0 while off
+static FRAME_DUMP_UNTIL: AtomicI64 = AtomicI64::new(0);
+/// Dump file with the bytes written to it so far
+static FRAME_DUMP_FILE: Mutex<Option<(BufWriter<File>, u64)>> = Mutex::new(None);
+
+/// Starts writing every incoming text frame to `FRAME_DUMP_PATH` for the given duration
+pub fn start_frame_dump(duration_mins: u32) -> std::io::Result<()> {
+    let file = File::create(FRAME_DUMP_PATH)?;
+
+    if let Ok(mut dump) = FRAME_DUMP_FILE.lock() {
+        *dump = Some((BufWriter::new(file), 0));
+    }
+
+    let until = chrono::Utc::now().timestamp_millis() + i64::from(duration_mins) * 60_000;
+    FRAME_DUMP_UNTIL.store(until, Ordering::Relaxed);
+
+    log::info!("Dumping raw websocket frames to {FRAME_DUMP_PATH} for {duration_mins} min");
+    Ok(())
+}
+
+pub fn stop_frame_dump() {
+    FRAME_DUMP_UNTIL.store(0, Ordering::Relaxed);
+
+    if let Ok(mut dump) = FRAME_DUMP_FILE.lock() {
+        if let Some((mut writer, written)) = dump.take() {
+            let _ = writer.flush();
+            log::info!("Frame dump stopped after {written} bytes");
+        }
+    }
+}
+
+pub fn frame_dump_running() -> bool {
+    FRAME_DUMP_UNTIL.load(Ordering::Relaxed) > chrono::Utc::now().timestamp_millis()
+}
+
+/// Appends a raw frame to the dump file, a single atomic load while no dump is running
+pub fn dump_frame(exchange: &str, label: &str, payload: &[u8]) {
+    let until = FRAME_DUMP_UNTIL.load(Ordering::Relaxed);
+    if until == 0 {
+        return;
+    }
+
+    let now = chrono::Utc::now().timestamp_millis();
+    if now > until {
+        stop_frame_dump();
+        return;
+    }
+
+    let Ok(mut dump) = FRAME_DUMP_FILE.lock() else {
+        return;
+    };
+    let Some((writer, written)) = dump.as_mut() else {
+        return;
+    };
+
+    let header = format!("{now} [{exchange} {label}] ");
+
+    if writer.write_all(header.as_bytes())
+        .and_then(|_| writer.write_all(payload))
+        .and_then(|_| writer.write_all(b"\n"))
+        .is_err()
+    {
+        drop(dump);
+        log::error!("Failed to write to {FRAME_DUMP_PATH}, frame dump stopped");
+        stop_frame_dump();
+        return;
+    }
+
+    *written += (header.len() + payload.len() + 1) as u64;
+
+    if *written > MAX_FRAME_DUMP_SIZE {
+        drop(dump);
+        log::warn!("{FRAME_DUMP_PATH} reached its size limit");
+        stop_frame_dump();
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 pub struct TickMultiplier(pub u16);
@@ -128,10 +397,29 @@ impl std::fmt::Display for TickMultiplier {
     }
 }
 
+/// Share of the price an auto picked ticksize aims for
+const AUTO_TICKSIZE_RATIO: f32 = 0.0005;
+
 impl TickMultiplier {
     pub fn multiply_with_min_tick_size(&self, min_tick_size: f32) -> f32 {
         self.0 as f32 * min_tick_size
     }
+
+    /// Out of the given multipliers, the one whose ticksize is closest to ~0.05% of the price
+    pub fn auto(price: f32, min_tick_size: f32, options: &[TickMultiplier]) -> Option<TickMultiplier> {
+        if price <= 0.0 || min_tick_size <= 0.0 {
+            return None;
+        }
+
+        let target = (price * AUTO_TICKSIZE_RATIO / min_tick_size).max(1.0);
+
+        // compared by ratio, so 2x is as far from 4x as 8x is
+        let distance = |multiplier: &TickMultiplier| (f32::from(multiplier.0) / target).ln().abs();
+
+        options.iter()
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+            .copied()
+    }
 }
 
 // connection types
@@ -139,6 +427,7 @@ impl TickMultiplier {
 pub enum Exchange {
     BinanceFutures,
     BybitLinear,
+    Coinbase,
 }
 
 impl std::fmt::Display for Exchange {
@@ -149,12 +438,48 @@ impl std::fmt::Display for Exchange {
             match self {
                 Exchange::BinanceFutures => "Binance Futures",
                 Exchange::BybitLinear => "Bybit Linear",
+                Exchange::Coinbase => "Coinbase",
             }
         )
     }
 }
 impl Exchange {
-    pub const ALL: [Exchange; 2] = [Exchange::BinanceFutures, Exchange::BybitLinear];
+    pub const ALL: [Exchange; 3] = [Exchange::BinanceFutures, Exchange::BybitLinear, Exchange::Coinbase];
+
+    pub fn provider(&self) -> Box<dyn MarketDataProvider> {
+        match self {
+            Exchange::BinanceFutures => Box::new(binance::Provider),
+            Exchange::BybitLinear => Box::new(bybit::Provider),
+            Exchange::Coinbase => Box::new(coinbase::Provider),
+        }
+    }
+}
+
+/// Everything the dashboard and the subscriptions need from an exchange
+/// 
+/// Adding an exchange means implementing this and returning it from `Exchange::provider`
+pub trait MarketDataProvider {
+    /// Whether the market stream can carry every fill instead of aggregated trades
+    fn supports_raw_trades(&self) -> bool {
+        false
+    }
+
+    /// Depth and trades of the given tickers, on as few connections as the exchange allows
+    fn connect_market_stream(&self, streams: Vec<MarketStream>) -> BoxStream<'static, MarketEvent>;
+
+    /// Most kline streams the exchange serves on one connection, more are spread over several
+    fn kline_streams_per_connection(&self) -> usize {
+        usize::MAX
+    }
+
+    fn connect_kline_stream(&self, streams: Vec<(Ticker, Timeframe)>) -> BoxStream<'static, MarketEvent>;
+
+    fn fetch_klines(&self, ticker: Ticker, timeframe: Timeframe) -> BoxFuture<'static, Result<Vec<Kline>, StreamError>>;
+
+    /// Same as `fetch_klines`, but for the bars up to `end_time` instead of the latest ones
+    fn fetch_klines_until(&self, ticker: Ticker, timeframe: Timeframe, end_time: i64) -> BoxFuture<'static, Result<Vec<Kline>, StreamError>>;
+
+    fn fetch_ticksize(&self, ticker: Ticker) -> BoxFuture<'static, Result<f32, StreamError>>;
 }
 
 impl std::fmt::Display for Ticker {
@@ -207,10 +532,99 @@ impl std::fmt::Display for Timeframe {
                 Timeframe::M5 => "5m",
                 Timeframe::M15 => "15m",
                 Timeframe::M30 => "30m",
+                Timeframe::H1 => "1h",
+                Timeframe::H4 => "4h",
+                Timeframe::D1 => "1d",
             }
         )
     }
 }
+/// Which trade channel a depth and trades stream subscribes to, only Binance offers both
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize, Serialize)]
+pub enum TradeMode {
+    /// Trades filled by the same taker order at the same price arrive as one
+    #[default]
+    Aggregated,
+    /// Every fill, at a much higher message rate
+    Raw,
+}
+impl TradeMode {
+    pub const ALL: [TradeMode; 2] = [TradeMode::Aggregated, TradeMode::Raw];
+}
+impl std::fmt::Display for TradeMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TradeMode::Aggregated => write!(f, "Aggregated"),
+            TradeMode::Raw => write!(f, "Tick by tick"),
+        }
+    }
+}
+
+/// A ticker's depth and trades stream, with the options it gets subscribed with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MarketStream {
+    pub ticker: Ticker,
+    pub trade_mode: TradeMode,
+    pub depth_cadence: DepthCadence,
+    /// Infer the side of trades the exchange didn't flag, instead of counting them as buys
+    pub tick_rule: bool,
+}
+
+/// Aggressor side from price changes, for trades arriving without one
+/// 
+/// An uptick is a buy, a downtick a sell, and an unchanged price keeps the previous side
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TickRule {
+    enabled: bool,
+    last_price: Option<f32>,
+    last_is_sell: bool,
+}
+
+impl TickRule {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled, ..Self::default() }
+    }
+
+    /// Whether the trade was a sell, the exchange flag wins whenever there is one
+    pub fn is_sell(&mut self, price: f32, flag: Option<bool>) -> bool {
+        let is_sell = match (flag, self.last_price) {
+            (Some(is_sell), _) => is_sell,
+            (None, _) if !self.enabled => false,
+            (None, Some(last_price)) if price > last_price => false,
+            (None, Some(last_price)) if price < last_price => true,
+            (None, _) => self.last_is_sell,
+        };
+
+        self.last_price = Some(price);
+        self.last_is_sell = is_sell;
+
+        is_sell
+    }
+}
+
+/// How often Binance pushes depth diffs, the futures API has no real-time channel
+/// 
+/// Heatmap buckets follow the depth updates, so a slower cadence also means coarser buckets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Deserialize, Serialize)]
+pub enum DepthCadence {
+    #[default]
+    Ms100,
+    Ms250,
+    Ms500,
+}
+impl DepthCadence {
+    pub const ALL: [DepthCadence; 3] = [DepthCadence::Ms100, DepthCadence::Ms250, DepthCadence::Ms500];
+}
+impl std::fmt::Display for DepthCadence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DepthCadence::Ms100 => write!(f, "100ms"),
+            DepthCadence::Ms250 => write!(f, "250ms"),
+            DepthCadence::Ms500 => write!(f, "500ms"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum Timeframe {
     M1,
@@ -218,9 +632,14 @@ pub enum Timeframe {
     M5,
     M15,
     M30,
+    H1,
+    H4,
+    D1,
 }
 impl Timeframe {
-    pub const ALL: [Timeframe; 5] = [Timeframe::M1, Timeframe::M3, Timeframe::M5, Timeframe::M15, Timeframe::M30];
+    pub const ALL: [Timeframe; 8] = [
+        Timeframe::M1, Timeframe::M3, Timeframe::M5, Timeframe::M15, Timeframe::M30, Timeframe::H1, Timeframe::H4, Timeframe::D1
+    ];
 
     pub fn to_minutes(&self) -> u16 {
         match self {
@@ -229,30 +648,57 @@ impl Timeframe {
             Timeframe::M5 => 5,
             Timeframe::M15 => 15,
             Timeframe::M30 => 30,
+            Timeframe::H1 => 60,
+            Timeframe::H4 => 240,
+            Timeframe::D1 => 1440,
         }
     }
-}
 
-#[derive(Debug)]
-pub enum BinanceWsState {
-    Connected(binance::market_data::Connection),
-    Disconnected,
-}
-impl Default for BinanceWsState {
-    fn default() -> Self {
-        Self::Disconnected
+    /// Length of a bar, what bucket and resampling maths should work in
+    pub fn to_millis(self) -> u64 {
+        u64::from(self.to_minutes()) * 60_000
     }
 }
 
-#[derive(Debug)]
-pub enum BybitWsState {
-    Connected(bybit::market_data::Connection),
-    Disconnected,
-}
-impl Default for BybitWsState {
-    fn default() -> Self {
-        Self::Disconnected
+/// Aggregates time sorted `klines` of `from` into the coarser `to` timeframe, empty if `to` isn't a multiple of `from`.
+/// A leading bucket missing its first klines is dropped as its open would be off,
+/// the trailing one is kept as the bar that's still forming. Gaps stay gaps
+pub fn resample_klines(klines: &[Kline], from: Timeframe, to: Timeframe) -> Vec<Kline> {
+    let from_ms = from.to_millis();
+    let to_ms = to.to_millis();
+
+    if to_ms < from_ms || !to_ms.is_multiple_of(from_ms) {
+        return vec![];
+    }
+
+    let mut resampled: Vec<Kline> = Vec::new();
+
+    for kline in klines {
+        let bucket_time = kline.time - (kline.time % to_ms);
+
+        match resampled.last_mut() {
+            Some(bucket) if bucket.time == bucket_time => {
+                bucket.high = bucket.high.max(kline.high);
+                bucket.low = bucket.low.min(kline.low);
+                bucket.close = kline.close;
+                // -1 marks a total without the taker split, summing those would lose the marker
+                if bucket.volume.0 == -1.0 || kline.volume.0 == -1.0 {
+                    let total = |volume: (f32, f32)| if volume.0 == -1.0 { volume.1 } else { volume.0 + volume.1 };
+                    bucket.volume = (-1.0, total(bucket.volume) + total(kline.volume));
+                } else {
+                    bucket.volume.0 += kline.volume.0;
+                    bucket.volume.1 += kline.volume.1;
+                }
+            },
+            _ => resampled.push(Kline { time: bucket_time, ..*kline }),
+        }
     }
+
+    if klines.first().is_some_and(|kline| kline.time % to_ms != 0) {
+        resampled.remove(0);
+    }
+
+    resampled
 }
 
 pub enum UserWsState {
@@ -265,12 +711,20 @@ impl Default for UserWsState {
     }
 }
 
+/// What a market or kline stream reports, the same for every exchange
 #[derive(Debug, Clone)]
-pub enum MarketEvents {
-    Binance(binance::market_data::Event),
-    Bybit(bybit::market_data::Event),
+pub enum Event {
+    Connected,
+    Disconnected(String),
+    DepthReceived(Ticker, FeedLatency, i64, Depth, Vec<Trade>),
+    KlineReceived(Ticker, Kline, Timeframe),
+    Stats(String, StreamStats),
 }
 
+/// A stream event along with the exchange it came from
+#[derive(Debug, Clone)]
+pub struct MarketEvent(pub Exchange, pub Event);
+
 #[derive(thiserror::Error, Debug)]
 pub enum StreamError {
     #[error("FetchError: {0}")]
@@ -281,4 +735,185 @@ pub enum StreamError {
     WebsocketError(String),
     #[error("UnknownError: {0}")]
     UnknownError(String),
-}
\ No newline at end of file
+}
+/// Sent with REST requests, Coinbase's API turns away requests without one
+pub const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// Pings the exchange's REST API to tell whether it's reachable at all
+/// 
+/// e.g. a firewall or a region block would otherwise just show up as empty charts
+pub async fn check_connectivity(exchange: Exchange) -> Result<(), StreamError> {
+    let url = match exchange {
+        Exchange::BinanceFutures => "https://fapi.binance.com/fapi/v1/ping",
+        Exchange::BybitLinear => "https://api.bybit.com/v5/market/time",
+        Exchange::Coinbase => "https://api.exchange.coinbase.com/time",
+    };
+
+    let client = reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(std::time::Duration::from_secs(5))
+        .build()
+        .map_err(StreamError::FetchError)?;
+
+    let response = client.get(url).send().await
+        .map_err(StreamError::FetchError)?;
+
+    if !response.status().is_success() {
+        return Err(StreamError::UnknownError(
+            format!("{exchange} responded with {}", response.status())
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: u64 = 60_000;
+
+    fn kline(minute: u64, open: f32, high: f32, low: f32, close: f32) -> Kline {
+        Kline { time: minute * MINUTE, open, high, low, close, volume: (1.0, 2.0) }
+    }
+
+    #[test]
+    fn resample_aggregates_ohlcv() {
+        let klines = [
+            kline(0, 10.0, 12.0, 9.0, 11.0),
+            kline(1, 11.0, 15.0, 10.0, 14.0),
+            kline(2, 14.0, 14.5, 8.0, 9.0),
+        ];
+
+        let resampled = resample_klines(&klines, Timeframe::M1, Timeframe::M3);
+
+        assert_eq!(resampled.len(), 1);
+        let bar = resampled[0];
+        assert_eq!((bar.time, bar.open, bar.high, bar.low, bar.close), (0, 10.0, 15.0, 8.0, 9.0));
+        assert_eq!(bar.volume, (3.0, 6.0));
+    }
+
+    #[test]
+    fn resample_drops_a_partial_leading_bucket_and_keeps_the_trailing_one() {
+        // 3 and 4 are the tail of the 0..5 bucket, 10 and 11 the start of the 10..15 one
+        let klines: Vec<Kline> = [3, 4, 5, 6, 7, 8, 9, 10, 11].into_iter()
+            .map(|minute| kline(minute, 1.0, 2.0, 0.5, 1.5))
+            .collect();
+
+        let resampled = resample_klines(&klines, Timeframe::M1, Timeframe::M5);
+
+        let times: Vec<u64> = resampled.iter().map(|bar| bar.time / MINUTE).collect();
+        assert_eq!(times, vec![5, 10]);
+        assert_eq!(resampled[1].volume, (2.0, 4.0));
+    }
+
+    #[test]
+    fn resample_leaves_gaps_as_gaps() {
+        let klines = [
+            kline(0, 1.0, 1.0, 1.0, 1.0),
+            kline(1, 1.0, 1.0, 1.0, 1.0),
+            // nothing traded from 2 to 9
+            kline(10, 2.0, 2.0, 2.0, 2.0),
+        ];
+
+        let times: Vec<u64> = resample_klines(&klines, Timeframe::M1, Timeframe::M5).iter()
+            .map(|bar| bar.time / MINUTE)
+            .collect();
+        assert_eq!(times, vec![0, 10]);
+    }
+
+    #[test]
+    fn resample_refuses_timeframes_that_dont_divide() {
+        let klines: Vec<Kline> = (0..15).map(|minute| kline(minute, 1.0, 1.0, 1.0, 1.0)).collect();
+
+        // 5 isn't a multiple of 3
+        assert!(resample_klines(&klines, Timeframe::M3, Timeframe::M5).is_empty());
+        // finer than the source
+        assert!(resample_klines(&klines, Timeframe::M5, Timeframe::M1).is_empty());
+    }
+
+    #[test]
+    fn resample_keeps_totals_without_a_taker_split() {
+        let klines = [
+            Kline { volume: (-1.0, 4.0), ..kline(0, 1.0, 1.0, 1.0, 1.0) },
+            Kline { volume: (-1.0, 6.0), ..kline(1, 1.0, 1.0, 1.0, 1.0) },
+            Kline { volume: (-1.0, 5.0), ..kline(2, 1.0, 1.0, 1.0, 1.0) },
+        ];
+
+        assert_eq!(resample_klines(&klines, Timeframe::M1, Timeframe::M3)[0].volume, (-1.0, 15.0));
+    }
+
+    #[test]
+    fn tick_rule_classifies_by_price_change() {
+        let mut tick_rule = TickRule::new(true);
+
+        let sides: Vec<bool> = [100.0, 100.5, 100.5, 100.0, 100.0, 100.0, 100.5]
+            .into_iter()
+            .map(|price| tick_rule.is_sell(price, None))
+            .collect();
+
+        // the first trade has nothing to compare to and counts as a buy,
+        // zero ticks keep the side of the last change
+        assert_eq!(sides, vec![false, false, false, true, true, true, false]);
+    }
+
+    #[test]
+    fn tick_rule_defers_to_the_exchange_flag() {
+        let mut tick_rule = TickRule::new(true);
+
+        // a flagged sell on an uptick stays a sell, and a zero tick after it follows the flag
+        assert!(!tick_rule.is_sell(100.0, None));
+        assert!(tick_rule.is_sell(101.0, Some(true)));
+        assert!(tick_rule.is_sell(101.0, None));
+        assert!(!tick_rule.is_sell(99.0, Some(false)));
+    }
+
+    #[test]
+    fn disabled_tick_rule_treats_unflagged_trades_as_buys() {
+        let mut tick_rule = TickRule::new(false);
+
+        assert!(!tick_rule.is_sell(100.0, None));
+        assert!(!tick_rule.is_sell(99.0, None));
+        assert!(tick_rule.is_sell(98.0, Some(true)));
+    }
+
+    #[test]
+    fn kline_streams_past_the_limit_spread_over_connections() {
+        let streams: Vec<(Ticker, Timeframe)> = Ticker::ALL.iter()
+            .flat_map(|ticker| [Timeframe::M1, Timeframe::M5].map(|timeframe| (*ticker, timeframe)))
+            .collect();
+
+        // each fake connection reports stats under its label, then sends one bar per stream it carries
+        let events: Vec<Event> = futures::executor::block_on(
+            connect_kline_batches(streams.clone(), 3, |batch, stats_label| {
+                let klines = batch.into_iter()
+                    .map(|(ticker, timeframe)| Event::KlineReceived(ticker, kline(0, 1.0, 1.0, 1.0, 1.0), timeframe));
+
+                let stats = StreamStats { msgs_per_sec: 0.0, bytes_per_sec: 0.0 };
+
+                futures::stream::iter(std::iter::once(Event::Stats(stats_label, stats)).chain(klines))
+            })
+            .collect()
+        );
+
+        let mut labels: Vec<String> = events.iter()
+            .filter_map(|event| match event {
+                Event::Stats(label, _) => Some(label.clone()),
+                _ => None,
+            })
+            .collect();
+        labels.sort();
+        assert_eq!(labels, ["klines #2 (3)", "klines #3 (2)", "klines (3)"]);
+
+        let mut received: Vec<(Ticker, Timeframe)> = events.iter()
+            .filter_map(|event| match event {
+                Event::KlineReceived(ticker, _, timeframe) => Some((*ticker, *timeframe)),
+                _ => None,
+            })
+            .collect();
+        let mut expected = streams;
+        received.sort_by_key(|stream| format!("{stream:?}"));
+        expected.sort_by_key(|stream| format!("{stream:?}"));
+        assert_eq!(received, expected);
+    }
+}
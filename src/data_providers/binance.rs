@@ -1,2 +1,43 @@
+use iced::futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
+
+use super::{Kline, MarketDataProvider, MarketEvent, Exchange, StreamError, Ticker, Timeframe, MarketStream};
+
 pub mod market_data;
-pub mod user_data;
\ No newline at end of file
+pub mod user_data;
+
+pub struct Provider;
+
+impl MarketDataProvider for Provider {
+    fn supports_raw_trades(&self) -> bool {
+        true
+    }
+
+    fn connect_market_stream(&self, streams: Vec<MarketStream>) -> BoxStream<'static, MarketEvent> {
+        market_data::connect_market_stream(streams)
+            .map(|event| MarketEvent(Exchange::BinanceFutures, event))
+            .boxed()
+    }
+
+    /// Binance futures caps a combined stream connection at 200 streams, extra ones fail silently
+    fn kline_streams_per_connection(&self) -> usize {
+        200
+    }
+
+    fn connect_kline_stream(&self, streams: Vec<(Ticker, Timeframe)>) -> BoxStream<'static, MarketEvent> {
+        market_data::connect_kline_stream(streams, self.kline_streams_per_connection())
+            .map(|event| MarketEvent(Exchange::BinanceFutures, event))
+            .boxed()
+    }
+
+    fn fetch_klines(&self, ticker: Ticker, timeframe: Timeframe) -> BoxFuture<'static, Result<Vec<Kline>, StreamError>> {
+        market_data::fetch_klines(ticker, timeframe, None).boxed()
+    }
+
+    fn fetch_klines_until(&self, ticker: Ticker, timeframe: Timeframe, end_time: i64) -> BoxFuture<'static, Result<Vec<Kline>, StreamError>> {
+        market_data::fetch_klines(ticker, timeframe, Some(end_time)).boxed()
+    }
+
+    fn fetch_ticksize(&self, ticker: Ticker) -> BoxFuture<'static, Result<f32, StreamError>> {
+        market_data::fetch_ticksize(ticker).boxed()
+    }
+}
@@ -3,6 +3,8 @@ use futures::stream::Stream;
 use serde::Deserializer;
 use futures::sink::SinkExt;
 
+use std::collections::HashMap;
+
 use crate::{Ticker, Timeframe};
 
 use bytes::Bytes;
@@ -13,17 +15,11 @@ use sonic_rs::to_object_iter_unchecked;
 use anyhow::{Context, Result};
 
 use fastwebsockets::{FragmentCollector, OpCode};
-use http_body_util::Empty;
-use hyper::header::{CONNECTION, UPGRADE};
 use hyper::upgrade::Upgraded;
-use hyper::Request;
 use hyper_util::rt::TokioIo;
-use tokio::net::TcpStream;
-use tokio_rustls::rustls::{ClientConfig, OwnedTrustAnchor};
-use tokio_rustls::TlsConnector;
 
 use crate::data_providers::{
-    LocalDepthCache, Trade, Depth, Order, FeedLatency, Kline, StreamError,
+    LocalDepthCache, Trade, Order, FeedLatency, Kline, StreamError, StreamStatsCounter, dump_frame, MarketStream, TradeMode, DepthCadence, TickRule, connect_with_timeout, connect_websocket, connect_kline_batches, Event,
 };
 
 #[allow(clippy::large_enum_variant)]
@@ -34,17 +30,6 @@ enum State {
     ),
 }
 
-#[derive(Debug, Clone)]
-pub enum Event {
-    Connected(Connection),
-    Disconnected(String),
-    DepthReceived(Ticker, FeedLatency, i64, Depth, Vec<Trade>),
-    KlineReceived(Ticker, Kline, Timeframe),
-}
-
-#[derive(Debug, Clone)]
-pub struct Connection;
-
 impl<'de> Deserialize<'de> for Order {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -68,6 +53,17 @@ pub struct FetchedDepth {
     asks: Vec<Order>,
 }
 
+impl From<FetchedDepth> for LocalDepthCache {
+    fn from(depth: FetchedDepth) -> Self {
+        LocalDepthCache {
+            last_update_id: depth.update_id,
+            time: depth.time,
+            bids: depth.bids,
+            asks: depth.asks,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct SonicDepth {
 	#[serde(rename = "T")]
@@ -100,8 +96,12 @@ struct SonicTrade {
 	price: String,
 	#[serde(rename = "q")]
 	qty: String,
-	#[serde(rename = "m")]
-	is_sell: bool,
+	/// Whether the buyer was the maker, some fills come without it
+	#[serde(rename = "m", default)]
+	is_sell: Option<bool>,
+	/// Only on raw trades, where fills other than "MARKET" (liquidations, ADL) aren't taker flow
+	#[serde(rename = "X", default)]
+	exec_type: Option<String>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -134,8 +134,8 @@ struct SonicKlineWrap {
 
 #[derive(Debug)]
 enum StreamData {
-	Trade(SonicTrade),
-	Depth(SonicDepth),
+	Trade(Ticker, SonicTrade),
+	Depth(Ticker, SonicDepth),
     Kline(Ticker, SonicKline),
 }
 
@@ -152,6 +152,7 @@ impl StreamName {
             match after_at {
                 _ if after_at.starts_with("dep") => StreamName::Depth,
                 _ if after_at.starts_with("agg") => StreamName::Trade,
+                _ if after_at.starts_with("trade") => StreamName::Trade,
                 _ if after_at.starts_with("kli") => StreamName::Kline,
                 _ => StreamName::Unknown,
             }
@@ -161,6 +162,13 @@ impl StreamName {
     }
 }
 
+/// Ticker of a combined stream name like `btcusdt@depth@100ms`
+fn ticker_from_stream(stream: &str) -> Option<Ticker> {
+    let symbol = stream.split('@').next()?;
+
+    Ticker::ALL.into_iter().find(|ticker| ticker.get_string() == symbol)
+}
+
 #[derive(Debug)]
 enum StreamWrapper {
 	Trade,
@@ -170,6 +178,7 @@ enum StreamWrapper {
 
 fn feed_de(bytes: &Bytes) -> Result<StreamData> {
 	let mut stream_type: Option<StreamWrapper> = None;
+	let mut stream_ticker: Option<Ticker> = None;
 
 	let iter: sonic_rs::ObjectJsonIter = unsafe { to_object_iter_unchecked(bytes) };
 
@@ -179,6 +188,8 @@ fn feed_de(bytes: &Bytes) -> Result<StreamData> {
 
 		if k == "stream" {
 			if let Some(val) = v.as_str() {
+                stream_ticker = ticker_from_stream(val);
+
                 match StreamName::from_stream_type(val) {
 					StreamName::Depth => {
 						stream_type = Some(StreamWrapper::Depth);
@@ -199,14 +210,16 @@ fn feed_de(bytes: &Bytes) -> Result<StreamData> {
 				Some(StreamWrapper::Trade) => {
 					let trade: SonicTrade = sonic_rs::from_str(&v.as_raw_faststr())
 						.context("Error parsing trade")?;
+					let ticker = stream_ticker.context("No ticker in trade stream name")?;
 
-					return Ok(StreamData::Trade(trade));
+					return Ok(StreamData::Trade(ticker, trade));
 				},
 				Some(StreamWrapper::Depth) => {
 					let depth: SonicDepth = sonic_rs::from_str(&v.as_raw_faststr())
 						.context("Error parsing depth")?;
+					let ticker = stream_ticker.context("No ticker in depth stream name")?;
 
-					return Ok(StreamData::Depth(depth));
+					return Ok(StreamData::Depth(ticker, depth));
 				},
                 Some(StreamWrapper::Kline) => {
                     let kline_wrap: SonicKlineWrap = sonic_rs::from_str(&v.as_raw_faststr())
@@ -234,230 +247,174 @@ fn feed_de(bytes: &Bytes) -> Result<StreamData> {
 	Err(anyhow::anyhow!("Unknown data"))
 }
 
-fn tls_connector() -> Result<TlsConnector> {
-	let mut root_store = tokio_rustls::rustls::RootCertStore::empty();
-
-	root_store.add_trust_anchors(
-		webpki_roots::TLS_SERVER_ROOTS.0.iter().map(|ta| {
-			OwnedTrustAnchor::from_subject_spki_name_constraints(
-			ta.subject,
-			ta.spki,
-			ta.name_constraints,
-			)
-		}),
-	);
-
-	let config = ClientConfig::builder()
-		.with_safe_defaults()
-		.with_root_certificates(root_store)
-		.with_no_client_auth();
-
-	Ok(TlsConnector::from(std::sync::Arc::new(config)))
+async fn connect(domain: &str, streams: &str) -> Result<FragmentCollector<TokioIo<Upgraded>>> {
+    connect_websocket(domain, &format!("wss://{domain}:443/stream?streams={streams}")).await
 }
 
-async fn connect(domain: &str, streams: &str) -> Result<FragmentCollector<TokioIo<Upgraded>>> {
-	let mut addr = String::from(domain);
-	addr.push_str(":443");
-
-	let tcp_stream: TcpStream = TcpStream::connect(&addr).await?;
-	let tls_connector: TlsConnector = tls_connector().unwrap();
-	let domain: tokio_rustls::rustls::ServerName =
-	tokio_rustls::rustls::ServerName::try_from(domain).map_err(|_| {
-		std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid dnsname")
-	})?;
-
-	let tls_stream: tokio_rustls::client::TlsStream<TcpStream> = tls_connector.connect(domain, tcp_stream).await?;
-
-    let url = format!("wss://{}/stream?streams={}", &addr, streams);
-
-	let req: Request<Empty<Bytes>> = Request::builder()
-	.method("GET")
-	.uri(url)
-	.header("Host", &addr)
-	.header(UPGRADE, "websocket")
-	.header(CONNECTION, "upgrade")
-	.header(
-		"Sec-WebSocket-Key",
-		fastwebsockets::handshake::generate_key(),
-	)
-	.header("Sec-WebSocket-Version", "13")
-	.body(Empty::<Bytes>::new())?;
-
-	let (ws, _) = fastwebsockets::handshake::client(&SpawnExecutor, req, tls_stream).await?;
-	Ok(FragmentCollector::new(ws))
+/// Book syncing and the trades gathered between depth updates, for one ticker of a market stream
+struct TickerFeed {
+    orderbook: LocalDepthCache,
+    prev_id: u64,
+    trades_buffer: Vec<Trade>,
+    trade_latencies: Vec<i64>,
+    tick_rule: TickRule,
 }
-struct SpawnExecutor;
-
-impl<Fut> hyper::rt::Executor<Fut> for SpawnExecutor
-where
-  Fut: std::future::Future + Send + 'static,
-  Fut::Output: Send + 'static,
-{
-  fn execute(&self, fut: Fut) {
-	tokio::task::spawn(fut);
-  }
+
+impl TickerFeed {
+    fn new(tick_rule: bool) -> Self {
+        Self {
+            orderbook: LocalDepthCache::new(),
+            prev_id: 0,
+            trades_buffer: Vec::new(),
+            trade_latencies: Vec::new(),
+            tick_rule: TickRule::new(tick_rule),
+        }
+    }
+
+    fn fetched(&mut self, depth: FetchedDepth) {
+        self.orderbook.fetched(LocalDepthCache::from(depth));
+        self.prev_id = 0;
+    }
+
+    /// Starts over from a new snapshot, only this ticker's book gets reset
+    async fn resync(&mut self, ticker: Ticker) -> Result<(), String> {
+        let depth = fetch_depth(ticker).await
+            .map_err(|e| format!("Failed to fetch depth for {ticker}, error: {e}"))?;
+
+        self.fetched(depth);
+
+        Ok(())
+    }
 }
 
-pub fn connect_market_stream(ticker: Ticker) -> impl Stream<Item = Event> {    
+/// Depth and trades of every given ticker, combined on a single connection
+pub fn connect_market_stream(streams: Vec<MarketStream>) -> impl Stream<Item = Event> {    
     stream::channel (
         100,
         move |mut output| async move {
             let mut state = State::Disconnected;     
-            let mut trades_buffer: Vec<Trade> = Vec::new(); 
-
-            let selected_ticker = ticker;
-
-            let symbol_str = match selected_ticker {
-                Ticker::BTCUSDT => "btcusdt",
-                Ticker::ETHUSDT => "ethusdt",
-                Ticker::SOLUSDT => "solusdt",
-                Ticker::LTCUSDT => "ltcusdt",
-            };
 
-            let stream_1 = format!("{symbol_str}@aggTrade");
-            let stream_2 = format!("{symbol_str}@depth@100ms");
+            let mut feeds: HashMap<Ticker, TickerFeed> = streams.iter()
+                .map(|stream| (stream.ticker, TickerFeed::new(stream.tick_rule)))
+                .collect();
 
-            let mut orderbook: LocalDepthCache = LocalDepthCache::new();
+            let stream_str = streams.iter().flat_map(|stream| {
+                let symbol_str = stream.ticker.get_string();
 
-            let mut already_fetching: bool = false;
+                let trades = match stream.trade_mode {
+                    TradeMode::Aggregated => format!("{symbol_str}@aggTrade"),
+                    TradeMode::Raw => format!("{symbol_str}@trade"),
+                };
+                let depth = match stream.depth_cadence {
+                    DepthCadence::Ms100 => format!("{symbol_str}@depth@100ms"),
+                    DepthCadence::Ms250 => format!("{symbol_str}@depth"),
+                    DepthCadence::Ms500 => format!("{symbol_str}@depth@500ms"),
+                };
 
-            let mut prev_id: u64 = 0;
+                [trades, depth]
+            }).collect::<Vec<String>>().join("/");
 
-            let mut trade_latencies: Vec<i64> = Vec::new();
+            let mut stream_stats = StreamStatsCounter::default();
+            let stats_label = match &streams[..] {
+                [stream] => format!("{} depth/trades", stream.ticker),
+                _ => format!("depth/trades ({})", streams.len()),
+            };
 
             loop {
                 match &mut state {
                     State::Disconnected => {        
-                        let streams = format!("{stream_1}/{stream_2}");
-
                         let domain: &str = "fstream.binance.com";
 
-                        if let Ok(websocket) = connect(domain, streams.as_str()
-                        )
-                        .await {
-                            let (tx, rx) = tokio::sync::oneshot::channel();
-                                                
-                            tokio::spawn(async move {
-                                let fetched_depth = fetch_depth(selected_ticker).await;
-
-                                let depth = match fetched_depth {
-                                    Ok(depth) => {
-                                        LocalDepthCache {
-                                            last_update_id: depth.update_id,
-                                            time: depth.time,
-                                            bids: depth.bids,
-                                            asks: depth.asks,
+                        match connect_with_timeout(connect(domain, stream_str.as_str())).await {
+                            Ok(websocket) => {
+                                let tickers: Vec<Ticker> = feeds.keys().copied().collect();
+
+                                match futures::future::try_join_all(tickers.iter().map(|ticker| fetch_depth(*ticker))).await {
+                                    Ok(snapshots) => {
+                                        for (ticker, depth) in tickers.iter().zip(snapshots) {
+                                            if let Some(feed) = feeds.get_mut(ticker) {
+                                                feed.fetched(depth);
+                                            }
                                         }
+
+                                        state = State::Connected(websocket);
+                                        let _ = output.send(Event::Connected).await;                                 
                                     },
                                     Err(e) => {
-                                        log::error!("Failed to fetch depth for {}, error: {}", symbol_str, e);
-                                        return;
+                                        let _ = output.send(Event::Disconnected(
+                                            format!("Failed to fetch depth snapshots, error: {e}")
+                                        )).await;
                                     }
-                                };
-
-                                let _ = tx.send(depth);
-                            });
-                            match rx.await {
-                                Ok(depth) => {
-                                    orderbook.fetched(depth);
-
-                                    prev_id = 0;
-
-                                    state = State::Connected(websocket);
-                                    let _ = output.send(Event::Connected(Connection)).await;                                 
-                                },
-                                Err(e) => {
-                                    let _ = output.send(Event::Disconnected(
-                                        format!("Failed to send fetched depth for {}, error: {}", symbol_str, e)
-                                    )).await.expect("Trying to send disconnect event...");
                                 }
-                            }
-                        } else {
-                            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                            },
+                            Err(reason) => {
+                                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
 
-                            let _ = output.send(Event::Disconnected(
-                                "Failed to connect to websocket".to_string()
-                            )).await;
+                                let _ = output.send(Event::Disconnected(reason)).await;
+                            }
                         }
                     },
                     State::Connected(ws) => {
-                        let feed_latency: FeedLatency;
-
                         match ws.read_frame().await {
                             Ok(msg) => match msg.opcode {
                                 OpCode::Text => {                    
                                     let json_bytes: Bytes = Bytes::from(msg.payload.to_vec());
+
+                                    dump_frame("binance", &stats_label, &json_bytes);
+
+                                    if let Some(stats) = stream_stats.record(json_bytes.len()) {
+                                        let _ = output.send(Event::Stats(stats_label.clone(), stats)).await;
+                                    }
                     
                                     if let Ok(data) = feed_de(&json_bytes) {
                                         match data {
-                                            StreamData::Trade(de_trade) => {
+                                            StreamData::Trade(ticker, de_trade) => {
+                                                if de_trade.exec_type.as_deref().is_some_and(|exec_type| exec_type != "MARKET") {
+                                                    continue;
+                                                }
+
+                                                let Some(feed) = feeds.get_mut(&ticker) else {
+                                                    continue;
+                                                };
+
+                                                let price = str_f32_parse(&de_trade.price);
+
                                                 let trade = Trade {
                                                     time: de_trade.time as i64,
-                                                    is_sell: de_trade.is_sell,
-                                                    price: str_f32_parse(&de_trade.price),
+                                                    is_sell: feed.tick_rule.is_sell(price, de_trade.is_sell),
+                                                    price,
                                                     qty: str_f32_parse(&de_trade.qty),
                                                 };
 
-                                                trade_latencies.push(
+                                                feed.trade_latencies.push(
                                                     chrono::Utc::now().timestamp_millis() - trade.time
                                                 );
 
-                                                trades_buffer.push(trade);
+                                                feed.trades_buffer.push(trade);
                                             },
-                                            StreamData::Depth(de_depth) => {
-                                                if already_fetching {
-                                                    log::warn!("Already fetching...\n");
-    
+                                            StreamData::Depth(ticker, de_depth) => {
+                                                let Some(feed) = feeds.get_mut(&ticker) else {
                                                     continue;
-                                                }
+                                                };
     
-                                                let last_update_id = orderbook.get_fetch_id() as u64;
+                                                let last_update_id = feed.orderbook.get_fetch_id() as u64;
                                                 
                                                 if (de_depth.final_id <= last_update_id) || last_update_id == 0 {
                                                     continue;
                                                 }
     
-                                                if prev_id == 0 && (de_depth.first_id > last_update_id + 1) || (last_update_id + 1 > de_depth.final_id) {
-                                                    log::warn!("Out of sync at first event. Trying to resync...\n");
-    
-                                                    let (tx, rx) = tokio::sync::oneshot::channel();
-                                                    already_fetching = true;
-    
-                                                    tokio::spawn(async move {
-                                                        let fetched_depth = fetch_depth(selected_ticker).await;
+                                                if feed.prev_id == 0 && (de_depth.first_id > last_update_id + 1) || (last_update_id + 1 > de_depth.final_id) {
+                                                    log::warn!("{ticker} out of sync at first event. Trying to resync...\n");
     
-                                                        let depth = match fetched_depth {
-                                                            Ok(depth) => {
-                                                                LocalDepthCache {
-                                                                    last_update_id: depth.update_id,
-                                                                    time: depth.time,
-                                                                    bids: depth.bids,
-                                                                    asks: depth.asks,
-                                                                }
-                                                            },
-                                                            Err(e) => {
-                                                                log::error!("Failed to fetch depth for {}, error: {}", symbol_str, e);
-                                                                return;
-                                                            }
-                                                        };
-    
-                                                        let _ = tx.send(depth);
-                                                    });
-                                                    match rx.await {
-                                                        Ok(depth) => {
-                                                            orderbook.fetched(depth)
-                                                        },
-                                                        Err(e) => {
-                                                            state = State::Disconnected;
-                                                            let _ = output.send(Event::Disconnected(
-                                                                format!("Failed to send fetched depth for {}, error: {}", symbol_str, e)
-                                                            )).await.expect("Trying to send disconnect event...");
-                                                        }
+                                                    if let Err(reason) = feed.resync(ticker).await {
+                                                        state = State::Disconnected;
+                                                        let _ = output.send(Event::Disconnected(reason)).await;
+
+                                                        continue;
                                                     }
-                                                    already_fetching = false;
                                                 }
                                         
-                                                if (prev_id == 0) || (prev_id == de_depth.prev_final_id) {
+                                                if (feed.prev_id == 0) || (feed.prev_id == de_depth.prev_final_id) {
                                                     let time = de_depth.time as i64;
     
                                                     let depth_latency = chrono::Utc::now().timestamp_millis() - time;
@@ -473,16 +430,16 @@ pub fn connect_market_stream(ticker: Ticker) -> impl Stream<Item = Event> {
                                                         ).collect(),
                                                     };
     
-                                                    orderbook.update_depth_cache(depth_update);
+                                                    feed.orderbook.update_depth_cache(depth_update);
                                                     
-                                                    let avg_trade_latency = if !trade_latencies.is_empty() {
-                                                        let avg = trade_latencies.iter().sum::<i64>() / trade_latencies.len() as i64;
-                                                        trade_latencies.clear();
+                                                    let avg_trade_latency = if !feed.trade_latencies.is_empty() {
+                                                        let avg = feed.trade_latencies.iter().sum::<i64>() / feed.trade_latencies.len() as i64;
+                                                        feed.trade_latencies.clear();
                                                         Some(avg)
                                                     } else {
                                                         None
                                                     };
-                                                    feed_latency = FeedLatency {
+                                                    let feed_latency = FeedLatency {
                                                         time,
                                                         depth_latency,
                                                         trade_latency: avg_trade_latency,
@@ -490,22 +447,26 @@ pub fn connect_market_stream(ticker: Ticker) -> impl Stream<Item = Event> {
     
                                                     let _ = output.send(
                                                         Event::DepthReceived(
-                                                            selected_ticker,
+                                                            ticker,
                                                             feed_latency,
                                                             time, 
-                                                            orderbook.get_depth(),
-                                                            std::mem::take(&mut trades_buffer)
+                                                            feed.orderbook.get_depth(),
+                                                            std::mem::take(&mut feed.trades_buffer)
                                                         )
                                                     ).await;
     
-                                                    prev_id = de_depth.final_id;
+                                                    feed.prev_id = de_depth.final_id;
                                                 } else {
-                                                    state = State::Disconnected;
-                                                    let _ = output.send(
-                                                        Event::Disconnected(
-                                                            format!("Out of sync. Expected update_id: {}, got: {}", de_depth.prev_final_id, prev_id)
-                                                        )
-                                                    ).await;
+                                                    // the other tickers on the connection are fine, only this book starts over
+                                                    log::warn!(
+                                                        "{ticker} out of sync. Expected update_id: {}, got: {}. Resyncing...", 
+                                                        de_depth.prev_final_id, feed.prev_id
+                                                    );
+
+                                                    if let Err(reason) = feed.resync(ticker).await {
+                                                        state = State::Disconnected;
+                                                        let _ = output.send(Event::Disconnected(reason)).await;
+                                                    }
                                                 }
                                             },
                                             _ => {}
@@ -536,12 +497,19 @@ pub fn connect_market_stream(ticker: Ticker) -> impl Stream<Item = Event> {
     )
 }
 
-pub fn connect_kline_stream(streams: Vec<(Ticker, Timeframe)>) -> impl Stream<Item = Event> {    
+/// Kline streams on as many connections as `per_connection` streams each need
+pub fn connect_kline_stream(streams: Vec<(Ticker, Timeframe)>, per_connection: usize) -> impl Stream<Item = Event> {
+    connect_kline_batches(streams, per_connection, connect_kline_batch)
+}
+
+fn connect_kline_batch(streams: Vec<(Ticker, Timeframe)>, stats_label: String) -> impl Stream<Item = Event> {    
     stream::channel (
         100,
         move |mut output| async move {
             let mut state = State::Disconnected;    
 
+            let mut stream_stats = StreamStatsCounter::default();
+
             let stream_str = streams.iter().map(|(ticker, timeframe)| {
                 let symbol_str = match ticker {
                     Ticker::BTCUSDT => "btcusdt",
@@ -555,6 +523,9 @@ pub fn connect_kline_stream(streams: Vec<(Ticker, Timeframe)>) -> impl Stream<It
                     Timeframe::M5 => "5m",
                     Timeframe::M15 => "15m",
                     Timeframe::M30 => "30m",
+                    Timeframe::H1 => "1h",
+                    Timeframe::H4 => "4h",
+                    Timeframe::D1 => "1d",
                 };
                 format!("{symbol_str}@kline_{timeframe_str}")
             }).collect::<Vec<String>>().join("/");
@@ -566,18 +537,16 @@ pub fn connect_kline_stream(streams: Vec<(Ticker, Timeframe)>) -> impl Stream<It
 
                         let streams = stream_str.as_str();
                         
-                        if let Ok(websocket) = connect(
-                            domain, streams
-                        )
-                        .await {
-                            state = State::Connected(websocket);
-                            let _ = output.send(Event::Connected(Connection)).await;        
-                        } else {
-                            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-
-                            let _ = output.send(Event::Disconnected(
-                                "Failed to connect to websocket".to_string()
-                            )).await;
+                        match connect_with_timeout(connect(domain, streams)).await {
+                            Ok(websocket) => {
+                                state = State::Connected(websocket);
+                                let _ = output.send(Event::Connected).await;        
+                            },
+                            Err(reason) => {
+                                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+
+                                let _ = output.send(Event::Disconnected(reason)).await;
+                            }
                         }
                     },
                     State::Connected(ws) => {
@@ -585,6 +554,12 @@ pub fn connect_kline_stream(streams: Vec<(Ticker, Timeframe)>) -> impl Stream<It
                             Ok(msg) => match msg.opcode {
                                 OpCode::Text => {                    
                                     let json_bytes: Bytes = Bytes::from(msg.payload.to_vec());
+
+                                    dump_frame("binance", &stats_label, &json_bytes);
+
+                                    if let Some(stats) = stream_stats.record(json_bytes.len()) {
+                                        let _ = output.send(Event::Stats(stats_label.clone(), stats)).await;
+                                    }
                     
                                     if let Ok(StreamData::Kline(ticker, de_kline)) = feed_de(&json_bytes) {
                                         let buy_volume = str_f32_parse(&de_kline.taker_buy_base_asset_volume);
@@ -678,7 +653,7 @@ impl From<FetchedKlines> for Kline {
     }
 }
 
-pub async fn fetch_klines(ticker: Ticker, timeframe: Timeframe) -> Result<Vec<Kline>, StreamError> {
+pub async fn fetch_klines(ticker: Ticker, timeframe: Timeframe, end_time: Option<i64>) -> Result<Vec<Kline>, StreamError> {
     let symbol_str = ticker.get_string();
     let timeframe_str = match timeframe {
         Timeframe::M1 => "1m",
@@ -686,9 +661,16 @@ pub async fn fetch_klines(ticker: Ticker, timeframe: Timeframe) -> Result<Vec<Kl
         Timeframe::M5 => "5m",
         Timeframe::M15 => "15m",
         Timeframe::M30 => "30m",
+        Timeframe::H1 => "1h",
+        Timeframe::H4 => "4h",
+        Timeframe::D1 => "1d",
     };
 
-    let url = format!("https://fapi.binance.com/fapi/v1/klines?symbol={symbol_str}&interval={timeframe_str}&limit=720");
+    let mut url = format!("https://fapi.binance.com/fapi/v1/klines?symbol={symbol_str}&interval={timeframe_str}&limit=720");
+
+    if let Some(end_time) = end_time {
+        url.push_str(&format!("&endTime={end_time}"));
+    }
 
     let response = reqwest::get(&url)
         .await.map_err(StreamError::FetchError)?;
@@ -1 +1,47 @@
-pub mod market_data;
\ No newline at end of file
+use iced::futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
+
+use super::{Kline, MarketDataProvider, MarketEvent, Exchange, StreamError, Ticker, Timeframe, MarketStream};
+
+pub mod market_data;
+
+pub struct Provider;
+
+impl MarketDataProvider for Provider {
+    /// Still a connection per ticker, trade mode and depth cadence don't apply here
+    fn connect_market_stream(&self, streams: Vec<MarketStream>) -> BoxStream<'static, MarketEvent> {
+        let connections = streams.into_iter()
+            .map(|stream| market_data::connect_market_stream(stream.ticker, stream.tick_rule).boxed())
+            .collect::<Vec<_>>();
+
+        iced::futures::stream::select_all(connections)
+            .map(|event| MarketEvent(Exchange::BybitLinear, event))
+            .boxed()
+    }
+
+    fn connect_kline_stream(&self, streams: Vec<(Ticker, Timeframe)>) -> BoxStream<'static, MarketEvent> {
+        market_data::connect_kline_stream(streams, self.kline_streams_per_connection())
+            .map(|event| MarketEvent(Exchange::BybitLinear, event))
+            .boxed()
+    }
+
+    fn fetch_klines(&self, ticker: Ticker, timeframe: Timeframe) -> BoxFuture<'static, Result<Vec<Kline>, StreamError>> {
+        async move {
+            market_data::fetch_klines(ticker, timeframe, None).await
+                .map_err(|err| StreamError::UnknownError(err.to_string()))
+        }.boxed()
+    }
+
+    fn fetch_klines_until(&self, ticker: Ticker, timeframe: Timeframe, end_time: i64) -> BoxFuture<'static, Result<Vec<Kline>, StreamError>> {
+        async move {
+            market_data::fetch_klines(ticker, timeframe, Some(end_time)).await
+                .map_err(|err| StreamError::UnknownError(err.to_string()))
+        }.boxed()
+    }
+
+    fn fetch_ticksize(&self, ticker: Ticker) -> BoxFuture<'static, Result<f32, StreamError>> {
+        async move {
+            market_data::fetch_ticksize(ticker).await
+                .map_err(|err| StreamError::UnknownError(err.to_string()))
+        }.boxed()
+    }
+}
@@ -12,16 +12,10 @@ use anyhow::anyhow;
 use anyhow::{Context, Result};
 
 use fastwebsockets::{Frame, FragmentCollector, OpCode};
-use http_body_util::Empty;
-use hyper::header::{CONNECTION, UPGRADE};
 use hyper::upgrade::Upgraded;
-use hyper::Request;
 use hyper_util::rt::TokioIo;
-use tokio::net::TcpStream;
-use tokio_rustls::rustls::{ClientConfig, OwnedTrustAnchor};
-use tokio_rustls::TlsConnector;
 
-use crate::data_providers::{Depth, FeedLatency, Kline, LocalDepthCache, Order, Trade};
+use crate::data_providers::{connect_with_timeout, connect_websocket, connect_kline_batches, Event, FeedLatency, Kline, LocalDepthCache, Order, StreamStatsCounter, TickRule, Trade, dump_frame};
 use crate::{Ticker, Timeframe};
 
 #[allow(clippy::large_enum_variant)]
@@ -32,17 +26,6 @@ enum State {
     ),
 }
 
-#[derive(Debug, Clone)]
-pub enum Event {
-    Connected(Connection),
-    Disconnected(String),
-    DepthReceived(Ticker, FeedLatency, i64, Depth, Vec<Trade>),
-    KlineReceived(Ticker, Kline, Timeframe),
-}
-
-#[derive(Debug, Clone)]
-pub struct Connection;
-
 #[derive(Serialize, Deserialize, Debug)]
 struct SonicDepth {
 	#[serde(rename = "u")]
@@ -69,8 +52,9 @@ struct SonicTrade {
 	pub price: String,
 	#[serde(rename = "v")]
 	pub qty: String,
-	#[serde(rename = "S")]
-	pub is_sell: String,
+	/// "Buy" or "Sell", missing on some fills
+	#[serde(rename = "S", default)]
+	pub is_sell: Option<String>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -229,68 +213,8 @@ fn feed_de(bytes: &Bytes) -> Result<StreamData> {
     Err(anyhow::anyhow!("Unknown data"))
 }
 
-fn tls_connector() -> Result<TlsConnector> {
-	let mut root_store = tokio_rustls::rustls::RootCertStore::empty();
-
-	root_store.add_trust_anchors(
-		webpki_roots::TLS_SERVER_ROOTS.0.iter().map(|ta| {
-			OwnedTrustAnchor::from_subject_spki_name_constraints(
-			ta.subject,
-			ta.spki,
-			ta.name_constraints,
-			)
-		}),
-	);
-
-	let config = ClientConfig::builder()
-		.with_safe_defaults()
-		.with_root_certificates(root_store)
-		.with_no_client_auth();
-
-	Ok(TlsConnector::from(std::sync::Arc::new(config)))
-}
-
 async fn connect(domain: &str) -> Result<FragmentCollector<TokioIo<Upgraded>>> {
-	let mut addr = String::from(domain);
-    addr.push_str(":443");
-
-	let tcp_stream: TcpStream = TcpStream::connect(&addr).await?;
-	let tls_connector: TlsConnector = tls_connector().unwrap();
-	let domain: tokio_rustls::rustls::ServerName =
-	tokio_rustls::rustls::ServerName::try_from(domain).map_err(|_| {
-		std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid dnsname")
-	})?;
-
-	let tls_stream: tokio_rustls::client::TlsStream<TcpStream> = tls_connector.connect(domain, tcp_stream).await?;
-
-    let url = "wss://stream.bybit.com/v5/public/linear".to_string();
-
-	let req: Request<Empty<Bytes>> = Request::builder()
-	.method("GET")
-	.uri(url)
-	.header("Host", &addr)
-	.header(UPGRADE, "websocket")
-	.header(CONNECTION, "upgrade")
-	.header(
-		"Sec-WebSocket-Key",
-		fastwebsockets::handshake::generate_key(),
-	)
-	.header("Sec-WebSocket-Version", "13")
-	.body(Empty::<Bytes>::new())?;
-
-	let (ws, _) = fastwebsockets::handshake::client(&SpawnExecutor, req, tls_stream).await?;
-	Ok(FragmentCollector::new(ws))
-}
-struct SpawnExecutor;
-
-impl<Fut> hyper::rt::Executor<Fut> for SpawnExecutor
-where
-  Fut: std::future::Future + Send + 'static,
-  Fut::Output: Send + 'static,
-{
-  fn execute(&self, fut: Fut) {
-	tokio::task::spawn(fut);
-  }
+    connect_websocket(domain, "wss://stream.bybit.com/v5/public/linear").await
 }
 
 fn str_f32_parse(s: &str) -> f32 {
@@ -300,11 +224,25 @@ fn str_f32_parse(s: &str) -> f32 {
     })
 }
 
+/// Interval as Bybit names it, minutes up to 720 and letters for the longer ones
+fn timeframe_str(timeframe: Timeframe) -> &'static str {
+    match timeframe {
+        Timeframe::M1 => "1",
+        Timeframe::M3 => "3",
+        Timeframe::M5 => "5",
+        Timeframe::M15 => "15",
+        Timeframe::M30 => "30",
+        Timeframe::H1 => "60",
+        Timeframe::H4 => "240",
+        Timeframe::D1 => "D",
+    }
+}
+
 fn string_to_timeframe(interval: &str) -> Option<Timeframe> {
-    Timeframe::ALL.iter().find(|&tf| tf.to_string() == format!("{}m", interval)).copied()
+    Timeframe::ALL.iter().find(|tf| timeframe_str(**tf) == interval).copied()
 }
 
-pub fn connect_market_stream(ticker: Ticker) -> impl Stream<Item = Event> {
+pub fn connect_market_stream(ticker: Ticker, tick_rule: bool) -> impl Stream<Item = Event> {
     stream::channel (
         100,
         move |mut output| async move {
@@ -323,35 +261,39 @@ pub fn connect_market_stream(ticker: Ticker) -> impl Stream<Item = Event> {
 
             let mut trade_latencies: Vec<i64> = Vec::new();
 
+            let mut tick_rule = TickRule::new(tick_rule);
+
+            let mut stream_stats = StreamStatsCounter::default();
+            let stats_label = format!("{selected_ticker} depth/trades");
+
             loop {
                 match &mut state {
                     State::Disconnected => {        
                         let domain: &str = "stream.bybit.com";
 
-                        if let Ok(mut websocket) = connect(domain
-                        )
-                        .await {
-                            let subscribe_message: String = serde_json::json!({
-                                "op": "subscribe",
-                                "args": [stream_1, stream_2]
-                            }).to_string();
+                        match connect_with_timeout(connect(domain)).await {
+                            Ok(mut websocket) => {
+                                let subscribe_message: String = serde_json::json!({
+                                    "op": "subscribe",
+                                    "args": [stream_1, stream_2]
+                                }).to_string();
     
-                            if let Err(e) = websocket.write_frame(Frame::text(fastwebsockets::Payload::Borrowed(subscribe_message.as_bytes()))).await {
-                                let _ = output.send(Event::Disconnected(
-                                    format!("Failed subscribing: {}", e)
-                                )).await;
+                                if let Err(e) = websocket.write_frame(Frame::text(fastwebsockets::Payload::Borrowed(subscribe_message.as_bytes()))).await {
+                                    let _ = output.send(Event::Disconnected(
+                                        format!("Failed subscribing: {}", e)
+                                    )).await;
 
-                                continue;
-                            }
+                                    continue;
+                                }
 
-                            state = State::Connected(websocket);
-                            let _ = output.send(Event::Connected(Connection)).await; 
-                        } else {
-                            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                                state = State::Connected(websocket);
+                                let _ = output.send(Event::Connected).await; 
+                            },
+                            Err(reason) => {
+                                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
 
-                            let _ = output.send(Event::Disconnected(
-                                "Failed to connect to websocket".to_string()
-                            )).await;
+                                let _ = output.send(Event::Disconnected(reason)).await;
+                            }
                         }
                     },
                     State::Connected(websocket) => {
@@ -362,14 +304,28 @@ pub fn connect_market_stream(ticker: Ticker) -> impl Stream<Item = Event> {
                                 OpCode::Text => {       
                                     let json_bytes: Bytes = Bytes::from(msg.payload.to_vec());
 
+                                    dump_frame("bybit", &stats_label, &json_bytes);
+
+                                    if let Some(stats) = stream_stats.record(json_bytes.len()) {
+                                        let _ = output.send(Event::Stats(stats_label.clone(), stats)).await;
+                                    }
+
                                     if let Ok(data) = feed_de(&json_bytes) {
                                         match data {
                                             StreamData::Trade(de_trade_vec) => {
                                                 for de_trade in de_trade_vec.iter() {
+                                                    let price = str_f32_parse(&de_trade.price);
+
+                                                    let side = match de_trade.is_sell.as_deref() {
+                                                        Some("Sell") => Some(true),
+                                                        Some("Buy") => Some(false),
+                                                        _ => None,
+                                                    };
+
                                                     let trade = Trade {
                                                         time: de_trade.time as i64,
-                                                        is_sell: de_trade.is_sell == "Sell",
-                                                        price: str_f32_parse(&de_trade.price),
+                                                        is_sell: tick_rule.is_sell(price, side),
+                                                        price,
                                                         qty: str_f32_parse(&de_trade.qty),
                                                     };
 
@@ -451,22 +407,26 @@ pub fn connect_market_stream(ticker: Ticker) -> impl Stream<Item = Event> {
         },
     )
 }
- 
-pub fn connect_kline_stream(streams: Vec<(Ticker, Timeframe)>) -> impl Stream<Item = Event> {
+
+/// Bybit rejects a subscribe request carrying more than 10 topics, more go out as several requests
+const MAX_TOPICS_PER_SUBSCRIBE: usize = 10;
+
+/// Kline streams on as many connections as `per_connection` streams each need
+pub fn connect_kline_stream(streams: Vec<(Ticker, Timeframe)>, per_connection: usize) -> impl Stream<Item = Event> {
+    connect_kline_batches(streams, per_connection, connect_kline_batch)
+}
+
+fn connect_kline_batch(streams: Vec<(Ticker, Timeframe)>, stats_label: String) -> impl Stream<Item = Event> {
     stream::channel (
         100,
         move |mut output| async move {
             let mut state = State::Disconnected;    
 
+            let mut stream_stats = StreamStatsCounter::default();
+
             let stream_str = streams.iter().map(|(ticker, timeframe)| {
                 let symbol_str = ticker.get_string().to_uppercase();
-                let timeframe_str = match timeframe {
-                    Timeframe::M1 => "1",
-                    Timeframe::M3 => "3",
-                    Timeframe::M5 => "5",
-                    Timeframe::M15 => "15",
-                    Timeframe::M30 => "30",
-                };
+                let timeframe_str = timeframe_str(*timeframe);
                 format!("kline.{timeframe_str}.{symbol_str}")
             }).collect::<Vec<String>>();
  
@@ -475,31 +435,38 @@ pub fn connect_kline_stream(streams: Vec<(Ticker, Timeframe)>) -> impl Stream<It
                     State::Disconnected => {
                         let domain = "stream.bybit.com";
                         
-                        if let Ok(mut websocket) = connect(
-                            domain,
-                        )
-                        .await {
-                            let subscribe_message = serde_json::json!({
-                                "op": "subscribe",
-                                "args": stream_str 
-                            }).to_string();
-    
-                            if let Err(e) = websocket.write_frame(Frame::text(fastwebsockets::Payload::Borrowed(subscribe_message.as_bytes()))).await {
-                                let _ = output.send(Event::Disconnected
-                                    (format!("Failed subscribing: {}", e))
-                                ).await;
+                        match connect_with_timeout(connect(domain)).await {
+                            Ok(mut websocket) => {
+                                let mut subscribed = true;
+
+                                for topics in stream_str.chunks(MAX_TOPICS_PER_SUBSCRIBE) {
+                                    let subscribe_message = serde_json::json!({
+                                        "op": "subscribe",
+                                        "args": topics
+                                    }).to_string();
+
+                                    if let Err(e) = websocket.write_frame(Frame::text(fastwebsockets::Payload::Borrowed(subscribe_message.as_bytes()))).await {
+                                        let _ = output.send(Event::Disconnected
+                                            (format!("Failed subscribing: {}", e))
+                                        ).await;
+
+                                        subscribed = false;
+                                        break;
+                                    }
+                                }
 
-                                continue;
-                            }
+                                if !subscribed {
+                                    continue;
+                                }
 
-                            state = State::Connected(websocket);
-                            let _ = output.send(Event::Connected(Connection)).await;
-                        } else {
-                            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                                state = State::Connected(websocket);
+                                let _ = output.send(Event::Connected).await;
+                            },
+                            Err(reason) => {
+                                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
 
-                            let _ = output.send(Event::Disconnected(
-                                "Failed to connect to websocket".to_string()
-                            )).await;
+                                let _ = output.send(Event::Disconnected(reason)).await;
+                            }
                         }
                     }
                     State::Connected(websocket) => {
@@ -507,6 +474,12 @@ pub fn connect_kline_stream(streams: Vec<(Ticker, Timeframe)>) -> impl Stream<It
                             Ok(msg) => match msg.opcode {
                                 OpCode::Text => {                    
                                     let json_bytes: Bytes = Bytes::from(msg.payload.to_vec());
+
+                                    dump_frame("bybit", &stats_label, &json_bytes);
+
+                                    if let Some(stats) = stream_stats.record(json_bytes.len()) {
+                                        let _ = output.send(Event::Stats(stats_label.clone(), stats)).await;
+                                    }
                     
                                     if let Ok(StreamData::Kline(ticker, de_kline_vec)) = feed_de(&json_bytes) {
                                         for de_kline in de_kline_vec.iter() {
@@ -562,17 +535,15 @@ struct ApiResult {
     list: Vec<Vec<Value>>,
 }
 
-pub async fn fetch_klines(ticker: Ticker, timeframe: Timeframe) -> Result<Vec<Kline>> {
+pub async fn fetch_klines(ticker: Ticker, timeframe: Timeframe, end_time: Option<i64>) -> Result<Vec<Kline>> {
     let symbol_str = ticker.get_string().to_uppercase();
-    let timeframe_str = match timeframe {
-        Timeframe::M1 => "1",
-        Timeframe::M3 => "3",
-        Timeframe::M5 => "5",
-        Timeframe::M15 => "15",
-        Timeframe::M30 => "30",
-    };
+    let timeframe_str = timeframe_str(timeframe);
+
+    let mut url: String = format!("https://api.bybit.com/v5/market/kline?category=linear&symbol={symbol_str}&interval={timeframe_str}&limit=720");
 
-    let url: String = format!("https://api.bybit.com/v5/market/kline?category=linear&symbol={symbol_str}&interval={timeframe_str}&limit=720");
+    if let Some(end_time) = end_time {
+        url.push_str(&format!("&end={end_time}"));
+    }
 
     let response: reqwest::Response = reqwest::get(&url).await
         .context("Failed to send request")?;
@@ -0,0 +1,34 @@
+use iced::futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
+
+use super::{Kline, MarketDataProvider, MarketEvent, Exchange, StreamError, Ticker, Timeframe, MarketStream};
+
+pub mod market_data;
+
+pub struct Provider;
+
+impl MarketDataProvider for Provider {
+    /// Every ticker shares a connection, trade mode and depth cadence don't apply here
+    fn connect_market_stream(&self, streams: Vec<MarketStream>) -> BoxStream<'static, MarketEvent> {
+        market_data::connect_market_stream(streams)
+            .map(|event| MarketEvent(Exchange::Coinbase, event))
+            .boxed()
+    }
+
+    fn connect_kline_stream(&self, streams: Vec<(Ticker, Timeframe)>) -> BoxStream<'static, MarketEvent> {
+        market_data::connect_kline_stream(streams)
+            .map(|event| MarketEvent(Exchange::Coinbase, event))
+            .boxed()
+    }
+
+    fn fetch_klines(&self, ticker: Ticker, timeframe: Timeframe) -> BoxFuture<'static, Result<Vec<Kline>, StreamError>> {
+        market_data::fetch_klines(ticker, timeframe, None).boxed()
+    }
+
+    fn fetch_klines_until(&self, ticker: Ticker, timeframe: Timeframe, end_time: i64) -> BoxFuture<'static, Result<Vec<Kline>, StreamError>> {
+        market_data::fetch_klines(ticker, timeframe, Some(end_time)).boxed()
+    }
+
+    fn fetch_ticksize(&self, ticker: Ticker) -> BoxFuture<'static, Result<f32, StreamError>> {
+        market_data::fetch_ticksize(ticker).boxed()
+    }
+}
@@ -0,0 +1,727 @@
+use iced::{stream, futures};
+use futures::sink::SinkExt;
+use futures::stream::Stream;
+
+use std::collections::HashMap;
+
+use bytes::Bytes;
+use serde::Deserialize;
+
+use anyhow::Result;
+
+use fastwebsockets::{Frame, FragmentCollector, OpCode};
+use hyper::upgrade::Upgraded;
+use hyper_util::rt::TokioIo;
+
+use crate::data_providers::{
+    connect_with_timeout, connect_websocket, dump_frame, resample_klines, USER_AGENT, Event, FeedLatency, Kline, LocalDepthCache, MarketStream, Order, StreamError, StreamStatsCounter, TickRule, Trade,
+};
+use crate::{Ticker, Timeframe};
+
+const WS_DOMAIN: &str = "ws-feed.exchange.coinbase.com";
+const API_URL: &str = "https://api.exchange.coinbase.com";
+
+/// Levels kept per side from a book snapshot, the full book runs into the tens of thousands
+const SNAPSHOT_LEVELS: usize = 1000;
+
+#[allow(clippy::large_enum_variant)]
+enum State {
+    Disconnected,
+    Connected(
+        FragmentCollector<TokioIo<Upgraded>>
+    ),
+}
+
+/// Spot USD pair a ticker is traded against on Coinbase
+pub fn product_id(ticker: Ticker) -> &'static str {
+    match ticker {
+        Ticker::BTCUSDT => "BTC-USD",
+        Ticker::ETHUSDT => "ETH-USD",
+        Ticker::SOLUSDT => "SOL-USD",
+        Ticker::LTCUSDT => "LTC-USD",
+    }
+}
+
+fn ticker_of(product_id_str: &str) -> Option<Ticker> {
+    Ticker::ALL.iter().find(|ticker| product_id(**ticker) == product_id_str).copied()
+}
+
+#[derive(Deserialize, Debug)]
+struct Level(String, String);
+
+/// Side ("buy" or "sell"), price and the new size at that price, "0" removes the level
+#[derive(Deserialize, Debug)]
+struct Change(String, String, String);
+
+#[derive(Deserialize, Debug)]
+struct FeedTrade {
+    product_id: String,
+    time: String,
+    price: String,
+    size: String,
+    /// Side of the maker order, the taker went the other way
+    side: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type")]
+enum FeedMessage {
+    #[serde(rename = "snapshot")]
+    Snapshot {
+        product_id: String,
+        bids: Vec<Level>,
+        asks: Vec<Level>,
+    },
+    #[serde(rename = "l2update")]
+    L2Update {
+        product_id: String,
+        changes: Vec<Change>,
+        time: String,
+    },
+    #[serde(rename = "match")]
+    Match(FeedTrade),
+    #[serde(rename = "error")]
+    Error {
+        message: String,
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    /// Subscription acks, heartbeats and the `last_match` replayed on subscribing
+    #[serde(other)]
+    Other,
+}
+
+fn parse_time(time: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(time)
+        .map(|time| time.timestamp_millis())
+        .ok()
+}
+
+async fn connect(domain: &str) -> Result<FragmentCollector<TokioIo<Upgraded>>> {
+    connect_websocket(domain, &format!("wss://{WS_DOMAIN}")).await
+}
+
+fn str_f32_parse(s: &str) -> f32 {
+    s.parse::<f32>().unwrap_or_else(|e| {
+        log::error!("Failed to parse float: {}, error: {}", s, e);
+        0.0
+    })
+}
+
+fn subscribe_message(kind: &str, product_ids: &[&str], channels: &[&str]) -> String {
+    serde_json::json!({
+        "type": kind,
+        "product_ids": product_ids,
+        "channels": channels,
+    }).to_string()
+}
+
+/// Waits between reconnects after Coinbase answers with an error, a rejected subscription
+/// is sent straight back on every attempt and would otherwise reconnect in a tight loop
+#[derive(Default)]
+struct ErrorBackoff {
+    failures: u32,
+    /// Last error passed on, the same one repeating isn't reported again
+    reported: Option<String>,
+}
+
+impl ErrorBackoff {
+    const MAX_DELAY_SECS: u64 = 60;
+
+    /// Delay before the next attempt, doubling from a second, and the error if it's a new one
+    fn fail(&mut self, error: String) -> (std::time::Duration, Option<String>) {
+        let delay = (1u64 << self.failures.min(6)).min(Self::MAX_DELAY_SECS);
+        self.failures += 1;
+
+        let report = if self.reported.as_ref() != Some(&error) {
+            self.reported = Some(error.clone());
+            Some(error)
+        } else {
+            None
+        };
+
+        (std::time::Duration::from_secs(delay), report)
+    }
+
+    /// The feed is delivering again
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Book syncing and the trades gathered between depth updates, for one product of a market stream
+///
+/// `level2_batch` carries no sequence numbers, so the book is only trusted from a snapshot on,
+/// updates older than the book are dropped and a crossed book means one got lost
+struct ProductFeed {
+    orderbook: LocalDepthCache,
+    /// A snapshot arrived since (re)subscribing, updates before it don't apply to anything
+    synced: bool,
+    trades_buffer: Vec<Trade>,
+    trade_latencies: Vec<i64>,
+    tick_rule: TickRule,
+}
+
+impl ProductFeed {
+    fn new(tick_rule: bool) -> Self {
+        Self {
+            orderbook: LocalDepthCache::new(),
+            synced: false,
+            trades_buffer: Vec::new(),
+            trade_latencies: Vec::new(),
+            tick_rule: TickRule::new(tick_rule),
+        }
+    }
+
+    fn snapshot(&mut self, bids: &[Level], asks: &[Level]) {
+        let levels = |side: &[Level]| side.iter()
+            .take(SNAPSHOT_LEVELS)
+            .map(|level| Order { price: str_f32_parse(&level.0), qty: str_f32_parse(&level.1) })
+            .collect();
+
+        // snapshots carry no time, so the first update after one is never taken as stale
+        self.orderbook.fetched(LocalDepthCache {
+            last_update_id: 0,
+            time: 0,
+            bids: levels(bids),
+            asks: levels(asks),
+        });
+        self.synced = true;
+    }
+
+    /// Applies the changes unless they're older than the book, false once the book ends up crossed
+    fn update(&mut self, changes: &[Change], time: i64) -> bool {
+        if time < self.orderbook.time {
+            return true;
+        }
+
+        let mut bids = Vec::new();
+        let mut asks = Vec::new();
+
+        for Change(side, price, qty) in changes {
+            let order = Order { price: str_f32_parse(price), qty: str_f32_parse(qty) };
+
+            match side.as_str() {
+                "buy" => bids.push(order),
+                "sell" => asks.push(order),
+                _ => log::warn!("Unknown side in depth update: {side}"),
+            }
+        }
+
+        self.orderbook.update_depth_cache(LocalDepthCache {
+            last_update_id: self.orderbook.last_update_id + 1,
+            time,
+            bids,
+            asks,
+        });
+
+        let best_bid = self.orderbook.bids.iter().map(|order| order.price).fold(f32::MIN, f32::max);
+        let best_ask = self.orderbook.asks.iter().map(|order| order.price).fold(f32::MAX, f32::min);
+
+        best_bid < best_ask
+    }
+}
+
+/// Depth and trades of every given ticker, combined on a single connection
+pub fn connect_market_stream(streams: Vec<MarketStream>) -> impl Stream<Item = Event> {
+    stream::channel (
+        100,
+        move |mut output| async move {
+            let mut state: State = State::Disconnected;
+
+            let mut feeds: HashMap<Ticker, ProductFeed> = streams.iter()
+                .map(|stream| (stream.ticker, ProductFeed::new(stream.tick_rule)))
+                .collect();
+
+            let product_ids: Vec<&str> = streams.iter()
+                .map(|stream| product_id(stream.ticker))
+                .collect();
+
+            let mut stream_stats = StreamStatsCounter::default();
+            let mut backoff = ErrorBackoff::default();
+            let stats_label = match &streams[..] {
+                [stream] => format!("{} depth/trades", stream.ticker),
+                _ => format!("depth/trades ({})", streams.len()),
+            };
+
+            loop {
+                match &mut state {
+                    State::Disconnected => {
+                        match connect_with_timeout(connect(WS_DOMAIN)).await {
+                            Ok(mut websocket) => {
+                                let subscribe = subscribe_message("subscribe", &product_ids, &["level2_batch", "matches"]);
+
+                                if let Err(e) = websocket.write_frame(Frame::text(fastwebsockets::Payload::Borrowed(subscribe.as_bytes()))).await {
+                                    let _ = output.send(Event::Disconnected(
+                                        format!("Failed subscribing: {}", e)
+                                    )).await;
+
+                                    continue;
+                                }
+
+                                for feed in feeds.values_mut() {
+                                    feed.synced = false;
+                                }
+
+                                state = State::Connected(websocket);
+                                let _ = output.send(Event::Connected).await;
+                            },
+                            Err(reason) => {
+                                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+
+                                let _ = output.send(Event::Disconnected(reason)).await;
+                            }
+                        }
+                    },
+                    State::Connected(websocket) => {
+                        match websocket.read_frame().await {
+                            Ok(msg) => match msg.opcode {
+                                OpCode::Text => {
+                                    let json_bytes: Bytes = Bytes::from(msg.payload.to_vec());
+
+                                    dump_frame("coinbase", &stats_label, &json_bytes);
+
+                                    if let Some(stats) = stream_stats.record(json_bytes.len()) {
+                                        let _ = output.send(Event::Stats(stats_label.clone(), stats)).await;
+                                    }
+
+                                    match serde_json::from_slice::<FeedMessage>(&json_bytes) {
+                                        Ok(FeedMessage::Match(de_trade)) => {
+                                            let Some(feed) = ticker_of(&de_trade.product_id).and_then(|ticker| feeds.get_mut(&ticker)) else {
+                                                continue;
+                                            };
+
+                                            let price = str_f32_parse(&de_trade.price);
+
+                                            let side = match de_trade.side.as_str() {
+                                                "buy" => Some(true),
+                                                "sell" => Some(false),
+                                                _ => None,
+                                            };
+
+                                            let trade = Trade {
+                                                time: parse_time(&de_trade.time).unwrap_or_else(|| chrono::Utc::now().timestamp_millis()),
+                                                is_sell: feed.tick_rule.is_sell(price, side),
+                                                price,
+                                                qty: str_f32_parse(&de_trade.size),
+                                            };
+
+                                            feed.trade_latencies.push(
+                                                chrono::Utc::now().timestamp_millis() - trade.time
+                                            );
+
+                                            feed.trades_buffer.push(trade);
+                                        },
+                                        Ok(FeedMessage::Snapshot { product_id, bids, asks }) => {
+                                            backoff.reset();
+
+                                            if let Some(feed) = ticker_of(&product_id).and_then(|ticker| feeds.get_mut(&ticker)) {
+                                                feed.snapshot(&bids, &asks);
+                                            }
+                                        },
+                                        Ok(FeedMessage::L2Update { product_id, changes, time }) => {
+                                            let Some(ticker) = ticker_of(&product_id) else {
+                                                continue;
+                                            };
+                                            let Some(feed) = feeds.get_mut(&ticker).filter(|feed| feed.synced) else {
+                                                continue;
+                                            };
+
+                                            let time = parse_time(&time).unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+
+                                            if !feed.update(&changes, time) {
+                                                // the other products on the connection are fine, only this book starts over
+                                                log::warn!("{ticker} book crossed, an update got lost. Resubscribing...");
+
+                                                feed.synced = false;
+
+                                                let resubscribe = [
+                                                    subscribe_message("unsubscribe", &[product_id.as_str()], &["level2_batch"]),
+                                                    subscribe_message("subscribe", &[product_id.as_str()], &["level2_batch"]),
+                                                ];
+
+                                                for message in &resubscribe {
+                                                    if let Err(e) = websocket.write_frame(Frame::text(fastwebsockets::Payload::Borrowed(message.as_bytes()))).await {
+                                                        state = State::Disconnected;
+                                                        let _ = output.send(Event::Disconnected(
+                                                            format!("Failed resubscribing: {}", e)
+                                                        )).await;
+
+                                                        break;
+                                                    }
+                                                }
+
+                                                continue;
+                                            }
+
+                                            let depth_latency = chrono::Utc::now().timestamp_millis() - time;
+
+                                            let avg_trade_latency = if !feed.trade_latencies.is_empty() {
+                                                let avg = feed.trade_latencies.iter().sum::<i64>() / feed.trade_latencies.len() as i64;
+                                                feed.trade_latencies.clear();
+                                                Some(avg)
+                                            } else {
+                                                None
+                                            };
+                                            let feed_latency = FeedLatency {
+                                                time,
+                                                depth_latency,
+                                                trade_latency: avg_trade_latency,
+                                            };
+
+                                            let _ = output.send(
+                                                Event::DepthReceived(
+                                                    ticker,
+                                                    feed_latency,
+                                                    time,
+                                                    feed.orderbook.get_depth(),
+                                                    std::mem::take(&mut feed.trades_buffer)
+                                                )
+                                            ).await;
+                                        },
+                                        Ok(FeedMessage::Error { message, reason }) => {
+                                            state = State::Disconnected;
+
+                                            let (delay, report) = backoff.fail(
+                                                format!("Coinbase error: {message} {}", reason.unwrap_or_default())
+                                            );
+
+                                            if let Some(error) = report {
+                                                let _ = output.send(Event::Disconnected(error)).await;
+                                            }
+
+                                            tokio::time::sleep(delay).await;
+                                        },
+                                        Ok(FeedMessage::Other) => {},
+                                        Err(e) => {
+                                            log::error!("\nUnknown data: {:?}, error: {e}", &json_bytes);
+                                        }
+                                    }
+                                }
+                                OpCode::Close => {
+                                    state = State::Disconnected;
+                                    let _ = output.send(
+                                        Event::Disconnected("Connection closed".to_string())
+                                    ).await;
+                                }
+                                _ => {}
+                            },
+                            Err(e) => {
+                                state = State::Disconnected;
+                                let _ = output.send(
+                                    Event::Disconnected("Error reading frame: ".to_string() + &e.to_string())
+                                ).await;
+                            }
+                        }
+                    }
+                }
+            }
+        },
+    )
+}
+
+/// Adds a trade to the bar it falls in, or starts the next bar with it
+///
+/// Bars seeded from the REST candles have no buy/sell split, their volume stays a total
+fn apply_trade(bar: &mut Option<Kline>, timeframe: Timeframe, time: i64, price: f32, qty: f32, is_sell: bool) -> bool {
+    let timeframe_ms = timeframe.to_millis();
+    let bar_time = time as u64 - (time as u64 % timeframe_ms);
+
+    match bar {
+        Some(kline) if kline.time == bar_time => {
+            kline.high = kline.high.max(price);
+            kline.low = kline.low.min(price);
+            kline.close = price;
+
+            if kline.volume.0 == -1.0 || is_sell {
+                kline.volume.1 += qty;
+            } else {
+                kline.volume.0 += qty;
+            }
+        },
+        Some(kline) if kline.time > bar_time => return false,
+        _ => {
+            *bar = Some(Kline {
+                time: bar_time,
+                open: price,
+                high: price,
+                low: price,
+                close: price,
+                volume: if is_sell { (0.0, qty) } else { (qty, 0.0) },
+            });
+        },
+    }
+
+    true
+}
+
+/// Coinbase has no public kline channel, so bars are built from the trades of the `matches` channel,
+/// starting from the latest REST candle of each stream
+pub fn connect_kline_stream(streams: Vec<(Ticker, Timeframe)>) -> impl Stream<Item = Event> {
+    stream::channel (
+        100,
+        move |mut output| async move {
+            let mut state = State::Disconnected;
+
+            let mut stream_stats = StreamStatsCounter::default();
+            let mut backoff = ErrorBackoff::default();
+            let stats_label = format!("klines ({})", streams.len());
+
+            let mut product_ids: Vec<&str> = streams.iter()
+                .map(|(ticker, _)| product_id(*ticker))
+                .collect();
+            product_ids.sort_unstable();
+            product_ids.dedup();
+
+            let mut bars: HashMap<(Ticker, Timeframe), Option<Kline>> = HashMap::new();
+
+            loop {
+                match &mut state {
+                    State::Disconnected => {
+                        match connect_with_timeout(connect(WS_DOMAIN)).await {
+                            Ok(mut websocket) => {
+                                let subscribe = subscribe_message("subscribe", &product_ids, &["matches"]);
+
+                                if let Err(e) = websocket.write_frame(Frame::text(fastwebsockets::Payload::Borrowed(subscribe.as_bytes()))).await {
+                                    let _ = output.send(Event::Disconnected
+                                        (format!("Failed subscribing: {}", e))
+                                    ).await;
+
+                                    continue;
+                                }
+
+                                let seeds = futures::future::join_all(
+                                    streams.iter().map(|(ticker, timeframe)| fetch_klines(*ticker, *timeframe, None))
+                                ).await;
+
+                                bars = streams.iter().zip(seeds).map(|(stream, seed)| {
+                                    let latest = seed
+                                        .inspect_err(|e| log::warn!("No seed bar for {stream:?}, error: {e}"))
+                                        .ok()
+                                        .and_then(|klines| klines.last().copied());
+
+                                    (*stream, latest)
+                                }).collect();
+
+                                state = State::Connected(websocket);
+                                let _ = output.send(Event::Connected).await;
+                            },
+                            Err(reason) => {
+                                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+
+                                let _ = output.send(Event::Disconnected(reason)).await;
+                            }
+                        }
+                    }
+                    State::Connected(websocket) => {
+                        match websocket.read_frame().await {
+                            Ok(msg) => match msg.opcode {
+                                OpCode::Text => {
+                                    let json_bytes: Bytes = Bytes::from(msg.payload.to_vec());
+
+                                    dump_frame("coinbase", &stats_label, &json_bytes);
+
+                                    if let Some(stats) = stream_stats.record(json_bytes.len()) {
+                                        let _ = output.send(Event::Stats(stats_label.clone(), stats)).await;
+                                    }
+
+                                    match serde_json::from_slice::<FeedMessage>(&json_bytes) {
+                                        Ok(FeedMessage::Match(de_trade)) => {
+                                            backoff.reset();
+
+                                            let Some(ticker) = ticker_of(&de_trade.product_id) else {
+                                                continue;
+                                            };
+                                            let Some(time) = parse_time(&de_trade.time) else {
+                                                continue;
+                                            };
+
+                                            let price = str_f32_parse(&de_trade.price);
+                                            let qty = str_f32_parse(&de_trade.size);
+                                            let is_sell = de_trade.side == "buy";
+
+                                            for ((bar_ticker, timeframe), bar) in bars.iter_mut() {
+                                                if *bar_ticker != ticker || !apply_trade(bar, *timeframe, time, price, qty, is_sell) {
+                                                    continue;
+                                                }
+
+                                                if let Some(kline) = bar {
+                                                    let _ = output.send(Event::KlineReceived(ticker, *kline, *timeframe)).await;
+                                                }
+                                            }
+                                        },
+                                        Ok(FeedMessage::Error { message, reason }) => {
+                                            state = State::Disconnected;
+
+                                            let (delay, report) = backoff.fail(
+                                                format!("Coinbase error: {message} {}", reason.unwrap_or_default())
+                                            );
+
+                                            if let Some(error) = report {
+                                                let _ = output.send(Event::Disconnected(error)).await;
+                                            }
+
+                                            tokio::time::sleep(delay).await;
+                                        },
+                                        Ok(_) => {},
+                                        Err(e) => {
+                                            log::error!("\nUnknown data: {:?}, error: {e}", &json_bytes);
+                                        }
+                                    }
+                                }
+                                OpCode::Close => {
+                                    state = State::Disconnected;
+                                    let _ = output.send(
+                                        Event::Disconnected("Connection closed".to_string())
+                                    ).await;
+                                }
+                                _ => {}
+                            },
+                            Err(e) => {
+                                state = State::Disconnected;
+                                let _ = output.send(
+                                    Event::Disconnected("Error reading frame: ".to_string() + &e.to_string())
+                                ).await;
+                            }
+                        }
+                    }
+                }
+            }
+        },
+    )
+}
+
+fn http_client() -> Result<reqwest::Client, StreamError> {
+    reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .build()
+        .map_err(StreamError::FetchError)
+}
+
+/// Candles per request, the most Coinbase hands out at once
+const MAX_CANDLES_PER_REQUEST: i64 = 300;
+/// Requests per fetch, about as many bars as the other exchanges give
+const KLINE_PAGES: usize = 3;
+
+/// time (s), low, high, open, close, volume
+#[derive(Deserialize, Debug, Clone)]
+struct FetchedCandle(i64, f32, f32, f32, f32, f32);
+
+impl From<FetchedCandle> for Kline {
+    fn from(fetched: FetchedCandle) -> Self {
+        Self {
+            time: fetched.0 as u64 * 1000,
+            open: fetched.3,
+            high: fetched.2,
+            low: fetched.1,
+            close: fetched.4,
+            volume: (-1.0, fetched.5),
+        }
+    }
+}
+
+pub async fn fetch_klines(ticker: Ticker, timeframe: Timeframe, end_time: Option<i64>) -> Result<Vec<Kline>, StreamError> {
+    // there are no 3m, 30m and 4h candles, those get resampled from the closest granularity below
+    let base_timeframe = match timeframe {
+        Timeframe::M1 | Timeframe::M3 => Timeframe::M1,
+        Timeframe::M5 => Timeframe::M5,
+        Timeframe::M15 | Timeframe::M30 => Timeframe::M15,
+        Timeframe::H1 | Timeframe::H4 => Timeframe::H1,
+        Timeframe::D1 => Timeframe::D1,
+    };
+    let granularity = (base_timeframe.to_millis() / 1000) as i64;
+
+    let client = http_client()?;
+
+    let mut page_end = end_time.map_or_else(|| chrono::Utc::now().timestamp(), |end_time| end_time / 1000);
+    let mut klines: Vec<Kline> = Vec::new();
+
+    for _ in 0..KLINE_PAGES {
+        let page_start = page_end - granularity * (MAX_CANDLES_PER_REQUEST - 1);
+
+        let (Some(start), Some(end)) = (
+            chrono::DateTime::from_timestamp(page_start, 0),
+            chrono::DateTime::from_timestamp(page_end, 0),
+        ) else {
+            return Err(StreamError::ParseError(format!("Invalid kline range ending at {page_end}")));
+        };
+
+        let url = format!(
+            "{API_URL}/products/{}/candles?granularity={granularity}&start={}&end={}",
+            product_id(ticker), start.to_rfc3339(), end.to_rfc3339()
+        );
+
+        let response = client.get(&url).send()
+            .await.map_err(StreamError::FetchError)?;
+        let text = response.text()
+            .await.map_err(StreamError::FetchError)?;
+
+        let fetched: Vec<FetchedCandle> = serde_json::from_str(&text)
+            .map_err(|e| StreamError::ParseError(format!("Failed to parse klines: {e}, {text}")))?;
+
+        if fetched.is_empty() {
+            break;
+        }
+
+        klines.extend(fetched.into_iter().map(Kline::from));
+
+        page_end = page_start - granularity;
+    }
+
+    // newest first within a page
+    klines.sort_by_key(|kline| kline.time);
+    klines.dedup_by_key(|kline| kline.time);
+
+    if base_timeframe != timeframe {
+        return Ok(resample_klines(&klines, base_timeframe, timeframe));
+    }
+
+    Ok(klines)
+}
+
+pub async fn fetch_ticksize(ticker: Ticker) -> Result<f32, StreamError> {
+    let url = format!("{API_URL}/products/{}", product_id(ticker));
+
+    let response = http_client()?.get(&url).send()
+        .await.map_err(StreamError::FetchError)?;
+    let text = response.text()
+        .await.map_err(StreamError::FetchError)?;
+
+    let product: serde_json::Value = serde_json::from_str(&text)
+        .map_err(|e| StreamError::ParseError(format!("Failed to parse product: {}", e)))?;
+
+    let tick_size_str = product["quote_increment"].as_str()
+        .ok_or_else(|| StreamError::ParseError("quote_increment not found".to_string()))?;
+
+    let tick_size = tick_size_str.parse::<f32>()
+        .map_err(|e| StreamError::ParseError(format!("Failed to parse quote_increment: {}", e)))?;
+
+    Ok(tick_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_errors_back_off_and_report_once() {
+        let mut backoff = ErrorBackoff::default();
+
+        let delays: Vec<u64> = (0..8)
+            .map(|_| backoff.fail("Coinbase error: Failed to subscribe".to_string()))
+            .map(|(delay, _)| delay.as_secs())
+            .collect();
+        assert_eq!(delays, [1, 2, 4, 8, 16, 32, 60, 60]);
+
+        let mut backoff = ErrorBackoff::default();
+        assert!(backoff.fail("a".to_string()).1.is_some());
+        assert!(backoff.fail("a".to_string()).1.is_none());
+        assert!(backoff.fail("b".to_string()).1.is_some());
+
+        // once data flows again the same error counts as new
+        backoff.reset();
+        let (delay, report) = backoff.fail("b".to_string());
+        assert_eq!(delay.as_secs(), 1);
+        assert!(report.is_some());
+    }
+}
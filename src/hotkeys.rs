@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+
+use iced::keyboard::{self, Key, Modifiers};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::data_providers::Ticker;
+use crate::screen::dashboard::FocusMove;
+use crate::LayoutId;
+
+/// What a bound key combination does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Action {
+    ToggleLayoutModal,
+    ToggleSoloPane,
+    ToggleLayoutLock,
+    SelectLayout(LayoutId),
+    /// Back to the most recently used other layout
+    CycleLayout,
+    /// Focused pane to the ticker in this quick ticker slot, 1 to 9
+    QuickTicker(u8),
+    /// Moves the focus to another pane of the grid
+    MoveFocus(FocusMove),
+}
+
+/// A key with its modifiers, written like `Ctrl+Shift+F2` in the state file
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyCombo {
+    /// Lowercased character, or the name of a named key e.g. `F2`, `Tab`
+    key: String,
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+}
+
+impl KeyCombo {
+    fn new(key: &str, ctrl: bool, alt: bool, shift: bool) -> Self {
+        Self { key: key.to_lowercase(), ctrl, alt, shift }
+    }
+
+    pub fn from_key(key: &Key, modifiers: Modifiers) -> Option<Self> {
+        let key = match key {
+            Key::Character(c) => c.to_string(),
+            Key::Named(named) => format!("{named:?}"),
+            Key::Unidentified => return None,
+        };
+
+        Some(Self::new(&key, modifiers.command(), modifiers.alt(), modifiers.shift()))
+    }
+
+    /// Whether it's a plain character or an arrow key, which text inputs and open menus get first
+    pub fn is_typing(key: &Key, modifiers: Modifiers) -> bool {
+        use keyboard::key::Named;
+
+        let editing = matches!(
+            key,
+            Key::Character(_) | Key::Named(Named::ArrowLeft | Named::ArrowRight | Named::ArrowUp | Named::ArrowDown)
+        );
+
+        editing && !modifiers.command() && !modifiers.alt()
+    }
+}
+
+impl std::fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+
+        match self.key.chars().next() {
+            Some(first) if self.key.chars().count() > 1 => {
+                write!(f, "{}{}", first.to_uppercase(), &self.key[first.len_utf8()..])
+            },
+            _ => write!(f, "{}", self.key.to_uppercase()),
+        }
+    }
+}
+
+impl From<KeyCombo> for String {
+    fn from(combo: KeyCombo) -> Self {
+        combo.to_string()
+    }
+}
+
+impl TryFrom<String> for KeyCombo {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let mut parts: Vec<&str> = value.split('+').map(str::trim).collect();
+
+        // "Ctrl++" binds the plus key itself
+        if value.ends_with("++") {
+            parts.pop();
+            if let Some(last) = parts.last_mut() {
+                *last = "+";
+            }
+        }
+
+        let Some(key) = parts.pop().filter(|key| !key.is_empty()) else {
+            return Err(format!("No key in hotkey \"{value}\""));
+        };
+
+        let (mut ctrl, mut alt, mut shift) = (false, false, false);
+
+        for modifier in parts {
+            match modifier.to_lowercase().as_str() {
+                "ctrl" | "cmd" => ctrl = true,
+                "alt" => alt = true,
+                "shift" => shift = true,
+                other => return Err(format!("Unknown modifier \"{other}\" in hotkey \"{value}\"")),
+            }
+        }
+
+        Ok(Self::new(key, ctrl, alt, shift))
+    }
+}
+
+/// User rebindable key combinations, checked against every key press
+/// 
+/// Only the user's overrides are saved and get laid over the defaults on load,
+/// so later default bindings still show up. An override of `null` unbinds a default
+#[derive(Debug, Clone)]
+pub struct Hotkeys {
+    bindings: HashMap<KeyCombo, Action>,
+    overrides: HashMap<KeyCombo, Option<Action>>,
+}
+
+impl Hotkeys {
+    pub fn action(&self, combo: &KeyCombo) -> Option<Action> {
+        self.bindings.get(combo).copied()
+    }
+
+    fn with_overrides(overrides: HashMap<KeyCombo, Option<Action>>) -> Self {
+        let mut bindings = default_bindings();
+
+        for (combo, action) in &overrides {
+            match action {
+                Some(action) => bindings.insert(combo.clone(), *action),
+                None => bindings.remove(combo),
+            };
+        }
+
+        Self { bindings, overrides }
+    }
+}
+
+impl Default for Hotkeys {
+    fn default() -> Self {
+        Self::with_overrides(HashMap::new())
+    }
+}
+
+impl Serialize for Hotkeys {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.overrides.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Hotkeys {
+    /// Entries with a combo or action that doesn't parse are skipped instead of failing the whole state
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entries = HashMap::<String, serde_json::Value>::deserialize(deserializer)?;
+
+        let mut overrides = HashMap::new();
+
+        for (combo, action) in entries {
+            let combo = match KeyCombo::try_from(combo) {
+                Ok(combo) => combo,
+                Err(err) => {
+                    log::warn!("Skipping hotkey: {err}");
+                    continue;
+                },
+            };
+
+            match Option::<Action>::deserialize(action) {
+                Ok(action) => {
+                    overrides.insert(combo, action);
+                },
+                Err(err) => log::warn!("Skipping hotkey {combo}: {err}"),
+            }
+        }
+
+        Ok(Self::with_overrides(overrides))
+    }
+}
+
+fn default_bindings() -> HashMap<KeyCombo, Action> {
+    let mut bindings = HashMap::new();
+
+    bindings.insert(KeyCombo::new("F2", false, false, false), Action::ToggleLayoutModal);
+    bindings.insert(KeyCombo::new("f", false, false, false), Action::ToggleSoloPane);
+    bindings.insert(KeyCombo::new("l", true, false, false), Action::ToggleLayoutLock);
+    bindings.insert(KeyCombo::new("Tab", true, false, false), Action::CycleLayout);
+
+    bindings.insert(KeyCombo::new("Tab", false, false, false), Action::MoveFocus(FocusMove::Next));
+    bindings.insert(KeyCombo::new("Tab", false, false, true), Action::MoveFocus(FocusMove::Previous));
+    bindings.insert(KeyCombo::new("ArrowLeft", false, false, false), Action::MoveFocus(FocusMove::Left));
+    bindings.insert(KeyCombo::new("ArrowRight", false, false, false), Action::MoveFocus(FocusMove::Right));
+    bindings.insert(KeyCombo::new("ArrowUp", false, false, false), Action::MoveFocus(FocusMove::Up));
+    bindings.insert(KeyCombo::new("ArrowDown", false, false, false), Action::MoveFocus(FocusMove::Down));
+
+    for (index, layout) in LayoutId::ALL.iter().enumerate() {
+        let key = (index + 1).to_string();
+        bindings.insert(KeyCombo::new(&key, false, true, false), Action::SelectLayout(*layout));
+    }
+
+    for slot in 1..=QuickTickers::SLOTS as u8 {
+        bindings.insert(KeyCombo::new(&slot.to_string(), false, false, false), Action::QuickTicker(slot));
+    }
+
+    bindings
+}
+
+/// Tickers the number keys switch the focused pane to, slot 1 first
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct QuickTickers([Option<Ticker>; QuickTickers::SLOTS]);
+
+impl QuickTickers {
+    pub const SLOTS: usize = 9;
+
+    /// Ticker in a 1 based slot, `None` if it's empty or out of range
+    pub fn get(&self, slot: u8) -> Option<Ticker> {
+        self.0.get((slot as usize).checked_sub(1)?).copied().flatten()
+    }
+
+    pub fn set(&mut self, slot: u8, ticker: Option<Ticker>) {
+        if let Some(entry) = (slot as usize).checked_sub(1).and_then(|index| self.0.get_mut(index)) {
+            *entry = ticker;
+        }
+    }
+}
+
+impl Default for QuickTickers {
+    fn default() -> Self {
+        let mut slots = [None; Self::SLOTS];
+
+        for (slot, ticker) in slots.iter_mut().zip(Ticker::ALL) {
+            *slot = Some(ticker);
+        }
+
+        Self(slots)
+    }
+}
+
+/// Key press as a combo, `None` for plain characters and arrow keys a focused widget already took
+pub fn key_combo(event: &keyboard::Event, captured: bool) -> Option<KeyCombo> {
+    let keyboard::Event::KeyPressed { key, modifiers, .. } = event else {
+        return None;
+    };
+
+    if captured && KeyCombo::is_typing(key, *modifiers) {
+        return None;
+    }
+
+    KeyCombo::from_key(key, *modifiers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn combo(text: &str) -> KeyCombo {
+        KeyCombo::try_from(text.to_string()).expect("valid combo")
+    }
+
+    #[test]
+    fn overrides_lay_over_the_defaults() {
+        let hotkeys: Hotkeys = serde_json::from_str(r#"{ "F2": null, "Ctrl+K": "ToggleSoloPane" }"#).unwrap();
+
+        assert_eq!(hotkeys.action(&combo("F2")), None);
+        assert_eq!(hotkeys.action(&combo("Ctrl+K")), Some(Action::ToggleSoloPane));
+        // untouched defaults still apply
+        assert_eq!(hotkeys.action(&combo("Ctrl+L")), Some(Action::ToggleLayoutLock));
+    }
+
+    #[test]
+    fn only_overrides_are_saved() {
+        assert_eq!(serde_json::to_string(&Hotkeys::default()).unwrap(), "{}");
+
+        let hotkeys: Hotkeys = serde_json::from_str(r#"{ "Ctrl+K": "ToggleSoloPane" }"#).unwrap();
+        assert_eq!(serde_json::to_string(&hotkeys).unwrap(), r#"{"Ctrl+K":"ToggleSoloPane"}"#);
+    }
+
+    #[test]
+    fn bad_entries_are_skipped() {
+        let hotkeys: Hotkeys = serde_json::from_str(
+            r#"{ "Hyper+X": "ToggleSoloPane", "G": "NoSuchAction", "Ctrl+K": "ToggleSoloPane" }"#
+        ).unwrap();
+
+        assert_eq!(hotkeys.action(&combo("G")), None);
+        assert_eq!(hotkeys.action(&combo("Ctrl+K")), Some(Action::ToggleSoloPane));
+        assert_eq!(hotkeys.action(&combo("F2")), Some(Action::ToggleLayoutModal));
+    }
+}
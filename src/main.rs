@@ -5,22 +5,26 @@ mod charts;
 mod style;
 mod screen;
 mod logger;
+mod hotkeys;
+mod sound;
 
-use style::{ICON_FONT, ICON_BYTES, Icon};
+use style::{ICON_FONT, ICON_BYTES, DEFAULT_THEME, FOCUS_WIDTH_RANGE, FocusColor, FocusHighlight, Icon};
+use hotkeys::{Action, Hotkeys, KeyCombo, QuickTickers};
 
-use screen::{dashboard, Error, Notification};
+use screen::{dashboard, Error, Notification, Severity};
 use screen::dashboard::{
-    Dashboard,
-    pane::{self, SerializablePane}, Uuid,
+    Dashboard, ImbalanceAlert, PaneGaps, SoundAlert, DEFAULT_MAX_PANES, MAX_PANES_RANGE, PANE_SPACING_RANGE, RESIZE_HANDLE_RANGE,
+    pane::{self, SerializablePane, StarterDefaults}, Uuid,
     PaneContent, PaneSettings, PaneState, 
     SerializableDashboard, 
 };
-use data_providers::{binance, bybit, Exchange, MarketEvents, Ticker, Timeframe, StreamType};
+use data_providers::{Exchange, StreamKind, DEFAULT_CONNECT_TIMEOUT_SECS, MIN_CONNECT_TIMEOUT_SECS, MAX_CONNECT_TIMEOUT_SECS, DEFAULT_FRAME_DUMP_MINS, FRAME_DUMP_PATH, MarketEvent, TickMultiplier, Ticker, Timeframe, StreamType, StreamStats, MarketStream, TradeMode, Depth, Trade, Kline};
 
 use charts::footprint::FootprintChart;
 use charts::heatmap::HeatmapChart;
-use charts::candlestick::CandlestickChart;
+use charts::candlestick::{CandlestickChart, SessionHours, Sessions};
 use charts::timeandsales::TimeAndSales;
+use charts::{ChartPalette, CrosshairColor, CrosshairStyle, DEFAULT_LABEL_SIZE, MIN_LABEL_SIZE, MAX_LABEL_SIZE};
 
 use std::{collections::{HashMap, VecDeque}, vec};
 
@@ -30,7 +34,7 @@ use iced::{
     }, window::{self, Position}, Alignment, Color, Element, Length, Point, Size, Subscription, Task, Theme
 };
 use iced::widget::pane_grid::{self, Configuration};
-use iced::widget::{container, row, scrollable, text};
+use iced::widget::{container, row, scrollable, text, text_input};
 
 fn main() -> iced::Result {
     logger::setup(false, false).expect("Failed to initialize logger");
@@ -40,101 +44,12 @@ fn main() -> iced::Result {
             let mut de_state = SavedState {
                 layouts: HashMap::new(),
                 last_active_layout: state.last_active_layout,
+                recent_layouts: state.recent_layouts,
                 window_size: state.window_size,
-                window_position: state.window_position,
+                layout_names: state.names,
+                settings: state.settings.clamped(),
             };
 
-            fn configuration(pane: SerializablePane) -> Configuration<PaneState> {
-                match pane {
-                    SerializablePane::Split { axis, ratio, a, b } => Configuration::Split {
-                        axis: match axis {
-                            pane::Axis::Horizontal => pane_grid::Axis::Horizontal,
-                            pane::Axis::Vertical => pane_grid::Axis::Vertical,
-                        },
-                        ratio,
-                        a: Box::new(configuration(*a)),
-                        b: Box::new(configuration(*b)),
-                    },
-                    SerializablePane::Starter => {
-                        Configuration::Pane(PaneState::new(Uuid::new_v4(), vec![], PaneSettings::default()))
-                    },
-                    SerializablePane::CandlestickChart { stream_type, settings } => {
-                        let timeframe = settings.selected_timeframe
-                            .unwrap()
-                            .to_minutes();
-
-                        Configuration::Pane(
-                            PaneState::from_config(
-                                PaneContent::Candlestick(
-                                    CandlestickChart::new(
-                                        vec![], 
-                                        timeframe
-                                    )
-                                ),
-                                stream_type,
-                                settings
-                            )
-                        )
-                    },
-                    SerializablePane::FootprintChart { stream_type, settings } => {
-                        let ticksize = settings.tick_multiply
-                            .unwrap()
-                            .multiply_with_min_tick_size(
-                                settings.min_tick_size
-                                    .expect("No min tick size found, deleting dashboard_state.json probably fixes this")
-                            );
-                    
-                        let timeframe = settings.selected_timeframe
-                            .unwrap()
-                            .to_minutes();
-
-                        Configuration::Pane(
-                            PaneState::from_config(
-                                PaneContent::Footprint(
-                                    FootprintChart::new(
-                                        timeframe,
-                                        ticksize,
-                                        vec![], 
-                                        vec![]
-                                    )
-                                ),
-                                stream_type,
-                                settings
-                            )
-                        )
-                    },
-                    SerializablePane::HeatmapChart { stream_type, settings } => {
-                        let ticksize = settings.tick_multiply
-                            .unwrap()
-                            .multiply_with_min_tick_size(
-                                settings.min_tick_size
-                                    .expect("No min tick size found, deleting dashboard_state.json probably fixes this")
-                            );
-
-                        Configuration::Pane(
-                            PaneState::from_config(
-                                PaneContent::Heatmap(
-                                    HeatmapChart::new(ticksize)
-                                ),
-                                stream_type,
-                                settings
-                            )
-                        )
-                    },
-                    SerializablePane::TimeAndSales { stream_type, settings } => {
-                        Configuration::Pane(
-                            PaneState::from_config(
-                                PaneContent::TimeAndSales(
-                                    TimeAndSales::new()
-                                ),
-                                stream_type,
-                                settings
-                            )
-                        )
-                    },
-                }
-            }
-
             for (id, dashboard) in state.layouts.iter() {                
                 let dashboard = Dashboard::from_config(configuration(dashboard.pane.clone()));
 
@@ -151,27 +66,101 @@ fn main() -> iced::Result {
     };
 
     let window_size = saved_state.window_size.unwrap_or((1600.0, 900.0));
-    let window_position = saved_state.window_position.unwrap_or((0.0, 0.0));
 
+    // what `.centered()` did on the application builder, the daemon opens the main window itself
     let window_settings = window::Settings {
         size: iced::Size::new(window_size.0, window_size.1),
-        position: Position::Specific(Point::new(window_position.0, window_position.1)),
+        position: Position::Centered,
+        exit_on_close_request: false,
         ..Default::default()
     };
 
-    iced::application(
-        "Iced Trade",
+    // `iced::application` drives a single window, popped out panes need windows of their own
+    iced::daemon(
+        State::title,
         State::update,
         State::view,
     )
     .subscription(State::subscription)
-    .theme(|_| Theme::KanagawaDragon)
+    .theme(|state, _| state.settings.theme.clone())
     .antialiasing(true)
-    .window(window_settings)
-    .centered()   
+    // compiled into the binary, so there's no file that could go missing at runtime
     .font(ICON_BYTES)
-    .exit_on_close_request(false)
-    .run_with(move || State::new(saved_state))
+    .run_with(move || State::new(saved_state, window_settings))
+}
+
+/// Rebuilds a pane grid configuration from its serialized form
+fn configuration(pane: SerializablePane) -> Configuration<PaneState> {
+    match pane {
+        SerializablePane::Split { axis, ratio, a, b } => Configuration::Split {
+            axis: match axis {
+                pane::Axis::Horizontal => pane_grid::Axis::Horizontal,
+                pane::Axis::Vertical => pane_grid::Axis::Vertical,
+            },
+            ratio,
+            a: Box::new(configuration(*a)),
+            b: Box::new(configuration(*b)),
+        },
+        SerializablePane::Starter => {
+            Configuration::Pane(PaneState::new(Uuid::new_v4(), vec![], PaneSettings::default()))
+        },
+        SerializablePane::CandlestickChart { stream_type, settings } => {
+            let timeframe = settings.selected_timeframe
+                .map_or(1, |timeframe| timeframe.to_minutes());
+
+            Configuration::Pane(
+                PaneState::from_config(
+                    PaneContent::Candlestick(CandlestickChart::new(vec![], timeframe)),
+                    stream_type,
+                    settings
+                )
+            )
+        },
+        SerializablePane::FootprintChart { stream_type, settings } => {
+            let ticksize = restored_tick_size(&settings);
+        
+            let timeframe = settings.selected_timeframe
+                .map_or(1, |timeframe| timeframe.to_minutes());
+
+            Configuration::Pane(
+                PaneState::from_config(
+                    PaneContent::Footprint(FootprintChart::new(timeframe, ticksize, vec![], vec![])),
+                    stream_type,
+                    settings
+                )
+            )
+        },
+        SerializablePane::HeatmapChart { stream_type, settings } => {
+            let ticksize = restored_tick_size(&settings);
+
+            Configuration::Pane(
+                PaneState::from_config(
+                    PaneContent::Heatmap(HeatmapChart::new(ticksize)),
+                    stream_type,
+                    settings
+                )
+            )
+        },
+        SerializablePane::TimeAndSales { stream_type, settings } => {
+            Configuration::Pane(
+                PaneState::from_config(
+                    PaneContent::TimeAndSales(TimeAndSales::new()),
+                    stream_type,
+                    settings
+                )
+            )
+        },
+    }
+}
+
+/// Ticksize a restored chart starts on, a placeholder while the min tick hasn't been fetched yet,
+/// the chart gets regrouped once it arrives with the layout's fetches
+fn restored_tick_size(settings: &PaneSettings) -> f32 {
+    settings.min_tick_size.map_or(1.0, |min_tick_size| {
+        settings.tick_multiply
+            .unwrap_or(TickMultiplier(1))
+            .multiply_with_min_tick_size(min_tick_size)
+    })
 }
 
 #[derive(Debug, Clone)]
@@ -184,7 +173,9 @@ pub enum Message {
     ShowLayoutModal,
     HideLayoutModal,
 
-    MarketWsEvent(MarketEvents),
+    WindowReady,
+
+    MarketWsEvent(StreamKind, MarketEvent),
     
     Event(Event),
     SaveAndExit(window::Id, Option<Size>, Option<Point>),
@@ -192,109 +183,713 @@ pub enum Message {
     ToggleLayoutLock,
     ResetCurrentLayout,
     LayoutSelected(LayoutId),
+    CompareTimeframesChanged((Timeframe, Timeframe)),
+    SetDefaultPane(Option<pane::PanePreset>),
+    ThemeSelected(Theme),
+    CrosshairStyleChanged(CrosshairStyle),
+    PaletteSelected(ChartPalette),
+    PriceRulerToggled(bool),
+    CrosshairSyncToggled(bool),
+    SharedVolumeScaleToggled(bool),
+    LabelSizeChanged(f32),
+    ImbalanceAlertChanged(ImbalanceAlert),
+    SoundAlertChanged(SoundAlert),
+    ResumeReconnectToggled(bool),
+    BackfillOnReconnectToggled(bool),
+    OfflineBannerToggled(bool),
+    WarmLayoutsToggled(bool),
+    PoolMarketStreamsToggled(bool),
+    MinimizedChanged(bool),
+    IdleWhenMinimizedToggled(bool),
+    TickRuleToggled(bool),
+    MaxPanesChanged(u32),
+    PaneGapsChanged(PaneGaps),
+    FocusHighlightChanged(FocusHighlight),
+    ConfirmCloseToggled(bool),
+    TickerSwitchResetToggled(bool),
+    MemoryBudgetChanged(u32),
+    StarterDefaultsChanged(StarterDefaults),
+    QuickTickerChanged(u8, Option<Ticker>),
+    SessionsChanged(Sessions),
+    ConnectTimeoutChanged(u32),
+    ReconnectGraceChanged(u32),
+    MinSeverityChanged(Severity),
+    /// Redraws bar close countdowns
+    CountdownTick,
+    /// Grace period of the outage that started at the given time ran out
+    ReconnectGraceElapsed(Exchange, StreamKind, i64),
+    StreamStatsToggled(bool),
+    DebugOverlayToggled(bool),
+    FrameDumpToggled(bool),
+    FrameDumpMinsChanged(u32),
+    CopyStreamConfig,
+    SuspendCheck,
+    ReconnectStreams,
+    LayoutRenamed(String),
+    CopyLayoutTo(LayoutId),
+    ConfirmCopyLayout,
+    CancelCopyLayout,
+    ConnectivityChecked(Exchange, Result<(), String>),
     Dashboard(dashboard::Message),
 }
 
+/// Upper bound on how long startup waits for the first frame before fetching layout data
+const STARTUP_FETCH_FALLBACK_MS: u64 = 3000;
+
+/// How often the wall clock is sampled to notice the machine was suspended
+const SUSPEND_CHECK_INTERVAL_SECS: u64 = 5;
+/// A gap between samples longer than this is treated as a resume from sleep
+const SUSPEND_GAP_THRESHOLD_MS: i64 = 30_000;
+
+/// Stream stats not refreshed within this are from streams that went away
+const STREAM_STATS_STALE_MS: i64 = 3000;
+
+/// Share of each heatmap's oldest buckets evicted per pass once over the memory budget
+const MEMORY_EVICT_SHARE: f32 = 0.25;
+
+const DEFAULT_RECONNECT_GRACE_SECS: u32 = 3;
+const MAX_RECONNECT_GRACE_SECS: u32 = 30;
+
 struct State {
     layouts: HashMap<LayoutId, Dashboard>,
     last_active_layout: LayoutId,
+    /// Layouts in most recently used order, the active one first
+    recent_layouts: Vec<LayoutId>,
     show_layout_modal: bool,
+    /// Copy target that already has panes, waiting for confirmation to overwrite it
+    pending_layout_copy: Option<LayoutId>,
     exchange_latency: Option<(u32, u32)>,
     feed_latency_cache: VecDeque<data_providers::FeedLatency>,
     notification: Option<Notification>,
+    compare_timeframes: (Timeframe, Timeframe),
+    window_ready: bool,
+    layout_names: HashMap<LayoutId, String>,
+    settings: AppSettings,
+    /// Trade time of the last tone played, one cooldown across every layout and stream
+    last_sound: Option<i64>,
+    /// Start of each ongoing outage by the kind of connection it hit, and whether it was notified
+    disconnects: HashMap<(Exchange, StreamKind), (i64, bool)>,
+    last_suspend_check: i64,
+    main_window: window::Id,
+    minimized: bool,
+    show_stream_stats: bool,
+    /// Latest message rates per websocket, with the time they were received
+    stream_stats: HashMap<(Exchange, String), (StreamStats, i64)>,
+    /// How long a raw frame dump runs once started
+    frame_dump_mins: u32,
+    debug_overlay: bool,
+    /// Windows of the active layout's popped out panes
+    popouts: HashMap<window::Id, Uuid>,
 }
 
 impl State {
-    fn new(saved_state: SavedState) -> (Self, Task<Message>) {
+    fn new(saved_state: SavedState, window_settings: window::Settings) -> (Self, Task<Message>) {
         let mut tasks = vec![];
 
+        let (main_window, open_main_window) = window::open(window_settings);
+        tasks.push(open_main_window.discard());
+
         let last_active_layout = saved_state.last_active_layout;
 
-        let wait_and_fetch = Task::perform(
-            async { tokio::time::sleep(tokio::time::Duration::from_millis(200)).await; },
-            move |_| Message::LayoutSelected(last_active_layout)
+        let mut recent_layouts = saved_state.recent_layouts.clone();
+        recent_layouts.retain(|id| *id != last_active_layout);
+        recent_layouts.insert(0, last_active_layout);
+
+        let mut saved_state = saved_state;
+        for dashboard in saved_state.layouts.values_mut() {
+            saved_state.settings.apply_to(dashboard);
+        }
+
+        data_providers::CONNECT_TIMEOUT_SECS.store(saved_state.settings.connect_timeout_secs, std::sync::atomic::Ordering::Relaxed);
+
+        // layout fetches normally start on the first rendered frame,
+        // this only covers the case where no frame event reaches us
+        let fallback_fetch = Task::perform(
+            async { tokio::time::sleep(tokio::time::Duration::from_millis(STARTUP_FETCH_FALLBACK_MS)).await; },
+            move |_| Message::WindowReady
         );
-        tasks.push(wait_and_fetch);
+        tasks.push(fallback_fetch);
 
-        (
-            Self { 
+        for exchange in Exchange::ALL {
+            tasks.push(Task::perform(
+                data_providers::check_connectivity(exchange),
+                move |result| Message::ConnectivityChecked(exchange, result.map_err(|err| err.to_string()))
+            ));
+        }
+
+        let mut state = Self { 
                 layouts: saved_state.layouts,
                 last_active_layout,
+                recent_layouts,
                 show_layout_modal: false,
+                pending_layout_copy: None,
                 exchange_latency: None,
                 feed_latency_cache: VecDeque::new(),
                 notification: None,
-            },
-            Task::batch(tasks)
+                compare_timeframes: (Timeframe::M1, Timeframe::M15),
+                window_ready: false,
+                layout_names: saved_state.layout_names,
+                settings: saved_state.settings,
+                last_sound: None,
+                disconnects: HashMap::new(),
+                last_suspend_check: chrono::Utc::now().timestamp_millis(),
+                main_window,
+                minimized: false,
+                show_stream_stats: false,
+                stream_stats: HashMap::new(),
+                frame_dump_mins: DEFAULT_FRAME_DUMP_MINS,
+                debug_overlay: false,
+                popouts: HashMap::new(),
+            };
+
+        tasks.push(state.sync_popouts());
+
+        (state, Task::batch(tasks))
+    }
+
+    fn title(&self, window: window::Id) -> String {
+        self.popouts.get(&window)
+            .and_then(|pane_id| self.get_dashboard().get_pane_by_id(*pane_id))
+            .and_then(|pane_state| pane_state.preset())
+            .map_or("Iced Trade".to_string(), |preset| format!("Iced Trade - {preset}"))
+    }
+
+    /// Opens windows for panes that were popped out and closes the ones
+    /// whose pane got docked back or removed from the active layout
+    fn sync_popouts(&mut self) -> Task<Message> {
+        let popped_out = self.get_dashboard().popped_out_panes();
+
+        let mut tasks = vec![];
+
+        self.popouts.retain(|window, pane_id| {
+            let keep = popped_out.iter().any(|(id, _)| id == pane_id);
+            if !keep {
+                tasks.push(window::close(*window));
+            }
+            keep
+        });
+
+        for (pane_id, popout) in popped_out {
+            if self.popouts.values().any(|id| *id == pane_id) {
+                continue;
+            }
+
+            let (window, open_window) = window::open(window::Settings {
+                size: Size::new(popout.size.0, popout.size.1),
+                position: popout.position
+                    .map_or(Position::Default, |(x, y)| Position::Specific(Point::new(x, y))),
+                exit_on_close_request: false,
+                ..Default::default()
+            });
+
+            self.popouts.insert(window, pane_id);
+            tasks.push(open_window.discard());
+        }
+
+        Task::batch(tasks)
+    }
+
+    /// Starts an outage of one kind of connection, it's only notified if it doesn't
+    /// reconnect within the grace period
+    fn stream_disconnected(&mut self, exchange: Exchange, kind: StreamKind) -> Task<Message> {
+        // retries of an outage already being waited on
+        if self.disconnects.contains_key(&(exchange, kind)) {
+            return Task::none();
+        }
+
+        let since = chrono::Utc::now().timestamp_millis();
+        self.disconnects.insert((exchange, kind), (since, false));
+
+        let grace_secs = self.settings.reconnect_grace_secs;
+
+        Task::perform(
+            async move { tokio::time::sleep(tokio::time::Duration::from_secs(grace_secs.into())).await },
+            move |_| Message::ReconnectGraceElapsed(exchange, kind, since)
+        )
+    }
+
+    fn stream_connected(&mut self, exchange: Exchange, kind: StreamKind) -> Task<Message> {
+        let Some((since, notified)) = self.disconnects.remove(&(exchange, kind)) else {
+            return Task::none();
+        };
+
+        let backfill = if self.settings.backfill_on_reconnect {
+            self.get_dashboard().backfill_klines(exchange).map(Message::Dashboard)
+        } else {
+            Task::none()
+        };
+
+        if notified {
+            Task::batch(vec![
+                Task::perform(
+                    async {},
+                    move |_| Message::Notification(Notification::Info(format!("Reconnected {kind} streams to {exchange}")))
+                ),
+                backfill,
+            ])
+        } else {
+            let outage_ms = chrono::Utc::now().timestamp_millis() - since;
+            log::info!("{exchange} {kind} streams reconnected within the grace period, after {outage_ms}ms");
+
+            backfill
+        }
+    }
+
+    /// Exchanges down past the grace period, empty unless it's every connection in use
+    fn offline_exchanges(&self) -> Vec<Exchange> {
+        let streamed: Vec<(Exchange, StreamKind)> = Exchange::ALL.iter()
+            .flat_map(|exchange| {
+                let (connections, kline_streams) = self.stream_connections(*exchange);
+
+                [
+                    (!connections.is_empty()).then_some((*exchange, StreamKind::DepthAndTrades)),
+                    (!kline_streams.is_empty()).then_some((*exchange, StreamKind::Kline)),
+                ]
+            })
+            .flatten()
+            .collect();
+
+        if !streamed.iter().all(|connection| matches!(self.disconnects.get(connection), Some((_, true)))) {
+            return vec![];
+        }
+
+        let mut exchanges: Vec<Exchange> = streamed.into_iter().map(|(exchange, _)| exchange).collect();
+        exchanges.dedup();
+
+        exchanges
+    }
+
+    fn view_offline_banner(&self, exchanges: &[Exchange]) -> Element<'_, Message> {
+        let since = exchanges.iter()
+            .flat_map(|exchange| [StreamKind::DepthAndTrades, StreamKind::Kline].map(|kind| (*exchange, kind)))
+            .filter_map(|connection| self.disconnects.get(&connection).map(|(since, _)| *since))
+            .min()
+            .and_then(chrono::DateTime::from_timestamp_millis)
+            .map_or(String::new(), |since| since.with_timezone(&chrono::Local).format(" since %H:%M:%S").to_string());
+
+        let details = exchanges.iter()
+            .map(|exchange| exchange.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        container(
+            Text::new(format!("Offline \u{2014} no market data{since}, reconnecting to {details}...")).size(14)
+        )
+        .padding(4)
+        .width(Length::Fill)
+        .style(style::notification)
+        .into()
+    }
+
+    fn view_stream_stats(&self) -> Element<'_, Message> {
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let mut active_stats: Vec<_> = self.stream_stats.iter()
+            .filter(|(_, (_, received_at))| now - received_at < STREAM_STATS_STALE_MS)
+            .collect();
+        active_stats.sort_by(|((ex_a, label_a), _), ((ex_b, label_b), _)| {
+            (ex_a.to_string(), label_a).cmp(&(ex_b.to_string(), label_b))
+        });
+
+        let mut stats_row = Row::new()
+            .spacing(16)
+            .align_y(Alignment::Center);
+
+        if active_stats.is_empty() {
+            stats_row = stats_row.push(Text::new("Waiting for stream data...").size(12));
+        }
+
+        for ((exchange, label), (stats, _)) in active_stats {
+            stats_row = stats_row.push(
+                Text::new(format!(
+                    "{exchange} {label}: {:.0} msg/s, {:.1} KB/s",
+                    stats.msgs_per_sec, stats.bytes_per_sec / 1024.0
+                )).size(12)
+            );
+        }
+
+        container(stats_row)
+            .padding(4)
+            .style(style::notification)
+            .into()
+    }
+
+    fn close_popouts(&mut self) -> Task<Message> {
+        Task::batch(
+            self.popouts.drain()
+                .map(|(window, _)| window::close(window))
+                .collect::<Vec<_>>()
         )
     }
 
     fn update(&mut self, message: Message) -> Task<Message> {
         match message {
-            Message::MarketWsEvent(event) => {
-                let dashboard = self.get_mut_dashboard();
+            Message::MarketWsEvent(kind, MarketEvent(exchange, event)) => match event {
+                data_providers::Event::Connected => {
+                    log::info!("a {kind} stream connected to {exchange} WS");
 
-                match event {
-                    MarketEvents::Binance(event) => match event {
-                        binance::market_data::Event::Connected(connection) => {
-                            log::info!("a stream connected to Binance WS: {connection:?}");
-                        }
-                        binance::market_data::Event::Disconnected(event) => {
-                            log::info!("a stream disconnected from Binance WS: {event:?}");
-                        }
-                        binance::market_data::Event::DepthReceived(ticker, feed_latency, depth_update_t, depth, trades_buffer) => {                            
-                            let stream_type = StreamType::DepthAndTrades {
-                                exchange: Exchange::BinanceFutures,
-                                ticker,
-                            };
-                            
-                            if let Err(err) = dashboard.update_depth_and_trades(stream_type, depth_update_t, depth, trades_buffer) {
-                                log::error!("{err}, {stream_type:?}");
-                            }
-                        }
-                        binance::market_data::Event::KlineReceived(ticker, kline, timeframe) => {
-                            let stream_type = StreamType::Kline {
-                                exchange: Exchange::BinanceFutures,
-                                ticker,
-                                timeframe,
-                            };
-
-                            if let Err(err) = dashboard.update_latest_klines(&stream_type, &kline) {
-                                log::error!("{err}, {stream_type:?}");
-                            }
-                        }
-                    },
-                    MarketEvents::Bybit(event) => match event {
-                        bybit::market_data::Event::Connected(_) => {
-                            log::info!("a stream connected to Bybit WS");
-                        }
-                        bybit::market_data::Event::Disconnected(event) => {
-                            log::info!("a stream disconnected from Bybit WS: {event:?}");
-                        }
-                        bybit::market_data::Event::DepthReceived(ticker, feed_latency, depth_update_t, depth, trades_buffer) => {
-                            let stream_type = StreamType::DepthAndTrades {
-                                exchange: Exchange::BybitLinear,
-                                ticker,
-                            };
-                            
-                            if let Err(err) = dashboard.update_depth_and_trades(stream_type, depth_update_t, depth, trades_buffer) {
-                                log::error!("{err}, {stream_type:?}");
-                            }
-                        }
-                        bybit::market_data::Event::KlineReceived(ticker, kline, timeframe) => {
-                            let stream_type = StreamType::Kline {
-                                exchange: Exchange::BybitLinear,
-                                ticker,
-                                timeframe,
-                            };
-
-                            if let Err(err) = dashboard.update_latest_klines(&stream_type, &kline) {
-                                log::error!("{err}, {stream_type:?}");
-                            }
+                    self.stream_connected(exchange, kind)
+                },
+                data_providers::Event::Disconnected(reason) => {
+                    log::info!("a {kind} stream disconnected from {exchange} WS: {reason:?}");
+
+                    self.stream_disconnected(exchange, kind)
+                },
+                data_providers::Event::DepthReceived(ticker, _feed_latency, depth_update_t, depth, trades_buffer) => {
+                    let stream_type = StreamType::DepthAndTrades { exchange, ticker };
+
+                    self.update_depth_and_trades(stream_type, depth_update_t, depth, trades_buffer)
+                },
+                data_providers::Event::KlineReceived(ticker, kline, timeframe) => {
+                    let stream_type = StreamType::Kline { exchange, ticker, timeframe };
+
+                    self.update_latest_klines(stream_type, &kline);
+
+                    Task::none()
+                },
+                data_providers::Event::Stats(label, stats) => {
+                    self.stream_stats.insert(
+                        (exchange, label), 
+                        (stats, chrono::Utc::now().timestamp_millis())
+                    );
+
+                    Task::none()
+                },
+            },
+            Message::LayoutRenamed(name) => {
+                if name.trim().is_empty() {
+                    self.layout_names.remove(&self.last_active_layout);
+                } else {
+                    self.layout_names.insert(self.last_active_layout, name);
+                }
+
+                Task::none()
+            },
+            Message::ThemeSelected(theme) => {
+                self.settings.theme = theme;
+
+                Task::none()
+            },
+            Message::PaletteSelected(palette) => {
+                for dashboard in self.layouts.values_mut() {
+                    dashboard.set_palette(palette);
+                }
+                self.settings.palette = palette;
+
+                Task::none()
+            },
+            Message::CrosshairStyleChanged(crosshair_style) => {
+                for dashboard in self.layouts.values_mut() {
+                    dashboard.set_crosshair_style(crosshair_style);
+                }
+                self.settings.crosshair_style = crosshair_style;
+
+                Task::none()
+            },
+            Message::CrosshairSyncToggled(enabled) => {
+                for dashboard in self.layouts.values_mut() {
+                    dashboard.set_crosshair_sync(enabled);
+                }
+                self.settings.crosshair_sync = enabled;
+
+                Task::none()
+            },
+            Message::SharedVolumeScaleToggled(enabled) => {
+                for dashboard in self.layouts.values_mut() {
+                    dashboard.set_shared_volume_scale(enabled);
+                }
+                self.settings.shared_volume_scale = enabled;
+
+                Task::none()
+            },
+            Message::PriceRulerToggled(enabled) => {
+                for dashboard in self.layouts.values_mut() {
+                    dashboard.set_price_ruler(enabled);
+                }
+                self.settings.price_ruler = enabled;
+
+                Task::none()
+            },
+            Message::LabelSizeChanged(label_size) => {
+                for dashboard in self.layouts.values_mut() {
+                    dashboard.set_label_size(label_size);
+                }
+                self.settings.label_size = label_size;
+
+                Task::none()
+            },
+            Message::ResumeReconnectToggled(enabled) => {
+                self.settings.resume_reconnect = enabled;
+
+                Task::none()
+            },
+            Message::BackfillOnReconnectToggled(enabled) => {
+                self.settings.backfill_on_reconnect = enabled;
+
+                Task::none()
+            },
+            Message::OfflineBannerToggled(enabled) => {
+                self.settings.offline_banner = enabled;
+
+                Task::none()
+            },
+            Message::WarmLayoutsToggled(enabled) => {
+                self.settings.warm_layouts = enabled;
+
+                if !enabled {
+                    self.teardown_background_streams();
+                }
+
+                Task::none()
+            },
+            Message::PoolMarketStreamsToggled(enabled) => {
+                self.settings.pool_market_streams = enabled;
+
+                Task::none()
+            },
+            Message::TickRuleToggled(enabled) => {
+                self.settings.tick_rule = enabled;
+
+                Task::none()
+            },
+            Message::MinimizedChanged(minimized) => {
+                self.set_minimized(minimized);
+
+                Task::none()
+            },
+            Message::IdleWhenMinimizedToggled(enabled) => {
+                self.settings.idle_when_minimized = enabled;
+
+                if !enabled {
+                    for dashboard in self.layouts.values_mut() {
+                        dashboard.set_idle(false);
+                    }
+                } else if self.minimized {
+                    for dashboard in self.layouts.values_mut() {
+                        dashboard.set_idle(true);
+                    }
+                }
+
+                Task::none()
+            },
+            Message::MaxPanesChanged(max_panes) => {
+                for dashboard in self.layouts.values_mut() {
+                    dashboard.set_max_panes(max_panes);
+                }
+                self.settings.max_panes = max_panes;
+
+                Task::none()
+            },
+            Message::StarterDefaultsChanged(starter_defaults) => {
+                for dashboard in self.layouts.values_mut() {
+                    dashboard.set_starter_defaults(starter_defaults);
+                }
+                self.settings.starter_defaults = starter_defaults;
+
+                Task::none()
+            },
+            Message::CountdownTick => Task::none(),
+            Message::MinSeverityChanged(min_severity) => {
+                self.settings.min_severity = min_severity;
+
+                Task::none()
+            },
+            Message::QuickTickerChanged(slot, ticker) => {
+                self.settings.quick_tickers.set(slot, ticker);
+
+                Task::none()
+            },
+            Message::SessionsChanged(sessions) => {
+                for dashboard in self.layouts.values_mut() {
+                    dashboard.set_sessions(sessions);
+                }
+                self.settings.sessions = sessions;
+
+                Task::none()
+            },
+            Message::MemoryBudgetChanged(memory_budget_mb) => {
+                self.settings.memory_budget_mb = memory_budget_mb;
+
+                Task::none()
+            },
+            Message::TickerSwitchResetToggled(enabled) => {
+                for dashboard in self.layouts.values_mut() {
+                    dashboard.set_ticker_switch_reset(enabled);
+                }
+                self.settings.ticker_switch_reset = enabled;
+
+                Task::none()
+            },
+            Message::ConfirmCloseToggled(enabled) => {
+                for dashboard in self.layouts.values_mut() {
+                    dashboard.set_confirm_close(enabled);
+                }
+                self.settings.confirm_close = enabled;
+
+                Task::none()
+            },
+            Message::PaneGapsChanged(pane_gaps) => {
+                for dashboard in self.layouts.values_mut() {
+                    dashboard.set_pane_gaps(pane_gaps);
+                }
+                self.settings.pane_gaps = pane_gaps;
+
+                Task::none()
+            },
+            Message::FocusHighlightChanged(focus_highlight) => {
+                for dashboard in self.layouts.values_mut() {
+                    dashboard.set_focus_highlight(focus_highlight);
+                }
+                self.settings.focus_highlight = focus_highlight;
+
+                Task::none()
+            },
+            Message::ConnectTimeoutChanged(secs) => {
+                self.settings.connect_timeout_secs = secs;
+
+                data_providers::CONNECT_TIMEOUT_SECS.store(secs, std::sync::atomic::Ordering::Relaxed);
+
+                Task::none()
+            },
+            Message::ReconnectGraceChanged(secs) => {
+                self.settings.reconnect_grace_secs = secs;
+
+                Task::none()
+            },
+            Message::ReconnectGraceElapsed(exchange, kind, since) => {
+                match self.disconnects.get_mut(&(exchange, kind)) {
+                    Some((outage_start, notified)) if *outage_start == since => {
+                        *notified = true;
+
+                        // the banner already says it, no need for a warning per exchange
+                        if self.settings.offline_banner && !self.offline_exchanges().is_empty() {
+                            return Task::none();
                         }
+
+                        Task::perform(
+                            async {},
+                            move |_| Message::Notification(
+                                Notification::Warn(format!("Lost {kind} streams of {exchange}, reconnecting..."))
+                            )
+                        )
                     },
+                    _ => Task::none(),
+                }
+            },
+            Message::StreamStatsToggled(enabled) => {
+                self.show_stream_stats = enabled;
+                self.stream_stats.clear();
+
+                data_providers::STREAM_STATS_ENABLED.store(enabled, std::sync::atomic::Ordering::Relaxed);
+
+                Task::none()
+            },
+            Message::DebugOverlayToggled(enabled) => {
+                for dashboard in self.layouts.values_mut() {
+                    dashboard.set_debug_overlay(enabled);
+                }
+                self.debug_overlay = enabled;
+
+                Task::none()
+            },
+            Message::CopyStreamConfig => {
+                let config = match self.get_dashboard().stream_config() {
+                    Ok(config) => config,
+                    Err(err) => return Task::perform(
+                        async { err },
+                        move |err: Error| Message::ErrorOccurred(err)
+                    ),
+                };
+
+                let layout = match self.layout_names.get(&self.last_active_layout) {
+                    Some(name) => format!("{} ({name})", self.last_active_layout),
+                    None => self.last_active_layout.to_string(),
+                };
+
+                Task::batch(vec![
+                    iced::clipboard::write(format!(
+                        "iced-trade {}, {layout}\n\n{config}", 
+                        env!("CARGO_PKG_VERSION")
+                    )),
+                    Task::perform(
+                        async {},
+                        move |_| Message::Notification(
+                            Notification::Info("Stream config copied to clipboard".to_string())
+                        )
+                    ),
+                ])
+            },
+            Message::FrameDumpToggled(enabled) => {
+                if !enabled {
+                    data_providers::stop_frame_dump();
+                } else if let Err(err) = data_providers::start_frame_dump(self.frame_dump_mins) {
+                    log::error!("Failed to create {FRAME_DUMP_PATH}: {err}");
+
+                    return Task::perform(
+                        async {},
+                        move |_| Message::Notification(
+                            Notification::Error(format!("Failed to start frame dump: {err}"))
+                        )
+                    );
+                }
+
+                Task::none()
+            },
+            Message::FrameDumpMinsChanged(mins) => {
+                self.frame_dump_mins = mins;
+
+                Task::none()
+            },
+            Message::SuspendCheck => {
+                let now = chrono::Utc::now().timestamp_millis();
+                let gap = now - self.last_suspend_check;
+                self.last_suspend_check = now;
+
+                if !self.settings.resume_reconnect || gap < SUSPEND_GAP_THRESHOLD_MS {
+                    return Task::none();
                 }
 
+                log::warn!("Resumed after ~{}s of suspension, reconnecting streams", gap / 1000);
+
+                // dropping the streams tears their subscriptions down, they get rebuilt shortly after
+                self.get_mut_dashboard().pane_streams.clear();
+                // background layouts reconnect once they're opened again
+                self.teardown_background_streams();
+
+                Task::perform(
+                    async { tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await },
+                    move |_| Message::ReconnectStreams
+                )
+            },
+            Message::ReconnectStreams => {
+                self.get_mut_dashboard()
+                    .layout_changed()
+                    .map(Message::Dashboard)
+            },
+            Message::SoundAlertChanged(sound_alert) => {
+                self.settings.sound_alert = sound_alert;
+
+                Task::none()
+            },
+            Message::ImbalanceAlertChanged(imbalance_alert) => {
+                for dashboard in self.layouts.values_mut() {
+                    dashboard.set_imbalance_alert(imbalance_alert);
+                }
+                self.settings.imbalance_alert = imbalance_alert;
+
+                Task::none()
+            },
+            Message::SetDefaultPane(preset) => {
+                for dashboard in self.layouts.values_mut() {
+                    dashboard.default_pane = preset.clone();
+                }
+                self.settings.default_pane = preset;
+
+                Task::none()
+            },
+            Message::CompareTimeframesChanged(timeframes) => {
+                self.compare_timeframes = timeframes;
+
                 Task::none()
             },
             Message::ToggleLayoutLock => {
@@ -311,8 +906,16 @@ impl State {
                 
                 Task::none()
             },
-            Message::Event(event) => {
-                if let Event::CloseRequested(window) = event {     
+            Message::Event(event) => match event {
+                Event::CloseRequested(window) if self.popouts.contains_key(&window) => {
+                    // closing a popped out window docks its pane back into the grid
+                    let pane_id = self.popouts[&window];
+
+                    self.update(Message::Dashboard(
+                        dashboard::Message::Pane(pane::Message::Dock(pane_id))
+                    ))
+                },
+                Event::CloseRequested(window) => {
                     enum Either<L, R> {
                         Left(L),
                         Right(R),
@@ -334,9 +937,106 @@ impl State {
                         }
                         Message::SaveAndExit(window, size, position)
                     })
-                } else {
+                },
+                Event::WindowMoved(window, position) => {
+                    if let Some(pane_id) = self.popouts.get(&window).copied() {
+                        self.get_mut_dashboard().update_popout_window(pane_id, None, Some(position));
+                    }
                     Task::none()
-                }
+                },
+                Event::WindowResized(window, size) => {
+                    if let Some(pane_id) = self.popouts.get(&window).copied() {
+                        self.get_mut_dashboard().update_popout_window(pane_id, Some(size), None);
+                    } else if window == self.main_window {
+                        // some platforms report minimizing only as a resize to nothing
+                        self.set_minimized(size.width == 0.0 || size.height == 0.0);
+                    }
+                    Task::none()
+                },
+                Event::WindowFocused(window) => {
+                    if window == self.main_window {
+                        self.set_minimized(false);
+                    }
+                    Task::none()
+                },
+                Event::WindowUnfocused(window) => {
+                    if window == self.main_window {
+                        window::get_minimized(window)
+                            .map(|minimized| Message::MinimizedChanged(minimized.unwrap_or(false)))
+                    } else {
+                        Task::none()
+                    }
+                },
+                Event::ToggleSoloPane => {
+                    let dashboard = self.get_dashboard();
+
+                    let pane_message = if dashboard.panes.maximized().is_some() {
+                        pane::Message::Restore
+                    } else if let Some(focus) = dashboard.focus {
+                        pane::Message::MaximizePane(focus)
+                    } else {
+                        return Task::none();
+                    };
+
+                    self.update(Message::Dashboard(dashboard::Message::Pane(pane_message)))
+                },
+                Event::KeyPressed(combo) => {
+                    let Some(action) = self.settings.hotkeys.action(&combo) else {
+                        return Task::none();
+                    };
+
+                    match action {
+                        Action::ToggleLayoutModal => self.update(Message::Event(Event::ToggleLayoutModal)),
+                        Action::ToggleSoloPane => self.update(Message::Event(Event::ToggleSoloPane)),
+                        Action::ToggleLayoutLock => self.update(Message::ToggleLayoutLock),
+                        Action::SelectLayout(layout_id) => {
+                            if layout_id == self.last_active_layout {
+                                Task::none()
+                            } else {
+                                self.update(Message::LayoutSelected(layout_id))
+                            }
+                        },
+                        Action::CycleLayout => self.update(Message::Event(Event::CycleLayout)),
+                        Action::QuickTicker(slot) => self.update(Message::Event(Event::QuickTicker(slot))),
+                        Action::MoveFocus(focus_move) => self.update(Message::Event(Event::MoveFocus(focus_move))),
+                    }
+                },
+                Event::CycleLayout => {
+                    // the active layout is always first, so the second is the one used before it
+                    match self.recent_layouts.get(1) {
+                        Some(layout_id) => self.update(Message::LayoutSelected(*layout_id)),
+                        None => Task::none(),
+                    }
+                },
+                Event::QuickTicker(slot) => {
+                    let Some(ticker) = self.settings.quick_tickers.get(slot) else {
+                        return Task::none();
+                    };
+
+                    let dashboard = self.get_dashboard();
+
+                    let Some(pane_id) = dashboard.focus
+                        .and_then(|pane| dashboard.panes.get(pane))
+                        .map(|pane_state| pane_state.id) 
+                    else {
+                        return Task::none();
+                    };
+
+                    self.update(Message::Dashboard(dashboard::Message::Pane(
+                        pane::Message::TickerSelected(ticker, pane_id)
+                    )))
+                },
+                Event::MoveFocus(focus_move) => {
+                    self.update(Message::Dashboard(dashboard::Message::MoveFocus(focus_move)))
+                },
+                Event::ToggleLayoutModal => {
+                    if self.show_layout_modal {
+                        self.update(Message::HideLayoutModal)
+                    } else {
+                        self.update(Message::ShowLayoutModal)
+                    }
+                },
+                _ => Task::none(),
             },
             Message::SaveAndExit(window, size, position) => {
                 let mut layouts = HashMap::new();
@@ -350,8 +1050,11 @@ impl State {
                 let layout = SerializableState::from_parts(
                     layouts,
                     self.last_active_layout,
+                    self.recent_layouts.clone(),
                     size,
-                    position
+                    position,
+                    self.layout_names.clone(),
+                    self.settings.clone(),
                 );
             
                 match serde_json::to_string(&layout) {
@@ -365,7 +1068,7 @@ impl State {
                     Err(e) => log::error!("Failed to serialize layout: {}", e),
                 }
             
-                window::close(window)
+                window::close(window).chain(iced::exit())
             },
             Message::ShowLayoutModal => {
                 self.show_layout_modal = true;
@@ -373,9 +1076,18 @@ impl State {
             },
             Message::HideLayoutModal => {
                 self.show_layout_modal = false;
+                self.pending_layout_copy = None;
                 Task::none()
             },
             Message::Notification(notification) => {
+                let severity = notification.severity();
+
+                if severity < self.settings.min_severity {
+                    log::log!(severity.log_level(), "Notification not shown: {}", notification.text());
+
+                    return Task::none();
+                }
+
                 self.notification = Some(notification);
 
                 Task::perform(
@@ -443,29 +1155,95 @@ impl State {
                 Task::none()
             },
             Message::ResetCurrentLayout => {
-                let new_dashboard = Dashboard::empty();
+                let mut new_dashboard = Dashboard::empty();
+                self.settings.apply_to(&mut new_dashboard);
+                new_dashboard.set_debug_overlay(self.debug_overlay);
 
                 self.layouts.insert(self.last_active_layout, new_dashboard);
 
-                Task::perform(
-                    async {},
-                    move |_| Message::Notification(
-                        Notification::Info("Layout reset".to_string())
-                    )
-                )
+                Task::batch(vec![
+                    self.sync_popouts(),
+                    Task::perform(
+                        async {},
+                        move |_| Message::Notification(
+                            Notification::Info("Layout reset".to_string())
+                        )
+                    ),
+                ])
+            },
+            Message::CopyLayoutTo(target) => {
+                if target == self.last_active_layout {
+                    return Task::none();
+                }
+
+                if self.layouts.get(&target).is_some_and(Dashboard::has_content) {
+                    self.pending_layout_copy = Some(target);
+
+                    return Task::none();
+                }
+
+                self.copy_layout(target)
+            },
+            Message::ConfirmCopyLayout => {
+                match self.pending_layout_copy.take() {
+                    Some(target) => self.copy_layout(target),
+                    None => Task::none(),
+                }
+            },
+            Message::CancelCopyLayout => {
+                self.pending_layout_copy = None;
+
+                Task::none()
+            },
+            Message::ConnectivityChecked(exchange, result) => {
+                if let Err(err) = result {
+                    log::error!("{exchange} connectivity check failed: {err}");
+
+                    return Task::perform(
+                        async {},
+                        move |_| Message::Notification(
+                            Notification::Error(format!("{exchange} unreachable — region blocked or firewalled? ({err})"))
+                        )
+                    );
+                }
+
+                Task::none()
+            },
+            Message::WindowReady => {
+                if self.window_ready {
+                    return Task::none();
+                }
+                self.window_ready = true;
+
+                self.update(Message::LayoutSelected(self.last_active_layout))
             },
             Message::LayoutSelected(layout_id) => {
+                let close_popouts = self.close_popouts();
+
                 self.last_active_layout = layout_id;
 
+                self.recent_layouts.retain(|id| *id != layout_id);
+                self.recent_layouts.insert(0, layout_id);
+
+                if !self.settings.warm_layouts {
+                    self.teardown_background_streams();
+                }
+
                 let dashboard = self.get_mut_dashboard();
 
                 let layout_fetch_command = dashboard.layout_changed();
             
                 Task::batch(vec![
+                    close_popouts,
+                    self.sync_popouts(),
                     layout_fetch_command.map(Message::Dashboard),
                 ])
             },
             Message::Dashboard(message) => {
+                if let dashboard::Message::Notify(notification) = message {
+                    return self.update(Message::Notification(notification));
+                }
+
                 let dashboard = self.get_mut_dashboard();
                 
                 let command = dashboard.update(
@@ -474,12 +1252,21 @@ impl State {
 
                 Task::batch(vec![
                     command.map(Message::Dashboard),
+                    self.sync_popouts(),
                 ])
             },
         }
     }
 
-    fn view(&self) -> Element<'_, Message> {
+    fn view(&self, window: window::Id) -> Element<'_, Message> {
+        if let Some(pane_id) = self.popouts.get(&window) {
+            return match self.get_dashboard().get_pane_by_id(*pane_id) {
+                Some(pane_state) => pane_state.view_content()
+                    .map(|message| Message::Dashboard(dashboard::Message::Pane(message))),
+                None => Space::new(Length::Fill, Length::Fill).into(),
+            };
+        }
+
         let dashboard = self.get_dashboard();
 
         let layout_lock_button = button(
@@ -508,7 +1295,7 @@ impl State {
             .push(
                 tooltip(
                     layout_modal_button, 
-                    "Manage Layouts", tooltip::Position::Bottom
+                    "Manage Layouts (F2)", tooltip::Position::Bottom
                 ).style(style::tooltip)
             )
             .push(
@@ -563,7 +1350,7 @@ impl State {
             }
         }
 
-        let content = Column::new()
+        let mut content = Column::new()
             .padding(10)
             .spacing(10)
             .width(Length::Fill)
@@ -574,20 +1361,66 @@ impl State {
                     .push(ws_controls)
                     .push(Space::with_width(Length::Fill))
                     .push(layout_controls)
-            )
+            );
+
+        if self.settings.offline_banner {
+            let offline_exchanges = self.offline_exchanges();
+
+            if !offline_exchanges.is_empty() {
+                content = content.push(self.view_offline_banner(&offline_exchanges));
+            }
+        }
+
+        if self.show_stream_stats {
+            content = content.push(self.view_stream_stats());
+        }
+
+        let content = content
             .push(
                 dashboard.view().map(Message::Dashboard)
             );
 
         if self.show_layout_modal {
+            let named_layouts: Vec<NamedLayout> = LayoutId::ALL.iter()
+                .map(|id| NamedLayout { id: *id, name: self.layout_names.get(id).cloned() })
+                .collect();
+            let active_layout = named_layouts.iter()
+                .find(|layout| layout.id == self.last_active_layout)
+                .cloned();
+
+            let copy_targets: Vec<NamedLayout> = named_layouts.iter()
+                .filter(|layout| layout.id != self.last_active_layout)
+                .cloned()
+                .collect();
+
+            let copy_layout_picklist = pick_list(
+                copy_targets,
+                None::<NamedLayout>,
+                move |layout: NamedLayout| Message::CopyLayoutTo(layout.id)
+            )
+            .placeholder("Copy to...")
+            .style(style::picklist_primary)
+            .menu_style(style::picklist_menu_primary);
+
             let layout_picklist = pick_list(
-                &LayoutId::ALL[..],
-                Some(self.last_active_layout),
-                move |layout: LayoutId| Message::LayoutSelected(layout)
+                named_layouts,
+                active_layout,
+                move |layout: NamedLayout| Message::LayoutSelected(layout.id)
             );
 
+            let layout_name_input = text_input(
+                &self.last_active_layout.to_string(),
+                self.layout_names.get(&self.last_active_layout).map_or("", |name| name.as_str())
+            )
+            .on_input(Message::LayoutRenamed)
+            .size(14)
+            .width(iced::Pixels(200.0));
+
             let mut add_pane_button = button("Split selected pane").width(iced::Pixels(200.0));
             let mut replace_pane_button = button("Replace selected pane").width(iced::Pixels(200.0));
+            let mut compare_button = button("Compare timeframes").width(iced::Pixels(200.0));
+
+            let (compare_a, compare_b) = self.compare_timeframes;
 
             if dashboard.focus.is_some() {
                 replace_pane_button = replace_pane_button.on_press(
@@ -607,8 +1440,245 @@ impl State {
                         )
                     ))
                 );
+
+                if let Some(focus) = dashboard.focus {
+                    compare_button = compare_button.on_press(
+                        Message::Dashboard(dashboard::Message::Pane(
+                            pane::Message::CompareTimeframes(focus, self.compare_timeframes)
+                        ))
+                    );
+                }
+            }
+
+            let focused_preset = dashboard.focus
+                .and_then(|pane| dashboard.panes.get(pane))
+                .and_then(|pane_state| pane_state.preset());
+
+            let mut set_default_button = button("Set as default").width(iced::Pixels(200.0));
+            if let Some(preset) = focused_preset {
+                set_default_button = set_default_button.on_press(Message::SetDefaultPane(Some(preset)));
+            }
+
+            let mut default_pane_controls = Column::new()
+                .align_x(Alignment::Center)
+                .spacing(8)
+                .push(
+                    Text::new(
+                        self.settings.default_pane.as_ref()
+                            .map_or("New panes: Starter".to_string(), |preset| format!("New panes: {preset}"))
+                    ).size(12)
+                )
+                .push(
+                    tooltip(
+                        set_default_button,
+                        "New split/replaced panes open with the selected pane's content", 
+                        tooltip::Position::Top
+                    ).style(style::tooltip)
+                );
+
+            if self.settings.default_pane.is_some() {
+                default_pane_controls = default_pane_controls.push(
+                    button("Clear default")
+                        .width(iced::Pixels(200.0))
+                        .on_press(Message::SetDefaultPane(None))
+                );
             }
 
+            let compare_controls = Row::new()
+                .spacing(8)
+                .align_y(Alignment::Center)
+                .push(
+                    pick_list(
+                        &Timeframe::ALL[..],
+                        Some(compare_a),
+                        move |timeframe| Message::CompareTimeframesChanged((timeframe, compare_b))
+                    )
+                    .style(style::picklist_primary)
+                    .menu_style(style::picklist_menu_primary)
+                )
+                .push(
+                    pick_list(
+                        &Timeframe::ALL[..],
+                        Some(compare_b),
+                        move |timeframe| Message::CompareTimeframesChanged((compare_a, timeframe))
+                    )
+                    .style(style::picklist_primary)
+                    .menu_style(style::picklist_menu_primary)
+                );
+
+            let crosshair_style = self.settings.crosshair_style;
+
+            let crosshair_controls = Row::new()
+                .spacing(8)
+                .align_y(Alignment::Center)
+                .push(
+                    checkbox("Dashed", crosshair_style.dashed)
+                        .on_toggle(move |dashed| Message::CrosshairStyleChanged(
+                            CrosshairStyle { dashed, ..crosshair_style }
+                        ))
+                )
+                .push(
+                    pick_list(
+                        &CrosshairColor::ALL[..],
+                        Some(crosshair_style.color),
+                        move |color| Message::CrosshairStyleChanged(
+                            CrosshairStyle { color, ..crosshair_style }
+                        )
+                    )
+                    .style(style::picklist_primary)
+                    .menu_style(style::picklist_menu_primary)
+                )
+                .push(
+                    Slider::new(1.0..=3.0, crosshair_style.thickness, move |thickness| Message::CrosshairStyleChanged(
+                        CrosshairStyle { thickness, ..crosshair_style }
+                    ))
+                    .step(0.5)
+                    .width(iced::Pixels(80.0))
+                );
+
+            let sessions = self.settings.sessions;
+
+            let session_controls = Column::new()
+                .spacing(4)
+                .align_x(Alignment::Center)
+                .push(view_session_hours("Asia", sessions.asia, move |asia| Message::SessionsChanged(
+                    Sessions { asia, ..sessions }
+                )))
+                .push(view_session_hours("London", sessions.london, move |london| Message::SessionsChanged(
+                    Sessions { london, ..sessions }
+                )))
+                .push(view_session_hours("New York", sessions.new_york, move |new_york| Message::SessionsChanged(
+                    Sessions { new_york, ..sessions }
+                )));
+
+            let quick_ticker_controls = (1..=QuickTickers::SLOTS as u8)
+                .collect::<Vec<u8>>()
+                .chunks(3)
+                .fold(Column::new().spacing(4), |column, slots| {
+                    column.push(slots.iter().fold(Row::new().spacing(8).align_y(Alignment::Center), |row, &slot| {
+                        let ticker = self.settings.quick_tickers.get(slot);
+
+                        row.push(Text::new(slot.to_string()))
+                            .push(
+                                pick_list(
+                                    &Ticker::ALL[..],
+                                    ticker,
+                                    move |ticker| Message::QuickTickerChanged(slot, Some(ticker))
+                                )
+                                .placeholder("-")
+                                .text_size(13)
+                                .style(style::picklist_primary)
+                                .menu_style(style::picklist_menu_primary)
+                            )
+                            .push(
+                                button(Text::new("x").size(11))
+                                    .on_press_maybe(ticker.map(|_| Message::QuickTickerChanged(slot, None)))
+                            )
+                    }))
+                });
+
+            let imbalance_alert = self.settings.imbalance_alert;
+            let pane_gaps = self.settings.pane_gaps;
+            let focus_highlight = self.settings.focus_highlight;
+            let starter_defaults = self.settings.starter_defaults;
+
+            let starter_controls = tooltip(
+                Row::new()
+                    .spacing(8)
+                    .align_y(Alignment::Center)
+                    .push(Text::new("Starter"))
+                    .push(
+                        pick_list(
+                            &Exchange::ALL[..],
+                            starter_defaults.exchange,
+                            move |exchange| Message::StarterDefaultsChanged(
+                                StarterDefaults { exchange: Some(exchange), ..starter_defaults }
+                            )
+                        )
+                        .placeholder("exchange...")
+                        .style(style::picklist_primary)
+                        .menu_style(style::picklist_menu_primary)
+                    )
+                    .push(
+                        pick_list(
+                            &Timeframe::ALL[..],
+                            Some(starter_defaults.timeframe),
+                            move |timeframe| Message::StarterDefaultsChanged(
+                                StarterDefaults { timeframe, ..starter_defaults }
+                            )
+                        )
+                        .style(style::picklist_primary)
+                        .menu_style(style::picklist_menu_primary)
+                    ),
+                "Exchange and timeframe new starter panes come with, so picking a ticker is enough", 
+                tooltip::Position::Top
+            ).style(style::tooltip);
+
+            let sound_alert = self.settings.sound_alert;
+
+            let sound_alert_controls = Column::new()
+                .align_x(Alignment::Center)
+                .spacing(8)
+                .push(
+                    tooltip(
+                        checkbox("Sound on large trades", sound_alert.enabled)
+                            .on_toggle(move |enabled| Message::SoundAlertChanged(
+                                SoundAlert { enabled, ..sound_alert }
+                            )),
+                        "A high tone for buys and a low one for sells, only the largest trade of a burst sounds", 
+                        tooltip::Position::Top
+                    ).style(style::tooltip)
+                )
+                .push(Text::new(format!("Threshold: ${}k", sound_alert.threshold_k)).size(12))
+                .push(
+                    Slider::new(10..=2000, sound_alert.threshold_k, move |threshold_k| Message::SoundAlertChanged(
+                        SoundAlert { threshold_k, ..sound_alert }
+                    ))
+                    .step(10u32)
+                    .width(iced::Pixels(200.0))
+                )
+                .push(Text::new(format!("Cooldown: {}ms", sound_alert.cooldown_ms)).size(12))
+                .push(
+                    Slider::new(100..=5000, sound_alert.cooldown_ms, move |cooldown_ms| Message::SoundAlertChanged(
+                        SoundAlert { cooldown_ms, ..sound_alert }
+                    ))
+                    .step(100u32)
+                    .width(iced::Pixels(200.0))
+                );
+
+            let imbalance_alert_controls = Column::new()
+                .align_x(Alignment::Center)
+                .spacing(8)
+                .push(
+                    checkbox("Alert on aggressive flow imbalance", imbalance_alert.enabled)
+                        .on_toggle(move |enabled| Message::ImbalanceAlertChanged(
+                            ImbalanceAlert { enabled, ..imbalance_alert }
+                        ))
+                )
+                .push(Text::new(format!("Ratio: {:.1}x", imbalance_alert.ratio)).size(12))
+                .push(
+                    Slider::new(1.5..=10.0, imbalance_alert.ratio, move |ratio| Message::ImbalanceAlertChanged(
+                        ImbalanceAlert { ratio, ..imbalance_alert }
+                    ))
+                    .step(0.5)
+                    .width(iced::Pixels(200.0))
+                )
+                .push(Text::new(format!("Window: {}s", imbalance_alert.window_secs)).size(12))
+                .push(
+                    Slider::new(1..=60, imbalance_alert.window_secs, move |window_secs| Message::ImbalanceAlertChanged(
+                        ImbalanceAlert { window_secs, ..imbalance_alert }
+                    ))
+                    .width(iced::Pixels(200.0))
+                )
+                .push(Text::new(format!("Cooldown: {}s", imbalance_alert.cooldown_secs)).size(12))
+                .push(
+                    Slider::new(5..=300, imbalance_alert.cooldown_secs, move |cooldown_secs| Message::ImbalanceAlertChanged(
+                        ImbalanceAlert { cooldown_secs, ..imbalance_alert }
+                    ))
+                    .step(5u32)
+                    .width(iced::Pixels(200.0))
+                );
+
             let layout_modal = container(
                 Column::new()
                     .spacing(16)
@@ -616,46 +1686,500 @@ impl State {
                     .push(
                         Column::new()
                             .align_x(Alignment::Center)
-                            .push(Text::new("Layouts"))
+                            .push(Text::new("Layouts"))
+                            .padding([8, 0])
+                            .spacing(8)
+                            .push(
+                                Row::new()
+                                    .push(
+                                        Row::new()
+                                        .spacing(8)
+                                        .push(
+                                            tooltip(
+                                                button(Text::new("Reset"))
+                                                .on_press(Message::ResetCurrentLayout),
+                                                "Reset current layout", 
+                                                tooltip::Position::Top
+                                            ).style(style::tooltip)
+                                        )
+                                        .push(
+                                            layout_picklist
+                                            .style(style::picklist_primary)
+                                            .menu_style(style::picklist_menu_primary)
+                                        )
+                                        .push(
+                                            tooltip(
+                                                button(Text::new("i")).style(style::button_for_info),
+                                                "Layouts won't be saved if app exited abruptly", 
+                                                tooltip::Position::Top
+                                            ).style(style::tooltip)
+                                        )                         
+                                    )
+                            )
+                            .push(
+                                tooltip(
+                                    layout_name_input,
+                                    "Rename current layout", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(
+                                if let Some(target) = self.pending_layout_copy {
+                                    Column::new()
+                                        .align_x(Alignment::Center)
+                                        .spacing(4)
+                                        .push(Text::new(format!("Overwrite the panes of {target}?")).size(12))
+                                        .push(
+                                            Row::new()
+                                                .spacing(8)
+                                                .push(button("Overwrite").on_press(Message::ConfirmCopyLayout))
+                                                .push(button("Cancel").on_press(Message::CancelCopyLayout))
+                                        )
+                                        .into()
+                                } else {
+                                    Element::from(
+                                        tooltip(
+                                            copy_layout_picklist,
+                                            "Copies the current layout into the chosen one", 
+                                            tooltip::Position::Top
+                                        ).style(style::tooltip)
+                                    )
+                                }
+                            )
+                    )
+                    .push(
+                        Column::new()
+                            .align_x(Alignment::Center)
+                            .push(Text::new("Panes"))
+                            .padding([8, 0])
+                            .spacing(8)
+                            .push(add_pane_button)
+                            .push(replace_pane_button)
+                            .push(compare_controls)
+                            .push(
+                                tooltip(
+                                    compare_button,
+                                    "Splits the selected pane into two linked candlestick charts", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(default_pane_controls)
+                            .push(starter_controls)
+                            .push(
+                                tooltip(
+                                    Row::new()
+                                        .spacing(8)
+                                        .align_y(Alignment::Center)
+                                        .push(Text::new("Max panes"))
+                                        .push(
+                                            Slider::new(MAX_PANES_RANGE, self.settings.max_panes, Message::MaxPanesChanged)
+                                                .width(Length::Fixed(120.0))
+                                        )
+                                        .push(Text::new(self.settings.max_panes.to_string()).size(14)),
+                                    "Splitting is refused once a layout has this many panes", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(
+                                Row::new()
+                                    .spacing(8)
+                                    .align_y(Alignment::Center)
+                                    .push(Text::new("Pane gap"))
+                                    .push(
+                                        Slider::new(PANE_SPACING_RANGE, self.settings.pane_gaps.spacing, move |spacing| Message::PaneGapsChanged(
+                                            PaneGaps { spacing, ..pane_gaps }
+                                        ))
+                                        .width(Length::Fixed(120.0))
+                                    )
+                                    .push(Text::new(format!("{}px", self.settings.pane_gaps.spacing)).size(14))
+                            )
+                            .push(
+                                tooltip(
+                                    Row::new()
+                                        .spacing(8)
+                                        .align_y(Alignment::Center)
+                                        .push(Text::new("Resize handle"))
+                                        .push(
+                                            Slider::new(RESIZE_HANDLE_RANGE, self.settings.pane_gaps.resize_handle, move |resize_handle| Message::PaneGapsChanged(
+                                                PaneGaps { resize_handle, ..pane_gaps }
+                                            ))
+                                            .width(Length::Fixed(120.0))
+                                        )
+                                        .push(Text::new(format!("{}px", self.settings.pane_gaps.resize_handle)).size(14)),
+                                    "Width of the area between panes that can be dragged to resize them", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(
+                                tooltip(
+                                    Row::new()
+                                        .spacing(8)
+                                        .align_y(Alignment::Center)
+                                        .push(Text::new("Focus border"))
+                                        .push(
+                                            pick_list(
+                                                &FocusColor::ALL[..],
+                                                Some(focus_highlight.color),
+                                                move |color| Message::FocusHighlightChanged(
+                                                    FocusHighlight { color, ..focus_highlight }
+                                                )
+                                            )
+                                            .style(style::picklist_primary)
+                                            .menu_style(style::picklist_menu_primary)
+                                        )
+                                        .push(
+                                            Slider::new(FOCUS_WIDTH_RANGE, focus_highlight.width, move |width| Message::FocusHighlightChanged(
+                                                FocusHighlight { width, ..focus_highlight }
+                                            ))
+                                            .step(0.5)
+                                            .width(iced::Pixels(80.0))
+                                        ),
+                                    "Border around the focused pane, the one keyboard shortcuts act on", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(
+                                tooltip(
+                                    checkbox("Idle while minimized", self.settings.idle_when_minimized)
+                                        .on_toggle(Message::IdleWhenMinimizedToggled),
+                                    "Charts stop redrawing while the window is minimized. \
+                                    Streams stay connected and data keeps accumulating, it all shows up once restored", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(
+                                tooltip(
+                                    checkbox("Confirm closing panes", self.settings.confirm_close)
+                                        .on_toggle(Message::ConfirmCloseToggled),
+                                    "Asks before closing a pane that has a chart, empty panes close right away", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(
+                                tooltip(
+                                    checkbox("Reset ticksize on ticker switch", self.settings.ticker_switch_reset)
+                                        .on_toggle(Message::TickerSwitchResetToggled),
+                                    "A pane switched to another ticker clears its size filter and picks \
+                                    an auto ticksize for the new price, unless its ticksize is locked", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                    )       
+                    .push(
+                        Column::new()
+                            .align_x(Alignment::Center)
+                            .push(Text::new("Quick tickers"))
+                            .padding([8, 0])
+                            .spacing(8)
+                            .push(
+                                tooltip(
+                                    quick_ticker_controls,
+                                    "Number keys 1 to 9 switch the focused pane to the ticker in that slot", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                    )
+                    .push(
+                        Column::new()
+                            .align_x(Alignment::Center)
+                            .push(Text::new("Theme"))
+                            .padding([8, 0])
+                            .spacing(8)
+                            .push(
+                                pick_list(Theme::ALL, Some(&self.settings.theme), |theme| Message::ThemeSelected(theme.clone()))
+                                    .style(style::picklist_primary)
+                                    .menu_style(style::picklist_menu_primary)
+                            )
+                    )
+                    .push(
+                        Column::new()
+                            .align_x(Alignment::Center)
+                            .push(Text::new("Chart colors"))
+                            .padding([8, 0])
+                            .spacing(8)
+                            .push(
+                                tooltip(
+                                    pick_list(&ChartPalette::ALL[..], Some(self.settings.palette), Message::PaletteSelected)
+                                        .style(style::picklist_primary)
+                                        .menu_style(style::picklist_menu_primary),
+                                    "Used by every chart that doesn't pick its own colors in its settings", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                    )
+                    .push(
+                        Column::new()
+                            .align_x(Alignment::Center)
+                            .push(Text::new("Crosshair"))
+                            .padding([8, 0])
+                            .spacing(8)
+                            .push(crosshair_controls)
+                            .push(
+                                tooltip(
+                                    checkbox("Follow across linked panes", self.settings.crosshair_sync)
+                                        .on_toggle(Message::CrosshairSyncToggled),
+                                    "Hovering a candlestick or footprint chart draws a guide at the same time on the panes in its link group", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                    )
+                    .push(
+                        Column::new()
+                            .align_x(Alignment::Center)
+                            .push(Text::new("Axes"))
+                            .padding([8, 0])
+                            .spacing(8)
+                            .push(
+                                tooltip(
+                                    checkbox("Latest price label", self.settings.price_ruler)
+                                        .on_toggle(Message::PriceRulerToggled),
+                                    "Pins the last traded price on the price axis, colored by its last tick", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(
+                                tooltip(
+                                    checkbox("Shared volume scale", self.settings.shared_volume_scale)
+                                        .on_toggle(Message::SharedVolumeScaleToggled),
+                                    "Linked candlestick and footprint charts scale volume bars to the highest one in view across the group, \
+                                    so their heights can be compared", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(
+                                Row::new()
+                                    .spacing(8)
+                                    .align_y(Alignment::Center)
+                                    .push(Text::new("Label size"))
+                                    .push(
+                                        Slider::new(MIN_LABEL_SIZE..=MAX_LABEL_SIZE, self.settings.label_size, Message::LabelSizeChanged)
+                                            .step(1.0)
+                                            .width(Length::Fixed(120.0))
+                                    )
+                                    .push(Text::new(format!("{}px", self.settings.label_size)).size(14))
+                            )
+                    )
+                    .push(
+                        Column::new()
+                            .align_x(Alignment::Center)
+                            .push(Text::new("Sessions"))
+                            .padding([8, 0])
+                            .spacing(8)
+                            .push(
+                                tooltip(
+                                    session_controls,
+                                    "UTC hours shaded on candlestick panes with sessions on, \
+                                    an end at or before the start runs past midnight",
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                    )
+                    .push(
+                        Column::new()
+                            .align_x(Alignment::Center)
+                            .push(Text::new("Flow Alert"))
+                            .padding([8, 0])
+                            .spacing(8)
+                            .push(imbalance_alert_controls)
+                    )
+                    .push(
+                        Column::new()
+                            .align_x(Alignment::Center)
+                            .push(Text::new("Sound Alert"))
+                            .padding([8, 0])
+                            .spacing(8)
+                            .push(sound_alert_controls)
+                    )
+                    .push(
+                        Column::new()
+                            .align_x(Alignment::Center)
+                            .push(Text::new("Notifications"))
                             .padding([8, 0])
                             .spacing(8)
                             .push(
-                                Row::new()
-                                    .push(
-                                        Row::new()
+                                tooltip(
+                                    Row::new()
                                         .spacing(8)
+                                        .align_y(Alignment::Center)
+                                        .push(Text::new("Show from"))
                                         .push(
-                                            tooltip(
-                                                button(Text::new("Reset"))
-                                                .on_press(Message::ResetCurrentLayout),
-                                                "Reset current layout", 
-                                                tooltip::Position::Top
-                                            ).style(style::tooltip)
-                                        )
-                                        .push(
-                                            layout_picklist
+                                            pick_list(
+                                                &Severity::ALL[..],
+                                                Some(self.settings.min_severity),
+                                                Message::MinSeverityChanged
+                                            )
                                             .style(style::picklist_primary)
                                             .menu_style(style::picklist_menu_primary)
-                                        )
-                                        .push(
-                                            tooltip(
-                                                button(Text::new("i")).style(style::button_for_info),
-                                                "Layouts won't be saved if app exited abruptly", 
-                                                tooltip::Position::Top
-                                            ).style(style::tooltip)
-                                        )                         
-                                    )
+                                        ),
+                                    "Notifications below this severity are only written to the log", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
                             )
                     )
                     .push(
                         Column::new()
                             .align_x(Alignment::Center)
-                            .push(Text::new("Panes"))
+                            .push(Text::new("Connection"))
                             .padding([8, 0])
                             .spacing(8)
-                            .push(add_pane_button)
-                            .push(replace_pane_button)
-                    )       
+                            .push(
+                                tooltip(
+                                    checkbox("Reconnect after sleep", self.settings.resume_reconnect)
+                                        .on_toggle(Message::ResumeReconnectToggled),
+                                    "Restarts all streams when the machine wakes up from suspend", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(
+                                tooltip(
+                                    checkbox("Backfill klines on reconnect", self.settings.backfill_on_reconnect)
+                                        .on_toggle(Message::BackfillOnReconnectToggled),
+                                    "Refetches recent klines once an exchange is back and merges them into the charts, \
+                                    so the bars missed during the outage fill in instead of leaving a gap", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(
+                                tooltip(
+                                    checkbox("Offline banner", self.settings.offline_banner)
+                                        .on_toggle(Message::OfflineBannerToggled),
+                                    "When no exchange in use can be reached past the grace period, \
+                                    a banner stays up until one reconnects, instead of a warning per exchange", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(
+                                tooltip(
+                                    checkbox("Keep background layouts connected", self.settings.warm_layouts)
+                                        .on_toggle(Message::WarmLayoutsToggled),
+                                    "Layouts opened this session keep streaming after switching away, so they're live when you come back", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(
+                                tooltip(
+                                    checkbox("Share one connection per exchange", self.settings.pool_market_streams)
+                                        .on_toggle(Message::PoolMarketStreamsToggled),
+                                    "Depth and trades of all tickers on Binance go over a single socket. \
+                                    Adding or removing a ticker reconnects all of them", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(
+                                tooltip(
+                                    checkbox("Infer missing trade sides", self.settings.tick_rule)
+                                        .on_toggle(Message::TickRuleToggled),
+                                    "Trades the exchange sent without a side count as buys on an uptick and sells on a downtick, \
+                                    instead of all as buys", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(
+                                tooltip(
+                                    Row::new()
+                                        .spacing(8)
+                                        .align_y(Alignment::Center)
+                                        .push(Text::new("Connect timeout"))
+                                        .push(
+                                            Slider::new(
+                                                MIN_CONNECT_TIMEOUT_SECS..=MAX_CONNECT_TIMEOUT_SECS, 
+                                                self.settings.connect_timeout_secs, 
+                                                Message::ConnectTimeoutChanged
+                                            )
+                                            .width(Length::Fixed(120.0))
+                                        )
+                                        .push(Text::new(format!("{}s", self.settings.connect_timeout_secs)).size(14)),
+                                    "A websocket handshake hanging longer than this is dropped and retried", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(
+                                tooltip(
+                                    Row::new()
+                                        .spacing(8)
+                                        .align_y(Alignment::Center)
+                                        .push(Text::new("Reconnect grace"))
+                                        .push(
+                                            Slider::new(
+                                                0..=MAX_RECONNECT_GRACE_SECS, 
+                                                self.settings.reconnect_grace_secs, 
+                                                Message::ReconnectGraceChanged
+                                            )
+                                            .width(Length::Fixed(120.0))
+                                        )
+                                        .push(Text::new(format!("{}s", self.settings.reconnect_grace_secs)).size(14)),
+                                    "Connections recovering within this are only logged instead of notified", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(
+                                tooltip(
+                                    checkbox("Show stream stats", self.show_stream_stats)
+                                        .on_toggle(Message::StreamStatsToggled),
+                                    "Messages and bytes per second of each active websocket", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(
+                                tooltip(
+                                    checkbox("Show chart data readout", self.debug_overlay)
+                                        .on_toggle(Message::DebugOverlayToggled),
+                                    "Datapoints loaded and the time of the latest one in the corner of each chart, to tell an empty feed from a frozen one", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(
+                                tooltip(
+                                    Row::new()
+                                        .spacing(8)
+                                        .align_y(Alignment::Center)
+                                        .push(Text::new("Heatmap memory"))
+                                        .push(
+                                            Slider::new(0..=2048, self.settings.memory_budget_mb, Message::MemoryBudgetChanged)
+                                                .step(64u32)
+                                                .width(Length::Fixed(120.0))
+                                        )
+                                        .push(
+                                            Text::new(if self.settings.memory_budget_mb == 0 {
+                                                "no limit".to_string()
+                                            } else {
+                                                format!("{}MB", self.settings.memory_budget_mb)
+                                            }).size(14)
+                                        ),
+                                    "Once the heatmaps of the layout estimate more than this, \
+                                    each drops its oldest quarter. The data readout shows every heatmap's estimate", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(
+                                tooltip(
+                                    Row::new()
+                                        .spacing(8)
+                                        .align_y(Alignment::Center)
+                                        .push(
+                                            checkbox("Dump raw frames", data_providers::frame_dump_running())
+                                                .on_toggle(Message::FrameDumpToggled)
+                                        )
+                                        .push(
+                                            Slider::new(1..=30, self.frame_dump_mins, Message::FrameDumpMinsChanged)
+                                                .width(Length::Fixed(120.0))
+                                        )
+                                        .push(Text::new(format!("{} min", self.frame_dump_mins)).size(14)),
+                                    Text::new(format!(
+                                        "Writes every incoming websocket message to {FRAME_DUMP_PATH}, for diagnosing parsing issues. Stops on its own after the set time or at 50 MB"
+                                    )), 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(
+                                tooltip(
+                                    button(Text::new("Copy stream config"))
+                                        .on_press(Message::CopyStreamConfig),
+                                    "Copies the panes of the current layout with their streams and settings, to attach to bug reports", 
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                    )
                     .push(
                         button("Close")
                             .on_press(Message::HideLayoutModal)
@@ -675,50 +2199,30 @@ impl State {
     fn subscription(&self) -> Subscription<Message> {
         let mut all_subscriptions = Vec::new();
     
-        for (exchange, stream) in &self.get_dashboard().pane_streams {
+        for exchange in &Exchange::ALL {
+            let provider = exchange.provider();
+
+            let (connections, kline_streams) = self.stream_connections(*exchange);
+
             let mut depth_streams: Vec<Subscription<Message>> = Vec::new();
-            let mut kline_streams: Vec<(Ticker, Timeframe)> = Vec::new();
-    
-            for stream_types in stream.values() {
-                for stream_type in stream_types {
-                    match stream_type {
-                        StreamType::Kline { ticker, timeframe, .. } => {
-                            kline_streams.push((*ticker, *timeframe));
-                        },
-                        StreamType::DepthAndTrades { ticker, .. } => {
-                            let ticker = *ticker;
-
-                            let depth_stream = match exchange {
-                                Exchange::BinanceFutures => {
-                                    Subscription::run_with_id(ticker, binance::market_data::connect_market_stream(ticker))
-                                        .map(|event| Message::MarketWsEvent(MarketEvents::Binance(event)))
-                                },
-                                Exchange::BybitLinear => {
-                                    Subscription::run_with_id(ticker, bybit::market_data::connect_market_stream(ticker))
-                                        .map(|event| Message::MarketWsEvent(MarketEvents::Bybit(event)))
-                                },
-                            };
-                            depth_streams.push(depth_stream);
-                        },
-                        _ => {}
-                    }
-                }
+
+            for streams in connections {
+                depth_streams.push(
+                    Subscription::run_with_id(
+                        (*exchange, streams.clone()), 
+                        provider.connect_market_stream(streams)
+                    )
+                        .map(|event| Message::MarketWsEvent(StreamKind::DepthAndTrades, event))
+                );
             }
     
             if !kline_streams.is_empty() {
                 let kline_streams_id = kline_streams.clone();
 
-                let kline_subscription = match exchange {
-                    Exchange::BinanceFutures => {
-                        Subscription::run_with_id(kline_streams_id, binance::market_data::connect_kline_stream(kline_streams))
-                            .map(|event| Message::MarketWsEvent(MarketEvents::Binance(event)))
-                    },
-                    Exchange::BybitLinear => {
-                        Subscription::run_with_id(kline_streams_id, bybit::market_data::connect_kline_stream(kline_streams))
-                            .map(|event| Message::MarketWsEvent(MarketEvents::Bybit(event)))
-                    },
-                };
-                all_subscriptions.push(kline_subscription);
+                all_subscriptions.push(
+                    Subscription::run_with_id(kline_streams_id, provider.connect_kline_stream(kline_streams))
+                        .map(|event| Message::MarketWsEvent(StreamKind::Kline, event))
+                );
             }
     
             if !depth_streams.is_empty() {
@@ -727,10 +2231,241 @@ impl State {
         }
 
         all_subscriptions.push(events().map(Message::Event));
+
+        if !self.window_ready {
+            all_subscriptions.push(window::frames().map(|_| Message::WindowReady));
+        }
+
+        all_subscriptions.push(
+            iced::time::every(std::time::Duration::from_secs(SUSPEND_CHECK_INTERVAL_SECS))
+                .map(|_| Message::SuspendCheck)
+        );
+
+        if self.get_dashboard().has_bar_countdown() {
+            all_subscriptions.push(
+                iced::time::every(std::time::Duration::from_secs(1))
+                    .map(|_| Message::CountdownTick)
+            );
+        }
     
         Subscription::batch(all_subscriptions)
     }    
     
+    /// Sockets the streaming layouts need on an exchange, the depth streams grouped
+    /// by the connection they share and the kline streams that go over one of their own
+    fn stream_connections(&self, exchange: Exchange) -> (Vec<Vec<MarketStream>>, Vec<(Ticker, Timeframe)>) {
+        let mut depth_tickers: Vec<Ticker> = Vec::new();
+        let mut kline_streams: Vec<(Ticker, Timeframe)> = Vec::new();
+
+        // inactive layouts only show up here when they're kept warm
+        for dashboard in self.streaming_dashboards() {
+            let Some(stream) = dashboard.pane_streams.get(&exchange) else {
+                continue;
+            };
+
+            for stream_types in stream.values() {
+                for stream_type in stream_types {
+                    match stream_type {
+                        StreamType::Kline { ticker, timeframe, .. } if !kline_streams.contains(&(*ticker, *timeframe)) => {
+                            kline_streams.push((*ticker, *timeframe));
+                        },
+                        StreamType::DepthAndTrades { ticker, .. } if !depth_tickers.contains(ticker) => {
+                            depth_tickers.push(*ticker);
+                        },
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        let provider = exchange.provider();
+
+        // fixed order, so a pooled connection isn't restarted just because the tickers got listed differently
+        depth_tickers.sort_by_key(|ticker| Ticker::ALL.iter().position(|t| t == ticker));
+
+        let market_streams: Vec<MarketStream> = depth_tickers.into_iter().map(|ticker| {
+            let trade_mode = if provider.supports_raw_trades() && self.streaming_dashboards()
+                .any(|dashboard| dashboard.trade_mode(exchange, ticker) == TradeMode::Raw) {
+                TradeMode::Raw
+            } else {
+                TradeMode::Aggregated
+            };
+
+            let depth_cadence = self.streaming_dashboards()
+                .filter_map(|dashboard| dashboard.depth_cadence(exchange, ticker))
+                .min()
+                .unwrap_or_default();
+
+            MarketStream { ticker, trade_mode, depth_cadence, tick_rule: self.settings.tick_rule }
+        }).collect();
+
+        let connections: Vec<Vec<MarketStream>> = if self.settings.pool_market_streams && !market_streams.is_empty() {
+            vec![market_streams]
+        } else {
+            market_streams.into_iter().map(|stream| vec![stream]).collect()
+        };
+
+        (connections, kline_streams)
+    }
+
+    /// Active layout, plus the ones opened this session when background layouts are kept warm
+    fn streaming_dashboards(&self) -> impl Iterator<Item = &Dashboard> {
+        self.layouts.iter()
+            .filter(|(id, _)| self.settings.warm_layouts || **id == self.last_active_layout)
+            .map(|(_, dashboard)| dashboard)
+    }
+
+    /// Drops the streams of every inactive layout, their subscriptions go away with them
+    fn teardown_background_streams(&mut self) {
+        for (id, dashboard) in self.layouts.iter_mut() {
+            if *id != self.last_active_layout {
+                dashboard.pane_streams.clear();
+            }
+        }
+    }
+
+    /// Sounds the largest trade over the threshold, unless still within the cooldown of the last one
+    fn sound_large_trade(&mut self, trades: &[Trade]) {
+        let alert = self.settings.sound_alert;
+        let threshold = alert.threshold_k as f32 * 1000.0;
+
+        let Some(trade) = trades.iter()
+            .filter(|trade| trade.price * trade.qty >= threshold)
+            .max_by(|a, b| (a.price * a.qty).total_cmp(&(b.price * b.qty)))
+        else {
+            return;
+        };
+
+        if self.last_sound.is_some_and(|last| trade.time - last < i64::from(alert.cooldown_ms)) {
+            return;
+        }
+        self.last_sound = Some(trade.time);
+
+        sound::play(if trade.is_sell { sound::Tone::Sell } else { sound::Tone::Buy });
+    }
+
+    /// Feeds the update to the active layout (and warm ones), turning any flow alerts into notifications
+    fn update_depth_and_trades(&mut self, stream_type: StreamType, depth_update_t: i64, depth: Depth, trades_buffer: Vec<Trade>) -> Task<Message> {
+        if self.settings.sound_alert.enabled && self.streaming_dashboards().any(|dashboard| dashboard.has_stream(&stream_type)) {
+            self.sound_large_trade(&trades_buffer);
+        }
+
+        let mut alerts = vec![];
+
+        if self.settings.warm_layouts {
+            for (id, dashboard) in self.layouts.iter_mut() {
+                if *id == self.last_active_layout || !dashboard.has_stream(&stream_type) {
+                    continue;
+                }
+
+                match dashboard.update_depth_and_trades(stream_type, depth_update_t, depth.clone(), trades_buffer.clone()) {
+                    Ok(alert) => alerts.extend(alert),
+                    Err(err) => log::error!("{err}, {stream_type:?}"),
+                }
+            }
+        }
+
+        if !self.settings.warm_layouts || self.get_dashboard().has_stream(&stream_type) {
+            match self.get_mut_dashboard().update_depth_and_trades(stream_type, depth_update_t, depth, trades_buffer) {
+                Ok(alert) => alerts.extend(alert),
+                Err(err) => log::error!("{err}, {stream_type:?}"),
+            }
+        }
+
+        if self.settings.memory_budget_mb > 0 {
+            self.enforce_memory_budget();
+        }
+
+        Task::batch(alerts.into_iter().map(|notification| {
+            Task::perform(async { notification }, Message::Notification)
+        }))
+    }
+
+    /// Evicts the oldest share of every heatmap's buckets, in every layout, when their estimated total is over the budget
+    fn enforce_memory_budget(&mut self) {
+        let budget = self.settings.memory_budget_mb as usize * 1024 * 1024;
+
+        let used: usize = self.layouts.values().map(Dashboard::heatmap_memory).sum();
+
+        if used <= budget {
+            return;
+        }
+
+        let evicted: usize = self.layouts.values_mut()
+            .map(|dashboard| dashboard.evict_heatmap_share(MEMORY_EVICT_SHARE))
+            .sum();
+
+        log::info!(
+            "Heatmaps at ~{:.1}MB, over the {}MB budget, evicted {evicted} buckets down to ~{:.1}MB",
+            used as f32 / (1024.0 * 1024.0),
+            self.settings.memory_budget_mb,
+            self.layouts.values().map(Dashboard::heatmap_memory).sum::<usize>() as f32 / (1024.0 * 1024.0),
+        );
+    }
+
+    fn update_latest_klines(&mut self, stream_type: StreamType, kline: &Kline) {
+        if self.settings.warm_layouts {
+            for (id, dashboard) in self.layouts.iter_mut() {
+                if *id == self.last_active_layout || !dashboard.has_stream(&stream_type) {
+                    continue;
+                }
+
+                if let Err(err) = dashboard.update_latest_klines(&stream_type, kline) {
+                    log::error!("{err}, {stream_type:?}");
+                }
+            }
+
+            if !self.get_dashboard().has_stream(&stream_type) {
+                return;
+            }
+        }
+
+        if let Err(err) = self.get_mut_dashboard().update_latest_klines(&stream_type, kline) {
+            log::error!("{err}, {stream_type:?}");
+        }
+    }
+
+    fn set_minimized(&mut self, minimized: bool) {
+        if self.minimized == minimized {
+            return;
+        }
+        self.minimized = minimized;
+
+        if !self.settings.idle_when_minimized {
+            return;
+        }
+
+        for dashboard in self.layouts.values_mut() {
+            dashboard.set_idle(minimized);
+        }
+    }
+
+    /// Overwrites a layout slot with a copy of the active layout and switches to it
+    fn copy_layout(&mut self, target: LayoutId) -> Task<Message> {
+        let serialized_dashboard = SerializableDashboard::from(self.get_dashboard());
+
+        let mut new_dashboard = Dashboard::from_config(configuration(serialized_dashboard.pane));
+        self.settings.apply_to(&mut new_dashboard);
+        new_dashboard.set_debug_overlay(self.debug_overlay);
+
+        match self.layout_names.get(&self.last_active_layout).cloned() {
+            Some(name) => self.layout_names.insert(target, format!("{name} (copy)")),
+            None => self.layout_names.remove(&target),
+        };
+
+        self.layouts.insert(target, new_dashboard);
+
+        Task::batch(vec![
+            self.update(Message::LayoutSelected(target)),
+            Task::perform(
+                async {},
+                move |_| Message::Notification(
+                    Notification::Info(format!("Layout copied to {target}"))
+                )
+            ),
+        ])
+    }
+
     fn get_mut_dashboard(&mut self) -> &mut Dashboard {
         self.layouts
             .get_mut(&self.last_active_layout)
@@ -781,6 +2516,37 @@ impl State {
     }
 }
 
+fn view_session_hours<'a>(
+    name: &'a str,
+    hours: SessionHours,
+    on_change: impl Fn(SessionHours) -> Message + Copy + 'a,
+) -> Element<'a, Message> {
+    Row::new()
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .push(Text::new(name).width(Length::Fixed(70.0)))
+        .push(
+            pick_list(
+                &Sessions::HOURS[..],
+                Some(hours.start),
+                move |start| on_change(SessionHours { start, ..hours })
+            )
+            .style(style::picklist_primary)
+            .menu_style(style::picklist_menu_primary)
+        )
+        .push(Text::new("to"))
+        .push(
+            pick_list(
+                &Sessions::HOURS[..],
+                Some(hours.end),
+                move |end| on_change(SessionHours { end, ..hours })
+            )
+            .style(style::picklist_primary)
+            .menu_style(style::picklist_menu_primary)
+        )
+        .into()
+}
+
 fn modal<'a, Message>(
     base: impl Into<Element<'a, Message>>,
     content: impl Into<Element<'a, Message>>,
@@ -808,9 +2574,21 @@ where
     .into()
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Event {
     CloseRequested(window::Id),
+    WindowMoved(window::Id, Point),
+    WindowResized(window::Id, Size),
+    WindowFocused(window::Id),
+    WindowUnfocused(window::Id),
+    /// Resolved against the configured hotkeys
+    KeyPressed(KeyCombo),
+    ToggleLayoutModal,
+    ToggleSoloPane,
+    CycleLayout,
+    /// 1 based slot of the quick tickers
+    QuickTicker(u8),
+    MoveFocus(dashboard::FocusMove),
     Copy,
     Escape,
     Home,
@@ -823,11 +2601,19 @@ pub fn events() -> Subscription<Event> {
 
 fn filtered_events(
     event: iced::Event,
-    _status: iced::event::Status,
+    status: iced::event::Status,
     window: window::Id,
 ) -> Option<Event> {
     match &event {
         iced::Event::Window(window::Event::CloseRequested) => Some(Event::CloseRequested(window)),
+        iced::Event::Window(window::Event::Moved(position)) => Some(Event::WindowMoved(window, *position)),
+        iced::Event::Window(window::Event::Resized(size)) => Some(Event::WindowResized(window, *size)),
+        iced::Event::Window(window::Event::Focused) => Some(Event::WindowFocused(window)),
+        iced::Event::Window(window::Event::Unfocused) => Some(Event::WindowUnfocused(window)),
+        iced::Event::Keyboard(keyboard_event) => {
+            hotkeys::key_combo(keyboard_event, status == iced::event::Status::Captured)
+                .map(Event::KeyPressed)
+        },
         _ => None,
     }
 }
@@ -853,11 +2639,28 @@ impl LayoutId {
     const ALL: [LayoutId; 4] = [LayoutId::Layout1, LayoutId::Layout2, LayoutId::Layout3, LayoutId::Layout4];
 }
 
+/// Layout slot paired with its user given name, for the layout picklist
+#[derive(Debug, Clone, PartialEq)]
+struct NamedLayout {
+    id: LayoutId,
+    name: Option<String>,
+}
+impl std::fmt::Display for NamedLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{name}"),
+            None => write!(f, "{}", self.id),
+        }
+    }
+}
+
 struct SavedState {
     layouts: HashMap<LayoutId, Dashboard>,
     last_active_layout: LayoutId,
+    recent_layouts: Vec<LayoutId>,
     window_size: Option<(f32, f32)>,
-    window_position: Option<(f32, f32)>,
+    layout_names: HashMap<LayoutId, String>,
+    settings: AppSettings,
 }
 impl Default for SavedState {
     fn default() -> Self {
@@ -870,8 +2673,10 @@ impl Default for SavedState {
         SavedState {
             layouts,
             last_active_layout: LayoutId::Layout1,
+            recent_layouts: vec![],
             window_size: None,
-            window_position: None,
+            layout_names: HashMap::new(),
+            settings: AppSettings::default(),
         }
     }
 }
@@ -901,21 +2706,302 @@ fn read_layout_from_file(file_path: &str) -> Result<SerializableState, Box<dyn s
 struct SerializableState {
     pub layouts: HashMap<LayoutId, SerializableDashboard>,
     pub last_active_layout: LayoutId,
+    #[serde(default)]
+    pub recent_layouts: Vec<LayoutId>,
     pub window_size: Option<(f32, f32)>,
     pub window_position: Option<(f32, f32)>,
+    #[serde(default)]
+    pub names: HashMap<LayoutId, String>,
+    #[serde(flatten)]
+    pub settings: AppSettings,
 }
 impl SerializableState {
     fn from_parts(
         layouts: HashMap<LayoutId, SerializableDashboard>,
         last_active_layout: LayoutId,
+        recent_layouts: Vec<LayoutId>,
         size: Option<Size>,
         position: Option<Point>,
+        names: HashMap<LayoutId, String>,
+        settings: AppSettings,
     ) -> Self {
         SerializableState {
             layouts,
             last_active_layout,
+            recent_layouts,
             window_size: size.map(|s| (s.width, s.height)),
             window_position: position.map(|p| (p.x, p.y)),
+            names,
+            settings,
+        }
+    }
+}
+
+/// App-wide settings, shared by every layout and saved alongside them
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+struct AppSettings {
+    default_pane: Option<pane::PanePreset>,
+    crosshair_style: CrosshairStyle,
+    palette: ChartPalette,
+    price_ruler: bool,
+    crosshair_sync: bool,
+    shared_volume_scale: bool,
+    label_size: f32,
+    imbalance_alert: ImbalanceAlert,
+    sound_alert: SoundAlert,
+    resume_reconnect: bool,
+    /// Refetches klines once an exchange is back, merging them in to cover the outage
+    backfill_on_reconnect: bool,
+    /// One persistent banner instead of per-exchange warnings when no exchange in use is reachable
+    offline_banner: bool,
+    /// Keeps streams of layouts opened this session running while they're in the background
+    warm_layouts: bool,
+    /// Puts all of an exchange's depth and trade streams on one connection
+    pool_market_streams: bool,
+    /// Infers the side of trades that come without one from the price change
+    tick_rule: bool,
+    /// Charts stop redrawing on new data while the main window is minimized,
+    /// streams keep running and the data keeps accumulating for when it's restored
+    idle_when_minimized: bool,
+    connect_timeout_secs: u32,
+    /// Disconnects recovering within this only get logged, 0 notifies every one
+    reconnect_grace_secs: u32,
+    max_panes: u32,
+    pane_gaps: PaneGaps,
+    focus_highlight: FocusHighlight,
+    starter_defaults: StarterDefaults,
+    sessions: Sessions,
+    /// Notifications below this only get logged
+    min_severity: Severity,
+    /// Closing a pane that isn't a starter asks for confirmation first
+    confirm_close: bool,
+    /// A pane switched to another ticker drops its size filter and ticksize
+    ticker_switch_reset: bool,
+    /// Heatmap buckets of all layouts together get evicted early past this many megabytes, 0 is no limit
+    memory_budget_mb: u32,
+    hotkeys: Hotkeys,
+    quick_tickers: QuickTickers,
+    #[serde(with = "style::theme_name")]
+    theme: Theme,
+}
+impl Default for AppSettings {
+    fn default() -> Self {
+        AppSettings {
+            default_pane: None,
+            crosshair_style: CrosshairStyle::default(),
+            palette: ChartPalette::default(),
+            price_ruler: true,
+            crosshair_sync: false,
+            shared_volume_scale: false,
+            label_size: DEFAULT_LABEL_SIZE,
+            imbalance_alert: ImbalanceAlert::default(),
+            sound_alert: SoundAlert::default(),
+            resume_reconnect: true,
+            backfill_on_reconnect: true,
+            offline_banner: true,
+            warm_layouts: false,
+            pool_market_streams: false,
+            tick_rule: false,
+            idle_when_minimized: true,
+            connect_timeout_secs: DEFAULT_CONNECT_TIMEOUT_SECS,
+            reconnect_grace_secs: DEFAULT_RECONNECT_GRACE_SECS,
+            max_panes: DEFAULT_MAX_PANES,
+            pane_gaps: PaneGaps::default(),
+            focus_highlight: FocusHighlight::default(),
+            starter_defaults: StarterDefaults::default(),
+            sessions: Sessions::default(),
+            min_severity: Severity::default(),
+            confirm_close: true,
+            ticker_switch_reset: true,
+            memory_budget_mb: 0,
+            hotkeys: Hotkeys::default(),
+            quick_tickers: QuickTickers::default(),
+            theme: DEFAULT_THEME,
+        }
+    }
+}
+impl AppSettings {
+    /// Pulls values edited by hand in the saved file back into the ranges the UI allows
+    fn clamped(self) -> Self {
+        AppSettings {
+            connect_timeout_secs: self.connect_timeout_secs.clamp(MIN_CONNECT_TIMEOUT_SECS, MAX_CONNECT_TIMEOUT_SECS),
+            pane_gaps: self.pane_gaps.clamped(),
+            focus_highlight: self.focus_highlight.clamped(),
+            ..self
+        }
+    }
+
+    /// Hands the settings a dashboard keeps its own copy of over to it
+    fn apply_to(&self, dashboard: &mut Dashboard) {
+        dashboard.default_pane = self.default_pane.clone();
+        dashboard.set_crosshair_style(self.crosshair_style);
+        dashboard.set_palette(self.palette);
+        dashboard.set_price_ruler(self.price_ruler);
+        dashboard.set_crosshair_sync(self.crosshair_sync);
+        dashboard.set_shared_volume_scale(self.shared_volume_scale);
+        dashboard.set_label_size(self.label_size);
+        dashboard.set_imbalance_alert(self.imbalance_alert);
+        dashboard.set_max_panes(self.max_panes);
+        dashboard.set_pane_gaps(self.pane_gaps);
+        dashboard.set_focus_highlight(self.focus_highlight);
+        dashboard.set_starter_defaults(self.starter_defaults);
+        dashboard.set_sessions(self.sessions);
+        dashboard.set_confirm_close(self.confirm_close);
+        dashboard.set_ticker_switch_reset(self.ticker_switch_reset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn depth_stream(ticker: Ticker) -> StreamType {
+        StreamType::DepthAndTrades { exchange: Exchange::BinanceFutures, ticker }
+    }
+
+    fn streaming_dashboard(streams: Vec<StreamType>) -> Dashboard {
+        let mut dashboard = Dashboard::from_config(
+            Configuration::Pane(PaneState::new(Uuid::new_v4(), streams, PaneSettings::default()))
+        );
+        dashboard.pane_streams = dashboard.get_all_diff_streams();
+
+        dashboard
+    }
+
+    fn state_with(layout: Dashboard) -> State {
+        let mut saved_state = SavedState::default();
+        saved_state.layouts.insert(LayoutId::Layout1, layout);
+
+        State::new(saved_state, window::Settings::default()).0
+    }
+
+    fn connection_count(state: &State) -> usize {
+        Exchange::ALL.iter()
+            .map(|exchange| {
+                let (connections, kline_streams) = state.stream_connections(*exchange);
+                connections.len() + usize::from(!kline_streams.is_empty())
+            })
+            .sum()
+    }
+
+    #[test]
+    fn binance_provider_subscribes_what_the_direct_calls_did() {
+        let pane = |streams: Vec<StreamType>, trade_mode| Box::new(Configuration::Pane(
+            PaneState::new(Uuid::new_v4(), streams, PaneSettings { trade_mode, ..PaneSettings::default() })
+        ));
+        let kline = |ticker, timeframe| StreamType::Kline { exchange: Exchange::BinanceFutures, ticker, timeframe };
+
+        let mut dashboard = Dashboard::from_config(Configuration::Split {
+            axis: pane_grid::Axis::Vertical,
+            ratio: 0.5,
+            a: pane(
+                vec![depth_stream(Ticker::ETHUSDT), depth_stream(Ticker::BTCUSDT), kline(Ticker::BTCUSDT, Timeframe::M1)],
+                TradeMode::Aggregated,
+            ),
+            b: pane(
+                vec![depth_stream(Ticker::BTCUSDT), kline(Ticker::BTCUSDT, Timeframe::M1), kline(Ticker::ETHUSDT, Timeframe::M5)],
+                TradeMode::Raw,
+            ),
+        });
+        dashboard.pane_streams = dashboard.get_all_diff_streams();
+        let state = state_with(dashboard);
+
+        assert!(Exchange::BinanceFutures.provider().supports_raw_trades());
+
+        // the direct calls were one `binance::market_data::connect_market_stream(ticker, trade_mode)` per depth ticker,
+        // raw if any pane on it asked for raw, and one `connect_kline_stream` with every distinct kline pair
+        let (connections, mut kline_streams) = state.stream_connections(Exchange::BinanceFutures);
+        assert_eq!(
+            connections.iter()
+                .map(|streams| streams.iter().map(|stream| (stream.ticker, stream.trade_mode)).collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            vec![vec![(Ticker::BTCUSDT, TradeMode::Raw)], vec![(Ticker::ETHUSDT, TradeMode::Aggregated)]]
+        );
+
+        kline_streams.sort_by_key(|stream| format!("{stream:?}"));
+        assert_eq!(kline_streams, vec![(Ticker::BTCUSDT, Timeframe::M1), (Ticker::ETHUSDT, Timeframe::M5)]);
+    }
+
+    #[test]
+    fn switching_to_an_empty_layout_drops_every_stream() {
+        let mut state = state_with(streaming_dashboard(vec![depth_stream(Ticker::BTCUSDT)]));
+        assert_eq!(connection_count(&state), 1);
+
+        let _layout_tasks = state.update(Message::LayoutSelected(LayoutId::Layout2));
+
+        assert_eq!(connection_count(&state), 0);
+    }
+
+    #[test]
+    fn market_events_reach_the_exchange_they_came_from() {
+        let mut state = state_with(Dashboard::default());
+        let stats = StreamStats { msgs_per_sec: 10.0, bytes_per_sec: 2048.0 };
+
+        for exchange in Exchange::ALL {
+            let _none = state.update(Message::MarketWsEvent(
+                StreamKind::DepthAndTrades,
+                MarketEvent(exchange, data_providers::Event::Stats("depth".to_string(), stats))
+            ));
         }
+        for exchange in Exchange::ALL {
+            assert_eq!(state.stream_stats.get(&(exchange, "depth".to_string())).map(|(stats, _)| *stats), Some(stats));
+        }
+
+        let _grace = state.update(Message::MarketWsEvent(
+            StreamKind::Kline,
+            MarketEvent(Exchange::BybitLinear, data_providers::Event::Disconnected("closed".to_string()))
+        ));
+        assert_eq!(state.disconnects.keys().collect::<Vec<_>>(), vec![&(Exchange::BybitLinear, StreamKind::Kline)]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn kline_reconnect_leaves_a_depth_outage_running() {
+        let mut state = state_with(Dashboard::default());
+        let event = |kind, event| Message::MarketWsEvent(kind, MarketEvent(Exchange::BinanceFutures, event));
+
+        let _grace = state.update(event(StreamKind::DepthAndTrades, data_providers::Event::Disconnected("closed".to_string())));
+        let _grace = state.update(event(StreamKind::Kline, data_providers::Event::Disconnected("closed".to_string())));
+        let _backfill = state.update(event(StreamKind::Kline, data_providers::Event::Connected));
+
+        assert_eq!(
+            state.disconnects.keys().collect::<Vec<_>>(),
+            vec![&(Exchange::BinanceFutures, StreamKind::DepthAndTrades)]
+        );
+    }
+
+    #[test]
+    fn pooled_tickers_share_one_connection() {
+        let mut state = state_with(streaming_dashboard(Ticker::ALL.map(depth_stream).to_vec()));
+        assert_eq!(connection_count(&state), Ticker::ALL.len());
+
+        state.settings.pool_market_streams = true;
+
+        let (connections, _) = state.stream_connections(Exchange::BinanceFutures);
+        assert_eq!(connections.len(), 1);
+        assert_eq!(
+            connections[0].iter().map(|stream| stream.ticker).collect::<Vec<_>>(),
+            Ticker::ALL.to_vec()
+        );
+    }
+
+    #[test]
+    fn settings_stay_top_level_keys_of_the_saved_file() {
+        let settings = AppSettings { crosshair_sync: true, ..AppSettings::default() };
+        let layouts = HashMap::from([(LayoutId::Layout1, SerializableDashboard::from(&Dashboard::default()))]);
+
+        let mut saved = serde_json::to_value(
+            SerializableState::from_parts(layouts, LayoutId::Layout1, vec![], None, None, HashMap::new(), settings)
+        ).unwrap();
+        assert_eq!(saved["crosshair_sync"], true);
+
+        // a file from before these settings existed
+        let object = saved.as_object_mut().unwrap();
+        object.remove("crosshair_sync");
+        object.remove("price_ruler");
+
+        let restored: SerializableState = serde_json::from_value(saved).unwrap();
+        assert!(!restored.settings.crosshair_sync);
+        assert!(restored.settings.price_ruler);
+    }
+}
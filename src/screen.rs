@@ -1,5 +1,7 @@
 pub mod dashboard;
 
+use serde::{Deserialize, Serialize};
+
 #[derive(Debug, Clone)]
 pub enum Notification {
     Error(String),
@@ -7,6 +9,53 @@ pub enum Notification {
     Warn(String),
 }
 
+impl Notification {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Notification::Info(_) => Severity::Info,
+            Notification::Warn(_) => Severity::Warn,
+            Notification::Error(_) => Severity::Error,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        match self {
+            Notification::Info(text) | Notification::Warn(text) | Notification::Error(text) => text,
+        }
+    }
+}
+
+/// How serious a notification is, ordered so lower ones can be filtered out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Deserialize, Serialize)]
+pub enum Severity {
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 3] = [Severity::Info, Severity::Warn, Severity::Error];
+
+    pub fn log_level(&self) -> log::Level {
+        match self {
+            Severity::Info => log::Level::Info,
+            Severity::Warn => log::Level::Warn,
+            Severity::Error => log::Level::Error,
+        }
+    }
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Info => write!(f, "Info"),
+            Severity::Warn => write!(f, "Warn"),
+            Severity::Error => write!(f, "Error"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Error {
     FetchError(String),
@@ -1,19 +1,20 @@
 pub mod pane;
+mod export;
 
 use futures::TryFutureExt;
-use pane::SerializablePane;
+use pane::{SerializablePane, StarterDefaults};
 pub use pane::{Uuid, PaneState, PaneContent, PaneSettings};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    charts::{candlestick::CandlestickChart, footprint::FootprintChart, heatmap::HeatmapChart, timeandsales::TimeAndSales, Message as ChartMessage}, data_providers::{
-        binance, bybit, Depth, Exchange, Kline, TickMultiplier, Ticker, Timeframe, Trade
+    charts::{candlestick::{CandlestickChart, GhostOffset, MaConfig, Sessions}, BarTint, ChartPalette, CrosshairStyle, PriceScale, footprint::FootprintChart, heatmap::{HeatmapChart, RadiusScale, SpreadUnit, VolumeNorm, VolumeStyle}, timeandsales::{NotionalTiers, TimeAndSales}, Message as ChartMessage, DEFAULT_LABEL_SIZE}, data_providers::{
+        self, Depth, Exchange, Kline, TickMultiplier, Ticker, Timeframe, Trade, TradeMode, DepthCadence
     }, modal, style, StreamType
 };
 
 use super::{Error, Notification};
 
-use std::{collections::{HashMap, HashSet}, rc::Rc};
+use std::{collections::{HashMap, HashSet, VecDeque}, rc::Rc};
 use iced::{widget::{button, container, pane_grid::{self, Configuration}, Column, PaneGrid, Text}, window, Alignment, Element, Length, Point, Size, Task};
 
 #[derive(Debug, Clone)]
@@ -21,10 +22,108 @@ pub enum Message {
     Pane(pane::Message),
     ErrorOccurred(Error),
     Notification(Notification),
+    /// Outcome of something the user asked for, shown in the app's notification bar
+    Notify(Notification),
     FetchEvent(Result<Vec<Kline>, String>, StreamType, Uuid),
     FetchDistributeKlines(StreamType, Result<Vec<Kline>, String>),
     FetchDistributeTicks(StreamType, Result<f32, String>),
+    GhostKlinesFetched(Result<Vec<Kline>, String>, StreamType, GhostOffset, Uuid),
     FetchForLayout,
+    MoveFocus(FocusMove),
+}
+
+/// Where keyboard navigation takes the pane focus
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum FocusMove {
+    /// Following pane in reading order, wrapping around
+    Next,
+    Previous,
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Fewest bars a resampled timeframe switch has to yield, otherwise klines get fetched
+const MIN_RESAMPLED_KLINES: usize = 100;
+
+/// Soft cap on panes per layout, splits past it are refused
+pub const DEFAULT_MAX_PANES: u32 = 12;
+pub const MAX_PANES_RANGE: std::ops::RangeInclusive<u32> = 4..=32;
+
+pub const PANE_SPACING_RANGE: std::ops::RangeInclusive<u32> = 0..=16;
+pub const RESIZE_HANDLE_RANGE: std::ops::RangeInclusive<u32> = 2..=16;
+
+/// Gap between panes and the width of the grab area for resizing them, in pixels
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PaneGaps {
+    pub spacing: u32,
+    pub resize_handle: u32,
+}
+impl Default for PaneGaps {
+    fn default() -> Self {
+        Self {
+            spacing: 4,
+            resize_handle: 6,
+        }
+    }
+}
+impl PaneGaps {
+    /// Keeps hand edited values from the state file within what the sliders allow
+    pub fn clamped(self) -> Self {
+        Self {
+            spacing: self.spacing.clamp(*PANE_SPACING_RANGE.start(), *PANE_SPACING_RANGE.end()),
+            resize_handle: self.resize_handle.clamp(*RESIZE_HANDLE_RANGE.start(), *RESIZE_HANDLE_RANGE.end()),
+        }
+    }
+}
+
+
+/// Notifies when aggressive buy or sell volume dominates over a short rolling window
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ImbalanceAlert {
+    pub enabled: bool,
+    pub ratio: f32,
+    pub window_secs: u32,
+    pub cooldown_secs: u32,
+}
+impl Default for ImbalanceAlert {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ratio: 3.0,
+            window_secs: 5,
+            cooldown_secs: 30,
+        }
+    }
+}
+
+/// Plays a tone when a single trade above the notional threshold prints
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SoundAlert {
+    pub enabled: bool,
+    /// Smallest trade notional that sounds, in thousands
+    pub threshold_k: u32,
+    pub cooldown_ms: u32,
+}
+impl Default for SoundAlert {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold_k: 100,
+            cooldown_ms: 500,
+        }
+    }
+}
+
+/// Aggressive (buy, sell) volume per depth update, within the alert window
+#[derive(Debug, Default)]
+struct TradeFlow {
+    buckets: VecDeque<(i64, f32, f32)>,
+    last_alert: Option<i64>,
 }
 
 pub struct Dashboard {
@@ -33,6 +132,29 @@ pub struct Dashboard {
     pub layout_lock: bool,
     pub pane_streams: HashMap<Exchange, HashMap<Ticker, HashSet<StreamType>>>,
     pub notification: Option<Notification>,
+    pub default_pane: Option<pane::PanePreset>,
+    crosshair_style: CrosshairStyle,
+    /// Colors of the charts that don't pick their own
+    palette: ChartPalette,
+    price_ruler: bool,
+    debug_overlay: bool,
+    label_size: f32,
+    imbalance_alert: ImbalanceAlert,
+    trade_flows: HashMap<(Exchange, Ticker), TradeFlow>,
+    max_panes: u32,
+    pane_gaps: PaneGaps,
+    starter_defaults: StarterDefaults,
+    /// Hovering a pane draws a guide at the same time on the panes linked to it
+    crosshair_sync: bool,
+    /// Linked kline charts scale their volume bars to the same max
+    shared_volume_scale: bool,
+    /// Closing a pane with content waits for confirmation
+    confirm_close: bool,
+    /// Switching a pane's ticker resets its size filter and ticksize
+    ticker_switch_reset: bool,
+    /// Hours of the sessions candlestick panes can shade
+    sessions: Sessions,
+    focus_highlight: style::FocusHighlight,
 }
 impl Dashboard {
     pub fn empty() -> Self {
@@ -46,55 +168,25 @@ impl Dashboard {
                     axis: pane_grid::Axis::Vertical,
                     ratio: 0.5,
                     a: Box::new(Configuration::Pane(
-                        PaneState { 
-                            id: Uuid::new_v4(), 
-                            show_modal: false, 
-                            stream: vec![],
-                            content: PaneContent::Starter,
-                            settings: PaneSettings::default(),
-                        })
+                        PaneState::new(Uuid::new_v4(), vec![], PaneSettings::default()))
                     ),
                     b: Box::new(Configuration::Pane(
-                        PaneState { 
-                            id: Uuid::new_v4(), 
-                            show_modal: false, 
-                            stream: vec![],
-                            content: PaneContent::Starter,
-                            settings: PaneSettings::default(),
-                        })
+                        PaneState::new(Uuid::new_v4(), vec![], PaneSettings::default()))
                     ),
                 }),
                 b: Box::new(Configuration::Split {
                     axis: pane_grid::Axis::Vertical,
                     ratio: 0.5,
                     a: Box::new(Configuration::Pane(
-                        PaneState { 
-                            id: Uuid::new_v4(), 
-                            show_modal: false, 
-                            stream: vec![],
-                            content: PaneContent::Starter,
-                            settings: PaneSettings::default(),
-                        })                      
+                        PaneState::new(Uuid::new_v4(), vec![], PaneSettings::default()))
                     ),
                     b: Box::new(Configuration::Pane(
-                        PaneState { 
-                            id: Uuid::new_v4(), 
-                            show_modal: false, 
-                            stream: vec![],
-                            content: PaneContent::Starter,
-                            settings: PaneSettings::default(),
-                        })
+                        PaneState::new(Uuid::new_v4(), vec![], PaneSettings::default()))
                     ),
                 }),
             }),
             b: Box::new(Configuration::Pane(
-                PaneState { 
-                    id: Uuid::new_v4(), 
-                    show_modal: false, 
-                    stream: vec![],
-                    content: PaneContent::Starter,
-                    settings: PaneSettings::default(),
-                })
+                PaneState::new(Uuid::new_v4(), vec![], PaneSettings::default()))
             ),
         };
         
@@ -104,6 +196,23 @@ impl Dashboard {
             layout_lock: false,
             pane_streams: HashMap::new(),
             notification: None,
+            default_pane: None,
+            crosshair_style: CrosshairStyle::default(),
+            palette: ChartPalette::default(),
+            price_ruler: true,
+            debug_overlay: false,
+            label_size: DEFAULT_LABEL_SIZE,
+            imbalance_alert: ImbalanceAlert::default(),
+            trade_flows: HashMap::new(),
+            max_panes: DEFAULT_MAX_PANES,
+            pane_gaps: PaneGaps::default(),
+            starter_defaults: StarterDefaults::default(),
+            crosshair_sync: false,
+            shared_volume_scale: false,
+            confirm_close: true,
+            ticker_switch_reset: true,
+            sessions: Sessions::default(),
+            focus_highlight: style::FocusHighlight::default(),
         }
     }
 
@@ -114,6 +223,23 @@ impl Dashboard {
             layout_lock: false,
             pane_streams: HashMap::new(),
             notification: None,
+            default_pane: None,
+            crosshair_style: CrosshairStyle::default(),
+            palette: ChartPalette::default(),
+            price_ruler: true,
+            debug_overlay: false,
+            label_size: DEFAULT_LABEL_SIZE,
+            imbalance_alert: ImbalanceAlert::default(),
+            trade_flows: HashMap::new(),
+            max_panes: DEFAULT_MAX_PANES,
+            pane_gaps: PaneGaps::default(),
+            starter_defaults: StarterDefaults::default(),
+            crosshair_sync: false,
+            shared_volume_scale: false,
+            confirm_close: true,
+            ticker_switch_reset: true,
+            sessions: Sessions::default(),
+            focus_highlight: style::FocusHighlight::default(),
         }
     }
 
@@ -138,22 +264,63 @@ impl Dashboard {
                         }
                     },
                     pane::Message::SplitPane(axis, pane) => {        
+                        if self.panes.len() >= self.max_panes as usize {
+                            let max_panes = self.max_panes;
+
+                            return Task::perform(
+                                async {},
+                                move |_| Message::Notify(Notification::Warn(
+                                    format!("Pane limit of {max_panes} reached, close a pane or raise the limit first")
+                                ))
+                            );
+                        }
+
+                        let new_pane_id = Uuid::new_v4();
+
                         let focus_pane = if let Some((new_pane, _)) = 
-                            self.panes.split(axis, pane, PaneState::new(Uuid::new_v4(), vec![], PaneSettings::default())) {
+                            self.panes.split(axis, pane, PaneState::new(new_pane_id, vec![], self.starter_defaults.pane_settings())) {
                                     Some(new_pane)
                                 } else {
                                     None
                                 };
         
-                        if Some(focus_pane).is_some() {
+                        if focus_pane.is_some() {
                             self.focus = focus_pane;
+
+                            return self.apply_default_pane(new_pane_id);
                         }
                     },
                     pane::Message::ClosePane(pane) => {
+                        if self.confirm_close {
+                            if let Some(pane_state) = self.panes.get_mut(pane) {
+                                if !matches!(pane_state.content, PaneContent::Starter) {
+                                    pane_state.pending_close = true;
+
+                                    return Task::none();
+                                }
+                            }
+                        }
+
                         if let Some((_, sibling)) = self.panes.close(pane) {
                             self.focus = Some(sibling);
                         }
                     },
+                    pane::Message::ConfirmClose(pane_id) => {
+                        let pane = self.panes.iter()
+                            .find(|(_, pane_state)| pane_state.id == pane_id)
+                            .map(|(pane, _)| *pane);
+
+                        if let Some((_, sibling)) = pane.and_then(|pane| self.panes.close(pane)) {
+                            self.focus = Some(sibling);
+                        }
+                    },
+                    pane::Message::CancelClose(pane_id) => {
+                        for (_, pane_state) in self.panes.iter_mut() {
+                            if pane_state.id == pane_id {
+                                pane_state.pending_close = false;
+                            }
+                        }
+                    },
                     pane::Message::MaximizePane(pane) => {
                         self.panes.maximize(pane);
                     },
@@ -161,17 +328,22 @@ impl Dashboard {
                         self.panes.restore();
                     },
                     pane::Message::TickerSelected(ticker, pane_id) => {
-                        if let Ok(settings) = self.get_pane_settings_mut(pane_id) {
-                            settings.selected_ticker = Some(ticker);
-                        }
+                        return self.set_linked_ticker(pane_id, ticker);
                     },
                     pane::Message::ExchangeSelected(exchange, pane_id) => {
                         if let Ok(settings) = self.get_pane_settings_mut(pane_id) {
                             settings.selected_exchange = Some(exchange);
                         }
                     },
-                    pane::Message::ReplacePane(pane_id) => {
-                        self.replace_new_pane(pane_id);
+                    pane::Message::StarterTimeframeSelected(timeframe, pane_id) => {
+                        if let Ok(settings) = self.get_pane_settings_mut(pane_id) {
+                            settings.selected_timeframe = Some(timeframe);
+                        }
+                    },
+                    pane::Message::ReplacePane(pane) => {
+                        if let Some(new_pane_id) = self.replace_new_pane(pane) {
+                            return self.apply_default_pane(new_pane_id);
+                        }
                     },
                     pane::Message::ShowModal(pane_id) => {
                         if let Some(pane) = self.panes.get_mut(pane_id) {
@@ -211,118 +383,448 @@ impl Dashboard {
                             }
                         }
                     },
-                    pane::Message::PaneContentSelected(content, pane_id, pane_stream) => {        
-                        let mut tasks = vec![];
-                            
-                        let pane_content = match content.as_str() {
-                            "Heatmap chart" => PaneContent::Heatmap(
-                                HeatmapChart::new(1.0)
-                            ),
-                            "Footprint chart" => {
-                                PaneContent::Footprint(
-                                    FootprintChart::new(1, 1.0, vec![], vec![])
-                                )
+                    pane::Message::PaneContentSelected(content, pane_id, pane_stream) => {
+                        return self.init_pane_content(&content, pane_id, pane_stream);
+                    },
+                    pane::Message::VolumeStyleSelected(volume_style, pane_id) => {
+                        if let Err(err) = self.set_pane_volume_style(pane_id, volume_style) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::MaxTradeMarkersChanged(pane_id, max_trade_markers) => {
+                        if let Err(err) = self.set_pane_max_trade_markers(pane_id, max_trade_markers) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::AggregateIntervalSelected(pane_id, interval) => {
+                        if let Err(err) = self.set_pane_aggregate_time(pane_id, interval.0) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::RadiusScaleSelected(pane_id, radius_scale) => {
+                        if let Err(err) = self.set_pane_radius_scale(pane_id, radius_scale) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::VolumeNormChanged(pane_id, volume_norm) => {
+                        if let Err(err) = self.set_pane_volume_norm(pane_id, volume_norm) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::DepthSmoothingChanged(pane_id, depth_smoothing) => {
+                        if let Err(err) = self.set_pane_depth_smoothing(pane_id, depth_smoothing) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::HighlightPullsToggled(pane_id, enabled) => {
+                        if let Err(err) = self.set_pane_highlight_pulls(pane_id, enabled) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::DepthCutoffToggled(pane_id, enabled) => {
+                        if let Err(err) = self.set_pane_depth_cutoff(pane_id, enabled) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::TradeTrailToggled(pane_id, enabled) => {
+                        if let Err(err) = self.set_pane_trade_trail(pane_id, Some(enabled), None) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::TrailThresholdChanged(pane_id, threshold) => {
+                        if let Err(err) = self.set_pane_trade_trail(pane_id, None, Some(threshold)) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::DepthBandsToggled(pane_id, enabled) => {
+                        if let Err(err) = self.set_pane_depth_bands(pane_id, Some(enabled), None) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::DepthBandChanged(pane_id, index, width) => {
+                        if let Err(err) = self.set_pane_depth_bands(pane_id, None, Some((index, width))) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::OffsetGuidesToggled(pane_id, enabled) => {
+                        if let Err(err) = self.set_pane_offset_guides(pane_id, Some(enabled), None) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::GuideTicksChanged(pane_id, ticks) => {
+                        if let Err(err) = self.set_pane_offset_guides(pane_id, None, Some(ticks)) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::AggTicksizeSelected(agg_tick_multiply, pane_id) => {
+                        if let Err(err) = self.set_pane_agg_ticksize(pane_id, agg_tick_multiply) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::LiveBarToggled(pane_id, enabled) => {
+                        if let Err(err) = self.set_pane_live_bar(pane_id, enabled) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::RightMarginChanged(pane_id, bars) => {
+                        if let Err(err) = self.set_pane_right_margin(pane_id, bars) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::BarCountdownToggled(pane_id, enabled) => {
+                        if let Err(err) = self.set_pane_bar_countdown(pane_id, enabled) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::PriceLineToggled(pane_id, enabled) => {
+                        if let Err(err) = self.set_pane_price_line(pane_id, enabled) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::ShowSessionsToggled(pane_id, enabled) => {
+                        if let Err(err) = self.set_pane_show_sessions(pane_id, enabled) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::VolumeHeatToggled(pane_id, enabled) => {
+                        if let Err(err) = self.set_pane_volume_heat(pane_id, enabled) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::MarkDojiToggled(pane_id, mark_doji) => {
+                        if let Err(err) = self.set_pane_mark_doji(pane_id, mark_doji) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::GhostOffsetSelected(pane_id, ghost_offset) => {
+                        if let Err(err) = self.set_pane_ghost_offset(pane_id, ghost_offset) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+
+                        return self.fetch_ghost_klines(|pane_state| pane_state.id == pane_id);
+                    },
+                    pane::Message::BarPatternsToggled(pane_id, enabled) => {
+                        if let Err(err) = self.set_pane_bar_patterns(pane_id, Some(enabled), None, None) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::InsideBarTintSelected(pane_id, tint) => {
+                        if let Err(err) = self.set_pane_bar_patterns(pane_id, None, Some(tint), None) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::OutsideBarTintSelected(pane_id, tint) => {
+                        if let Err(err) = self.set_pane_bar_patterns(pane_id, None, None, Some(tint)) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::MovingAverageSet(pane_id, slot, ma) => {
+                        if let Err(err) = self.set_pane_moving_average(pane_id, slot, ma) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::TradeModeSelected(trade_mode, pane_id) => {
+                        match self.get_pane_settings_mut(pane_id) {
+                            Ok(pane_settings) => {
+                                pane_settings.trade_mode = trade_mode;
                             },
-                            "Candlestick chart" => {
-                                PaneContent::Candlestick(
-                                    CandlestickChart::new(vec![], 1)
+                            Err(err) => {
+                                return Task::perform(
+                                    async { err },
+                                    move |err: Error| Message::ErrorOccurred(err)
                                 )
+                            }
+                        }
+                    },
+                    pane::Message::DepthCadenceSelected(depth_cadence, pane_id) => {
+                        match self.get_pane_settings_mut(pane_id) {
+                            Ok(pane_settings) => {
+                                pane_settings.depth_cadence = depth_cadence;
                             },
-                            "Time&Sales" => PaneContent::TimeAndSales(
-                                TimeAndSales::new()
-                            ),
-                            _ => return Task::none(),
-                        };
-        
-                        // set pane's stream and content identifiers
-                        if let Err(err) = self.set_pane_content(pane_id, pane_content) {
-                            log::error!("Failed to set pane content: {}", err);
-                        } else {
-                            log::info!("Pane content set: {content}");
+                            Err(err) => {
+                                return Task::perform(
+                                    async { err },
+                                    move |err: Error| Message::ErrorOccurred(err)
+                                )
+                            }
                         }
-                        
-                        if let Err(err) = self.set_pane_stream(pane_id, pane_stream.to_vec()) {
-                            log::error!("Failed to set pane stream: {err}");
-                        } else {
-                            log::info!("Pane stream set: {pane_stream:?}");
-                        }
-                    
-                        // prepare unique streams for websocket
-                        for stream in pane_stream.iter() {
-                            match stream {
-                                StreamType::Kline { exchange, ticker, .. } | StreamType::DepthAndTrades { exchange, ticker } => {
-                                    self.pane_streams
-                                        .entry(*exchange)
-                                        .or_default()
-                                        .entry(*ticker)
-                                        .or_default()
-                                        .insert(*stream);
-                                }
-                                _ => {}
+                    },
+                    pane::Message::DepthProfileWidthChanged(pane_id, width) => {
+                        if let Err(err) = self.set_pane_depth_profile_width(pane_id, width) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::SpreadUnitSelected(spread_unit, pane_id) => {
+                        if let Err(err) = self.set_pane_spread_unit(pane_id, spread_unit) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::PriceScaleSelected(price_scale, pane_id) => {
+                        if let Err(err) = self.set_pane_price_scale(pane_id, price_scale) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::PaletteSelected(pane_id, palette) => {
+                        if let Err(err) = self.set_pane_palette(pane_id, palette) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::PinDraftChanged(pane_id, min_input, max_input) => {
+                        for (_, pane_state) in self.panes.iter_mut() {
+                            if pane_state.id == pane_id {
+                                pane_state.pin_draft = (min_input, max_input);
+                                break;
                             }
                         }
-                    
-                        log::info!("{:?}", &self.pane_streams);
-        
-                        // get fetch tasks for pane's content
-                        if ["Footprint chart", "Candlestick chart", "Heatmap chart"].contains(&content.as_str()) {
-                            for stream in pane_stream.iter() {
-                                match stream {
-                                    StreamType::Kline { exchange, ticker, .. } => {
-                                        if ["Candlestick chart", "Footprint chart"].contains(&content.as_str()) {
-                                            tasks.push(create_fetch_klines_task(*stream, pane_id));
-                                            
-                                            if content == "Footprint chart" {
-                                                tasks.push(create_fetch_ticksize_task(exchange, ticker, pane_id));
-                                            }
-                                        }
-                                    },
-                                    StreamType::DepthAndTrades { exchange, ticker } => {
-                                        tasks.push(create_fetch_ticksize_task(exchange, ticker, pane_id));
-                                    },
-                                    _ => {}
-                                }
+                    },
+                    pane::Message::GridAnchorDraftChanged(pane_id, value) => {
+                        for (_, pane_state) in self.panes.iter_mut() {
+                            if pane_state.id == pane_id {
+                                pane_state.anchor_draft = value;
+                                break;
+                            }
+                        }
+                    },
+                    pane::Message::GridAnchorSubmitted(pane_id) => {
+                        if let Err(err) = self.set_pane_grid_anchor(pane_id) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::ExpandOnBreakoutToggled(pane_id, enabled) => {
+                        if let Err(err) = self.set_pane_expand_on_breakout(pane_id, enabled) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::PriceRangePinned(pane_id, pinned_range) => {
+                        if let Err(err) = self.set_pane_pinned_range(pane_id, pinned_range) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::VolumeRatioChanged(pane_id, volume_ratio) => {
+                        if let Err(err) = self.set_pane_volume_ratio(pane_id, volume_ratio) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::PriceStepSelected(pane_id, price_step) => {
+                        if let Err(err) = self.set_pane_price_step(pane_id, price_step) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::ToggleDepthSource(exchange, pane_id) => {
+                        if let Err(err) = self.toggle_depth_source(pane_id, exchange) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::TicksizeLockToggled(pane_id, locked) => {
+                        if let Ok(settings) = self.get_pane_settings_mut(pane_id) {
+                            settings.ticksize_locked = locked;
+                        }
+                    },
+                    pane::Message::AutoTicksizeToggled(pane_id, enabled) => {
+                        for (_, pane_state) in self.panes.iter_mut() {
+                            if pane_state.id == pane_id {
+                                pane_state.settings.auto_ticksize = enabled;
+                                pane_state.auto_ticksize_pending = enabled;
+                            }
+                        }
+                    },
+                    pane::Message::ConfirmTicksize(pane_id, regroup) => {
+                        let pending = self.panes.iter_mut()
+                            .find(|(_, pane_state)| pane_state.id == pane_id)
+                            .and_then(|(_, pane_state)| pane_state.pending_tick_multiply.take());
+
+                        if let Some(tick_multiply) = pending {
+                            if let Err(err) = self.set_pane_ticksize(pane_id, tick_multiply, regroup) {
+                                return Task::perform(
+                                    async { err },
+                                    move |err: Error| Message::ErrorOccurred(err)
+                                )
+                            }
+                        }
+                    },
+                    pane::Message::CancelTicksize(pane_id) => {
+                        for (_, pane_state) in self.panes.iter_mut() {
+                            if pane_state.id == pane_id {
+                                pane_state.pending_tick_multiply = None;
+                            }
+                        }
+                    },
+                    pane::Message::NotionalTiersChanged(pane_id, notional_tiers) => {
+                        if let Err(err) = self.set_pane_notional_tiers(pane_id, notional_tiers) {
+                            return Task::perform(
+                                async { err },
+                                move |err: Error| Message::ErrorOccurred(err)
+                            )
+                        }
+                    },
+                    pane::Message::Popout(pane) => {
+                        // the window itself is opened by the app, see `State::sync_popouts`
+                        if let Some(pane_state) = self.panes.get_mut(pane) {
+                            pane_state.settings.popout.get_or_insert_with(pane::PopoutWindow::default);
+                        }
+                    },
+                    pane::Message::Dock(pane_id) => {
+                        if let Ok(settings) = self.get_pane_settings_mut(pane_id) {
+                            settings.popout = None;
+                        }
+                    },
+                    pane::Message::ExportDepth(pane_id) => {
+                        return self.export_depth(pane_id);
+                    },
+                    pane::Message::ExportPaneData(pane_id) => {
+                        return self.export_pane_data(pane_id);
+                    },
+                    pane::Message::CompareTimeframes(pane, timeframes) => {
+                        match self.split_compare_timeframes(pane, timeframes) {
+                            Ok(task) => return task,
+                            Err(reason) => {
+                                return Task::perform(
+                                    async { Notification::Warn(reason) },
+                                    Message::Notify
+                                )
                             }
                         }
-                        
-                        return Task::batch(tasks)
                     },
                     pane::Message::TimeframeSelected(timeframe, pane_id) => {    
                         let mut tasks = vec![];
                 
                         match self.set_pane_timeframe(pane_id, timeframe) {
                             Ok(stream_type) => {
-                                if let StreamType::Kline { exchange, ticker, timeframe } = stream_type {
+                                if let StreamType::Kline { exchange, ticker, timeframe } = *stream_type {
                                     let stream = *stream_type;
-                    
-                                    match exchange {
-                                        Exchange::BinanceFutures => {
-                                            tasks.push(
-                                                Task::perform(
-                                                    binance::market_data::fetch_klines(*ticker, *timeframe)
-                                                        .map_err(|err| format!("{err}")),
-                                                    move |klines| Message::FetchEvent(klines, stream, pane_id)
-                                                )
-                                            );
-                                        },
-                                        Exchange::BybitLinear => {                                    
-                                            tasks.push(
-                                                Task::perform(
-                                                    bybit::market_data::fetch_klines(*ticker, *timeframe)
-                                                        .map_err(|err| format!("{err}")),
-                                                    move |klines| Message::FetchEvent(klines, stream, pane_id)
-                                                )
-                                            );
-                                        },
+
+                                    if let Some(klines) = self.resample_loaded_klines(exchange, ticker, timeframe) {
+                                        self.insert_klines_vec(&stream, &klines, pane_id);
+
+                                        tasks.push(self.fetch_ghost_klines(|pane_state| pane_state.id == pane_id));
+                                    } else {
+                                        tasks.push(
+                                            Task::perform(
+                                                exchange.provider().fetch_klines(ticker, timeframe)
+                                                    .map_err(|err| format!("{err}")),
+                                                move |klines| Message::FetchEvent(klines, stream, pane_id)
+                                            )
+                                        );
+            
+                                        tasks.push(
+                                            Task::perform(
+                                                async {},
+                                                move |_| Message::Notification(Notification::Info("Fetching for klines...".to_string()))
+                                            )
+                                        );
                                     }
         
-                                    tasks.push(
-                                        Task::perform(
-                                            async {},
-                                            move |_| Message::Notification(Notification::Info("Fetching for klines...".to_string()))
-                                        )
-                                    );
-        
                                     self.pane_streams = self.get_all_diff_streams();
                                 }
                             },
@@ -336,8 +838,29 @@ impl Dashboard {
         
                         return Task::batch(tasks)
                     },
-                    pane::Message::TicksizeSelected(tick_multiply, pane_id) => {                        
-                        match self.set_pane_ticksize(pane_id, tick_multiply) {
+                    pane::Message::TicksizeSelected(tick_multiply, pane_id) => {
+                        // heatmap history would be cleared, ask first
+                        for (_, pane_state) in self.panes.iter_mut() {
+                            if pane_state.id == pane_id {
+                                if pane_state.settings.ticksize_locked {
+                                    return Task::none();
+                                }
+
+                                // picking one by hand overrides the auto ticksize
+                                pane_state.settings.auto_ticksize = false;
+                                pane_state.auto_ticksize_pending = false;
+
+                                if let PaneContent::Heatmap(chart) = &pane_state.content {
+                                    if chart.has_data() {
+                                        pane_state.pending_tick_multiply = Some(tick_multiply);
+
+                                        return Task::none();
+                                    }
+                                }
+                            }
+                        }
+
+                        match self.set_pane_ticksize(pane_id, tick_multiply, false) {
                             Ok(_) => {
                             },
                             Err(err) => {            
@@ -349,10 +872,8 @@ impl Dashboard {
                         }
                     },
                     pane::Message::SetMinTickSize(pane_id, ticksize) => {        
-                        match self.get_pane_settings_mut(pane_id) {
-                            Ok(pane_settings) => {
-                                pane_settings.min_tick_size = Some(ticksize);
-                            },
+                        match self.set_pane_min_tick_size(pane_id, ticksize) {
+                            Ok(_) => {},
                             Err(err) => {
                                 return Task::perform(
                                     async { err },
@@ -369,6 +890,8 @@ impl Dashboard {
             Message::Notification(notification) => {
                 dbg!(notification);
             },
+            // the app takes these before they get here
+            Message::Notify(_) => {},
             Message::FetchEvent(klines, pane_stream, pane_id) => {
                 if let Some(notification) = &self.notification {
                     match notification {
@@ -383,6 +906,8 @@ impl Dashboard {
                     Ok(klines) => {
                         if let StreamType::Kline { .. } = pane_stream {
                             self.insert_klines_vec(&pane_stream, &klines, pane_id);
+
+                            return self.fetch_ghost_klines(|pane_state| pane_state.id == pane_id);
                         } else {
                             log::error!("Invalid stream type for klines: {pane_stream:?}");
                         }
@@ -401,6 +926,8 @@ impl Dashboard {
                         if let Err(err) = self.find_and_insert_klines(&stream_type, &klines) {
                             log::error!("{err}");
                         }
+
+                        return self.fetch_ghost_klines(|pane_state| pane_state.matches_stream(&stream_type));
                     },
                     Err(err) => {
                         log::error!("{err}");
@@ -419,6 +946,38 @@ impl Dashboard {
                     }
                 }
             },
+            Message::GhostKlinesFetched(klines, stream, ghost_offset, pane_id) => {
+                match klines {
+                    Ok(klines) => {
+                        for (_, pane_state) in self.panes.iter_mut() {
+                            // a timeframe or offset switch during the fetch makes these stale
+                            if pane_state.id != pane_id
+                                || !pane_state.matches_stream(&stream)
+                                || pane_state.settings.ghost_offset != Some(ghost_offset)
+                            {
+                                continue;
+                            }
+
+                            if let PaneContent::Candlestick(chart) = &mut pane_state.content {
+                                chart.set_ghost_klines(klines);
+                            }
+                            break;
+                        }
+                    },
+                    Err(err) => {
+                        return Task::perform(
+                            async { err },
+                            move |err: String| Message::ErrorOccurred(Error::FetchError(err))
+                        )
+                    }
+                }
+            },
+            Message::MoveFocus(focus_move) => {
+                // a locked layout has no focus, a solo pane keeps it
+                if !self.layout_lock && self.panes.maximized().is_none() {
+                    self.focus = self.next_focus(focus_move).or(self.focus);
+                }
+            },
             Message::FetchForLayout => {
                 let mut tasks = vec![];
 
@@ -438,9 +997,41 @@ impl Dashboard {
         Task::none()
     }
 
+    /// Pane a focus move lands on, the first or last one while nothing is focused
+    fn next_focus(&self, focus_move: FocusMove) -> Option<pane_grid::Pane> {
+        let mut regions: Vec<_> = self.panes.layout()
+            .pane_regions(0.0, Size::new(4096.0, 4096.0))
+            .into_iter()
+            .collect();
+
+        // top to bottom, then left to right
+        regions.sort_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+
+        let order: Vec<pane_grid::Pane> = regions.into_iter().map(|(pane, _)| pane).collect();
+
+        let Some((index, focus)) = self.focus
+            .and_then(|focus| order.iter().position(|pane| *pane == focus).map(|index| (index, focus)))
+        else {
+            return match focus_move {
+                FocusMove::Previous => order.last().copied(),
+                _ => order.first().copied(),
+            };
+        };
+
+        match focus_move {
+            FocusMove::Next => order.get((index + 1) % order.len()).copied(),
+            FocusMove::Previous => order.get((index + order.len() - 1) % order.len()).copied(),
+            FocusMove::Left => self.panes.adjacent(focus, pane_grid::Direction::Left),
+            FocusMove::Right => self.panes.adjacent(focus, pane_grid::Direction::Right),
+            FocusMove::Up => self.panes.adjacent(focus, pane_grid::Direction::Up),
+            FocusMove::Down => self.panes.adjacent(focus, pane_grid::Direction::Down),
+        }
+    }
+
     pub fn view<'a>(&'a self) -> Element<'a, Message> {
         let focus = self.focus;
         let pane_locked = self.layout_lock;
+        let focus_highlight = self.focus_highlight;
         
         let mut pane_grid = PaneGrid::new(&self.panes, |id, pane, maximized| {
             let is_focused = !pane_locked && focus == Some(id);
@@ -449,14 +1040,15 @@ impl Dashboard {
                 self.panes.len(),
                 is_focused,
                 maximized,
+                focus_highlight,
             )
         })
-        .spacing(4);
+        .spacing(self.pane_gaps.spacing as f32);
     
         if !pane_locked {
             pane_grid = pane_grid
                 .on_click(pane::Message::PaneClicked)
-                .on_resize(6, pane::Message::PaneResized)
+                .on_resize(self.pane_gaps.resize_handle as f32, pane::Message::PaneResized)
                 .on_drag(pane::Message::PaneDragged);
         }
     
@@ -478,19 +1070,543 @@ impl Dashboard {
         )
     }
 
-    fn replace_new_pane(&mut self, pane: pane_grid::Pane) {
-        if let Some(pane) = self.panes.get_mut(pane) {
-            *pane = PaneState::new(Uuid::new_v4(), vec![], PaneSettings::default());
+    pub fn set_crosshair_style(&mut self, crosshair_style: CrosshairStyle) {
+        self.crosshair_style = crosshair_style;
+
+        for (_, pane_state) in self.panes.iter_mut() {
+            pane_state.content.set_crosshair_style(crosshair_style);
         }
     }
 
-    fn get_pane_settings_mut(&mut self, pane_id: Uuid) -> Result<&mut PaneSettings, Error> {
+    pub fn set_palette(&mut self, palette: ChartPalette) {
+        self.palette = palette;
+
         for (_, pane_state) in self.panes.iter_mut() {
-            if pane_state.id == pane_id {
-                return Ok(&mut pane_state.settings);
+            if pane_state.settings.palette.is_none() {
+                pane_state.content.set_palette(palette);
             }
         }
-        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    pub fn set_crosshair_sync(&mut self, enabled: bool) {
+        self.crosshair_sync = enabled;
+
+        if !enabled {
+            for (_, pane_state) in self.panes.iter_mut() {
+                pane_state.content.set_sync_crosshair_time(None);
+            }
+        }
+    }
+
+    pub fn set_shared_volume_scale(&mut self, enabled: bool) {
+        self.shared_volume_scale = enabled;
+
+        if enabled {
+            self.sync_volume_scales();
+        } else {
+            for (_, pane_state) in self.panes.iter_mut() {
+                pane_state.content.set_shared_volume_max(None);
+            }
+        }
+    }
+
+    /// Gives every linked kline chart the highest visible volume of its group, so bar heights compare
+    fn sync_volume_scales(&mut self) {
+        if !self.shared_volume_scale {
+            return;
+        }
+
+        let mut group_max: HashMap<pane::LinkGroup, f32> = HashMap::new();
+
+        for (_, pane_state) in self.panes.iter() {
+            if let (Some(link_group), Some(max_volume)) = (pane_state.settings.link_group, pane_state.content.visible_max_volume()) {
+                let shared = group_max.entry(link_group).or_insert(0.0);
+                *shared = shared.max(max_volume);
+            }
+        }
+
+        for (_, pane_state) in self.panes.iter_mut() {
+            let shared = pane_state.settings.link_group.and_then(|link_group| group_max.get(&link_group).copied());
+
+            pane_state.content.set_shared_volume_max(shared);
+        }
+    }
+
+    pub fn set_focus_highlight(&mut self, focus_highlight: style::FocusHighlight) {
+        self.focus_highlight = focus_highlight;
+    }
+
+    pub fn set_sessions(&mut self, sessions: Sessions) {
+        self.sessions = sessions;
+
+        for (_, pane_state) in self.panes.iter_mut() {
+            if let PaneContent::Candlestick(chart) = &mut pane_state.content {
+                chart.set_sessions(pane_state.settings.show_sessions.then_some(sessions));
+            }
+        }
+    }
+
+    /// Estimated bytes the buckets of the layout's heatmaps take
+    pub fn heatmap_memory(&self) -> usize {
+        self.panes.iter()
+            .filter_map(|(_, pane_state)| match &pane_state.content {
+                PaneContent::Heatmap(chart) => Some(chart.memory_estimate()),
+                _ => None,
+            })
+            .sum()
+    }
+
+    /// Drops the oldest `share` of every heatmap's buckets, giving back how many went
+    pub fn evict_heatmap_share(&mut self, share: f32) -> usize {
+        self.panes.iter_mut()
+            .filter_map(|(_, pane_state)| match &mut pane_state.content {
+                PaneContent::Heatmap(chart) => Some(chart.evict_share(share)),
+                _ => None,
+            })
+            .sum()
+    }
+
+    pub fn set_ticker_switch_reset(&mut self, enabled: bool) {
+        self.ticker_switch_reset = enabled;
+    }
+
+    pub fn set_confirm_close(&mut self, enabled: bool) {
+        self.confirm_close = enabled;
+
+        if !enabled {
+            for (_, pane_state) in self.panes.iter_mut() {
+                pane_state.pending_close = false;
+            }
+        }
+    }
+
+    pub fn set_price_ruler(&mut self, enabled: bool) {
+        self.price_ruler = enabled;
+
+        for (_, pane_state) in self.panes.iter_mut() {
+            pane_state.content.set_price_ruler(enabled);
+        }
+    }
+
+    pub fn set_debug_overlay(&mut self, enabled: bool) {
+        self.debug_overlay = enabled;
+
+        for (_, pane_state) in self.panes.iter_mut() {
+            pane_state.content.set_debug_overlay(enabled);
+        }
+    }
+
+    pub fn set_label_size(&mut self, label_size: f32) {
+        self.label_size = label_size;
+
+        for (_, pane_state) in self.panes.iter_mut() {
+            pane_state.content.set_label_size(label_size);
+        }
+    }
+
+    pub fn set_max_panes(&mut self, max_panes: u32) {
+        self.max_panes = max_panes;
+    }
+
+    pub fn set_pane_gaps(&mut self, pane_gaps: PaneGaps) {
+        self.pane_gaps = pane_gaps.clamped();
+    }
+
+    /// Also applied to starter panes nothing was picked in yet
+    pub fn set_starter_defaults(&mut self, starter_defaults: StarterDefaults) {
+        self.starter_defaults = starter_defaults;
+
+        for (_, pane_state) in self.panes.iter_mut() {
+            if matches!(pane_state.content, PaneContent::Starter) && pane_state.settings.selected_ticker.is_none() {
+                pane_state.settings.selected_exchange = starter_defaults.exchange;
+                pane_state.settings.selected_timeframe = Some(starter_defaults.timeframe);
+            }
+        }
+    }
+
+    /// Popped out panes have their own window, so they keep rendering
+    pub fn set_idle(&mut self, idle: bool) {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.settings.popout.is_none() {
+                pane_state.content.set_idle(idle);
+            }
+        }
+    }
+
+    pub fn has_stream(&self, stream_type: &StreamType) -> bool {
+        self.pane_streams.values()
+            .flat_map(|tickers| tickers.values())
+            .any(|streams| streams.contains(stream_type))
+    }
+
+    /// Raw trades are subscribed to if any pane on the stream asks for them
+    pub fn trade_mode(&self, exchange: Exchange, ticker: Ticker) -> TradeMode {
+        let stream = StreamType::DepthAndTrades { exchange, ticker };
+
+        let wants_raw = self.panes.iter().any(|(_, pane_state)| {
+            pane_state.matches_stream(&stream) && pane_state.settings.trade_mode == TradeMode::Raw
+        });
+
+        if wants_raw { TradeMode::Raw } else { TradeMode::Aggregated }
+    }
+
+    /// Every pane's streams and settings as text, meant to be pasted into bug reports
+    pub fn stream_config(&self) -> Result<String, Error> {
+        let mut config = Vec::new();
+
+        for (index, (_, pane_state)) in self.panes.iter().enumerate() {
+            let pane = serde_json::to_string_pretty(&SerializablePane::from(pane_state))
+                .map_err(|err| Error::ParseError(err.to_string()))?;
+
+            config.push(format!("Pane {}\n{pane}", index + 1));
+        }
+
+        Ok(config.join("\n\n"))
+    }
+
+    /// Fastest depth cadence asked for by a pane on the stream, `None` if no pane is on it
+    pub fn depth_cadence(&self, exchange: Exchange, ticker: Ticker) -> Option<DepthCadence> {
+        let stream = StreamType::DepthAndTrades { exchange, ticker };
+
+        self.panes.iter()
+            .filter(|(_, pane_state)| pane_state.matches_stream(&stream))
+            .map(|(_, pane_state)| pane_state.settings.depth_cadence)
+            .min()
+    }
+
+    /// Panes that should currently live in their own window
+    pub fn popped_out_panes(&self) -> Vec<(Uuid, pane::PopoutWindow)> {
+        self.panes.iter()
+            .filter_map(|(_, pane_state)| pane_state.settings.popout.map(|popout| (pane_state.id, popout)))
+            .collect()
+    }
+
+    /// Whether any pane shows something, a layout of only starter panes has nothing to lose
+    pub fn has_content(&self) -> bool {
+        self.panes.iter().any(|(_, pane_state)| pane_state.content.label().is_some())
+    }
+
+    pub fn get_pane_by_id(&self, pane_id: Uuid) -> Option<&PaneState> {
+        self.panes.iter()
+            .find(|(_, pane_state)| pane_state.id == pane_id)
+            .map(|(_, pane_state)| pane_state)
+    }
+
+    pub fn update_popout_window(&mut self, pane_id: Uuid, size: Option<Size>, position: Option<Point>) {
+        if let Ok(settings) = self.get_pane_settings_mut(pane_id) {
+            if let Some(popout) = settings.popout.as_mut() {
+                if let Some(size) = size {
+                    popout.size = (size.width, size.height);
+                }
+                if let Some(position) = position {
+                    popout.position = Some((position.x, position.y));
+                }
+            }
+        }
+    }
+
+    pub fn set_imbalance_alert(&mut self, imbalance_alert: ImbalanceAlert) {
+        self.imbalance_alert = imbalance_alert;
+
+        self.trade_flows.clear();
+    }
+
+    /// Keeps a rolling sum of aggressive buy/sell volume for the stream and
+    /// returns a notification when one side dominates by the alert's ratio
+    fn track_trade_flow(&mut self, exchange: Exchange, ticker: Ticker, time: i64, trades: &[Trade]) -> Option<Notification> {
+        let alert = self.imbalance_alert;
+
+        let flow = self.trade_flows.entry((exchange, ticker)).or_default();
+
+        let (buy_volume, sell_volume) = trades.iter().fold((0.0, 0.0), |(buy, sell), trade| {
+            if trade.is_sell { (buy, sell + trade.qty) } else { (buy + trade.qty, sell) }
+        });
+        flow.buckets.push_back((time, buy_volume, sell_volume));
+
+        let window_start = time - i64::from(alert.window_secs) * 1000;
+        while flow.buckets.front().is_some_and(|(bucket_time, _, _)| *bucket_time < window_start) {
+            flow.buckets.pop_front();
+        }
+
+        if flow.last_alert.is_some_and(|alert_time| time - alert_time < i64::from(alert.cooldown_secs) * 1000) {
+            return None;
+        }
+
+        let (buy_volume, sell_volume) = flow.buckets.iter()
+            .fold((0.0, 0.0), |(buy, sell), (_, bucket_buy, bucket_sell)| (buy + bucket_buy, sell + bucket_sell));
+
+        // a side with nothing against it still dominates, the epsilon keeps the ratio finite
+        let (side, volume, opposite) = if buy_volume >= sell_volume {
+            ("buying", buy_volume, sell_volume)
+        } else {
+            ("selling", sell_volume, buy_volume)
+        };
+
+        if volume <= 0.0 || volume / opposite.max(f32::EPSILON) < alert.ratio {
+            return None;
+        }
+
+        flow.last_alert = Some(time);
+
+        let strength = if opposite > 0.0 {
+            format!("{:.1}x", volume / opposite)
+        } else {
+            "one-sided".to_string()
+        };
+
+        Some(Notification::Warn(
+            format!("{ticker} ({exchange}): aggressive {side}, {strength} over {}s", alert.window_secs)
+        ))
+    }
+
+    /// Creates the chart for a content label on the given pane, registers its streams
+    /// and returns the fetch tasks it needs
+    fn init_pane_content(&mut self, content: &str, pane_id: Uuid, pane_stream: Vec<StreamType>) -> Task<Message> {
+        let mut tasks = vec![];
+
+        let mut pane_content = match content {
+            "Heatmap chart" => PaneContent::Heatmap(
+                HeatmapChart::new(1.0)
+            ),
+            "Footprint chart" => {
+                PaneContent::Footprint(
+                    FootprintChart::new(1, 1.0, vec![], vec![])
+                )
+            },
+            "Candlestick chart" => {
+                PaneContent::Candlestick(
+                    CandlestickChart::new(vec![], 1)
+                )
+            },
+            "Time&Sales" => PaneContent::TimeAndSales(
+                TimeAndSales::new()
+            ),
+            _ => return Task::none(),
+        };
+
+        pane_content.set_crosshair_style(self.crosshair_style);
+        pane_content.set_price_ruler(self.price_ruler);
+        pane_content.set_debug_overlay(self.debug_overlay);
+        pane_content.set_label_size(self.label_size);
+
+        if let Some((_, pane_state)) = self.panes.iter().find(|(_, pane_state)| pane_state.id == pane_id) {
+            pane_state.apply_settings(&mut pane_content, self.palette);
+
+            if let PaneContent::Candlestick(chart) = &mut pane_content {
+                chart.set_sessions(pane_state.settings.show_sessions.then_some(self.sessions));
+            }
+        }
+
+        // set pane's stream and content identifiers
+        if let Err(err) = self.set_pane_content(pane_id, pane_content) {
+            log::error!("Failed to set pane content: {}", err);
+        } else {
+            log::info!("Pane content set: {content}");
+        }
+
+        if let Err(err) = self.set_pane_stream(pane_id, pane_stream.clone()) {
+            log::error!("Failed to set pane stream: {err}");
+        } else {
+            log::info!("Pane stream set: {pane_stream:?}");
+        }
+
+        // prepare unique streams for websocket
+        for stream in pane_stream.iter() {
+            match stream {
+                StreamType::Kline { exchange, ticker, .. } | StreamType::DepthAndTrades { exchange, ticker } => {
+                    self.pane_streams
+                        .entry(*exchange)
+                        .or_default()
+                        .entry(*ticker)
+                        .or_default()
+                        .insert(*stream);
+                }
+                _ => {}
+            }
+        }
+
+        log::info!("{:?}", &self.pane_streams);
+
+        // get fetch tasks for pane's content
+        if ["Footprint chart", "Candlestick chart", "Heatmap chart"].contains(&content) {
+            for stream in pane_stream.iter() {
+                match stream {
+                    StreamType::Kline { exchange, ticker, .. } => {
+                        if ["Candlestick chart", "Footprint chart"].contains(&content) {
+                            tasks.push(create_fetch_klines_task(*stream, pane_id));
+
+                            tasks.push(create_fetch_ticksize_task(exchange, ticker, pane_id));
+                        }
+                    },
+                    StreamType::DepthAndTrades { exchange, ticker } => {
+                        tasks.push(create_fetch_ticksize_task(exchange, ticker, pane_id));
+                    },
+                    _ => {}
+                }
+            }
+        }
+
+        Task::batch(tasks)
+    }
+
+    /// Splits `pane` into two candlestick panes of the same ticker at different timeframes,
+    /// sharing a new link group
+    fn split_compare_timeframes(&mut self, pane: pane_grid::Pane, timeframes: (Timeframe, Timeframe)) -> Result<Task<Message>, String> {
+        let Some(pane_state) = self.panes.get(pane) else {
+            return Err("No pane found".to_string());
+        };
+
+        let (Some(exchange), Some(ticker)) = (
+            pane_state.settings.selected_exchange, 
+            pane_state.settings.selected_ticker
+        ) else {
+            return Err("Select an exchange and ticker on the pane first".to_string());
+        };
+
+        if self.panes.len() + 2 > self.max_panes as usize {
+            return Err(format!("Comparing would exceed the pane limit of {}", self.max_panes));
+        }
+
+        let link_group = self.next_link_group();
+
+        let mut settings = pane_state.settings;
+        settings.link_group = Some(link_group);
+
+        let mut tasks = vec![];
+        let mut split_target = pane;
+
+        for (timeframe, axis) in [
+            (timeframes.0, pane_grid::Axis::Horizontal), 
+            (timeframes.1, pane_grid::Axis::Vertical),
+        ] {
+            let pane_id = Uuid::new_v4();
+
+            let mut pane_settings = settings;
+            pane_settings.selected_timeframe = Some(timeframe);
+
+            let Some((new_pane, _)) = self.panes.split(
+                axis, split_target, PaneState::new(pane_id, vec![], pane_settings)
+            ) else {
+                return Err("Failed to split pane".to_string());
+            };
+            split_target = new_pane;
+
+            let label = "Candlestick chart";
+            
+            tasks.push(
+                self.init_pane_content(label, pane_id, pane::content_streams(label, exchange, ticker, timeframe))
+            );
+        }
+
+        self.focus = Some(split_target);
+
+        Ok(Task::batch(tasks))
+    }
+
+    fn next_link_group(&self) -> pane::LinkGroup {
+        let highest = self.panes.iter()
+            .filter_map(|(_, pane_state)| pane_state.settings.link_group)
+            .map(|group| group.0)
+            .max();
+
+        pane::LinkGroup(highest.map_or(1, |group| group.wrapping_add(1)))
+    }
+
+    /// Sets the ticker of a pane and of every pane linked to it, 
+    /// rebuilding the charts of the ones already showing content
+    fn set_linked_ticker(&mut self, pane_id: Uuid, ticker: Ticker) -> Task<Message> {
+        let link_group = self.panes.iter()
+            .find(|(_, pane_state)| pane_state.id == pane_id)
+            .and_then(|(_, pane_state)| pane_state.settings.link_group);
+
+        let mut to_rebuild = vec![];
+
+        for (_, pane_state) in self.panes.iter_mut() {
+            let is_linked = pane_state.id == pane_id 
+                || (link_group.is_some() && pane_state.settings.link_group == link_group);
+
+            if !is_linked || pane_state.settings.selected_ticker == Some(ticker) {
+                continue;
+            }
+
+            pane_state.settings.selected_ticker = Some(ticker);
+
+            if self.ticker_switch_reset && pane_state.content.label().is_some() {
+                pane_state.settings.reset_for_ticker();
+            }
+
+            if let (Some(label), Some(exchange)) = (
+                pane_state.content.label(), 
+                pane_state.settings.selected_exchange
+            ) {
+                let timeframe = pane_state.settings.selected_timeframe.unwrap_or(Timeframe::M1);
+
+                to_rebuild.push(
+                    (label, pane_state.id, pane::content_streams(label, exchange, ticker, timeframe))
+                );
+            }
+        }
+
+        if to_rebuild.is_empty() {
+            return Task::none();
+        }
+
+        let tasks: Vec<Task<Message>> = to_rebuild.into_iter()
+            .map(|(label, id, streams)| self.init_pane_content(label, id, streams))
+            .collect();
+
+        self.pane_streams = self.get_all_diff_streams();
+
+        Task::batch(tasks)
+    }
+
+    fn replace_new_pane(&mut self, pane: pane_grid::Pane) -> Option<Uuid> {
+        let pane = self.panes.get_mut(pane)?;
+
+        let new_pane_id = Uuid::new_v4();
+        *pane = PaneState::new(new_pane_id, vec![], self.starter_defaults.pane_settings());
+
+        Some(new_pane_id)
+    }
+
+    /// Populates a freshly created pane with the default preset, if one is set
+    fn apply_default_pane(&mut self, pane_id: Uuid) -> Task<Message> {
+        let Some(preset) = self.default_pane.clone() else {
+            return Task::none();
+        };
+
+        if let Ok(settings) = self.get_pane_settings_mut(pane_id) {
+            settings.selected_exchange = Some(preset.exchange);
+            settings.selected_ticker = Some(preset.ticker);
+            settings.selected_timeframe = Some(preset.timeframe);
+        }
+
+        let pane_stream = pane::content_streams(&preset.content, preset.exchange, preset.ticker, preset.timeframe);
+
+        self.init_pane_content(&preset.content, pane_id, pane_stream)
+    }
+
+    fn get_pane_settings_mut(&mut self, pane_id: Uuid) -> Result<&mut PaneSettings, Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                return Ok(&mut pane_state.settings);
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_min_tick_size(&mut self, pane_id: Uuid, min_tick_size: f32) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.min_tick_size = Some(min_tick_size);
+                pane_state.content.set_min_tick_size(min_tick_size);
+
+                pane_state.auto_ticksize_pending = pane_state.settings.auto_ticksize;
+
+                return Ok(());
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
     }
 
     fn set_pane_content(&mut self, pane_id: Uuid, content: PaneContent) -> Result<(), &str> {
@@ -512,84 +1628,820 @@ impl Dashboard {
                 return Ok(());
             }
         }
-        Err("No pane found")
+        Err("No pane found")
+    }
+
+    fn set_pane_ticksize(&mut self, pane_id: Uuid, new_tick_multiply: TickMultiplier, regroup: bool) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.tick_multiply = Some(new_tick_multiply);
+
+                if let Some(min_tick_size) = pane_state.settings.min_tick_size {
+                    match pane_state.content {
+                        PaneContent::Footprint(ref mut chart) => {
+                            chart.change_tick_size(
+                                new_tick_multiply.multiply_with_min_tick_size(min_tick_size)
+                            );
+                            
+                            return Ok(());
+                        },
+                        PaneContent::Heatmap(ref mut chart) => {
+                            let tick_size = new_tick_multiply.multiply_with_min_tick_size(min_tick_size);
+
+                            if regroup {
+                                chart.regroup_tick_size(tick_size);
+                            } else {
+                                chart.change_tick_size(tick_size);
+                            }
+                            
+                            return Ok(());
+                        },
+                        _ => {
+                            return Err(Error::UnknownError("No chart found to change ticksize".to_string()));
+                        }
+                    }
+                } else {
+                    return Err(Error::UnknownError("No min tick size found".to_string()));
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found to change ticksize".to_string()))
+    }
+    
+    fn set_pane_timeframe(&mut self, pane_id: Uuid, new_timeframe: Timeframe) -> Result<&StreamType, Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.selected_timeframe = Some(new_timeframe);
+
+                for stream_type in pane_state.stream.iter_mut() {
+                    match stream_type {
+                        StreamType::Kline { timeframe, .. } => {
+                            *timeframe = new_timeframe;
+
+                            match pane_state.content {
+                                PaneContent::Candlestick(_) => {
+                                    return Ok(stream_type);
+                                },
+                                PaneContent::Footprint(_) => {
+                                    return Ok(stream_type);
+                                },
+                                _ => {}
+                            }
+                        },
+                        _ => {}
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found to change tiemframe".to_string()))
+    }
+
+    fn set_pane_size_filter(&mut self, pane_id: Uuid, new_size_filter: f32) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.trade_size_filter = Some(new_size_filter);
+
+                match pane_state.content {
+                    PaneContent::Heatmap(ref mut chart) => {
+                        chart.set_size_filter(new_size_filter);
+
+                        return Ok(());
+                    },
+                    PaneContent::TimeAndSales(ref mut chart) => {
+                        chart.set_size_filter(new_size_filter);
+                        
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_volume_style(&mut self, pane_id: Uuid, volume_style: VolumeStyle) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.volume_style = volume_style;
+
+                match pane_state.content {
+                    PaneContent::Heatmap(ref mut chart) => {
+                        chart.set_volume_style(volume_style);
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_max_trade_markers(&mut self, pane_id: Uuid, max_trade_markers: u32) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.max_trade_markers = max_trade_markers;
+
+                match pane_state.content {
+                    PaneContent::Heatmap(ref mut chart) => {
+                        chart.set_max_trade_markers(max_trade_markers);
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_aggregate_time(&mut self, pane_id: Uuid, aggregate_ms: i64) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.aggregate_ms = aggregate_ms;
+
+                match pane_state.content {
+                    PaneContent::Heatmap(ref mut chart) => {
+                        chart.set_aggregate_time(aggregate_ms);
+
+                        pane_state.settings.visible_span_ms = chart.get_visible_span();
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_radius_scale(&mut self, pane_id: Uuid, radius_scale: RadiusScale) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.radius_scale = radius_scale;
+
+                match pane_state.content {
+                    PaneContent::Heatmap(ref mut chart) => {
+                        chart.set_radius_scale(radius_scale);
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_volume_norm(&mut self, pane_id: Uuid, volume_norm: VolumeNorm) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.volume_norm = volume_norm;
+
+                match pane_state.content {
+                    PaneContent::Heatmap(ref mut chart) => {
+                        chart.set_volume_norm(volume_norm);
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_depth_smoothing(&mut self, pane_id: Uuid, depth_smoothing: f32) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.depth_smoothing = depth_smoothing;
+
+                match pane_state.content {
+                    PaneContent::Heatmap(ref mut chart) => {
+                        chart.set_depth_smoothing(depth_smoothing);
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_right_margin(&mut self, pane_id: Uuid, bars: u32) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.right_margin = bars;
+
+                match pane_state.content {
+                    PaneContent::Candlestick(ref mut chart) => {
+                        chart.set_right_margin(bars);
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_bar_countdown(&mut self, pane_id: Uuid, enabled: bool) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.bar_countdown = enabled;
+
+                match pane_state.content {
+                    PaneContent::Candlestick(ref mut chart) => {
+                        chart.set_bar_countdown(enabled);
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    /// Whether a chart shows a countdown that needs redrawing every second
+    pub fn has_bar_countdown(&self) -> bool {
+        self.panes.iter().any(|(_, pane_state)| pane_state.content.has_bar_countdown())
+    }
+
+    fn set_pane_price_line(&mut self, pane_id: Uuid, enabled: bool) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.price_line = enabled;
+
+                match pane_state.content {
+                    PaneContent::Candlestick(ref mut chart) => {
+                        chart.set_price_line(enabled);
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_show_sessions(&mut self, pane_id: Uuid, enabled: bool) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.show_sessions = enabled;
+
+                match pane_state.content {
+                    PaneContent::Candlestick(ref mut chart) => {
+                        chart.set_sessions(enabled.then_some(self.sessions));
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_volume_heat(&mut self, pane_id: Uuid, enabled: bool) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.volume_heat = enabled;
+
+                match pane_state.content {
+                    PaneContent::Candlestick(ref mut chart) => {
+                        chart.set_volume_heat(enabled);
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_mark_doji(&mut self, pane_id: Uuid, mark_doji: bool) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.mark_doji = mark_doji;
+
+                match pane_state.content {
+                    PaneContent::Candlestick(ref mut chart) => {
+                        chart.set_mark_doji(mark_doji);
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_live_bar(&mut self, pane_id: Uuid, enabled: bool) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.live_bar = enabled;
+
+                match pane_state.content {
+                    PaneContent::Footprint(ref mut chart) => {
+                        chart.set_live_bar(enabled);
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_ghost_offset(&mut self, pane_id: Uuid, ghost_offset: Option<GhostOffset>) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.ghost_offset = ghost_offset;
+
+                match pane_state.content {
+                    PaneContent::Candlestick(ref mut chart) => {
+                        chart.set_ghost_offset(ghost_offset.map(|offset| offset.duration()));
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    /// Fetches the earlier session for candlestick panes with ghost candles on, among those `filter` picks
+    fn fetch_ghost_klines(&self, filter: impl Fn(&PaneState) -> bool) -> Task<Message> {
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let tasks = self.panes.iter()
+            .map(|(_, pane_state)| pane_state)
+            .filter(|pane_state| filter(pane_state) && matches!(pane_state.content, PaneContent::Candlestick(_)))
+            .filter_map(|pane_state| {
+                let ghost_offset = pane_state.settings.ghost_offset?;
+                let stream = *pane_state.stream.iter()
+                    .find(|stream| matches!(stream, StreamType::Kline { .. }))?;
+
+                let StreamType::Kline { exchange, ticker, timeframe } = stream else {
+                    return None;
+                };
+
+                let pane_id = pane_state.id;
+                let end_time = now - ghost_offset.duration().as_millis() as i64;
+
+                Some(Task::perform(
+                    exchange.provider().fetch_klines_until(ticker, timeframe, end_time)
+                        .map_err(|err| format!("{err}")),
+                    move |klines| Message::GhostKlinesFetched(klines, stream, ghost_offset, pane_id)
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        Task::batch(tasks)
+    }
+
+    fn set_pane_bar_patterns(
+        &mut self,
+        pane_id: Uuid,
+        enabled: Option<bool>,
+        inside: Option<BarTint>,
+        outside: Option<BarTint>,
+    ) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                if let Some(enabled) = enabled {
+                    pane_state.settings.bar_patterns = enabled;
+                }
+                if let Some(inside) = inside {
+                    pane_state.settings.inside_bar_tint = inside;
+                }
+                if let Some(outside) = outside {
+                    pane_state.settings.outside_bar_tint = outside;
+                }
+
+                match pane_state.content {
+                    PaneContent::Candlestick(ref mut chart) => {
+                        chart.set_bar_patterns(pane_state.settings.bar_patterns());
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_moving_average(&mut self, pane_id: Uuid, slot: usize, ma: Option<MaConfig>) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                let Some(entry) = pane_state.settings.moving_averages.get_mut(slot) else {
+                    return Err(Error::UnknownError("No moving average slot found".to_string()));
+                };
+                *entry = ma;
+
+                match pane_state.content {
+                    PaneContent::Candlestick(ref mut chart) => {
+                        chart.set_moving_averages(&pane_state.settings.moving_averages);
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_depth_bands(&mut self, pane_id: Uuid, enabled: Option<bool>, band: Option<(usize, f32)>) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                if let Some(enabled) = enabled {
+                    pane_state.settings.depth_bands = enabled;
+                }
+                if let Some((index, width)) = band {
+                    if let Some(band_width) = pane_state.settings.band_widths.get_mut(index) {
+                        *band_width = width;
+                    }
+                }
+
+                match pane_state.content {
+                    PaneContent::Heatmap(ref mut chart) => {
+                        chart.set_depth_bands(
+                            pane_state.settings.depth_bands.then_some(pane_state.settings.band_widths)
+                        );
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_trade_trail(&mut self, pane_id: Uuid, enabled: Option<bool>, threshold: Option<f32>) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                if let Some(enabled) = enabled {
+                    pane_state.settings.trade_trail = enabled;
+                }
+                if let Some(threshold) = threshold {
+                    pane_state.settings.trail_threshold = threshold;
+                }
+
+                match pane_state.content {
+                    PaneContent::Heatmap(ref mut chart) => {
+                        chart.set_trade_trail(
+                            pane_state.settings.trade_trail.then_some(pane_state.settings.trail_threshold)
+                        );
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_highlight_pulls(&mut self, pane_id: Uuid, enabled: bool) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.highlight_pulls = enabled;
+
+                match pane_state.content {
+                    PaneContent::Heatmap(ref mut chart) => {
+                        chart.set_highlight_pulls(enabled);
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_depth_cutoff(&mut self, pane_id: Uuid, enabled: bool) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.depth_cutoff = enabled;
+
+                match pane_state.content {
+                    PaneContent::Heatmap(ref mut chart) => {
+                        chart.set_depth_cutoff(enabled);
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_depth_profile_width(&mut self, pane_id: Uuid, width: f32) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.depth_profile_width = width;
+
+                match pane_state.content {
+                    PaneContent::Heatmap(ref mut chart) => {
+                        chart.set_depth_profile_width(width);
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_spread_unit(&mut self, pane_id: Uuid, spread_unit: SpreadUnit) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.spread_unit = spread_unit;
+
+                match pane_state.content {
+                    PaneContent::Heatmap(ref mut chart) => {
+                        chart.set_spread_unit(spread_unit);
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_price_scale(&mut self, pane_id: Uuid, price_scale: PriceScale) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.price_scale = price_scale;
+
+                match pane_state.content {
+                    PaneContent::Candlestick(ref mut chart) => {
+                        chart.set_price_scale(price_scale);
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_price_step(&mut self, pane_id: Uuid, price_step: Option<f32>) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.price_step = price_step;
+
+                match pane_state.content {
+                    PaneContent::Heatmap(_) | PaneContent::Footprint(_) | PaneContent::Candlestick(_) => {
+                        pane_state.content.set_price_step(price_step);
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_volume_ratio(&mut self, pane_id: Uuid, volume_ratio: f32) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.volume_ratio = volume_ratio;
+
+                match pane_state.content {
+                    PaneContent::Heatmap(_) | PaneContent::Footprint(_) | PaneContent::Candlestick(_) => {
+                        pane_state.content.set_volume_ratio(volume_ratio);
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_palette(&mut self, pane_id: Uuid, palette: Option<ChartPalette>) -> Result<(), Error> {
+        let default_palette = self.palette;
+
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.palette = palette;
+
+                match pane_state.content {
+                    PaneContent::Heatmap(_) | PaneContent::Footprint(_) | PaneContent::Candlestick(_) => {
+                        pane_state.content.set_palette(palette.unwrap_or(default_palette));
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_agg_ticksize(&mut self, pane_id: Uuid, agg_tick_multiply: Option<TickMultiplier>) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                pane_state.settings.agg_tick_multiply = agg_tick_multiply;
+
+                match pane_state.content {
+                    PaneContent::Footprint(ref mut chart) => {
+                        chart.set_agg_tick_size(pane_state.settings.agg_tick_size());
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    fn set_pane_offset_guides(&mut self, pane_id: Uuid, enabled: Option<bool>, ticks: Option<f32>) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                if let Some(enabled) = enabled {
+                    pane_state.settings.offset_guides = enabled;
+                }
+                if let Some(ticks) = ticks {
+                    pane_state.settings.guide_ticks = ticks;
+                }
+
+                match pane_state.content {
+                    PaneContent::Heatmap(_) | PaneContent::Candlestick(_) => {
+                        pane_state.content.set_offset_guides(
+                            pane_state.settings.offset_guides.then_some(pane_state.settings.guide_ticks)
+                        );
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
+    }
+
+    /// Applies the typed in grid anchor, an empty one resets it to 0
+    fn set_pane_grid_anchor(&mut self, pane_id: Uuid) -> Result<(), Error> {
+        for (_, pane_state) in self.panes.iter_mut() {
+            if pane_state.id == pane_id {
+                let draft = std::mem::take(&mut pane_state.anchor_draft);
+
+                let grid_anchor = if draft.trim().is_empty() {
+                    0.0
+                } else {
+                    match draft.trim().parse::<f32>() {
+                        Ok(price) if price.is_finite() && price >= 0.0 => price,
+                        _ => return Err(Error::UnknownError(format!("Invalid grid anchor: {draft}"))),
+                    }
+                };
+
+                pane_state.settings.grid_anchor = grid_anchor;
+
+                match pane_state.content {
+                    PaneContent::Heatmap(_) | PaneContent::Footprint(_) => {
+                        pane_state.content.set_grid_anchor(grid_anchor);
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
+                    }
+                }
+            }
+        }
+        Err(Error::UnknownError("No pane found".to_string()))
     }
 
-    fn set_pane_ticksize(&mut self, pane_id: Uuid, new_tick_multiply: TickMultiplier) -> Result<(), Error> {
+    fn set_pane_pinned_range(&mut self, pane_id: Uuid, pinned_range: Option<(f32, f32)>) -> Result<(), Error> {
         for (_, pane_state) in self.panes.iter_mut() {
             if pane_state.id == pane_id {
-                pane_state.settings.tick_multiply = Some(new_tick_multiply);
+                pane_state.settings.pinned_range = pinned_range;
+                pane_state.pin_draft = (String::new(), String::new());
 
-                if let Some(min_tick_size) = pane_state.settings.min_tick_size {
-                    match pane_state.content {
-                        PaneContent::Footprint(ref mut chart) => {
-                            chart.change_tick_size(
-                                new_tick_multiply.multiply_with_min_tick_size(min_tick_size)
-                            );
-                            
-                            return Ok(());
-                        },
-                        PaneContent::Heatmap(ref mut chart) => {
-                            chart.change_tick_size(
-                                new_tick_multiply.multiply_with_min_tick_size(min_tick_size)
-                            );
-                            
-                            return Ok(());
-                        },
-                        _ => {
-                            return Err(Error::UnknownError("No chart found to change ticksize".to_string()));
-                        }
+                match pane_state.content {
+                    PaneContent::Heatmap(_) | PaneContent::Candlestick(_) => {
+                        pane_state.content.set_pinned_range(pinned_range);
+
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
                     }
-                } else {
-                    return Err(Error::UnknownError("No min tick size found".to_string()));
                 }
             }
         }
-        Err(Error::UnknownError("No pane found to change ticksize".to_string()))
+        Err(Error::UnknownError("No pane found".to_string()))
     }
-    
-    fn set_pane_timeframe(&mut self, pane_id: Uuid, new_timeframe: Timeframe) -> Result<&StreamType, Error> {
+
+    fn set_pane_expand_on_breakout(&mut self, pane_id: Uuid, enabled: bool) -> Result<(), Error> {
         for (_, pane_state) in self.panes.iter_mut() {
             if pane_state.id == pane_id {
-                pane_state.settings.selected_timeframe = Some(new_timeframe);
+                pane_state.settings.expand_on_breakout = enabled;
 
-                for stream_type in pane_state.stream.iter_mut() {
-                    match stream_type {
-                        StreamType::Kline { timeframe, .. } => {
-                            *timeframe = new_timeframe;
+                match pane_state.content {
+                    PaneContent::Heatmap(_) | PaneContent::Candlestick(_) => {
+                        pane_state.content.set_expand_on_breakout(enabled);
 
-                            match pane_state.content {
-                                PaneContent::Candlestick(_) => {
-                                    return Ok(stream_type);
-                                },
-                                PaneContent::Footprint(_) => {
-                                    return Ok(stream_type);
-                                },
-                                _ => {}
-                            }
-                        },
-                        _ => {}
+                        return Ok(());
+                    },
+                    _ => {
+                        return Err(Error::UnknownError("No chart found".to_string()));
                     }
                 }
             }
         }
-        Err(Error::UnknownError("No pane found to change tiemframe".to_string()))
+        Err(Error::UnknownError("No pane found".to_string()))
     }
 
-    fn set_pane_size_filter(&mut self, pane_id: Uuid, new_size_filter: f32) -> Result<(), Error> {
+    fn set_pane_notional_tiers(&mut self, pane_id: Uuid, notional_tiers: NotionalTiers) -> Result<(), Error> {
         for (_, pane_state) in self.panes.iter_mut() {
             if pane_state.id == pane_id {
-                pane_state.settings.trade_size_filter = Some(new_size_filter);
+                pane_state.settings.notional_tiers = notional_tiers;
 
                 match pane_state.content {
-                    PaneContent::Heatmap(ref mut chart) => {
-                        chart.set_size_filter(new_size_filter);
-
-                        return Ok(());
-                    },
                     PaneContent::TimeAndSales(ref mut chart) => {
-                        chart.set_size_filter(new_size_filter);
-                        
+                        chart.set_notional_tiers(notional_tiers);
+
                         return Ok(());
                     },
                     _ => {
@@ -601,19 +2453,161 @@ impl Dashboard {
         Err(Error::UnknownError("No pane found".to_string()))
     }
 
+    /// Adds or removes a depth stream of another exchange on a heatmap pane
+    fn toggle_depth_source(&mut self, pane_id: Uuid, exchange: Exchange) -> Result<(), Error> {
+        let Some((_, pane_state)) = self.panes.iter_mut().find(|(_, pane_state)| pane_state.id == pane_id) else {
+            return Err(Error::UnknownError("No pane found".to_string()));
+        };
+
+        let Some(ticker) = pane_state.settings.selected_ticker else {
+            return Err(Error::PaneSetError("No ticker selected".to_string()));
+        };
+
+        let PaneContent::Heatmap(ref mut chart) = pane_state.content else {
+            return Err(Error::UnknownError("No chart found".to_string()));
+        };
+
+        let stream = StreamType::DepthAndTrades { exchange, ticker };
+
+        if pane_state.stream.contains(&stream) {
+            if pane_state.stream.len() == 1 {
+                return Err(Error::PaneSetError("A heatmap needs at least one depth source".to_string()));
+            }
+            pane_state.stream.retain(|s| *s != stream);
+        } else {
+            pane_state.stream.push(stream);
+        }
+
+        chart.reset_sources();
+
+        self.pane_streams = self.get_all_diff_streams();
+
+        Ok(())
+    }
+
+    /// Writes the latest heatmap depth of a pane to a JSON file next to the app
+    fn export_depth(&self, pane_id: Uuid) -> Task<Message> {
+        let result = self.panes.iter()
+            .find(|(_, pane_state)| pane_state.id == pane_id)
+            .ok_or_else(|| "No pane found".to_string())
+            .and_then(|(_, pane_state)| {
+                let PaneContent::Heatmap(chart) = &pane_state.content else {
+                    return Err("No chart found".to_string());
+                };
+                let snapshot = chart.latest_snapshot()
+                    .ok_or_else(|| "No depth collected yet".to_string())?;
+
+                let ticker = pane_state.settings.selected_ticker
+                    .map_or("unknown".to_string(), |ticker| ticker.to_string());
+                let file_path = format!("depth_{ticker}_{}.json", snapshot.time);
+
+                let json = serde_json::to_string_pretty(&snapshot)
+                    .map_err(|err| err.to_string())?;
+
+                crate::write_json_to_file(&json, &file_path)
+                    .map_err(|err| format!("Failed to write {file_path}: {err}"))?;
+
+                Ok(file_path)
+            });
+
+        let notification = match result {
+            Ok(file_path) => {
+                log::info!("Exported depth snapshot to {file_path}");
+
+                Notification::Info(format!("Depth exported to {file_path}"))
+            },
+            Err(err) => {
+                log::error!("Depth export failed: {err}");
+
+                Notification::Error(format!("Depth export failed: {err}"))
+            }
+        };
+
+        Task::perform(async { notification }, Message::Notify)
+    }
+
+    /// Writes the klines loaded in a candlestick or footprint pane to a CSV file next to the app
+    fn export_pane_data(&self, pane_id: Uuid) -> Task<Message> {
+        let notification = match self.write_pane_klines(pane_id) {
+            Ok(Some((file_path, count))) => {
+                log::info!("Exported {count} klines to {file_path}");
+
+                Notification::Info(format!("Klines exported to {file_path}"))
+            },
+            Ok(None) => Notification::Warn("No klines loaded yet to export".to_string()),
+            Err(err) => {
+                log::error!("Kline export failed: {err}");
+
+                Notification::Error(format!("Kline export failed: {err}"))
+            }
+        };
+
+        Task::perform(async { notification }, Message::Notify)
+    }
+
+    /// Path and row count of the written file, `None` if the pane has no klines yet
+    fn write_pane_klines(&self, pane_id: Uuid) -> Result<Option<(String, usize)>, String> {
+        let Some((_, pane_state)) = self.panes.iter().find(|(_, pane_state)| pane_state.id == pane_id) else {
+            return Err("No pane found".to_string());
+        };
+
+        let klines = match &pane_state.content {
+            PaneContent::Candlestick(chart) => chart.get_klines(),
+            PaneContent::Footprint(chart) => chart.get_klines(),
+            _ => return Err("No chart found".to_string()),
+        };
+
+        let Some(last_kline) = klines.last() else {
+            return Ok(None);
+        };
+
+        let ticker = pane_state.settings.selected_ticker
+            .map_or("unknown".to_string(), |ticker| ticker.to_string());
+        let timeframe = pane_state.settings.selected_timeframe
+            .map_or("unknown".to_string(), |timeframe| timeframe.to_string());
+        let file_path = format!("klines_{ticker}_{timeframe}_{}.csv", last_kline.time);
+
+        export::write_klines_csv(&klines, &file_path)
+            .map_err(|err| format!("Failed to write {file_path}: {err}"))?;
+
+        Ok(Some((file_path, klines.len())))
+    }
+
     pub fn find_and_insert_ticksizes(&mut self, stream_type: &StreamType, tick_sizes: f32) -> Result<(), &str> {
         let mut found_match = false;
 
         for (_, pane_state) in self.panes.iter_mut() {
             if pane_state.matches_stream(stream_type) {
                 match &mut pane_state.content {
-                    PaneContent::Footprint(_) => {
-                        pane_state.settings.min_tick_size = Some(tick_sizes);
+                    PaneContent::Footprint(_) | PaneContent::Heatmap(_) | PaneContent::Candlestick(_) => {
+                        let saved_min_tick_size = pane_state.settings.min_tick_size;
 
-                        found_match = true;
-                    },
-                    PaneContent::Heatmap(_) => {
                         pane_state.settings.min_tick_size = Some(tick_sizes);
+                        pane_state.content.set_min_tick_size(tick_sizes);
+
+                        // restored charts were built on the saved min tick, which the exchange may have changed since,
+                        // or on a placeholder when there was none saved yet
+                        if saved_min_tick_size != Some(tick_sizes) {
+                            if let Some(saved) = saved_min_tick_size {
+                                log::warn!("Min tick size of {stream_type:?} changed from {saved} to {tick_sizes}, adjusting the ticksize");
+                            }
+
+                            if let Some(tick_multiply) = pane_state.settings.tick_multiply {
+                                let tick_size = tick_multiply.multiply_with_min_tick_size(tick_sizes);
+
+                                match &mut pane_state.content {
+                                    PaneContent::Footprint(chart) => chart.change_tick_size(tick_size),
+                                    PaneContent::Heatmap(chart) => chart.regroup_tick_size(tick_size),
+                                    _ => {}
+                                }
+                            }
+                        }
+
+                        if let PaneContent::Footprint(chart) = &mut pane_state.content {
+                            chart.set_agg_tick_size(pane_state.settings.agg_tick_size());
+                        }
+
+                        pane_state.auto_ticksize_pending = pane_state.settings.auto_ticksize;
 
                         found_match = true;
                     },
@@ -629,32 +2623,23 @@ impl Dashboard {
         }
     }
 
-    pub fn find_and_insert_klines(&mut self, stream_type: &StreamType, klines: &Vec<Kline>) -> Result<(), &str> {
+    /// Merges fetched klines into the charts on the stream, bars with the same open time get replaced
+    /// and the rest of the series stays, so refetches after a disconnect don't wipe the history
+    pub fn find_and_insert_klines(&mut self, stream_type: &StreamType, klines: &[Kline]) -> Result<(), &str> {
         let mut found_match = false;
 
         for (_, pane_state) in self.panes.iter_mut() {
             if pane_state.matches_stream(stream_type) {
-                match stream_type {
-                    StreamType::Kline { timeframe, .. } => {
-                        let timeframe_u16 = timeframe.to_minutes();
-
-                        match &mut pane_state.content {
-                            PaneContent::Candlestick(chart) => {
-                                *chart = CandlestickChart::new(klines.to_vec(), timeframe_u16);
-
-                                found_match = true;
-                            },
-                            PaneContent::Footprint(chart) => {
-                                let raw_trades = chart.get_raw_trades();
-
-                                let tick_size = chart.get_tick_size();
+                match &mut pane_state.content {
+                    PaneContent::Candlestick(chart) => {
+                        chart.merge_klines(klines);
 
-                                *chart = FootprintChart::new(timeframe_u16, tick_size, klines.to_vec(), raw_trades);
+                        found_match = true;
+                    },
+                    PaneContent::Footprint(chart) => {
+                        chart.merge_klines(klines);
 
-                                found_match = true;
-                            },
-                            _ => {}
-                        }
+                        found_match = true;
                     },
                     _ => {}
                 }
@@ -662,12 +2647,42 @@ impl Dashboard {
         }
 
         if found_match {
+            self.sync_volume_scales();
+
             Ok(())
         } else {
             Err("No matching pane found for the stream")
         }
     }
 
+    /// Refetches the klines of the exchange's streams to fill in what a disconnect missed
+    pub fn backfill_klines(&self, exchange: Exchange) -> Task<Message> {
+        let pane_streams: HashMap<Exchange, HashMap<Ticker, HashSet<StreamType>>> = self.pane_streams.iter()
+            .filter(|(stream_exchange, _)| **stream_exchange == exchange)
+            .map(|(stream_exchange, streams)| (*stream_exchange, streams.clone()))
+            .collect();
+
+        Task::batch(klines_fetch_all_task(&pane_streams))
+    }
+
+    /// Klines for `timeframe` built from a finer timeframe some candlestick chart already holds,
+    /// if that leaves enough bars to skip fetching them
+    fn resample_loaded_klines(&self, exchange: Exchange, ticker: Ticker, timeframe: Timeframe) -> Option<Vec<Kline>> {
+        self.panes.iter()
+            .filter(|(_, pane_state)| pane_state.stream.iter().any(|stream| matches!(
+                stream, StreamType::Kline { exchange: ex, ticker: t, .. } if *ex == exchange && *t == ticker
+            )))
+            .filter_map(|(_, pane_state)| match &pane_state.content {
+                PaneContent::Candlestick(chart) => Timeframe::ALL.iter()
+                    .find(|tf| tf.to_minutes() == chart.get_timeframe())
+                    .filter(|tf| tf.to_minutes() < timeframe.to_minutes())
+                    .map(|tf| data_providers::resample_klines(&chart.get_klines(), *tf, timeframe)),
+                _ => None,
+            })
+            .filter(|klines| klines.len() >= MIN_RESAMPLED_KLINES)
+            .max_by_key(|klines| klines.len())
+    }
+
     pub fn insert_klines_vec(&mut self, stream_type: &StreamType, klines: &Vec<Kline>, pane_id: Uuid) {
         for (_, pane_state) in self.panes.iter_mut() {
             if pane_state.id == pane_id {
@@ -675,24 +2690,39 @@ impl Dashboard {
                     StreamType::Kline { timeframe, .. } => {
                         let timeframe_u16 = timeframe.to_minutes();
 
-                        match &mut pane_state.content {
-                            PaneContent::Candlestick(chart) => {
-                                *chart = CandlestickChart::new(klines.to_vec(), timeframe_u16);
+                        let mut content = match &pane_state.content {
+                            PaneContent::Candlestick(_) => {
+                                let mut chart = CandlestickChart::new(klines.to_vec(), timeframe_u16);
+                                chart.set_sessions(pane_state.settings.show_sessions.then_some(self.sessions));
+
+                                PaneContent::Candlestick(chart)
                             },
                             PaneContent::Footprint(chart) => {
                                 let raw_trades = chart.get_raw_trades();
 
                                 let tick_size = chart.get_tick_size();
 
-                                *chart = FootprintChart::new(timeframe_u16, tick_size, klines.to_vec(), raw_trades);
+                                PaneContent::Footprint(
+                                    FootprintChart::new(timeframe_u16, tick_size, klines.to_vec(), raw_trades)
+                                )
                             },
-                            _ => {}
-                        }
+                            _ => continue,
+                        };
+
+                        pane_state.apply_settings(&mut content, self.palette);
+                        content.set_crosshair_style(self.crosshair_style);
+                        content.set_price_ruler(self.price_ruler);
+                        content.set_debug_overlay(self.debug_overlay);
+                        content.set_label_size(self.label_size);
+
+                        pane_state.content = content;
                     },
                     _ => {}
                 }
             }
         }
+
+        self.sync_volume_scales();
     }
 
     pub fn update_latest_klines(&mut self, stream_type: &StreamType, kline: &Kline) -> Result<(), &str> {
@@ -705,11 +2735,15 @@ impl Dashboard {
                     PaneContent::Footprint(chart) => chart.update_latest_kline(kline),
                     _ => {}
                 }
+                pane_state.sync_pinned_range();
+
                 found_match = true;
             }
         }
     
         if found_match {
+            self.sync_volume_scales();
+
             Ok(())
         } else {
             self.pane_streams = self.get_all_diff_streams();
@@ -718,18 +2752,44 @@ impl Dashboard {
         }
     }
 
-    pub fn update_depth_and_trades(&mut self, stream_type: StreamType, depth_update_t: i64, depth: Depth, trades_buffer: Vec<Trade>) -> Result<(), &str> {
+    /// Feeds a depth and trades update to the matching panes, returning a flow alert if it raised one
+    pub fn update_depth_and_trades(&mut self, stream_type: StreamType, depth_update_t: i64, depth: Depth, trades_buffer: Vec<Trade>) -> Result<Option<Notification>, &str> {
         let mut found_match = false;
         
         let depth = Rc::new(depth);
 
         let trades_buffer = trades_buffer.into_boxed_slice();
 
+        let mut auto_ticksizes = vec![];
+
         for (_, pane_state) in self.panes.iter_mut() {
             if pane_state.matches_stream(&stream_type) {
+                if pane_state.auto_ticksize_pending && !pane_state.settings.ticksize_locked {
+                    let price = trades_buffer.last().map(|trade| trade.price);
+
+                    if let (Some(price), Some(min_tick_size)) = (price, pane_state.settings.min_tick_size) {
+                        pane_state.auto_ticksize_pending = false;
+
+                        let options = pane_state.content.tick_multipliers();
+
+                        if let Some(tick_multiply) = TickMultiplier::auto(price, min_tick_size, options) {
+                            if pane_state.settings.tick_multiply != Some(tick_multiply) {
+                                auto_ticksizes.push((pane_state.id, tick_multiply));
+                            }
+                        }
+                    }
+                }
+
                 match &mut pane_state.content {
                     PaneContent::Heatmap(chart) => {
-                        chart.insert_datapoint(&trades_buffer, depth_update_t, Rc::clone(&depth));
+                        match stream_type {
+                            StreamType::DepthAndTrades { exchange, ticker } if pane_state.stream.len() > 1 => {
+                                chart.insert_merged_datapoint((exchange, ticker), &trades_buffer, depth_update_t, Rc::clone(&depth));
+                            },
+                            _ => {
+                                chart.insert_datapoint(&trades_buffer, depth_update_t, Rc::clone(&depth));
+                            },
+                        }
                     },
                     PaneContent::Footprint(chart) => {
                         chart.insert_datapoint(&trades_buffer, depth_update_t);
@@ -739,13 +2799,28 @@ impl Dashboard {
                     },
                     _ => {}
                 }
+                pane_state.sync_pinned_range();
 
                 found_match = true;
             }
         }
 
+        for (pane_id, tick_multiply) in auto_ticksizes {
+            if let Err(err) = self.set_pane_ticksize(pane_id, tick_multiply, false) {
+                log::warn!("Couldn't apply auto ticksize: {err:?}");
+            }
+        }
+
+        let mut flow_alert = None;
+
+        if found_match && self.imbalance_alert.enabled {
+            if let StreamType::DepthAndTrades { exchange, ticker } = stream_type {
+                flow_alert = self.track_trade_flow(exchange, ticker, depth_update_t, &trades_buffer);
+            }
+        }
+
         if found_match {
-            Ok(())
+            Ok(flow_alert)
         } else {
             self.pane_streams = self.get_all_diff_streams();
 
@@ -754,31 +2829,54 @@ impl Dashboard {
     }
 
     fn update_chart_state(&mut self, pane_id: Uuid, chart_message: ChartMessage) -> Result<(), Error> {
-        for (_, pane_state) in self.panes.iter_mut() {
-            if pane_state.id == pane_id {
-                match pane_state.content {
-                    PaneContent::Heatmap(ref mut chart) => {
-                        chart.update(&chart_message);
+        let Some((_, pane_state)) = self.panes.iter_mut().find(|(_, pane_state)| pane_state.id == pane_id) else {
+            return Err(Error::UnknownError("No pane found to update its state".to_string()));
+        };
 
-                        return Ok(());
-                    },
-                    PaneContent::Footprint(ref mut chart) => {
-                        chart.update(&chart_message);
+        match pane_state.content {
+            PaneContent::Heatmap(ref mut chart) => {
+                chart.update(&chart_message);
 
-                        return Ok(());
-                    },
-                    PaneContent::Candlestick(ref mut chart) => {
-                        chart.update(&chart_message);
+                pane_state.settings.visible_span_ms = chart.get_visible_span();
+            },
+            PaneContent::Footprint(ref mut chart) => {
+                chart.update(&chart_message);
+            },
+            PaneContent::Candlestick(ref mut chart) => {
+                chart.update(&chart_message);
 
-                        return Ok(());
-                    },
-                    _ => {
-                        return Err(Error::UnknownError("No chart found".to_string()));
+                pane_state.settings.fib = chart.get_fib();
+            },
+            _ => {
+                return Err(Error::UnknownError("No chart found".to_string()));
+            }
+        }
+
+        if matches!(chart_message, ChartMessage::CrosshairToggle | ChartMessage::AutoscaleToggle) {
+            if let Some((crosshair, autoscale)) = pane_state.content.get_view_toggles() {
+                pane_state.settings.crosshair = Some(crosshair);
+                pane_state.settings.autoscale = Some(autoscale);
+            }
+        }
+
+        if self.crosshair_sync && matches!(chart_message, ChartMessage::CrosshairMoved(_)) {
+            if let Some(link_group) = pane_state.settings.link_group {
+                let time = pane_state.content.crosshair_time();
+
+                for (_, linked) in self.panes.iter_mut() {
+                    if linked.id != pane_id && linked.settings.link_group == Some(link_group) {
+                        linked.content.set_sync_crosshair_time(time);
                     }
                 }
             }
         }
-        Err(Error::UnknownError("No pane found to update its state".to_string()))
+
+        // panning and zooming change what's visible
+        if !matches!(chart_message, ChartMessage::CrosshairMoved(_) | ChartMessage::MeasureUpdate(_)) {
+            self.sync_volume_scales();
+        }
+
+        Ok(())
     }
 
     pub fn get_all_diff_streams(&mut self) -> HashMap<Exchange, HashMap<Ticker, HashSet<StreamType>>> {
@@ -819,20 +2917,11 @@ fn create_fetch_klines_task(
     pane_id: Uuid,
 ) -> Task<Message> {
     match stream {
-        StreamType::Kline { exchange, ticker, timeframe } => {
-            match exchange {
-                Exchange::BinanceFutures => Task::perform(
-                    binance::market_data::fetch_klines(ticker, timeframe)
-                        .map_err(|err| format!("{err}")),
-                    move |klines| Message::FetchEvent(klines, stream, pane_id),
-                ),
-                Exchange::BybitLinear => Task::perform(
-                    bybit::market_data::fetch_klines(ticker, timeframe)
-                        .map_err(|err| format!("{err}")),
-                    move |klines| Message::FetchEvent(klines, stream, pane_id),
-                ),
-            }
-        },
+        StreamType::Kline { exchange, ticker, timeframe } => Task::perform(
+            exchange.provider().fetch_klines(ticker, timeframe)
+                .map_err(|err| format!("{err}")),
+            move |klines| Message::FetchEvent(klines, stream, pane_id),
+        ),
         _ => Task::none(),
     }
 }
@@ -842,22 +2931,13 @@ fn create_fetch_ticksize_task(
     ticker: &Ticker,
     pane_id: Uuid,
 ) -> Task<Message> {
-    match exchange {
-        Exchange::BinanceFutures => Task::perform(
-            binance::market_data::fetch_ticksize(*ticker),
-            move |result| match result {
-                Ok(ticksize) => Message::Pane(pane::Message::SetMinTickSize(pane_id, ticksize)),
-                Err(err) => Message::ErrorOccurred(Error::FetchError(err.to_string())),
-            },
-        ),
-        Exchange::BybitLinear => Task::perform(
-            bybit::market_data::fetch_ticksize(*ticker),
-            move |result| match result {
-                Ok(ticksize) => Message::Pane(pane::Message::SetMinTickSize(pane_id, ticksize)),
-                Err(err) => Message::ErrorOccurred(Error::FetchError(err.to_string())),
-            },
-        ),
-    }
+    Task::perform(
+        exchange.provider().fetch_ticksize(*ticker),
+        move |result| match result {
+            Ok(ticksize) => Message::Pane(pane::Message::SetMinTickSize(pane_id, ticksize)),
+            Err(err) => Message::ErrorOccurred(Error::FetchError(err.to_string())),
+        },
+    )
 }
 
 fn klines_fetch_all_task(stream_types: &HashMap<Exchange, HashMap<Ticker, HashSet<StreamType>>>) -> Vec<Task<Message>> {
@@ -882,28 +2962,14 @@ fn klines_fetch_all_task(stream_types: &HashMap<Exchange, HashMap<Ticker, HashSe
             let timeframe = timeframe;
             let exchange = *exchange;
 
-            match exchange {
-                Exchange::BinanceFutures => {
-                    let fetch_klines = Task::perform(
-                        binance::market_data::fetch_klines(ticker, timeframe)
-                            .map_err(|err| format!("{err}")),
-                        move |klines| Message::FetchDistributeKlines(
-                            StreamType::Kline { exchange, ticker, timeframe }, klines
-                        )
-                    );
-                    tasks.push(fetch_klines);
-                },
-                Exchange::BybitLinear => {
-                    let fetch_klines = Task::perform(
-                        bybit::market_data::fetch_klines(ticker, timeframe)
-                            .map_err(|err| format!("{err}")),
-                        move |klines| Message::FetchDistributeKlines(
-                            StreamType::Kline { exchange, ticker, timeframe }, klines
-                        )
-                    );
-                    tasks.push(fetch_klines);
-                }
-            }
+            let fetch_klines = Task::perform(
+                exchange.provider().fetch_klines(ticker, timeframe)
+                    .map_err(|err| format!("{err}")),
+                move |klines| Message::FetchDistributeKlines(
+                    StreamType::Kline { exchange, ticker, timeframe }, klines
+                )
+            );
+            tasks.push(fetch_klines);
         }
     }
 
@@ -931,28 +2997,14 @@ fn ticksize_fetch_all_task(stream_types: &HashMap<Exchange, HashMap<Ticker, Hash
             let ticker = ticker;
             let exchange = *exchange;
 
-            match exchange {
-                Exchange::BinanceFutures => {
-                    let fetch_ticksize = Task::perform(
-                        binance::market_data::fetch_ticksize(ticker)
-                            .map_err(|err| format!("{err}")),
-                        move |ticksize| Message::FetchDistributeTicks(
-                            StreamType::DepthAndTrades { exchange, ticker }, ticksize
-                        )
-                    );
-                    tasks.push(fetch_ticksize);
-                },
-                Exchange::BybitLinear => {
-                    let fetch_ticksize = Task::perform(
-                        bybit::market_data::fetch_ticksize(ticker)
-                            .map_err(|err| format!("{err}")),
-                        move |ticksize| Message::FetchDistributeTicks(
-                            StreamType::DepthAndTrades { exchange, ticker }, ticksize
-                        )
-                    );
-                    tasks.push(fetch_ticksize);
-                }
-            }
+            let fetch_ticksize = Task::perform(
+                exchange.provider().fetch_ticksize(ticker)
+                    .map_err(|err| format!("{err}")),
+                move |ticksize| Message::FetchDistributeTicks(
+                    StreamType::DepthAndTrades { exchange, ticker }, ticksize
+                )
+            );
+            tasks.push(fetch_ticksize);
         }
     }
 
@@ -1008,4 +3060,90 @@ impl Default for SerializableDashboard {
             pane: SerializablePane::Starter,
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dashboard_with(settings: PaneSettings) -> (Dashboard, pane_grid::Pane) {
+        let dashboard = Dashboard::from_config(
+            Configuration::Pane(PaneState::new(Uuid::new_v4(), vec![], settings))
+        );
+        let pane = *dashboard.panes.iter().next().expect("one pane").0;
+
+        (dashboard, pane)
+    }
+
+    #[test]
+    fn compare_timeframes_builds_a_linked_candlestick_pair() {
+        let (mut dashboard, pane) = dashboard_with(PaneSettings {
+            selected_exchange: Some(Exchange::BinanceFutures),
+            selected_ticker: Some(Ticker::BTCUSDT),
+            ..PaneSettings::default()
+        });
+
+        let _fetches = dashboard.split_compare_timeframes(pane, (Timeframe::M1, Timeframe::M5))
+            .expect("split succeeds");
+
+        let pair: Vec<&PaneState> = dashboard.panes.iter()
+            .filter(|(other, _)| **other != pane)
+            .map(|(_, pane_state)| pane_state)
+            .collect();
+        assert_eq!(pair.len(), 2);
+
+        let timeframes: HashSet<Timeframe> = pair.iter()
+            .filter_map(|pane_state| pane_state.settings.selected_timeframe)
+            .collect();
+        assert_eq!(timeframes, HashSet::from([Timeframe::M1, Timeframe::M5]));
+
+        let link_group = pair[0].settings.link_group;
+        assert!(link_group.is_some());
+
+        for pane_state in pair {
+            assert_eq!(pane_state.content.label(), Some("Candlestick chart"));
+            assert_eq!(pane_state.settings.selected_ticker, Some(Ticker::BTCUSDT));
+            assert_eq!(pane_state.settings.link_group, link_group);
+            assert!(pane_state.stream.iter().any(|stream| matches!(
+                stream,
+                StreamType::Kline { ticker: Ticker::BTCUSDT, timeframe, .. }
+                    if Some(*timeframe) == pane_state.settings.selected_timeframe
+            )));
+        }
+    }
+
+    #[test]
+    fn compare_timeframes_needs_a_ticker() {
+        let (mut dashboard, pane) = dashboard_with(PaneSettings::default());
+
+        assert!(dashboard.split_compare_timeframes(pane, (Timeframe::M1, Timeframe::M5)).is_err());
+        assert_eq!(dashboard.panes.len(), 1);
+    }
+    fn trades(buy: f32, sell: f32) -> Vec<Trade> {
+        [(false, buy), (true, sell)].into_iter()
+            .filter(|(_, qty)| *qty > 0.0)
+            .map(|(is_sell, qty)| Trade { time: 0, is_sell, price: 100.0, qty })
+            .collect()
+    }
+
+    #[test]
+    fn trade_flow_alerts_on_one_sided_volume() {
+        let (mut dashboard, _) = dashboard_with(PaneSettings::default());
+        dashboard.set_imbalance_alert(ImbalanceAlert { enabled: true, ..ImbalanceAlert::default() });
+
+        let alert = dashboard.track_trade_flow(Exchange::BinanceFutures, Ticker::BTCUSDT, 0, &trades(5.0, 0.0));
+        assert!(matches!(alert, Some(Notification::Warn(text)) if text.contains("buying")));
+
+        // still cooling down
+        let alert = dashboard.track_trade_flow(Exchange::BinanceFutures, Ticker::BTCUSDT, 1_000, &trades(5.0, 0.0));
+        assert!(alert.is_none());
+    }
+
+    #[test]
+    fn trade_flow_stays_quiet_when_balanced_or_empty() {
+        let (mut dashboard, _) = dashboard_with(PaneSettings::default());
+        dashboard.set_imbalance_alert(ImbalanceAlert { enabled: true, ..ImbalanceAlert::default() });
+
+        assert!(dashboard.track_trade_flow(Exchange::BinanceFutures, Ticker::BTCUSDT, 0, &trades(2.0, 1.0)).is_none());
+        assert!(dashboard.track_trade_flow(Exchange::BybitLinear, Ticker::BTCUSDT, 0, &[]).is_none());
+    }
+}
@@ -0,0 +1,30 @@
+use std::{fs::File, io::{self, BufWriter, Write}};
+
+use crate::data_providers::Kline;
+
+/// Writes klines to a CSV file, one row per bar from oldest to newest
+pub fn write_klines_csv(klines: &[Kline], file_path: &str) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(file_path)?);
+
+    writeln!(writer, "time,datetime,open,high,low,close,volume,buy_volume,sell_volume")?;
+
+    for kline in klines {
+        let datetime = chrono::DateTime::from_timestamp_millis(kline.time as i64)
+            .map_or(String::new(), |datetime| datetime.format("%Y-%m-%d %H:%M:%S").to_string());
+
+        // without a taker split the total is all there is, the buy and sell columns stay empty
+        let (volume, buy_volume, sell_volume) = if kline.volume.0 == -1.0 {
+            (kline.volume.1, String::new(), String::new())
+        } else {
+            (kline.volume.0 + kline.volume.1, kline.volume.0.to_string(), kline.volume.1.to_string())
+        };
+
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{},{}",
+            kline.time, datetime, kline.open, kline.high, kline.low, kline.close, volume, buy_volume, sell_volume
+        )?;
+    }
+
+    writer.flush()
+}
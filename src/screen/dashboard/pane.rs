@@ -1,14 +1,14 @@
 use std::fmt;
 
-use iced::{alignment, widget::{button, container, pane_grid, pick_list, row, scrollable, text, tooltip, Column, Container, Row, Slider, Text}, Alignment, Element, Length, Renderer, Theme};
+use iced::{alignment, widget::{button, checkbox, container, pane_grid, pick_list, row, scrollable, text, text_input, tooltip, Column, Container, Row, Slider, Text}, Alignment, Element, Length, Renderer, Theme};
 use serde::{Deserialize, Serialize};
 pub use uuid::Uuid;
 
 use crate::{
     charts::{
-        self, candlestick::CandlestickChart, footprint::FootprintChart, heatmap::HeatmapChart, timeandsales::TimeAndSales
+        self, candlestick::{CandlestickChart, GhostOffset, MaConfig, MaKind}, BarTint, ChartPalette, CrosshairStyle, FibDraw, PriceScale, DEFAULT_VOLUME_RATIO, MAX_VOLUME_RATIO, PRICE_STEP_BASES, footprint::FootprintChart, heatmap::{AggregateInterval, HeatmapChart, RadiusScale, SpreadUnit, VolumeNorm, VolumeStyle}, timeandsales::{NotionalTiers, TimeAndSales}
     }, data_providers::{
-        Exchange, TickMultiplier, Ticker, Timeframe
+        Exchange, TickMultiplier, Ticker, Timeframe, TradeMode, DepthCadence
     }, modal, style::{self, Icon, ICON_FONT}, StreamType
 };
 
@@ -18,13 +18,17 @@ pub enum Message {
     PaneResized(pane_grid::ResizeEvent),
     PaneDragged(pane_grid::DragEvent),
     ClosePane(pane_grid::Pane),
+    ConfirmClose(Uuid),
+    CancelClose(Uuid),
     SplitPane(pane_grid::Axis, pane_grid::Pane),
     MaximizePane(pane_grid::Pane),
     Restore,
     TicksizeSelected(TickMultiplier, Uuid),
+    AggTicksizeSelected(Option<TickMultiplier>, Uuid),
     TimeframeSelected(Timeframe, Uuid),
     TickerSelected(Ticker, Uuid),
     ExchangeSelected(Exchange, Uuid),
+    StarterTimeframeSelected(Timeframe, Uuid),
     ShowModal(pane_grid::Pane),
     HideModal(Uuid),
     PaneContentSelected(String, Uuid, Vec<StreamType>),
@@ -32,6 +36,57 @@ pub enum Message {
     ChartUserUpdate(charts::Message, Uuid),
     SliderChanged(Uuid, f32),
     SetMinTickSize(Uuid, f32),
+    CompareTimeframes(pane_grid::Pane, (Timeframe, Timeframe)),
+    VolumeStyleSelected(VolumeStyle, Uuid),
+    VolumeNormChanged(Uuid, VolumeNorm),
+    RadiusScaleSelected(Uuid, RadiusScale),
+    AggregateIntervalSelected(Uuid, AggregateInterval),
+    MaxTradeMarkersChanged(Uuid, u32),
+    DepthSmoothingChanged(Uuid, f32),
+    DepthProfileWidthChanged(Uuid, f32),
+    HighlightPullsToggled(Uuid, bool),
+    DepthCutoffToggled(Uuid, bool),
+    TradeTrailToggled(Uuid, bool),
+    TrailThresholdChanged(Uuid, f32),
+    DepthBandsToggled(Uuid, bool),
+    DepthBandChanged(Uuid, usize, f32),
+    OffsetGuidesToggled(Uuid, bool),
+    GuideTicksChanged(Uuid, f32),
+    MarkDojiToggled(Uuid, bool),
+    VolumeHeatToggled(Uuid, bool),
+    ShowSessionsToggled(Uuid, bool),
+    PriceLineToggled(Uuid, bool),
+    BarCountdownToggled(Uuid, bool),
+    RightMarginChanged(Uuid, u32),
+    ExpandOnBreakoutToggled(Uuid, bool),
+    LiveBarToggled(Uuid, bool),
+    BarPatternsToggled(Uuid, bool),
+    GhostOffsetSelected(Uuid, Option<GhostOffset>),
+    InsideBarTintSelected(Uuid, BarTint),
+    OutsideBarTintSelected(Uuid, BarTint),
+    /// Sets or, with `None`, removes the moving average in a slot
+    MovingAverageSet(Uuid, usize, Option<MaConfig>),
+    PaletteSelected(Uuid, Option<ChartPalette>),
+    PinDraftChanged(Uuid, String, String),
+    PriceRangePinned(Uuid, Option<(f32, f32)>),
+    GridAnchorDraftChanged(Uuid, String),
+    GridAnchorSubmitted(Uuid),
+    TradeModeSelected(TradeMode, Uuid),
+    DepthCadenceSelected(DepthCadence, Uuid),
+    SpreadUnitSelected(SpreadUnit, Uuid),
+    PriceScaleSelected(PriceScale, Uuid),
+    ToggleDepthSource(Exchange, Uuid),
+    TicksizeLockToggled(Uuid, bool),
+    AutoTicksizeToggled(Uuid, bool),
+    ConfirmTicksize(Uuid, bool),
+    CancelTicksize(Uuid),
+    ExportDepth(Uuid),
+    ExportPaneData(Uuid),
+    VolumeRatioChanged(Uuid, f32),
+    PriceStepSelected(Uuid, Option<f32>),
+    Popout(pane_grid::Pane),
+    Dock(Uuid),
+    NotionalTiersChanged(Uuid, NotionalTiers),
 }
 
 #[derive(Debug)]
@@ -41,6 +96,16 @@ pub struct PaneState {
     pub stream: Vec<StreamType>,
     pub content: PaneContent,
     pub settings: PaneSettings,
+    /// Ticksize change waiting for confirmation, as it'd clear collected data
+    pub pending_tick_multiply: Option<TickMultiplier>,
+    /// Min and max prices being typed in before pinning the range
+    pub pin_draft: (String, String),
+    /// Grid anchor price being typed in, applied on enter
+    pub anchor_draft: String,
+    /// Auto ticksize still waiting for a price to pick from
+    pub auto_ticksize_pending: bool,
+    /// Close was pressed and is waiting for confirmation
+    pub pending_close: bool,
 }
 
 impl PaneState {
@@ -51,17 +116,113 @@ impl PaneState {
             stream,
             content: PaneContent::Starter,
             settings,
+            pending_tick_multiply: None,
+            pin_draft: (String::new(), String::new()),
+            anchor_draft: String::new(),
+            auto_ticksize_pending: false,
+            pending_close: false,
         }
     }
 
-    pub fn from_config(content: PaneContent, stream: Vec<StreamType>, settings: PaneSettings) -> Self {
-        Self {
+    pub fn from_config(mut content: PaneContent, stream: Vec<StreamType>, settings: PaneSettings) -> Self {
+        let mut pane_state = Self {
             id: Uuid::new_v4(),
             show_modal: false,
             stream,
-            content,
+            content: PaneContent::Starter,
             settings,
+            pending_tick_multiply: None,
+            pin_draft: (String::new(), String::new()),
+            anchor_draft: String::new(),
+            auto_ticksize_pending: false,
+            pending_close: false,
+        };
+
+        // the dashboard's palette is laid over once it's known
+        pane_state.apply_settings(&mut content, ChartPalette::default());
+        pane_state.content = content;
+
+        pane_state
+    }
+
+    /// Carries the pane's saved settings over to a chart, whenever one gets built or rebuilt for it,
+    /// `default_palette` being the dashboard's for panes without a palette of their own
+    pub fn apply_settings(&self, content: &mut PaneContent, default_palette: ChartPalette) {
+        let settings = &self.settings;
+
+        match content {
+            PaneContent::Candlestick(chart) => {
+                chart.set_price_scale(settings.price_scale);
+                chart.set_mark_doji(settings.mark_doji);
+                chart.set_volume_heat(settings.volume_heat);
+                chart.set_price_line(settings.price_line);
+                chart.set_bar_countdown(settings.bar_countdown);
+                chart.set_right_margin(settings.right_margin);
+                chart.set_bar_patterns(settings.bar_patterns());
+                chart.set_moving_averages(&settings.moving_averages);
+                chart.set_fib(settings.fib);
+                chart.set_ghost_offset(settings.ghost_offset.map(|offset| offset.duration()));
+                chart.set_view_toggles(settings.crosshair, settings.autoscale);
+            },
+            PaneContent::Footprint(chart) => {
+                chart.set_agg_tick_size(settings.agg_tick_size());
+                chart.set_live_bar(settings.live_bar);
+                chart.set_view_toggles(settings.crosshair, settings.autoscale);
+            },
+            PaneContent::Heatmap(chart) => {
+                chart.set_volume_style(settings.volume_style);
+                chart.set_volume_norm(settings.volume_norm);
+                chart.set_radius_scale(settings.radius_scale);
+                chart.set_max_trade_markers(settings.max_trade_markers);
+                chart.set_aggregate_time(settings.aggregate_ms);
+                chart.set_visible_span(settings.visible_span_ms);
+                chart.set_depth_smoothing(settings.depth_smoothing);
+                chart.set_depth_profile_width(settings.depth_profile_width);
+                chart.set_highlight_pulls(settings.highlight_pulls);
+                chart.set_depth_cutoff(settings.depth_cutoff);
+                chart.set_trade_trail(settings.trade_trail.then_some(settings.trail_threshold));
+                chart.set_depth_bands(settings.depth_bands.then_some(settings.band_widths));
+                chart.set_spread_unit(settings.spread_unit);
+                chart.set_view_toggles(settings.crosshair, settings.autoscale);
+            },
+            PaneContent::TimeAndSales(chart) => {
+                chart.set_notional_tiers(settings.notional_tiers);
+            },
+            PaneContent::Starter => {},
         }
+
+        content.set_pinned_range(settings.pinned_range);
+        content.set_expand_on_breakout(settings.expand_on_breakout);
+        content.set_grid_anchor(settings.grid_anchor);
+        content.set_offset_guides(settings.offset_guides.then_some(settings.guide_ticks));
+        content.set_volume_ratio(settings.volume_ratio);
+        content.set_price_step(settings.price_step);
+        content.set_palette(settings.palette.unwrap_or(default_palette));
+
+        if let Some(min_tick_size) = settings.min_tick_size {
+            content.set_min_tick_size(min_tick_size);
+        }
+    }
+
+    /// The pane's content without its title bar, also used by popped out windows
+    pub fn view_content(&self) -> Element<'_, Message> {
+        let content = match self.content {
+            PaneContent::Starter => view_starter(&self.id, &self.settings),
+
+            PaneContent::Heatmap(ref chart) => view_chart(self, chart),
+
+            PaneContent::Footprint(ref chart) => view_chart(self, chart),
+
+            PaneContent::Candlestick(ref chart) => view_chart(self, chart),
+
+            PaneContent::TimeAndSales(ref chart) => view_chart(self, chart),
+        };
+
+        if self.pending_close {
+            return view_close_confirm(content, self.id);
+        }
+
+        content
     }
 
     pub fn view<'a>(
@@ -70,6 +231,7 @@ impl PaneState {
         panes: usize,
         is_focused: bool,
         maximized: bool,
+        focus_highlight: style::FocusHighlight,
     ) -> iced::widget::pane_grid::Content<'a, Message, Theme, Renderer> {
         let stream_info = self.stream.iter().find_map(|stream: &StreamType| {
             match stream {
@@ -102,32 +264,34 @@ impl PaneState {
                     match exchange {
                         Exchange::BinanceFutures => text(char::from(Icon::BinanceLogo).to_string()).font(ICON_FONT),
                         Exchange::BybitLinear => text(char::from(Icon::BybitLogo).to_string()).font(ICON_FONT),
+                        // no logo in the icon font yet
+                        Exchange::Coinbase => text("CB"),
                     }
                 )
                 .push(Text::new(info));
+
+            if let Some(link_group) = self.settings.link_group {
+                stream_info_element = stream_info_element
+                    .push(text(char::from(Icon::Link).to_string()).font(ICON_FONT))
+                    .push(Text::new(link_group.to_string()));
+            }
         }
         
         let mut content: pane_grid::Content<'_, Message, _, Renderer> = 
             pane_grid::Content::new({
-                match self.content {
-                    PaneContent::Starter => view_starter(&self.id, &self.settings),
-
-                    PaneContent::Heatmap(ref chart) => view_chart(self, chart),
-
-                    PaneContent::Footprint(ref chart) => view_chart(self, chart),
-
-                    PaneContent::Candlestick(ref chart) => view_chart(self, chart),
-
-                    PaneContent::TimeAndSales(ref chart) => view_chart(self, chart),
+                if self.settings.popout.is_some() {
+                    view_popped_out(self.id)
+                } else {
+                    self.view_content()
                 }
             })
-            .style(
+            .style(move |theme| {
                 if is_focused {
-                    style::pane_focused
+                    style::pane_focused(theme, focus_highlight)
                 } else {
-                    style::pane_active
+                    style::pane_active(theme)
                 }
-            );
+            });
 
         let title_bar = pane_grid::TitleBar::new(stream_info_element)
             .controls(view_controls(
@@ -151,9 +315,36 @@ impl PaneState {
         content
     }
 
+    /// Content and stream selection of this pane, usable as a preset for new panes
+    pub fn preset(&self) -> Option<PanePreset> {
+        let label = self.content.label()?;
+
+        Some(PanePreset {
+            content: label.to_string(),
+            exchange: self.settings.selected_exchange?,
+            ticker: self.settings.selected_ticker?,
+            timeframe: self.settings.selected_timeframe.unwrap_or(Timeframe::M1),
+        })
+    }
+
+    /// Exchange and ticker of every depth stream feeding this pane
+    pub fn depth_sources(&self) -> Vec<(Exchange, Ticker)> {
+        self.stream.iter().filter_map(|stream| match stream {
+            StreamType::DepthAndTrades { exchange, ticker } => Some((*exchange, *ticker)),
+            _ => None,
+        }).collect()
+    }
+
     pub fn matches_stream(&self, stream_type: &StreamType) -> bool {
         self.stream.iter().any(|stream| stream == stream_type)
     }
+
+    /// Keeps a pinned range widened by a breakout, so it's what gets saved
+    pub fn sync_pinned_range(&mut self) {
+        if self.settings.expand_on_breakout && self.settings.pinned_range.is_some() {
+            self.settings.pinned_range = self.content.get_pinned_range();
+        }
+    }
 }
 
 trait ChartView {
@@ -166,6 +357,14 @@ impl ChartView for HeatmapChart {
 
         let underlay = self.view().map(move |message| Message::ChartUserUpdate(message, pane_id));
 
+        if let Some(tick_multiply) = pane.pending_tick_multiply {
+            let can_regroup = pane.settings.min_tick_size.is_some_and(|min_tick_size| {
+                self.can_regroup(tick_multiply.multiply_with_min_tick_size(min_tick_size))
+            });
+
+            return view_ticksize_confirm(underlay, pane_id, tick_multiply, can_regroup);
+        }
+
         if pane.show_modal {
             let size_filter = &self.get_size_filter();
 
@@ -189,6 +388,422 @@ impl ChartView for HeatmapChart {
                                 Text::new(format!("${size_filter}")).size(16)
                             )
                     )
+                    .push(
+                        Column::new()
+                            .align_x(Alignment::Center)
+                            .spacing(4)
+                            .push(
+                                tooltip(
+                                    Text::new("Trade Size Curve"),
+                                    "Square root and logarithmic keep mid-size trades apart when a few huge ones dominate",
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(
+                                pick_list(
+                                    &RadiusScale::ALL[..],
+                                    Some(pane.settings.radius_scale),
+                                    move |scale| Message::RadiusScaleSelected(pane_id, scale)
+                                )
+                                .style(style::picklist_primary)
+                                .menu_style(style::picklist_menu_primary)
+                            )
+                    )
+                    .push(
+                        Column::new()
+                            .align_x(Alignment::Center)
+                            .spacing(4)
+                            .push(
+                                tooltip(
+                                    Text::new("Aggregation"),
+                                    "Depth updates and trades within this are grouped into one column. \
+                                    Changing it clears what's been collected so far",
+                                    tooltip::Position::Top
+                                ).style(style::tooltip)
+                            )
+                            .push(
+                                pick_list(
+                                    &AggregateInterval::ALL[..],
+                                    Some(AggregateInterval(pane.settings.aggregate_ms)),
+                                    move |interval| Message::AggregateIntervalSelected(pane_id, interval)
+                                )
+                                .style(style::picklist_primary)
+                                .menu_style(style::picklist_menu_primary)
+                            )
+                    )
+                    .push(view_visible_span(pane_id, pane.settings.visible_span_ms, pane.settings.aggregate_ms))
+                    .push(view_max_trade_markers(pane_id, pane.settings.max_trade_markers))
+                    .push(
+                        Column::new()
+                            .align_x(Alignment::Center)
+                            .spacing(4)
+                            .push(Text::new("Volume Bars"))
+                            .push(
+                                pick_list(
+                                    &VolumeStyle::ALL[..],
+                                    Some(pane.settings.volume_style),
+                                    move |style| Message::VolumeStyleSelected(style, pane_id)
+                                )
+                                .style(style::picklist_primary)
+                                .menu_style(style::picklist_menu_primary)
+                            )
+                    )
+                    .push(view_volume_norm(pane_id, pane.settings.volume_norm))
+                    .push(
+                        Column::new()
+                            .align_x(Alignment::Center)
+                            .spacing(4)
+                            .push(Text::new("Depth Profile"))
+                            .push(
+                                Slider::new(
+                                    0.0..=HeatmapChart::MAX_DEPTH_PROFILE_WIDTH, 
+                                    pane.settings.depth_profile_width, 
+                                    move |value| Message::DepthProfileWidthChanged(pane_id, value)
+                                )
+                                .step(20.0)
+                            )
+                            .push(
+                                Text::new(
+                                    if pane.settings.depth_profile_width > 0.0 {
+                                        format!("Panel, {}px", pane.settings.depth_profile_width)
+                                    } else {
+                                        "Inline".to_string()
+                                    }
+                                ).size(16)
+                            )
+                    )
+                    .push_maybe(
+                        pane.stream.iter()
+                            .any(|stream| matches!(stream, StreamType::DepthAndTrades { exchange: Exchange::BinanceFutures, .. }))
+                            .then(|| view_trade_mode(pane_id, pane.settings.trade_mode))
+                    )
+                    .push_maybe(
+                        pane.stream.iter()
+                            .any(|stream| matches!(stream, StreamType::DepthAndTrades { exchange: Exchange::BinanceFutures, .. }))
+                            .then(|| view_depth_cadence(pane_id, pane.settings.depth_cadence))
+                    )
+                    .push(
+                        Column::new()
+                            .align_x(Alignment::Center)
+                            .spacing(4)
+                            .push(Text::new("Depth Smoothing"))
+                            .push(
+                                Slider::new(
+                                    0.0..=HeatmapChart::MAX_DEPTH_SMOOTHING, 
+                                    pane.settings.depth_smoothing, 
+                                    move |value| Message::DepthSmoothingChanged(pane_id, value)
+                                )
+                                .step(0.05)
+                            )
+                            .push(
+                                Text::new(
+                                    if pane.settings.depth_smoothing > 0.0 {
+                                        format!("{:.2}", pane.settings.depth_smoothing)
+                                    } else {
+                                        "Off".to_string()
+                                    }
+                                ).size(16)
+                            )
+                    )
+                    .push(
+                        tooltip(
+                            checkbox("Highlight pulled liquidity", pane.settings.highlight_pulls)
+                                .on_toggle(move |enabled| Message::HighlightPullsToggled(pane_id, enabled)),
+                            "Tints levels whose size dropped sharply since the previous update",
+                            tooltip::Position::Top
+                        ).style(style::tooltip)
+                    )
+                    .push(
+                        tooltip(
+                            checkbox("Cut off depth beyond view", pane.settings.depth_cutoff)
+                                .on_toggle(move |enabled| Message::DepthCutoffToggled(pane_id, enabled)),
+                            "Sums levels further than a screen off the visible range into one level past each edge, \
+                            lighter on deep books but liquidity bands only count what's kept",
+                            tooltip::Position::Top
+                        ).style(style::tooltip)
+                    )
+                    .push(view_trade_trail(pane_id, pane.settings.trade_trail, pane.settings.trail_threshold))
+                    .push(view_depth_bands(pane_id, pane.settings.depth_bands, pane.settings.band_widths))
+                    .push(view_offset_guides(pane_id, pane.settings.offset_guides, pane.settings.guide_ticks))
+                    .push(
+                        Column::new()
+                            .align_x(Alignment::Center)
+                            .spacing(4)
+                            .push(Text::new("Spread"))
+                            .push(
+                                pick_list(
+                                    &SpreadUnit::ALL[..],
+                                    Some(pane.settings.spread_unit),
+                                    move |unit| Message::SpreadUnitSelected(unit, pane_id)
+                                )
+                                .style(style::picklist_primary)
+                                .menu_style(style::picklist_menu_primary)
+                            )
+                    )
+                    .push(view_volume_ratio(pane_id, pane.settings.volume_ratio))
+                    .push(view_price_step(pane_id, pane.settings.price_step))
+                    .push(view_palette(pane_id, pane.settings.palette))
+                    .push(view_pinned_range(pane_id, &pane.settings, &pane.pin_draft))
+                    .push(view_grid_anchor(pane_id, pane.settings.grid_anchor, &pane.anchor_draft))
+                    .push(
+                        checkbox("Lock ticksize", pane.settings.ticksize_locked)
+                            .on_toggle(move |locked| Message::TicksizeLockToggled(pane_id, locked))
+                    )
+                    .push(view_auto_ticksize(pane_id, pane.settings.auto_ticksize))
+                    .push(
+                        Column::new()
+                            .align_x(Alignment::Center)
+                            .spacing(4)
+                            .push(Text::new("Merge Depth"))
+                            .push(
+                                Exchange::ALL.iter().fold(Column::new().spacing(4), |column, &exchange| {
+                                    let sources = pane.depth_sources();
+                                    let is_source = sources.iter().any(|(source, _)| *source == exchange);
+
+                                    let mut source_checkbox = checkbox(exchange.to_string(), is_source);
+                                    // the last remaining source can't be removed
+                                    if !(is_source && sources.len() == 1) {
+                                        source_checkbox = source_checkbox
+                                            .on_toggle(move |_| Message::ToggleDepthSource(exchange, pane_id));
+                                    }
+
+                                    column.push(source_checkbox)
+                                })
+                            )
+                    )
+                    .push(
+                        tooltip(
+                            button("Export depth").on_press(Message::ExportDepth(pane_id)),
+                            "Writes the latest depth and trades to a JSON file",
+                            tooltip::Position::Top
+                        ).style(style::tooltip)
+                    )
+                    .push( 
+                        Row::new()
+                            .spacing(10)
+                            .push(
+                                button("Close")
+                                .on_press(Message::HideModal(pane_id))
+                            )
+                    )
+            )
+            .width(Length::Shrink)
+            .padding(20)
+            .max_width(500)
+            .style(style::chart_modal);
+
+            return modal(underlay, signup, Message::HideModal(pane_id));
+        } else {
+            underlay
+        }
+    }
+}
+impl ChartView for FootprintChart {
+    fn view(&self, pane: &PaneState) -> Element<Message> {
+        let pane_id = pane.id;
+
+        let underlay = self.view().map(move |message| Message::ChartUserUpdate(message, pane_id));
+
+        if pane.show_modal {
+            let signup: Container<Message, Theme, _> = container(
+                Column::new()
+                    .spacing(10)
+                    .align_x(Alignment::Center)
+                    .push(
+                        Text::new("Footprint > Settings")
+                            .size(16)
+                    )
+                    .push(view_volume_ratio(pane_id, pane.settings.volume_ratio))
+                    .push(view_price_step(pane_id, pane.settings.price_step))
+                    .push(view_palette(pane_id, pane.settings.palette))
+                    .push(view_agg_ticksize(pane_id, pane.settings.agg_tick_multiply))
+                    .push(view_grid_anchor(pane_id, pane.settings.grid_anchor, &pane.anchor_draft))
+                    .push(
+                        checkbox("Lock ticksize", pane.settings.ticksize_locked)
+                            .on_toggle(move |locked| Message::TicksizeLockToggled(pane_id, locked))
+                    )
+                    .push(view_auto_ticksize(pane_id, pane.settings.auto_ticksize))
+                    .push(
+                        tooltip(
+                            checkbox("Fade the forming bar", pane.settings.live_bar)
+                                .on_toggle(move |enabled| Message::LiveBarToggled(pane_id, enabled)),
+                            "The bar still taking trades is drawn lighter as it fills in, until it closes",
+                            tooltip::Position::Top
+                        ).style(style::tooltip)
+                    )
+                    .push(
+                        tooltip(
+                            button("Export klines").on_press(Message::ExportPaneData(pane_id)),
+                            "Writes the loaded bars to a CSV file",
+                            tooltip::Position::Top
+                        ).style(style::tooltip)
+                    )
+                    .push( 
+                        Row::new()
+                            .spacing(10)
+                            .push(
+                                button("Close")
+                                .on_press(Message::HideModal(pane_id))
+                            )
+                    )
+            )
+            .width(Length::Shrink)
+            .padding(20)
+            .max_width(500)
+            .style(style::chart_modal);
+
+            modal(underlay, signup, Message::HideModal(pane_id))
+        } else {
+            underlay
+        }
+    }
+}
+impl ChartView for TimeAndSales {
+    fn view(&self, pane: &PaneState) -> Element<Message> {
+        let pane_id = pane.id;
+
+        let underlay = self.view();
+
+        if pane.show_modal {
+            let size_filter = &self.get_size_filter();
+
+            let signup = container(
+                Column::new()
+                    .spacing(10)
+                    .align_x(Alignment::Center)
+                    .push(
+                        Text::new("Time&Sales > Settings")
+                            .size(16)
+                    )
+                    .push(
+                        Column::new()
+                            .align_x(Alignment::Center)
+                            .push(Text::new("Size Filtering"))
+                            .push(
+                                Slider::new(0.0..=50000.0, *size_filter, move |value| Message::SliderChanged(pane_id, value))
+                                    .step(500.0)
+                            )
+                            .push(
+                                Text::new(format!("${size_filter}")).size(16)
+                            )
+                    )
+                    .push(view_notional_tiers(pane_id, pane.settings.notional_tiers))
+                    .push( 
+                        Row::new()
+                            .spacing(10)
+                            .push(
+                                button("Close")
+                                .on_press(Message::HideModal(pane_id))
+                            )
+                    )
+            )
+            .width(Length::Shrink)
+            .padding(20)
+            .max_width(500)
+            .style(style::chart_modal);
+
+            return modal(underlay, signup, Message::HideModal(pane_id));
+        } else {
+            underlay
+        }
+    }
+}
+impl ChartView for CandlestickChart {
+    fn view(&self, pane: &PaneState) -> Element<Message> {
+        let pane_id = pane.id;
+
+        let underlay = self.view().map(move |message| Message::ChartUserUpdate(message, pane_id));
+
+        if pane.show_modal {
+            let signup: Container<Message, Theme, _> = container(
+                Column::new()
+                    .spacing(10)
+                    .align_x(Alignment::Center)
+                    .push(
+                        Text::new("Candlestick > Settings")
+                            .size(16)
+                    )
+                    .push(
+                        Column::new()
+                            .align_x(Alignment::Center)
+                            .spacing(4)
+                            .push(Text::new("Price Scale"))
+                            .push(
+                                pick_list(
+                                    &PriceScale::ALL[..],
+                                    Some(self.get_price_scale()),
+                                    move |scale| Message::PriceScaleSelected(scale, pane_id)
+                                )
+                                .style(style::picklist_primary)
+                                .menu_style(style::picklist_menu_primary)
+                            )
+                    )
+                    .push(view_volume_ratio(pane_id, pane.settings.volume_ratio))
+                    .push(view_price_step(pane_id, pane.settings.price_step))
+                    .push(view_palette(pane_id, pane.settings.palette))
+                    .push(view_pinned_range(pane_id, &pane.settings, &pane.pin_draft))
+                    .push(
+                        tooltip(
+                            checkbox("Mark doji bars", pane.settings.mark_doji)
+                                .on_toggle(move |enabled| Message::MarkDojiToggled(pane_id, enabled)),
+                            "Bars with a body under 10% of their range get a neutral marker",
+                            tooltip::Position::Top
+                        ).style(style::tooltip)
+                    )
+                    .push(
+                        tooltip(
+                            checkbox("Volume heat", pane.settings.volume_heat)
+                                .on_toggle(move |enabled| Message::VolumeHeatToggled(pane_id, enabled)),
+                            "Shades the background brighter where the visible bars traded more volume",
+                            tooltip::Position::Top
+                        ).style(style::tooltip)
+                    )
+                    .push(
+                        tooltip(
+                            checkbox("Sessions", pane.settings.show_sessions)
+                                .on_toggle(move |enabled| Message::ShowSessionsToggled(pane_id, enabled)),
+                            "Shades the Asia, London and New York sessions behind intraday bars, their UTC hours are set in the layout settings",
+                            tooltip::Position::Top
+                        ).style(style::tooltip)
+                    )
+                    .push(
+                        tooltip(
+                            checkbox("Price line", pane.settings.price_line)
+                                .on_toggle(move |enabled| Message::PriceLineToggled(pane_id, enabled)),
+                            "Dashed line across the whole chart at the forming bar's close, colored by the last tick",
+                            tooltip::Position::Top
+                        ).style(style::tooltip)
+                    )
+                    .push(
+                        tooltip(
+                            checkbox("Bar close countdown", pane.settings.bar_countdown)
+                                .on_toggle(move |enabled| Message::BarCountdownToggled(pane_id, enabled)),
+                            "Time left until the forming bar closes, shown under the latest price label",
+                            tooltip::Position::Top
+                        ).style(style::tooltip)
+                    )
+                    .push(view_right_margin(pane_id, pane.settings.right_margin))
+                    .push(view_bar_patterns(pane_id, &pane.settings))
+                    .push(view_moving_averages(pane_id, &pane.settings))
+                    .push(view_ghost_offset(pane_id, pane.settings.ghost_offset))
+                    .push(view_offset_guides(pane_id, pane.settings.offset_guides, pane.settings.guide_ticks))
+                    .push(
+                        tooltip(
+                            button(Text::new("Clear Fibonacci").size(14))
+                                .on_press_maybe(pane.settings.fib.map(|_| {
+                                    Message::ChartUserUpdate(charts::Message::FibCleared, pane_id)
+                                })),
+                            "Draw one with the F button next to the time axis, drag its 0% or 100% line to adjust",
+                            tooltip::Position::Top
+                        ).style(style::tooltip)
+                    )
+                    .push(
+                        tooltip(
+                            button("Export klines").on_press(Message::ExportPaneData(pane_id)),
+                            "Writes the loaded bars to a CSV file",
+                            tooltip::Position::Top
+                        ).style(style::tooltip)
+                    )
                     .push( 
                         Row::new()
                             .spacing(10)
@@ -203,74 +818,737 @@ impl ChartView for HeatmapChart {
             .max_width(500)
             .style(style::chart_modal);
 
-            return modal(underlay, signup, Message::HideModal(pane_id));
-        } else {
-            underlay
-        }
+            modal(underlay, signup, Message::HideModal(pane_id))
+        } else {
+            underlay
+        }
+    }
+}
+
+fn view_notional_tiers<'a>(pane_id: Uuid, tiers: NotionalTiers) -> Element<'a, Message> {
+    let mut column = Column::new()
+        .align_x(Alignment::Center)
+        .spacing(4)
+        .push(
+            checkbox("Color by notional tier", tiers.enabled)
+                .on_toggle(move |enabled| Message::NotionalTiersChanged(pane_id, NotionalTiers { enabled, ..tiers }))
+        );
+
+    if tiers.enabled {
+        column = column
+            .push(Text::new(format!("Medium from ${}", tiers.medium)).size(14))
+            .push(
+                Slider::new(1000.0..=500_000.0, tiers.medium, move |medium| {
+                    Message::NotionalTiersChanged(pane_id, NotionalTiers { medium, large: tiers.large.max(medium), ..tiers })
+                })
+                .step(1000.0)
+            )
+            .push(Text::new(format!("Large from ${}", tiers.large)).size(14))
+            .push(
+                Slider::new(1000.0..=2_000_000.0, tiers.large, move |large| {
+                    Message::NotionalTiersChanged(pane_id, NotionalTiers { large, medium: tiers.medium.min(large), ..tiers })
+                })
+                .step(5000.0)
+            );
+    }
+
+    column.into()
+}
+
+fn view_trade_mode<'a>(pane_id: Uuid, trade_mode: TradeMode) -> Element<'a, Message> {
+    let mut column = Column::new()
+        .align_x(Alignment::Center)
+        .spacing(4)
+        .push(Text::new("Trades"))
+        .push(
+            pick_list(
+                &TradeMode::ALL[..],
+                Some(trade_mode),
+                move |mode| Message::TradeModeSelected(mode, pane_id)
+            )
+            .style(style::picklist_primary)
+            .menu_style(style::picklist_menu_primary)
+        );
+
+    if trade_mode == TradeMode::Raw {
+        column = column.push(
+            Text::new("Every fill is streamed, expect several times the message rate").size(12)
+        );
+    }
+
+    column.into()
+}
+
+fn view_right_margin<'a>(pane_id: Uuid, right_margin: u32) -> Element<'a, Message> {
+    Column::new()
+        .align_x(Alignment::Center)
+        .spacing(4)
+        .push(
+            tooltip(
+                Text::new("Right Margin"),
+                "Empty bars left ahead of the latest one, panning right still brings it to the edge",
+                tooltip::Position::Top
+            ).style(style::tooltip)
+        )
+        .push(
+            Slider::new(
+                CandlestickChart::RIGHT_MARGIN_RANGE, 
+                right_margin, 
+                move |value| Message::RightMarginChanged(pane_id, value)
+            )
+        )
+        .push(Text::new(format!("{right_margin} bars")).size(14))
+        .into()
+}
+
+fn view_visible_span<'a>(pane_id: Uuid, visible_span_ms: u32, aggregate_ms: i64) -> Element<'a, Message> {
+    let range = HeatmapChart::visible_span_range(aggregate_ms);
+    let secs = visible_span_ms / 1000;
+
+    Column::new()
+        .align_x(Alignment::Center)
+        .spacing(4)
+        .push(
+            tooltip(
+                Text::new("Visible Span"),
+                "How far back the chart's width reaches, scrolling over the chart adjusts it too",
+                tooltip::Position::Top
+            ).style(style::tooltip)
+        )
+        .push(
+            Slider::new(
+                (range.start() / 1000)..=(range.end() / 1000), 
+                secs, 
+                move |secs| Message::ChartUserUpdate(
+                    charts::Message::VisibleSpanChanged(secs * 1000), 
+                    pane_id
+                )
+            )
+            .step(5u32)
+        )
+        .push(
+            Text::new(if secs >= 60 { format!("{}m {:02}s", secs / 60, secs % 60) } else { format!("{secs}s") })
+                .size(14)
+        )
+        .into()
+}
+
+fn view_max_trade_markers<'a>(pane_id: Uuid, max_trade_markers: u32) -> Element<'a, Message> {
+    Column::new()
+        .align_x(Alignment::Center)
+        .spacing(4)
+        .push(
+            tooltip(
+                Text::new("Trade Markers per Bucket"),
+                "Only the largest trades of a bucket get their own circle, the rest are merged into one. \
+                Lower it if busy tickers make the heatmap stutter",
+                tooltip::Position::Top
+            ).style(style::tooltip)
+        )
+        .push(
+            Slider::new(
+                HeatmapChart::TRADE_MARKERS_RANGE, 
+                max_trade_markers, 
+                move |value| Message::MaxTradeMarkersChanged(pane_id, value)
+            )
+            .step(10u32)
+        )
+        .push(Text::new(max_trade_markers.to_string()).size(14))
+        .into()
+}
+
+fn view_depth_cadence<'a>(pane_id: Uuid, depth_cadence: DepthCadence) -> Element<'a, Message> {
+    Column::new()
+        .align_x(Alignment::Center)
+        .spacing(4)
+        .push(
+            tooltip(
+                Text::new("Depth Updates"),
+                "Slower updates ease the load with many panes, the heatmap's buckets get as coarse as the updates. \
+                The fastest asked for by any pane on the ticker is used",
+                tooltip::Position::Top
+            ).style(style::tooltip)
+        )
+        .push(
+            pick_list(
+                &DepthCadence::ALL[..],
+                Some(depth_cadence),
+                move |cadence| Message::DepthCadenceSelected(cadence, pane_id)
+            )
+            .style(style::picklist_primary)
+            .menu_style(style::picklist_menu_primary)
+        )
+        .into()
+}
+
+fn view_volume_norm<'a>(pane_id: Uuid, volume_norm: VolumeNorm) -> Element<'a, Message> {
+    let mut column = Column::new()
+        .align_x(Alignment::Center)
+        .spacing(4)
+        .push(Text::new("Volume Scale"))
+        .push(
+            pick_list(
+                VolumeNorm::options(volume_norm),
+                Some(volume_norm),
+                move |norm| Message::VolumeNormChanged(pane_id, norm)
+            )
+            .style(style::picklist_primary)
+            .menu_style(style::picklist_menu_primary)
+        );
+
+    match volume_norm {
+        VolumeNorm::VisibleMax => {},
+        VolumeNorm::Fixed(volume) => {
+            // log scale so both fractional and large contract sizes are reachable
+            column = column
+                .push(
+                    Slider::new(-2.0..=4.0, volume.log10(), move |exp| {
+                        Message::VolumeNormChanged(pane_id, VolumeNorm::Fixed(10f32.powf(exp)))
+                    })
+                    .step(0.05)
+                )
+                .push(Text::new(format!("{volume:.2}")).size(16));
+        },
+        VolumeNorm::Percentile(percentile) => {
+            column = column
+                .push(
+                    Slider::new(50.0..=100.0, percentile, move |percentile| {
+                        Message::VolumeNormChanged(pane_id, VolumeNorm::Percentile(percentile))
+                    })
+                    .step(1.0)
+                )
+                .push(Text::new(format!("p{percentile:.0}")).size(16));
+        },
+    }
+
+    column.into()
+}
+
+fn view_bar_patterns<'a>(pane_id: Uuid, settings: &PaneSettings) -> Element<'a, Message> {
+    let mut column = Column::new()
+        .align_x(Alignment::Center)
+        .spacing(4)
+        .push(
+            tooltip(
+                checkbox("Inside/outside bars", settings.bar_patterns)
+                    .on_toggle(move |enabled| Message::BarPatternsToggled(pane_id, enabled)),
+                "Tints bars within the previous bar's range (inside) or engulfing it (outside)",
+                tooltip::Position::Top
+            ).style(style::tooltip)
+        );
+
+    if settings.bar_patterns {
+        column = column
+            .push(
+                Row::new()
+                    .align_y(Alignment::Center)
+                    .spacing(4)
+                    .push(Text::new("Inside").size(14))
+                    .push(
+                        pick_list(
+                            &BarTint::ALL[..],
+                            Some(settings.inside_bar_tint),
+                            move |tint| Message::InsideBarTintSelected(pane_id, tint)
+                        )
+                        .style(style::picklist_primary)
+                        .menu_style(style::picklist_menu_primary)
+                    )
+            )
+            .push(
+                Row::new()
+                    .align_y(Alignment::Center)
+                    .spacing(4)
+                    .push(Text::new("Outside").size(14))
+                    .push(
+                        pick_list(
+                            &BarTint::ALL[..],
+                            Some(settings.outside_bar_tint),
+                            move |tint| Message::OutsideBarTintSelected(pane_id, tint)
+                        )
+                        .style(style::picklist_primary)
+                        .menu_style(style::picklist_menu_primary)
+                    )
+            );
+    }
+
+    column.into()
+}
+
+fn view_moving_averages<'a>(pane_id: Uuid, settings: &PaneSettings) -> Element<'a, Message> {
+    let mut column = Column::new()
+        .align_x(Alignment::Center)
+        .spacing(4)
+        .push(
+            tooltip(
+                Text::new("Moving Averages"),
+                "Lines over the closes, drawn once there are enough bars for the period",
+                tooltip::Position::Top
+            ).style(style::tooltip)
+        );
+
+    for (slot, ma) in settings.moving_averages.iter().enumerate() {
+        let Some(ma) = *ma else { continue };
+
+        column = column
+            .push(
+                Row::new()
+                    .align_y(Alignment::Center)
+                    .spacing(4)
+                    .push(
+                        pick_list(
+                            &MaKind::ALL[..],
+                            Some(ma.kind),
+                            move |kind| Message::MovingAverageSet(pane_id, slot, Some(MaConfig { kind, ..ma }))
+                        )
+                        .style(style::picklist_primary)
+                        .menu_style(style::picklist_menu_primary)
+                    )
+                    .push(
+                        pick_list(
+                            &BarTint::ALL[..],
+                            Some(ma.color),
+                            move |color| Message::MovingAverageSet(pane_id, slot, Some(MaConfig { color, ..ma }))
+                        )
+                        .style(style::picklist_primary)
+                        .menu_style(style::picklist_menu_primary)
+                    )
+                    .push(
+                        button(Text::new("Remove").size(14))
+                            .on_press(Message::MovingAverageSet(pane_id, slot, None))
+                    )
+            )
+            .push(
+                Row::new()
+                    .align_y(Alignment::Center)
+                    .spacing(4)
+                    .push(
+                        Slider::new(
+                            MaConfig::PERIOD_RANGE,
+                            ma.period as u32,
+                            move |period| Message::MovingAverageSet(
+                                pane_id, slot, Some(MaConfig { period: period as usize, ..ma })
+                            )
+                        )
+                    )
+                    .push(Text::new(format!("{} bars", ma.period)).size(14))
+            );
+    }
+
+    let free_slot = settings.moving_averages.iter().position(Option::is_none);
+
+    column
+        .push(
+            button(Text::new("Add moving average").size(14))
+                .on_press_maybe(free_slot.map(|slot| {
+                    Message::MovingAverageSet(pane_id, slot, Some(MaConfig::for_slot(slot)))
+                }))
+        )
+        .into()
+}
+
+fn view_offset_guides<'a>(pane_id: Uuid, offset_guides: bool, guide_ticks: f32) -> Element<'a, Message> {
+    let mut column = Column::new()
+        .align_x(Alignment::Center)
+        .spacing(4)
+        .push(
+            tooltip(
+                checkbox("Offset guides", offset_guides)
+                    .on_toggle(move |enabled| Message::OffsetGuidesToggled(pane_id, enabled)),
+                "Lines a fixed number of ticks above and below the last price, following it",
+                tooltip::Position::Top
+            ).style(style::tooltip)
+        );
+
+    if offset_guides {
+        column = column
+            .push(
+                Slider::new(1.0..=200.0, guide_ticks, move |value| Message::GuideTicksChanged(pane_id, value))
+                    .step(1.0)
+            )
+            .push(Text::new(format!("{guide_ticks} ticks")).size(16));
+    }
+
+    column.into()
+}
+
+fn view_depth_bands<'a>(pane_id: Uuid, depth_bands: bool, band_widths: [f32; 3]) -> Element<'a, Message> {
+    let mut column = Column::new()
+        .align_x(Alignment::Center)
+        .spacing(4)
+        .push(
+            tooltip(
+                checkbox("Liquidity bands", depth_bands)
+                    .on_toggle(move |enabled| Message::DepthBandsToggled(pane_id, enabled)),
+                "Bid / ask quantity resting within each distance from mid, from the current book",
+                tooltip::Position::Top
+            ).style(style::tooltip)
+        );
+
+    if depth_bands {
+        for (index, width) in band_widths.into_iter().enumerate() {
+            column = column.push(
+                Row::new()
+                    .align_y(Alignment::Center)
+                    .spacing(4)
+                    .push(
+                        Slider::new(0.05..=5.0, width, move |value| Message::DepthBandChanged(pane_id, index, value))
+                            .step(0.05)
+                    )
+                    .push(Text::new(format!("{width:.2}%")).size(14))
+            );
+        }
+    }
+
+    column.into()
+}
+
+fn view_trade_trail<'a>(pane_id: Uuid, trade_trail: bool, threshold: f32) -> Element<'a, Message> {
+    let mut column = Column::new()
+        .align_x(Alignment::Center)
+        .spacing(4)
+        .push(
+            tooltip(
+                checkbox("Trade trail", trade_trail)
+                    .on_toggle(move |enabled| Message::TradeTrailToggled(pane_id, enabled)),
+                "Connects the largest trade of each update, tracing where big flow pushed price",
+                tooltip::Position::Top
+            ).style(style::tooltip)
+        );
+
+    if trade_trail {
+        column = column
+            .push(
+                Slider::new(0.0..=250000.0, threshold, move |value| Message::TrailThresholdChanged(pane_id, value))
+                    .step(5000.0)
+            )
+            .push(Text::new(format!("${threshold}")).size(16));
+    }
+
+    column.into()
+}
+
+fn view_volume_ratio<'a>(pane_id: Uuid, volume_ratio: f32) -> Element<'a, Message> {
+    Column::new()
+        .align_x(Alignment::Center)
+        .spacing(4)
+        .push(Text::new("Volume Area"))
+        .push(
+            Slider::new(0.0..=MAX_VOLUME_RATIO, volume_ratio, move |value| Message::VolumeRatioChanged(pane_id, value))
+                .step(0.025)
+        )
+        .push(
+            Text::new(
+                if volume_ratio > 0.0 {
+                    format!("{:.1}%", volume_ratio * 100.0)
+                } else {
+                    "Hidden".to_string()
+                }
+            ).size(16)
+        )
+        .into()
+}
+
+fn view_agg_ticksize<'a>(pane_id: Uuid, agg_tick_multiply: Option<TickMultiplier>) -> Element<'a, Message> {
+    let mut reset_button = button(Text::new("Reset").size(14));
+    if agg_tick_multiply.is_some() {
+        reset_button = reset_button.on_press(Message::AggTicksizeSelected(None, pane_id));
+    }
+
+    Column::new()
+        .align_x(Alignment::Center)
+        .spacing(4)
+        .push(
+            tooltip(
+                Text::new("Trade Aggregation"),
+                "Price step trades are binned at, independent of the displayed ticksize",
+                tooltip::Position::Top
+            ).style(style::tooltip)
+        )
+        .push(
+            Row::new()
+                .align_y(Alignment::Center)
+                .spacing(4)
+                .push(
+                    pick_list(
+                        FOOTPRINT_TICK_MULTIPLIERS,
+                        agg_tick_multiply,
+                        move |tick_multiply| Message::AggTicksizeSelected(Some(tick_multiply), pane_id)
+                    )
+                    .placeholder("Same as chart")
+                    .style(style::picklist_primary)
+                    .menu_style(style::picklist_menu_primary)
+                )
+                .push(reset_button)
+        )
+        .into()
+}
+
+fn view_price_step<'a>(pane_id: Uuid, price_step: Option<f32>) -> Element<'a, Message> {
+    let mut reset_button = button(Text::new("Reset").size(14));
+    if price_step.is_some() {
+        reset_button = reset_button.on_press(Message::PriceStepSelected(pane_id, None));
+    }
+
+    Column::new()
+        .align_x(Alignment::Center)
+        .spacing(4)
+        .push(
+            tooltip(
+                Text::new("Price Labels Grid"),
+                "Keeps the y-axis labels on multiples of this, for instruments trading in e.g. 0.25 increments",
+                tooltip::Position::Top
+            ).style(style::tooltip)
+        )
+        .push(
+            Row::new()
+                .align_y(Alignment::Center)
+                .spacing(4)
+                .push(
+                    pick_list(
+                        &PRICE_STEP_BASES[..],
+                        price_step,
+                        move |step| Message::PriceStepSelected(pane_id, Some(step))
+                    )
+                    .placeholder("Auto")
+                    .style(style::picklist_primary)
+                    .menu_style(style::picklist_menu_primary)
+                )
+                .push(reset_button)
+        )
+        .into()
+}
+
+fn view_ghost_offset<'a>(pane_id: Uuid, ghost_offset: Option<GhostOffset>) -> Element<'a, Message> {
+    let mut reset_button = button(Text::new("Off").size(14));
+    if ghost_offset.is_some() {
+        reset_button = reset_button.on_press(Message::GhostOffsetSelected(pane_id, None));
     }
+
+    Column::new()
+        .align_x(Alignment::Center)
+        .spacing(4)
+        .push(
+            tooltip(
+                Text::new("Ghost Candles"),
+                "Faint candles of an earlier session at the same time of day, to compare today's path against it",
+                tooltip::Position::Top
+            ).style(style::tooltip)
+        )
+        .push(
+            Row::new()
+                .align_y(Alignment::Center)
+                .spacing(4)
+                .push(
+                    pick_list(
+                        &GhostOffset::ALL[..],
+                        ghost_offset,
+                        move |offset| Message::GhostOffsetSelected(pane_id, Some(offset))
+                    )
+                    .placeholder("Off")
+                    .style(style::picklist_primary)
+                    .menu_style(style::picklist_menu_primary)
+                )
+                .push(reset_button)
+        )
+        .into()
 }
-impl ChartView for FootprintChart {
-    fn view(&self, pane: &PaneState) -> Element<Message> {
-        let pane_id = pane.id;
 
-        self.view().map(move |message| Message::ChartUserUpdate(message, pane_id))
+fn view_palette<'a>(pane_id: Uuid, palette: Option<ChartPalette>) -> Element<'a, Message> {
+    let mut reset_button = button(Text::new("Reset").size(14));
+    if palette.is_some() {
+        reset_button = reset_button.on_press(Message::PaletteSelected(pane_id, None));
     }
+
+    Column::new()
+        .align_x(Alignment::Center)
+        .spacing(4)
+        .push(Text::new("Colors"))
+        .push(
+            Row::new()
+                .align_y(Alignment::Center)
+                .spacing(4)
+                .push(
+                    pick_list(
+                        &ChartPalette::ALL[..],
+                        palette,
+                        move |palette| Message::PaletteSelected(pane_id, Some(palette))
+                    )
+                    .placeholder("Default")
+                    .style(style::picklist_primary)
+                    .menu_style(style::picklist_menu_primary)
+                )
+                .push(reset_button)
+        )
+        .into()
 }
-impl ChartView for TimeAndSales {
-    fn view(&self, pane: &PaneState) -> Element<Message> {
-        let pane_id = pane.id;
 
-        let underlay = self.view();
+fn view_pinned_range<'a>(pane_id: Uuid, settings: &PaneSettings, draft: &(String, String)) -> Element<'a, Message> {
+    let pinned_range = settings.pinned_range;
 
-        if pane.show_modal {
-            let size_filter = &self.get_size_filter();
+    let (min_input, max_input) = draft.clone();
 
-            let signup = container(
-                Column::new()
+    let parsed_range = match (min_input.trim().parse::<f32>(), max_input.trim().parse::<f32>()) {
+        (Ok(low), Ok(high)) if low > 0.0 && low < high => Some((low, high)),
+        _ => None,
+    };
+
+    let (min_placeholder, max_placeholder) = pinned_range
+        .map_or(("Min".to_string(), "Max".to_string()), |(low, high)| (low.to_string(), high.to_string()));
+
+    let min_field = {
+        let max_input = max_input.clone();
+
+        text_input(&min_placeholder, &min_input)
+            .on_input(move |value| Message::PinDraftChanged(pane_id, value, max_input.clone()))
+            .width(Length::Fixed(100.0))
+    };
+    let max_field = text_input(&max_placeholder, &max_input)
+        .on_input(move |value| Message::PinDraftChanged(pane_id, min_input.clone(), value))
+        .width(Length::Fixed(100.0));
+
+    Column::new()
+        .align_x(Alignment::Center)
+        .spacing(4)
+        .push(Text::new("Pinned Range"))
+        .push(
+            Row::new()
+                .align_y(Alignment::Center)
+                .spacing(4)
+                .push(min_field)
+                .push(max_field)
+        )
+        .push(
+            Row::new()
+                .spacing(4)
+                .push(
+                    button(Text::new("Pin").size(14))
+                        .on_press_maybe(parsed_range.map(|range| Message::PriceRangePinned(pane_id, Some(range))))
+                )
+                .push(
+                    button(Text::new("Unpin").size(14))
+                        .on_press_maybe(pinned_range.map(|_| Message::PriceRangePinned(pane_id, None)))
+                )
+        )
+        .push(
+            tooltip(
+                checkbox("Expand on breakout", settings.expand_on_breakout)
+                    .on_toggle(move |enabled| Message::ExpandOnBreakoutToggled(pane_id, enabled)),
+                "Price leaving the pinned range widens it just enough to stay in view, instead of getting clipped",
+                tooltip::Position::Top
+            ).style(style::tooltip)
+        )
+        .into()
+}
+
+fn view_grid_anchor<'a>(pane_id: Uuid, grid_anchor: f32, draft: &str) -> Element<'a, Message> {
+    tooltip(
+        Row::new()
+            .align_y(Alignment::Center)
+            .spacing(4)
+            .push(Text::new("Grid anchor"))
+            .push(
+                text_input(&grid_anchor.to_string(), draft)
+                    .on_input(move |value| Message::GridAnchorDraftChanged(pane_id, value))
+                    .on_submit(Message::GridAnchorSubmitted(pane_id))
+                    .width(Length::Fixed(100.0))
+            ),
+        "Price levels get grouped on a grid running through this price, e.g. a round number. \
+        0 keeps them on multiples of the ticksize, enter to apply",
+        tooltip::Position::Top
+    ).style(style::tooltip).into()
+}
+
+fn view_ticksize_confirm<'a>(
+    underlay: Element<'a, Message>,
+    pane_id: Uuid,
+    tick_multiply: TickMultiplier,
+    can_regroup: bool,
+) -> Element<'a, Message> {
+    let mut regroup_button = button("Regroup");
+    if can_regroup {
+        regroup_button = regroup_button.on_press(Message::ConfirmTicksize(pane_id, true));
+    }
+
+    let confirm: Container<Message, Theme, _> = container(
+        Column::new()
+            .spacing(10)
+            .align_x(Alignment::Center)
+            .push(
+                Text::new(format!("Change ticksize to {tick_multiply}?"))
+                    .size(16)
+            )
+            .push(
+                Text::new(
+                    if can_regroup {
+                        "Collected history can be regrouped into the new ticksize, or cleared"
+                    } else {
+                        "Collected history is finer than the new ticksize allows and will be cleared"
+                    }
+                ).size(12)
+            )
+            .push(
+                Row::new()
                     .spacing(10)
-                    .align_x(Alignment::Center)
                     .push(
-                        Text::new("Time&Sales > Settings")
-                            .size(16)
+                        tooltip(
+                            regroup_button,
+                            "Only possible when the new ticksize is a multiple of the current one",
+                            tooltip::Position::Top
+                        ).style(style::tooltip)
                     )
                     .push(
-                        Column::new()
-                            .align_x(Alignment::Center)
-                            .push(Text::new("Size Filtering"))
-                            .push(
-                                Slider::new(0.0..=50000.0, *size_filter, move |value| Message::SliderChanged(pane_id, value))
-                                    .step(500.0)
-                            )
-                            .push(
-                                Text::new(format!("${size_filter}")).size(16)
-                            )
+                        button("Clear")
+                            .on_press(Message::ConfirmTicksize(pane_id, false))
                     )
-                    .push( 
-                        Row::new()
-                            .spacing(10)
-                            .push(
-                                button("Close")
-                                .on_press(Message::HideModal(pane_id))
-                            )
+                    .push(
+                        button("Cancel")
+                            .on_press(Message::CancelTicksize(pane_id))
                     )
             )
-            .width(Length::Shrink)
-            .padding(20)
-            .max_width(500)
-            .style(style::chart_modal);
+    )
+    .width(Length::Shrink)
+    .padding(20)
+    .max_width(500)
+    .style(style::chart_modal);
 
-            return modal(underlay, signup, Message::HideModal(pane_id));
-        } else {
-            underlay
-        }
-    }
+    modal(underlay, confirm, Message::CancelTicksize(pane_id))
 }
-impl ChartView for CandlestickChart {
-    fn view(&self, pane: &PaneState) -> Element<Message> {
-        let pane_id = pane.id;
 
-        self.view().map(move |message| Message::ChartUserUpdate(message, pane_id))
-    }
+fn view_close_confirm<'a>(
+    underlay: Element<'a, Message>,
+    pane_id: Uuid,
+) -> Element<'a, Message> {
+    let confirm: Container<Message, Theme, _> = container(
+        Column::new()
+            .spacing(10)
+            .align_x(Alignment::Center)
+            .push(
+                Text::new("Close this pane?")
+                    .size(16)
+            )
+            .push(
+                Text::new("Its streams and collected data will be dropped")
+                    .size(12)
+            )
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .push(
+                        button("Close")
+                            .on_press(Message::ConfirmClose(pane_id))
+                    )
+                    .push(
+                        button("Cancel")
+                            .on_press(Message::CancelClose(pane_id))
+                    )
+            )
+    )
+    .width(Length::Shrink)
+    .padding(20)
+    .max_width(500)
+    .style(style::chart_modal);
+
+    modal(underlay, confirm, Message::CancelClose(pane_id))
 }
 
 fn view_chart<'a, C: ChartView>(
@@ -305,7 +1583,7 @@ fn view_controls<'a>(
     match pane_type {
         PaneContent::Heatmap(_) => {
             let ticksize_picker = pick_list(
-                [TickMultiplier(1), TickMultiplier(2), TickMultiplier(5), TickMultiplier(10), TickMultiplier(25), TickMultiplier(50)],
+                HEATMAP_TICK_MULTIPLIERS,
                 settings.tick_multiply, 
                 move |tick_multiply| Message::TicksizeSelected(tick_multiply, pane_id)
             ).placeholder("Ticksize multiplier...").text_size(11).width(iced::Pixels(80.0));
@@ -319,7 +1597,9 @@ fn view_controls<'a>(
                 )
                 .style(style::tooltip);
     
-            row = row.push(ticksize_tooltip);
+            row = row.push(
+                if settings.ticksize_locked { view_ticksize_locked(settings) } else { ticksize_tooltip.into() }
+            );
         },
         PaneContent::TimeAndSales(_) => {
         },
@@ -356,7 +1636,9 @@ fn view_controls<'a>(
                 )
                 .style(style::tooltip);
     
-            row = row.push(ticksize_tooltip);
+            row = row.push(
+                if settings.ticksize_locked { view_ticksize_locked(settings) } else { ticksize_tooltip.into() }
+            );
         },
         PaneContent::Candlestick(_) => {
             let timeframe_picker = pick_list(
@@ -385,6 +1667,10 @@ fn view_controls<'a>(
         (container(text(char::from(icon).to_string()).font(ICON_FONT).size(14)).width(25).center_x(iced::Pixels(25.0)), message),
     ];
 
+    if settings.popout.is_none() && !matches!(pane_type, PaneContent::Starter) {
+        buttons.push((container(text("\u{2197}").size(14)).width(25).center_x(iced::Pixels(25.0)), Message::Popout(pane)));
+    }
+
     if total_panes > 1 {
         buttons.push((container(text(char::from(Icon::Close).to_string()).font(ICON_FONT).size(14)).width(25).center_x(iced::Pixels(25.0)), Message::ClosePane(pane)));
     }
@@ -401,6 +1687,52 @@ fn view_controls<'a>(
     row.into()
 }
 
+fn view_popped_out<'a>(pane_id: Uuid) -> Element<'a, Message> {
+    container(
+        Column::new()
+            .spacing(8)
+            .align_x(Alignment::Center)
+            .push(Text::new("Popped out to its own window"))
+            .push(
+                button("Dock back")
+                    .on_press(Message::Dock(pane_id))
+            )
+    )
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .center_x(Length::Fill)
+    .center_y(Length::Fill)
+    .into()
+}
+
+fn view_auto_ticksize<'a>(pane_id: Uuid, auto_ticksize: bool) -> Element<'a, Message> {
+    tooltip(
+        checkbox("Auto ticksize", auto_ticksize)
+            .on_toggle(move |enabled| Message::AutoTicksizeToggled(pane_id, enabled)),
+        "Picks a ticksize around 0.05% of the price when the pane loads, choosing one by hand turns it off",
+        tooltip::Position::Top
+    )
+    .style(style::tooltip)
+    .into()
+}
+
+fn view_ticksize_locked<'a>(settings: &PaneSettings) -> Element<'a, Message> {
+    let tick_multiply = settings.tick_multiply.map_or(String::new(), |tm| tm.to_string());
+
+    tooltip(
+        row![
+            text(char::from(Icon::Locked).to_string()).font(ICON_FONT).size(11),
+            text(tick_multiply).size(11),
+        ]
+        .spacing(4)
+        .align_y(Alignment::Center),
+        "Ticksize locked, unlock in settings",
+        tooltip::Position::FollowCursor
+    )
+    .style(style::tooltip)
+    .into()
+}
+
 fn view_starter<'a>(
     pane_id: &'a Uuid,
     pane_settings: &'a PaneSettings,
@@ -417,19 +1749,7 @@ fn view_starter<'a>(
                         || { log::error!("No timeframe found"); Timeframe::M1 }
                     );
 
-                    let pane_stream: Vec<StreamType> = match label {
-                        "Heatmap chart" | "Time&Sales" => vec![
-                            StreamType::DepthAndTrades { exchange, ticker }
-                        ],
-                        "Footprint chart" => vec![
-                            StreamType::DepthAndTrades { exchange, ticker }, 
-                            StreamType::Kline { exchange, ticker, timeframe }
-                        ],
-                        "Candlestick chart" => vec![
-                            StreamType::Kline { exchange, ticker, timeframe }
-                        ],
-                        _ => vec![]
-                    };
+                    let pane_stream = content_streams(label, exchange, ticker, timeframe);
                 
                     btn = btn.on_press(
                         Message::PaneContentSelected(
@@ -455,11 +1775,18 @@ fn view_starter<'a>(
         move |exchange| Message::ExchangeSelected(exchange, *pane_id),
     ).placeholder("exchange...").text_size(13).width(Length::Fill);
 
+    let timeframe_selector = pick_list(
+        &Timeframe::ALL[..],
+        pane_settings.selected_timeframe,
+        move |timeframe| Message::StarterTimeframeSelected(timeframe, *pane_id),
+    ).placeholder("timeframe...").text_size(13).width(Length::Fill);
+
     let picklists = Row::new()
         .spacing(6)
         .align_y(Alignment::Center)
         .push(exchange_selector.style(style::picklist_primary).menu_style(style::picklist_menu_primary))
-        .push(symbol_selector.style(style::picklist_primary).menu_style(style::picklist_menu_primary));
+        .push(symbol_selector.style(style::picklist_primary).menu_style(style::picklist_menu_primary))
+        .push(timeframe_selector.style(style::picklist_primary).menu_style(style::picklist_menu_primary));
 
     let column = Column::new()
         .padding(10)
@@ -483,6 +1810,32 @@ fn view_starter<'a>(
     container.into()
 }
 
+/// Streams a pane needs for the given content label, as listed in the starter pane
+pub fn content_streams(label: &str, exchange: Exchange, ticker: Ticker, timeframe: Timeframe) -> Vec<StreamType> {
+    match label {
+        "Heatmap chart" | "Time&Sales" => vec![
+            StreamType::DepthAndTrades { exchange, ticker }
+        ],
+        "Footprint chart" => vec![
+            StreamType::DepthAndTrades { exchange, ticker }, 
+            StreamType::Kline { exchange, ticker, timeframe }
+        ],
+        "Candlestick chart" => vec![
+            StreamType::Kline { exchange, ticker, timeframe }
+        ],
+        _ => vec![]
+    }
+}
+
+pub const HEATMAP_TICK_MULTIPLIERS: [TickMultiplier; 6] = [
+    TickMultiplier(1), TickMultiplier(2), TickMultiplier(5), TickMultiplier(10), TickMultiplier(25), TickMultiplier(50),
+];
+
+pub const FOOTPRINT_TICK_MULTIPLIERS: [TickMultiplier; 8] = [
+    TickMultiplier(1), TickMultiplier(2), TickMultiplier(5), TickMultiplier(10), TickMultiplier(25), TickMultiplier(50), TickMultiplier(100), TickMultiplier(200),
+];
+
+#[allow(clippy::large_enum_variant)]
 pub enum PaneContent {
     Heatmap(HeatmapChart),
     Footprint(FootprintChart),
@@ -491,6 +1844,201 @@ pub enum PaneContent {
     Starter,
 }
 
+impl PaneContent {
+    pub fn set_crosshair_style(&mut self, crosshair_style: CrosshairStyle) {
+        match self {
+            PaneContent::Heatmap(chart) => chart.set_crosshair_style(crosshair_style),
+            PaneContent::Footprint(chart) => chart.set_crosshair_style(crosshair_style),
+            PaneContent::Candlestick(chart) => chart.set_crosshair_style(crosshair_style),
+            PaneContent::TimeAndSales(_) | PaneContent::Starter => {},
+        }
+    }
+
+    pub fn set_debug_overlay(&mut self, enabled: bool) {
+        match self {
+            PaneContent::Heatmap(chart) => chart.set_debug_overlay(enabled),
+            PaneContent::Footprint(chart) => chart.set_debug_overlay(enabled),
+            PaneContent::Candlestick(chart) => chart.set_debug_overlay(enabled),
+            PaneContent::TimeAndSales(_) | PaneContent::Starter => {},
+        }
+    }
+
+    pub fn set_price_ruler(&mut self, enabled: bool) {
+        match self {
+            PaneContent::Heatmap(chart) => chart.set_price_ruler(enabled),
+            PaneContent::Footprint(chart) => chart.set_price_ruler(enabled),
+            PaneContent::Candlestick(chart) => chart.set_price_ruler(enabled),
+            PaneContent::TimeAndSales(_) | PaneContent::Starter => {},
+        }
+    }
+
+    pub fn set_label_size(&mut self, label_size: f32) {
+        match self {
+            PaneContent::Heatmap(chart) => chart.set_label_size(label_size),
+            PaneContent::Footprint(chart) => chart.set_label_size(label_size),
+            PaneContent::Candlestick(chart) => chart.set_label_size(label_size),
+            PaneContent::TimeAndSales(_) | PaneContent::Starter => {},
+        }
+    }
+
+    pub fn set_palette(&mut self, palette: ChartPalette) {
+        match self {
+            PaneContent::Heatmap(chart) => chart.set_palette(palette),
+            PaneContent::Footprint(chart) => chart.set_palette(palette),
+            PaneContent::Candlestick(chart) => chart.set_palette(palette),
+            PaneContent::TimeAndSales(_) | PaneContent::Starter => {},
+        }
+    }
+
+    pub fn set_offset_guides(&mut self, offset_guides: Option<f32>) {
+        match self {
+            PaneContent::Heatmap(chart) => chart.set_offset_guides(offset_guides),
+            PaneContent::Candlestick(chart) => chart.set_offset_guides(offset_guides),
+            PaneContent::Footprint(_) | PaneContent::TimeAndSales(_) | PaneContent::Starter => {},
+        }
+    }
+
+    pub fn set_grid_anchor(&mut self, grid_anchor: f32) {
+        match self {
+            PaneContent::Heatmap(chart) => chart.set_grid_anchor(grid_anchor),
+            PaneContent::Footprint(chart) => chart.set_grid_anchor(grid_anchor),
+            PaneContent::Candlestick(_) | PaneContent::TimeAndSales(_) | PaneContent::Starter => {},
+        }
+    }
+
+    pub fn has_bar_countdown(&self) -> bool {
+        match self {
+            PaneContent::Candlestick(chart) => chart.has_bar_countdown(),
+            _ => false,
+        }
+    }
+
+    /// Pinned range as the chart has it now, breakouts may have widened it
+    pub fn get_pinned_range(&self) -> Option<(f32, f32)> {
+        match self {
+            PaneContent::Heatmap(chart) => chart.get_pinned_range(),
+            PaneContent::Candlestick(chart) => chart.get_pinned_range(),
+            PaneContent::Footprint(_) | PaneContent::TimeAndSales(_) | PaneContent::Starter => None,
+        }
+    }
+
+    pub fn set_expand_on_breakout(&mut self, enabled: bool) {
+        match self {
+            PaneContent::Heatmap(chart) => chart.set_expand_on_breakout(enabled),
+            PaneContent::Candlestick(chart) => chart.set_expand_on_breakout(enabled),
+            PaneContent::Footprint(_) | PaneContent::TimeAndSales(_) | PaneContent::Starter => {},
+        }
+    }
+
+    pub fn set_pinned_range(&mut self, pinned_range: Option<(f32, f32)>) {
+        match self {
+            PaneContent::Heatmap(chart) => chart.set_pinned_range(pinned_range),
+            PaneContent::Candlestick(chart) => chart.set_pinned_range(pinned_range),
+            PaneContent::Footprint(_) | PaneContent::TimeAndSales(_) | PaneContent::Starter => {},
+        }
+    }
+
+    pub fn set_price_step(&mut self, price_step: Option<f32>) {
+        match self {
+            PaneContent::Heatmap(chart) => chart.set_price_step(price_step),
+            PaneContent::Footprint(chart) => chart.set_price_step(price_step),
+            PaneContent::Candlestick(chart) => chart.set_price_step(price_step),
+            PaneContent::TimeAndSales(_) | PaneContent::Starter => {},
+        }
+    }
+
+    pub fn set_volume_ratio(&mut self, volume_ratio: f32) {
+        match self {
+            PaneContent::Heatmap(chart) => chart.set_volume_ratio(volume_ratio),
+            PaneContent::Footprint(chart) => chart.set_volume_ratio(volume_ratio),
+            PaneContent::Candlestick(chart) => chart.set_volume_ratio(volume_ratio),
+            PaneContent::TimeAndSales(_) | PaneContent::Starter => {},
+        }
+    }
+
+    pub fn set_min_tick_size(&mut self, min_tick_size: f32) {
+        match self {
+            PaneContent::Heatmap(chart) => chart.set_min_tick_size(min_tick_size),
+            PaneContent::Footprint(chart) => chart.set_min_tick_size(min_tick_size),
+            PaneContent::Candlestick(chart) => chart.set_min_tick_size(min_tick_size),
+            PaneContent::TimeAndSales(_) | PaneContent::Starter => {},
+        }
+    }
+
+    pub fn set_idle(&mut self, idle: bool) {
+        match self {
+            PaneContent::Heatmap(chart) => chart.set_idle(idle),
+            PaneContent::Footprint(chart) => chart.set_idle(idle),
+            PaneContent::Candlestick(chart) => chart.set_idle(idle),
+            PaneContent::TimeAndSales(_) | PaneContent::Starter => {},
+        }
+    }
+
+    /// Time hovered on a kline chart, what linked panes follow
+    pub fn crosshair_time(&self) -> Option<i64> {
+        match self {
+            PaneContent::Footprint(chart) => chart.crosshair_time(),
+            PaneContent::Candlestick(chart) => chart.crosshair_time(),
+            PaneContent::Heatmap(_) | PaneContent::TimeAndSales(_) | PaneContent::Starter => None,
+        }
+    }
+
+    /// (crosshair, autoscale) of a chart, `None` for content without those buttons
+    pub fn get_view_toggles(&self) -> Option<(bool, bool)> {
+        match self {
+            PaneContent::Heatmap(chart) => Some(chart.get_view_toggles()),
+            PaneContent::Footprint(chart) => Some(chart.get_view_toggles()),
+            PaneContent::Candlestick(chart) => Some(chart.get_view_toggles()),
+            PaneContent::TimeAndSales(_) | PaneContent::Starter => None,
+        }
+    }
+
+    pub fn set_sync_crosshair_time(&mut self, time: Option<i64>) {
+        match self {
+            PaneContent::Footprint(chart) => chart.set_sync_crosshair_time(time),
+            PaneContent::Candlestick(chart) => chart.set_sync_crosshair_time(time),
+            PaneContent::Heatmap(_) | PaneContent::TimeAndSales(_) | PaneContent::Starter => {},
+        }
+    }
+
+    /// Highest visible volume of a kline chart, `None` for content without volume bars to share
+    pub fn visible_max_volume(&self) -> Option<f32> {
+        match self {
+            PaneContent::Footprint(chart) => Some(chart.visible_max_volume()),
+            PaneContent::Candlestick(chart) => Some(chart.visible_max_volume()),
+            PaneContent::Heatmap(_) | PaneContent::TimeAndSales(_) | PaneContent::Starter => None,
+        }
+    }
+
+    pub fn set_shared_volume_max(&mut self, shared_volume_max: Option<f32>) {
+        match self {
+            PaneContent::Footprint(chart) => chart.set_shared_volume_max(shared_volume_max),
+            PaneContent::Candlestick(chart) => chart.set_shared_volume_max(shared_volume_max),
+            PaneContent::Heatmap(_) | PaneContent::TimeAndSales(_) | PaneContent::Starter => {},
+        }
+    }
+
+    /// Ticksize multipliers offered for this content, empty if it has no ticksize
+    pub fn tick_multipliers(&self) -> &'static [TickMultiplier] {
+        match self {
+            PaneContent::Heatmap(_) => &HEATMAP_TICK_MULTIPLIERS,
+            PaneContent::Footprint(_) => &FOOTPRINT_TICK_MULTIPLIERS,
+            PaneContent::Candlestick(_) | PaneContent::TimeAndSales(_) | PaneContent::Starter => &[],
+        }
+    }
+
+    /// The starter pane label this content was created from
+    pub fn label(&self) -> Option<&'static str> {
+        match self {
+            PaneContent::Heatmap(_) => Some("Heatmap chart"),
+            PaneContent::Footprint(_) => Some("Footprint chart"),
+            PaneContent::Candlestick(_) => Some("Candlestick chart"),
+            PaneContent::TimeAndSales(_) => Some("Time&Sales"),
+            PaneContent::Starter => None,
+        }
+    }
+}
+
 impl fmt::Debug for PaneContent {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -503,7 +2051,78 @@ impl fmt::Debug for PaneContent {
     }
 }
 
+/// Content a new pane gets populated with instead of the starter pane
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PanePreset {
+    pub content: String,
+    pub exchange: Exchange,
+    pub ticker: Ticker,
+    pub timeframe: Timeframe,
+}
+
+impl fmt::Display for PanePreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.content.as_str() {
+            "Candlestick chart" | "Footprint chart" => write!(
+                f, "{} {} {} {}", self.content, self.timeframe, self.ticker, self.exchange
+            ),
+            _ => write!(f, "{} {} {}", self.content, self.ticker, self.exchange),
+        }
+    }
+}
+
+/// What a starter pane comes preselected with, leaving less to pick before it shows anything
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct StarterDefaults {
+    pub exchange: Option<Exchange>,
+    pub timeframe: Timeframe,
+}
+impl Default for StarterDefaults {
+    fn default() -> Self {
+        Self {
+            exchange: None,
+            timeframe: Timeframe::M1,
+        }
+    }
+}
+impl StarterDefaults {
+    pub fn pane_settings(&self) -> PaneSettings {
+        PaneSettings {
+            selected_exchange: self.exchange,
+            selected_timeframe: Some(self.timeframe),
+            ..PaneSettings::default()
+        }
+    }
+}
+
+/// Size and position of a pane's own window while it's popped out of the grid
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct PopoutWindow {
+    pub size: (f32, f32),
+    pub position: Option<(f32, f32)>,
+}
+impl Default for PopoutWindow {
+    fn default() -> Self {
+        Self {
+            size: (800.0, 600.0),
+            position: None,
+        }
+    }
+}
+
+/// Panes sharing a link group follow each other's ticker changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct LinkGroup(pub u8);
+
+impl fmt::Display for LinkGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 #[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(default)]
 pub struct PaneSettings {
     pub min_tick_size: Option<f32>,
     pub trade_size_filter: Option<f32>,
@@ -511,6 +2130,100 @@ pub struct PaneSettings {
     pub selected_ticker: Option<Ticker>,
     pub selected_exchange: Option<Exchange>,
     pub selected_timeframe: Option<Timeframe>,
+    pub link_group: Option<LinkGroup>,
+    pub volume_style: VolumeStyle,
+    pub volume_norm: VolumeNorm,
+    pub radius_scale: RadiusScale,
+    pub max_trade_markers: u32,
+    /// Time window a heatmap shows across its width
+    pub visible_span_ms: u32,
+    /// Width of a heatmap bucket
+    pub aggregate_ms: i64,
+    /// Heatmap depth intensity EMA weight, 0 is off
+    pub depth_smoothing: f32,
+    /// Width of the heatmap's current book panel, 0 draws the book inline
+    pub depth_profile_width: f32,
+    pub highlight_pulls: bool,
+    /// Heatmap only groups depth within a screen of the visible price range
+    pub depth_cutoff: bool,
+    pub trade_trail: bool,
+    /// Smallest notional a trade needs to be part of the trade trail
+    pub trail_threshold: f32,
+    pub depth_bands: bool,
+    /// Percent distances from mid the liquidity bands sum the book within
+    pub band_widths: [f32; 3],
+    pub mark_doji: bool,
+    pub volume_heat: bool,
+    pub show_sessions: bool,
+    pub price_line: bool,
+    pub bar_countdown: bool,
+    /// Empty bars ahead of the latest candle
+    pub right_margin: u32,
+    /// Fade the footprint bar that's still forming
+    pub live_bar: bool,
+    /// Tint inside and outside bars on a candlestick pane
+    pub bar_patterns: bool,
+    pub inside_bar_tint: BarTint,
+    pub outside_bar_tint: BarTint,
+    /// Moving averages on a candlestick pane, empty slots draw nothing
+    pub moving_averages: [Option<MaConfig>; MaConfig::SLOTS],
+    pub trade_mode: TradeMode,
+    pub depth_cadence: DepthCadence,
+    pub spread_unit: SpreadUnit,
+    pub price_scale: PriceScale,
+    pub ticksize_locked: bool,
+    /// Pick the ticksize from the price magnitude once the pane loads
+    pub auto_ticksize: bool,
+    /// Share of the chart height used by volume bars, 0 hides them
+    pub volume_ratio: f32,
+    pub popout: Option<PopoutWindow>,
+    pub notional_tiers: NotionalTiers,
+    /// Overrides the default chart colors for this pane
+    pub palette: Option<ChartPalette>,
+    /// Grid base of the y-axis labels, `None` picks from the default steps
+    pub price_step: Option<f32>,
+    /// Fixed (lowest, highest) prices of the y-axis, `None` follows the data
+    pub pinned_range: Option<(f32, f32)>,
+    /// Widen the pinned range when price breaks out of it
+    pub expand_on_breakout: bool,
+    /// Last state of the chart's crosshair button, `None` until it's toggled
+    pub crosshair: Option<bool>,
+    /// Last state of the chart's autoscale button, `None` until it's toggled
+    pub autoscale: Option<bool>,
+    /// Fibonacci retracement drawn on a candlestick pane
+    pub fib: Option<FibDraw>,
+    /// Earlier session overlaid as faint candles on a candlestick pane
+    pub ghost_offset: Option<GhostOffset>,
+    /// Footprint trade binning step, `None` bins at the chart's tick size
+    pub agg_tick_multiply: Option<TickMultiplier>,
+    /// Price heatmap and footprint levels are grouped around, 0 groups on plain tick multiples
+    pub grid_anchor: f32,
+    pub offset_guides: bool,
+    /// Distance of the offset guides from the last price, in min ticks
+    pub guide_ticks: f32,
+}
+impl PaneSettings {
+    /// Drops the size filter and ticksize tuned for the previous ticker, the auto
+    /// ticksize then picks one from the new price unless the ticksize is locked
+    pub fn reset_for_ticker(&mut self) {
+        self.trade_size_filter = Self::default().trade_size_filter;
+
+        if !self.ticksize_locked {
+            self.auto_ticksize = true;
+        }
+    }
+
+    /// Tints of (inside, outside) bars when they're highlighted
+    pub fn bar_patterns(&self) -> Option<(BarTint, BarTint)> {
+        self.bar_patterns.then_some((self.inside_bar_tint, self.outside_bar_tint))
+    }
+
+    /// Footprint trade binning step in price, once the min tick size is known
+    pub fn agg_tick_size(&self) -> Option<f32> {
+        self.agg_tick_multiply
+            .zip(self.min_tick_size)
+            .map(|(tick_multiply, min_tick_size)| tick_multiply.multiply_with_min_tick_size(min_tick_size))
+    }
 }
 impl Default for PaneSettings {
     fn default() -> Self {
@@ -521,6 +2234,53 @@ impl Default for PaneSettings {
             selected_ticker: None,
             selected_exchange: None,
             selected_timeframe: Some(Timeframe::M1),
+            link_group: None,
+            volume_style: VolumeStyle::default(),
+            volume_norm: VolumeNorm::default(),
+            radius_scale: RadiusScale::default(),
+            max_trade_markers: HeatmapChart::DEFAULT_MAX_TRADE_MARKERS,
+            visible_span_ms: HeatmapChart::DEFAULT_VISIBLE_SPAN_MS,
+            aggregate_ms: HeatmapChart::DEFAULT_AGGREGATE_MS,
+            depth_smoothing: 0.0,
+            depth_profile_width: 0.0,
+            highlight_pulls: false,
+            depth_cutoff: false,
+            trade_trail: false,
+            trail_threshold: 10000.0,
+            depth_bands: false,
+            band_widths: [0.1, 0.5, 1.0],
+            mark_doji: false,
+            volume_heat: false,
+            show_sessions: false,
+            price_line: false,
+            bar_countdown: false,
+            right_margin: CandlestickChart::DEFAULT_RIGHT_MARGIN,
+            live_bar: false,
+            bar_patterns: false,
+            inside_bar_tint: BarTint::Amber,
+            outside_bar_tint: BarTint::Blue,
+            moving_averages: [None; MaConfig::SLOTS],
+            trade_mode: TradeMode::default(),
+            depth_cadence: DepthCadence::default(),
+            spread_unit: SpreadUnit::default(),
+            price_scale: PriceScale::default(),
+            ticksize_locked: false,
+            auto_ticksize: false,
+            volume_ratio: DEFAULT_VOLUME_RATIO,
+            popout: None,
+            notional_tiers: NotionalTiers::default(),
+            palette: None,
+            price_step: None,
+            pinned_range: None,
+            expand_on_breakout: false,
+            crosshair: None,
+            autoscale: None,
+            grid_anchor: 0.0,
+            fib: None,
+            ghost_offset: None,
+            agg_tick_multiply: None,
+            offset_guides: false,
+            guide_ticks: 10.0,
         }
     }
 }
@@ -0,0 +1,64 @@
+use std::sync::{mpsc, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+use rodio::{source::SineWave, OutputStream, Sink, Source};
+
+const TONE_LENGTH: Duration = Duration::from_millis(120);
+const TONE_VOLUME: f32 = 0.2;
+
+/// Which side printed, buys get the higher pitch
+#[derive(Debug, Clone, Copy)]
+pub enum Tone {
+    Buy,
+    Sell,
+}
+
+impl Tone {
+    fn frequency(self) -> f32 {
+        match self {
+            Tone::Buy => 880.0,
+            Tone::Sell => 440.0,
+        }
+    }
+}
+
+static PLAYER: OnceLock<mpsc::Sender<Tone>> = OnceLock::new();
+
+/// Queues a tone on the audio thread, starting it on first use
+/// 
+/// The output stream can't leave the thread it was opened on, so it lives there
+/// for the rest of the process. Without an audio device tones are just dropped
+pub fn play(tone: Tone) {
+    let sender = PLAYER.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel::<Tone>();
+
+        thread::spawn(move || {
+            let (_stream, handle) = match OutputStream::try_default() {
+                Ok(output) => output,
+                Err(err) => {
+                    log::error!("No audio output for sound alerts: {err}");
+                    return;
+                }
+            };
+
+            for tone in receiver {
+                match Sink::try_new(&handle) {
+                    Ok(sink) => {
+                        sink.append(
+                            SineWave::new(tone.frequency())
+                                .take_duration(TONE_LENGTH)
+                                .amplify(TONE_VOLUME)
+                        );
+                        sink.detach();
+                    },
+                    Err(err) => log::warn!("Couldn't play sound alert: {err}"),
+                }
+            }
+        });
+
+        sender
+    });
+
+    let _ = sender.send(tone);
+}
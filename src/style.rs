@@ -2,8 +2,30 @@ use iced::widget::button::Status;
 use iced::widget::container::Style;
 use iced::{Border, Color, Font, Theme, overlay};
 use iced::widget::pick_list;
+use serde::{Deserialize, Serialize};
 
 pub const ICON_BYTES: &[u8] = include_bytes!("fonts/icons.ttf");
+pub const DEFAULT_THEME: Theme = Theme::KanagawaDragon;
+
+/// Keeps the theme as its name in the state file, as `Theme` isn't serializable itself
+pub mod theme_name {
+    use iced::Theme;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(theme: &Theme, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&theme.to_string())
+    }
+
+    /// Unknown names fall back to the default theme instead of failing the whole state
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Theme, D::Error> {
+        let name = String::deserialize(deserializer)?;
+
+        Ok(Theme::ALL.iter()
+            .find(|theme| theme.to_string() == name)
+            .cloned()
+            .unwrap_or(super::DEFAULT_THEME))
+    }
+}
 pub const ICON_FONT: Font = Font::with_name("icons");
 
 pub enum Icon {
@@ -92,21 +114,85 @@ pub fn pane_active(theme: &Theme) -> Style {
         ..Default::default()
     }
 }
-pub fn pane_focused(theme: &Theme) -> Style {
+pub fn pane_focused(theme: &Theme, highlight: FocusHighlight) -> Style {
     let palette = theme.extended_palette();
 
     Style {
         text_color: Some(palette.background.weak.text),
         background: Some(Color::BLACK.into()),
         border: Border {
-            width: 1.0,
-            color: palette.background.weak.color,
+            width: highlight.width,
+            color: highlight.color.color(theme),
             radius: 4.0.into(),
         },
         ..Default::default()
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum FocusColor {
+    #[default]
+    Subtle,
+    Accent,
+    White,
+    Yellow,
+}
+
+impl FocusColor {
+    pub const ALL: [FocusColor; 4] = [FocusColor::Subtle, FocusColor::Accent, FocusColor::White, FocusColor::Yellow];
+
+    fn color(&self, theme: &Theme) -> Color {
+        let palette = theme.extended_palette();
+
+        match self {
+            FocusColor::Subtle => palette.background.weak.color,
+            FocusColor::Accent => palette.primary.base.color,
+            FocusColor::White => Color::from_rgba8(255, 255, 255, 0.8),
+            FocusColor::Yellow => Color::from_rgba8(230, 200, 80, 0.9),
+        }
+    }
+}
+
+impl std::fmt::Display for FocusColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FocusColor::Subtle => write!(f, "Subtle"),
+            FocusColor::Accent => write!(f, "Accent"),
+            FocusColor::White => write!(f, "White"),
+            FocusColor::Yellow => write!(f, "Yellow"),
+        }
+    }
+}
+
+pub const FOCUS_WIDTH_RANGE: std::ops::RangeInclusive<f32> = 1.0..=4.0;
+
+/// Border around the focused pane, shared by every layout
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FocusHighlight {
+    pub color: FocusColor,
+    pub width: f32,
+}
+
+impl Default for FocusHighlight {
+    fn default() -> Self {
+        Self {
+            color: FocusColor::Subtle,
+            width: 1.0,
+        }
+    }
+}
+
+impl FocusHighlight {
+    /// Keeps a hand edited width from the state file within what the slider allows
+    pub fn clamped(self) -> Self {
+        Self {
+            width: self.width.clamp(*FOCUS_WIDTH_RANGE.start(), *FOCUS_WIDTH_RANGE.end()),
+            ..self
+        }
+    }
+}
+
 pub fn chart_modal(theme: &Theme) -> Style {
     let palette = theme.extended_palette();
 